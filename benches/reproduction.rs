@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::mem;
+use std::rc::Rc;
+use evo2::bench::{scenario_world, time_steps};
+use evo2::cell::{Cell, CellConstants, NoMate, RandomMutationNumberSource};
+use evo2::cell_columns::CellColumns;
+use evo2::genome::Genome;
+
+const NUM_CHILDREN: usize = 100_000;
+
+/// Times a cell having children and adding them to a population, with and without mutation,
+/// and reports the memory the children's genomes take, along with that of an evolving world's.
+fn reproduction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reproduction");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(NUM_CHILDREN as u64));
+    for (name, mutation_stdev) in [("unmutated", 0.0), ("mutated", 0.05)] {
+        let constants = Rc::new(CellConstants {
+            attempted_eating_energy_mutation_stdev: mutation_stdev.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut parent = Cell::new(&constants, Genome::DEFAULT);
+        report_genome_memory(&format!("{} {} children", NUM_CHILDREN, name), &have_children(&mut parent));
+
+        group.bench_function(name, |b| b.iter(|| have_children(&mut parent)));
+    }
+    group.finish();
+
+    let mut world = scenario_world(NUM_CHILDREN, 5.0);
+    time_steps(&mut world, 10);
+    report_genome_memory("world after 10 steps", world.cells());
+}
+
+fn have_children(parent: &mut Cell) -> CellColumns {
+    let mut mutation_number_source = RandomMutationNumberSource::with_seed(0);
+    let mut children = CellColumns::new();
+    for _ in 0..NUM_CHILDREN {
+        children.extend(parent.as_cell_mut().create_selected_child(1.into(), &mut NoMate, &mut mutation_number_source));
+    }
+    children
+}
+
+fn report_genome_memory(name: &str, cells: &CellColumns) {
+    println!("{}: {} bytes of genomes for {} cells ({} if unshared)",
+             name, cells.genome_memory_bytes(), cells.len(), cells.len() * mem::size_of::<Genome>());
+}
+
+criterion_group!(benches, reproduction);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use evo2::bench::scenario_world;
+use evo2::cell::RandomMutationNumberSource;
+
+fn world_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::step");
+    group.sample_size(10);
+    for num_cells in [1_000, 10_000, 100_000] {
+        for (food_name, food_per_cell) in [("scarce", 0.5), ("plentiful", 5.0)] {
+            group.throughput(Throughput::Elements(num_cells as u64));
+            group.bench_with_input(
+                BenchmarkId::new(food_name, num_cells),
+                &num_cells,
+                |b, &num_cells| {
+                    b.iter_batched(
+                        || (scenario_world(num_cells, food_per_cell), RandomMutationNumberSource::with_seed(0)),
+                        |(mut world, mut mutation_number_source)| world.step(&mut mutation_number_source),
+                        BatchSize::LargeInput)
+                });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, world_step);
+criterion_main!(benches);
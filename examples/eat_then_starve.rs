@@ -1,4 +1,5 @@
-use evo2::cell::{Cell, CellConstants, CellParams};
+use evo2::cell::{Cell, CellConstants};
+use evo2::genome::Genome;
 use evo2::main_support::run;
 use evo2::world::World;
 use std::rc::Rc;
@@ -15,10 +16,10 @@ fn main() {
                     health_reduction_per_energy_expended: 0.1.into(),
                     ..CellConstants::DEFAULT
                 }),
-                CellParams {
+                Genome {
                     attempted_eating_energy: 1.into(),
                     attempted_healing_energy: 2.into(),
-                    ..CellParams::DEFAULT
+                    ..Genome::DEFAULT
                 })
                 .with_energy(10.into()),
         ])
@@ -0,0 +1,90 @@
+//! A window onto a running world, redrawn after every step. Takes the same options as the
+//! command line, e.g. `cargo run --features viz --example live_view -- --scenario malthus`.
+//!
+//! The background brightens with the world's food, relative to the most it has held so far.
+//! Cells are dots laid out in population order, colored by `--color-by` or, after pressing L,
+//! by the founder of their lineage. Space pauses and resumes.
+
+use evo2::coloring::Rgb;
+use evo2::main_support::{create_world_and_mutation_number_source, Args};
+use evo2::phylogeny::Phylogeny;
+use evo2::world::World;
+use macroquad::color::{Color, WHITE};
+use macroquad::input::{is_key_pressed, KeyCode};
+use macroquad::shapes::draw_circle;
+use macroquad::text::draw_text;
+use macroquad::window::{clear_background, next_frame, screen_height, screen_width};
+
+const MARGIN: f32 = 30.0;
+
+#[macroquad::main("evo2")]
+async fn main() {
+    let args = Args::parse_with_scenario();
+    if let Err(message) = args.validate() {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    }
+    let (mut world, mut mutation_number_source) = create_world_and_mutation_number_source(&args);
+    let mut phylogeny = Phylogeny::new(&world);
+    let mut max_food = world.food().value();
+    let mut step = 0;
+    let mut paused = false;
+    let mut by_lineage = false;
+
+    loop {
+        if is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+        }
+        if is_key_pressed(KeyCode::L) {
+            by_lineage = !by_lineage;
+        }
+        if !paused && step < args.run.steps && world.num_cells() > 0 {
+            world.step_observed(&mut mutation_number_source, &mut phylogeny);
+            step += 1;
+            max_food = max_food.max(world.food().value());
+        }
+
+        draw_world(&world, &args, &phylogeny, max_food, by_lineage);
+        draw_text(format!("step {}: {} cells, food {:.1}{}", step, world.num_cells(), world.food(),
+                          if paused { " (paused)" } else { "" }),
+                  10.0, 20.0, 20.0, WHITE);
+        next_frame().await;
+    }
+}
+
+fn draw_world(world: &World, args: &Args, phylogeny: &Phylogeny, max_food: f32, by_lineage: bool) {
+    let food_intensity = if max_food > 0.0 { world.food().value() / max_food } else { 0.0 };
+    clear_background(Color::new(0.0, 0.3 * food_intensity, 0.0, 1.0));
+
+    let num_cells = world.num_cells();
+    if num_cells == 0 {
+        return;
+    }
+    let columns = (num_cells as f32).sqrt().ceil() as usize;
+    let rows = num_cells.div_ceil(columns);
+    let spacing = ((screen_width() - 2.0 * MARGIN) / columns as f32)
+        .min((screen_height() - 2.0 * MARGIN) / rows as f32);
+    let radius = (spacing * 0.4).max(1.0);
+    let cells = world.cells();
+    for cell in cells.iter() {
+        let index = cell.index();
+        let rgb = if by_lineage {
+            lineage_color(founder(phylogeny, cell.id()))
+        } else {
+            args.run.color_by.color(&cell)
+        };
+        let x = MARGIN + ((index % columns) as f32 + 0.5) * spacing;
+        let y = MARGIN + ((index / columns) as f32 + 0.5) * spacing;
+        draw_circle(x, y, radius, Color::from_rgba(rgb.0, rgb.1, rgb.2, 255));
+    }
+}
+
+/// The earliest tracked ancestor of the cell, or the cell itself if it is a founder.
+fn founder(phylogeny: &Phylogeny, id: u64) -> u64 {
+    phylogeny.ancestors(id).last().copied().unwrap_or(id)
+}
+
+/// Spreads founders' hues by the golden angle, so that lineages with nearby ids look distinct.
+fn lineage_color(founder: u64) -> Rgb {
+    Rgb::from_hsv((founder as f32 * 137.5) % 360.0, 0.8, 1.0)
+}
@@ -1,4 +1,5 @@
-use evo2::cell::{Cell, CellConstants, CellParams};
+use evo2::cell::{Cell, CellConstants};
+use evo2::genome::Genome;
 use evo2::food_sources::LinearlyGrowingFoodSource;
 use evo2::main_support::run;
 use evo2::world::World;
@@ -15,14 +16,16 @@ fn main() {
                     health_increase_per_healing_energy: 0.5.into(),
                     health_reduction_from_entropy: 0.5.into(),
                     health_reduction_per_energy_expended: 0.1.into(),
+                    maintenance_energy: 0.5.into(),
+                    health_reduction_per_maintenance_shortfall: 0.2.into(),
                     ..CellConstants::DEFAULT
                 }),
-                CellParams {
+                Genome {
                     attempted_eating_energy: 1.into(),
                     attempted_healing_energy: 2.into(),
                     child_threshold_energy: 2.into(),
                     child_threshold_food: 1.into(),
-                    ..CellParams::DEFAULT
+                    ..Genome::DEFAULT
                 })
                 .with_energy(10.into()),
         ])
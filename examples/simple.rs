@@ -1,8 +1,8 @@
-use evo2::main_support::{create_and_run_world, Args};
+use evo2::main_support::{create_and_run_world, Args, RunArgs};
 
 fn main() {
     create_and_run_world(&Args {
-        steps: 20,
+        run: RunArgs { steps: 20, ..RunArgs::DEFAULT },
         ..Args::DEFAULT
     });
 }
@@ -0,0 +1,174 @@
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::thread;
+use std::thread::JoinHandle;
+
+/// Writes on a background thread, so that a run isn't held up between steps by a slow terminal
+/// or disk. Writes collect in a buffer that goes to the thread whenever it fills. `flush` sends
+/// what is left and waits until the thread has written and flushed everything sent so far,
+/// returning the first error in writing any of it. Dropping the writer also writes everything,
+/// but can't report errors. If the thread falls more than a few buffers behind, writes wait for
+/// it, so that output can't pile up in memory.
+pub struct AsyncWriter {
+    buffer: Vec<u8>,
+    sender: Option<SyncSender<Message>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    Write(Vec<u8>),
+    Flush(SyncSender<io::Result<()>>),
+}
+
+impl AsyncWriter {
+    pub const BUFFER_SIZE: usize = 64 * 1024;
+    const MAX_PENDING_BUFFERS: usize = 16;
+
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(Self::MAX_PENDING_BUFFERS);
+        let thread = thread::spawn(move || write_messages(writer, receiver));
+        AsyncWriter {
+            buffer: Vec::with_capacity(Self::BUFFER_SIZE),
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let buffer = mem::replace(&mut self.buffer, Vec::with_capacity(Self::BUFFER_SIZE));
+        self.send(Message::Write(buffer))
+    }
+
+    fn send(&self, message: Message) -> io::Result<()> {
+        match &self.sender {
+            Some(sender) => sender.send(message).map_err(|_| thread_stopped()),
+            None => Err(thread_stopped()),
+        }
+    }
+}
+
+impl Write for AsyncWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= Self::BUFFER_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()?;
+        let (reply_sender, reply_receiver) = mpsc::sync_channel(1);
+        self.send(Message::Flush(reply_sender))?;
+        reply_receiver.recv().unwrap_or_else(|_| Err(thread_stopped()))
+    }
+}
+
+impl Drop for AsyncWriter {
+    fn drop(&mut self) {
+        let _ = self.send_buffer();
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn thread_stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "output thread has stopped")
+}
+
+/// Writes until the `AsyncWriter` is dropped. After an error, skips writes until the next flush
+/// has reported it.
+fn write_messages<W: Write>(mut writer: W, receiver: Receiver<Message>) {
+    let mut error = None;
+    for message in receiver {
+        match message {
+            Message::Write(buffer) => {
+                if error.is_none() {
+                    error = writer.write_all(&buffer).err();
+                }
+            }
+            Message::Flush(reply_sender) => {
+                let result = match error.take() {
+                    Some(err) => Err(err),
+                    None => writer.flush(),
+                };
+                let _ = reply_sender.send(result);
+            }
+        }
+    }
+    let _ = writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flush_waits_for_everything_written() {
+        let buffer = SharedBuffer::default();
+        let mut writer = AsyncWriter::new(buffer.clone());
+        let line = "x".repeat(1000);
+        for step in 0..200 {
+            writeln!(writer, "{}: {}", step, line).unwrap();
+        }
+        writer.flush().unwrap();
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(written.lines().count(), 200);
+        assert!(written.lines().enumerate().all(|(step, written_line)| written_line == format!("{}: {}", step, line)));
+    }
+
+    #[test]
+    fn dropping_writes_everything() {
+        let buffer = SharedBuffer::default();
+        let mut writer = AsyncWriter::new(buffer.clone());
+        write!(writer, "last words").unwrap();
+        drop(writer);
+        assert_eq!(*buffer.0.lock().unwrap(), b"last words");
+    }
+
+    #[test]
+    fn flush_reports_write_errors() {
+        let mut writer = AsyncWriter::new(FailingWriter);
+        write!(writer, "lost").unwrap();
+        assert_eq!(writer.flush().unwrap_err().to_string(), "disk full");
+        writer.flush().unwrap();
+    }
+}
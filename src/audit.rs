@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Whether `World::step` checks its books each step, and what it does when they do not balance,
+/// for catching modeling bugs such as food eaten twice or energy made from nothing. An audit
+/// checks that the food, protein, and toxin pools change by exactly what was added, eaten,
+/// decayed, neutralized, and left behind by spending, and that cells end each phase of a step
+/// with no more energy than their food, protein, light, thefts, and trades could have given
+/// them. Energy is not conserved exactly, since cells lose it to maintenance, spending, excess
+/// storage, `create_child_energy`, and donations, so the energy checks are bounds that those
+/// sinks cannot break rather than balances.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Audit {
+    #[default]
+    Off,
+    /// Logs each imbalance as a warning and carries on.
+    Log,
+    /// Panics at the first imbalance.
+    Panic,
+}
+
+/// Relative to the larger of the amounts compared, for the rounding of sums of `f32`s.
+const TOLERANCE: f64 = 1e-4;
+
+impl Audit {
+    pub fn is_on(self) -> bool {
+        self != Audit::Off
+    }
+
+    /// Reports an imbalance if `actual` differs from `expected`.
+    pub(crate) fn check_balance(self, step: u32, what: &str, actual: f64, expected: f64) {
+        if (actual - expected).abs() > Self::tolerance(actual, expected) {
+            self.report(step, format!("{} is {} but should be {}", what, actual, expected));
+        }
+    }
+
+    /// Reports an imbalance if `actual` is more than `limit`.
+    pub(crate) fn check_limit(self, step: u32, what: &str, actual: f64, limit: f64) {
+        if actual - limit > Self::tolerance(actual, limit) {
+            self.report(step, format!("{} is {} but should be at most {}", what, actual, limit));
+        }
+    }
+
+    fn tolerance(amount1: f64, amount2: f64) -> f64 {
+        TOLERANCE * amount1.abs().max(amount2.abs()).max(1.0)
+    }
+
+    fn report(self, step: u32, imbalance: String) {
+        match self {
+            Audit::Off => {}
+            Audit::Log => warn!(step, "audit: {}", imbalance),
+            Audit::Panic => panic!("audit failed in step {}: {}", step, imbalance),
+        }
+    }
+}
+
+impl FromStr for Audit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Audit::Off),
+            "log" => Ok(Audit::Log),
+            "panic" => Ok(Audit::Panic),
+            _ => Err(format!("unknown audit mode '{}' (expected off, log, or panic)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Audit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Audit::Off => "off",
+            Audit::Log => "log",
+            Audit::Panic => "panic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_modes_parse_from_their_names() {
+        for audit in [Audit::Off, Audit::Log, Audit::Panic] {
+            assert_eq!(audit.to_string().parse(), Ok(audit));
+        }
+        assert!("loud".parse::<Audit>().is_err());
+    }
+
+    #[test]
+    fn balances_within_rounding_pass() {
+        Audit::Panic.check_balance(0, "food", 1000.0, 1000.01);
+        Audit::Panic.check_limit(0, "energy", 2.00001, 2.0);
+        Audit::Log.check_balance(0, "food", 1.0, 2.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "audit failed in step 3: food is 1 but should be 2")]
+    fn imbalance_panics() {
+        Audit::Panic.check_balance(3, "food", 1.0, 2.0);
+    }
+}
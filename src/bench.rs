@@ -0,0 +1,75 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::cell::{Cell, CellConstants, RandomMutationNumberSource};
+use crate::food_sources::ConstantFoodSource;
+use crate::genome::Genome;
+use crate::world::World;
+
+/// A fixed, seeded scenario for measuring `World::step`: `num_cells` cells that eat, heal, and
+/// reproduce, with `food_per_cell` food per starting cell added each step.
+pub fn scenario_world(num_cells: usize, food_per_cell: f32) -> World {
+    let constants = Rc::new(CellConstants {
+        create_child_energy: 1.into(),
+        energy_yield_from_digestion: 1.into(),
+        food_yield_from_eating: 2.into(),
+        health_increase_per_healing_energy: 0.1.into(),
+        health_reduction_from_entropy: 0.05.into(),
+        health_reduction_per_energy_expended: 0.01.into(),
+        attempted_eating_energy_mutation_stdev: 0.05.into(),
+        attempted_healing_energy_mutation_stdev: 0.05.into(),
+        child_threshold_energy_mutation_stdev: 0.05.into(),
+        ..CellConstants::DEFAULT
+    });
+    let cell = Cell::new(
+        &constants,
+        Genome {
+            attempted_eating_energy: 1.into(),
+            attempted_healing_energy: 0.5.into(),
+            child_threshold_energy: 5.into(),
+            child_threshold_food: 0.into(),
+            ..Genome::DEFAULT
+        })
+        .with_energy(10.into());
+
+    World::new()
+        .with_seed(0)
+        .with_cells(vec![cell; num_cells])
+        .with_food_sources(vec![
+            Box::new(ConstantFoodSource::new((food_per_cell * num_cells as f32).into()))
+        ])
+}
+
+pub struct BenchResult {
+    pub num_steps: u32,
+    pub cell_steps: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn steps_per_second(&self) -> f64 {
+        self.num_steps as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn cell_steps_per_second(&self) -> f64 {
+        self.cell_steps as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Times `num_steps` steps of the world, stopping early if every cell dies.
+pub fn time_steps(world: &mut World, num_steps: u32) -> BenchResult {
+    let mut mutation_number_source = RandomMutationNumberSource::with_seed(0);
+    let mut result = BenchResult {
+        num_steps: 0,
+        cell_steps: 0,
+        elapsed: Duration::ZERO,
+    };
+
+    let start = Instant::now();
+    while result.num_steps < num_steps && world.num_cells() > 0 {
+        result.cell_steps += world.num_cells() as u64;
+        world.step(&mut mutation_number_source);
+        result.num_steps += 1;
+    }
+    result.elapsed = start.elapsed();
+    result
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::cell::{saturation, CellConstants, CellState};
+use crate::genome::Genome;
+use crate::number_types::{F32Positive, F32ZeroToOne};
+
+/// What decides how hard a cell tries at eating, healing, and reproducing. `Fixed` cells always
+/// try as hard as their genes say. `Net` cells scale those efforts by the outputs of their brains.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellController {
+    #[default]
+    Fixed,
+    Net,
+}
+
+impl FromStr for CellController {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(CellController::Fixed),
+            "net" => Ok(CellController::Net),
+            _ => Err(format!("unknown cell controller '{}' (expected fixed or net)", s)),
+        }
+    }
+}
+
+impl fmt::Display for CellController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CellController::Fixed => "fixed",
+            CellController::Net => "net",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A bias of 1 and the cell's energy, health, food per cell, and age, in that order.
+pub const NUM_BRAIN_INPUTS: usize = 5;
+
+/// The weights of a tiny feedforward network with no hidden layer, connecting each of the
+/// brain's inputs to each of its outputs. Each weight is a gene from 0 to 1 standing for a weight
+/// from -1 to 1, so that a weight of 0.5 is no connection and a brain of them changes nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrainWeights {
+    pub eating: [F32ZeroToOne; NUM_BRAIN_INPUTS],
+    pub healing: [F32ZeroToOne; NUM_BRAIN_INPUTS],
+    pub reproduction: [F32ZeroToOne; NUM_BRAIN_INPUTS],
+}
+
+impl BrainWeights {
+    pub const NEUTRAL: BrainWeights = BrainWeights::uniform(F32ZeroToOne::unchecked(0.5));
+
+    pub const ZERO: BrainWeights = BrainWeights::uniform(F32ZeroToOne::unchecked(0.0));
+
+    pub const fn uniform(weight: F32ZeroToOne) -> Self {
+        BrainWeights {
+            eating: [weight; NUM_BRAIN_INPUTS],
+            healing: [weight; NUM_BRAIN_INPUTS],
+            reproduction: [weight; NUM_BRAIN_INPUTS],
+        }
+    }
+
+    pub fn efforts(&self, inputs: &[f32; NUM_BRAIN_INPUTS]) -> Efforts {
+        Efforts {
+            eating: effort(&self.eating, inputs),
+            healing: effort(&self.healing, inputs),
+            reproduction: effort(&self.reproduction, inputs),
+        }
+    }
+}
+
+impl Default for BrainWeights {
+    fn default() -> Self {
+        Self::NEUTRAL
+    }
+}
+
+/// Factors multiplying a cell's attempted eating and healing energies and the energy it puts
+/// toward a child. A cell whose reproduction effort is below 1 can't afford its child threshold
+/// energy, so it waits; above 1, it gives its children more.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Efforts {
+    pub eating: F32Positive,
+    pub healing: F32Positive,
+    pub reproduction: F32Positive,
+}
+
+impl Efforts {
+    pub const FULL: Efforts = Efforts {
+        eating: F32Positive::unchecked(1.0),
+        healing: F32Positive::unchecked(1.0),
+        reproduction: F32Positive::unchecked(1.0),
+    };
+}
+
+/// The cell's efforts under its `CellController`.
+pub(crate) fn efforts(constants: &CellConstants, genome: &Genome, energy: F32Positive, health: F32ZeroToOne, state: &CellState) -> Efforts {
+    match constants.controller {
+        CellController::Fixed => Efforts::FULL,
+        CellController::Net => genome.brain.efforts(&inputs(constants, genome, energy, health, state)),
+    }
+}
+
+/// The brain's inputs, each from 0 to 1: energy and the food the cell remembers saturate at its
+/// `state_energy_scale` and `state_food_scale`, as for its internal state, and age is the
+/// fraction of `max_age` the cell has lived.
+fn inputs(constants: &CellConstants, genome: &Genome, energy: F32Positive, health: F32ZeroToOne, state: &CellState) -> [f32; NUM_BRAIN_INPUTS] {
+    [
+        1.0,
+        saturation(energy, genome.state_energy_scale),
+        health.value(),
+        saturation(state.remembered_food.unwrap_or(0.into()), genome.state_food_scale),
+        state.age as f32 / constants.max_age as f32,
+    ]
+}
+
+/// Twice the logistic function of the weighted inputs, running from 0 to 2 and passing through 1
+/// when they add up to 0.
+fn effort(weights: &[F32ZeroToOne; NUM_BRAIN_INPUTS], inputs: &[f32; NUM_BRAIN_INPUTS]) -> F32Positive {
+    let net_input: f32 = weights.iter().zip(inputs)
+        .map(|(weight, input)| (2.0 * weight.value() - 1.0) * input)
+        .sum();
+    F32Positive::clipped(2.0 / (1.0 + (-net_input).exp()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controllers_parse_from_their_names() {
+        for controller in [CellController::Fixed, CellController::Net] {
+            assert_eq!(controller.to_string().parse(), Ok(controller));
+        }
+        assert!("fuzzy".parse::<CellController>().is_err());
+    }
+
+    #[test]
+    fn neutral_brain_changes_nothing() {
+        assert_eq!(BrainWeights::NEUTRAL.efforts(&[1.0, 0.5, 1.0, 0.25, 0.0]), Efforts::FULL);
+    }
+
+    #[test]
+    fn weights_push_efforts_up_or_down() {
+        let weights = BrainWeights {
+            eating: [0.5.into(), 0.0.into(), 0.5.into(), 0.5.into(), 0.5.into()],
+            healing: [0.5.into(), 0.5.into(), 1.0.into(), 0.5.into(), 0.5.into()],
+            ..BrainWeights::NEUTRAL
+        };
+        let efforts = weights.efforts(&[1.0, 1.0, 1.0, 0.0, 0.0]);
+        assert!(efforts.eating.value() < 1.0);
+        assert!(efforts.healing.value() > 1.0);
+        assert_eq!(efforts.reproduction.value(), 1.0);
+    }
+}
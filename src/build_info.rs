@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Identifies the code that produced a result: crate version, git commit (suffixed with
+/// `-dirty` if there were uncommitted changes), and cargo build profile.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_commit: String,
+    pub profile: String,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("EVO2_GIT_COMMIT").to_string(),
+            profile: env!("EVO2_BUILD_PROFILE").to_string(),
+        }
+    }
+}
+
+impl Default for BuildInfo {
+    fn default() -> Self {
+        BuildInfo {
+            version: "unknown".to_string(),
+            git_commit: "unknown".to_string(),
+            profile: "unknown".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "evo2 {} ({}, {})", self.version, self.git_commit, self.profile)
+    }
+}
@@ -1,168 +1,849 @@
-use rand::rngs::ThreadRng;
-use rand_distr::{Normal, Distribution};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::rc::Rc;
+use crate::brain::{efforts, CellController, Efforts};
+use crate::genome::Genome;
+use crate::mutation_models::MutationModel;
 use crate::number_types::{F32Positive, F32ZeroToOne, F32ZeroToOnePerF32Positive};
+use crate::process_order::{CellBehavior, CellBehaviors, CellProcess, ProcessOrder};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Cell {
     constants: Rc<CellConstants>,
-    params: CellParams,
+    genome: Rc<Genome>,
+    energy: F32Positive,
+    health: F32ZeroToOne,
     state: CellState,
 }
 
 impl Cell {
-    pub fn new(constants: &Rc<CellConstants>, params: CellParams) -> Self {
+    pub fn new(constants: &Rc<CellConstants>, genome: Genome) -> Self {
         Cell {
             constants: Rc::clone(constants),
-            params,
+            genome: Rc::new(genome),
+            energy: 0.0.into(),
+            health: 1.0.into(),
             state: CellState::DEFAULT,
         }
     }
 
+    pub(crate) fn from_parts(constants: Rc<CellConstants>, genome: Rc<Genome>, energy: F32Positive, health: F32ZeroToOne, state: CellState) -> Self {
+        Cell { constants, genome, energy, health, state }
+    }
+
+    pub(crate) fn into_parts(self) -> (Rc<CellConstants>, Rc<Genome>, F32Positive, F32ZeroToOne, CellState) {
+        (self.constants, self.genome, self.energy, self.health, self.state)
+    }
+
     pub fn with_health(mut self, health: F32ZeroToOne) -> Self {
-        self.state.health = health;
+        self.health = health;
         self
     }
 
     pub fn with_energy(mut self, energy: F32Positive) -> Self {
-        self.state.energy = energy;
+        self.energy = energy;
         self
     }
 
+    pub fn constants(&self) -> &CellConstants {
+        &self.constants
+    }
+
+    pub fn genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    pub fn state(&self) -> &CellState {
+        &self.state
+    }
+
+    /// Unique among the cells of a world, which assigns it when the cell is added.
+    pub fn id(&self) -> u64 {
+        self.state.id
+    }
+
+    pub fn parent_id(&self) -> Option<u64> {
+        self.state.parent_id
+    }
+
+    pub fn num_children(&self) -> u32 {
+        self.state.num_children
+    }
+
+    pub(crate) fn set_id(&mut self, id: u64) {
+        self.state.id = id;
+    }
+
     pub fn health(&self) -> F32ZeroToOne {
-        self.state.health
+        self.health
     }
 
     pub fn energy(&self) -> F32Positive {
-        self.state.energy
+        self.energy
+    }
+
+    pub fn age(&self) -> u32 {
+        self.state.age
     }
 
     pub fn is_alive(&self) -> bool {
         self.health().value() > 0.0
     }
 
-    pub fn step(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment) -> (Option<Cell>, F32Positive) {
+    pub fn is_dormant(&self) -> bool {
+        self.state.dormant
+    }
+
+    /// What took the last of the cell's health, if it has none left or was culled.
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        self.state.death_cause
+    }
+
+    pub fn attack_energy(&self) -> F32Positive {
+        self.state.attack_energy
+    }
+
+    pub fn trade_energy(&self) -> F32Positive {
+        self.state.trade_energy
+    }
+
+    pub fn as_cell_mut(&mut self) -> CellMut<'_> {
+        CellMut::new(&self.constants, &mut self.genome, &mut self.energy, &mut self.health, &mut self.state)
+    }
+
+    pub fn step(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment) -> CellStepOutcome {
+        self.as_cell_mut().step(mutation_number_source, environment)
+    }
+
+    pub fn step_with_mate(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment, mate: &mut dyn MateSource) -> CellStepOutcome {
+        self.as_cell_mut().step_with_mate(mutation_number_source, environment, mate)
+    }
+
+    pub fn attack(&mut self, victim: &mut Cell) {
+        self.as_cell_mut().attack(&mut victim.as_cell_mut());
+    }
+
+    pub fn trade(&mut self, partner: &mut Cell) {
+        self.as_cell_mut().trade(&mut partner.as_cell_mut());
+    }
+
+    pub fn withdraw_trade_offer(&mut self) {
+        self.as_cell_mut().withdraw_trade_offer();
+    }
+
+    pub fn donate(&mut self, recipient: &mut Cell) -> F32Positive {
+        self.as_cell_mut().donate(&mut recipient.as_cell_mut())
+    }
+
+    pub fn face_death_hazard<R: Rng>(&mut self, rng: &mut R) {
+        self.as_cell_mut().face_death_hazard(rng);
+    }
+
+    pub fn reproduce_from_accumulated_energy(&mut self, mate: &mut dyn MateSource, mutation_number_source: &mut dyn MutationNumberSource) -> Vec<Cell> {
+        self.as_cell_mut().reproduce_from_accumulated_energy(mate, mutation_number_source)
+    }
+}
+
+/// Mutable access to the parts of a cell wherever they are stored, such as in `CellColumns`.
+/// Cell behavior is implemented here so that it can run without assembling an owned `Cell`.
+pub struct CellMut<'a> {
+    constants: &'a Rc<CellConstants>,
+    genome: &'a mut Rc<Genome>,
+    energy: &'a mut F32Positive,
+    health: &'a mut F32ZeroToOne,
+    state: &'a mut CellState,
+}
+
+impl<'a> CellMut<'a> {
+    pub fn new(constants: &'a Rc<CellConstants>, genome: &'a mut Rc<Genome>, energy: &'a mut F32Positive, health: &'a mut F32ZeroToOne, state: &'a mut CellState) -> Self {
+        CellMut { constants, genome, energy, health, state }
+    }
+
+    pub fn step(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment) -> CellStepOutcome {
+        self.step_with_mate(mutation_number_source, environment, &mut NoMate)
+    }
+
+    /// Like `step`, but any child is a crossover of this cell's genome and that of a mate from
+    /// `mate`, which is only asked for one if the cell reproduces.
+    pub fn step_with_mate(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment, mate: &mut dyn MateSource) -> CellStepOutcome {
+        self.update_dormancy(environment.food_per_cell);
+        self.pay_maintenance();
+        self.starve();
+        self.poison(environment.toxin_per_cell);
+        self.remember_food(environment.food_per_cell);
+        self.update_internal_state(environment.food_per_cell);
+        if self.state.dormant {
+            return self.rest(environment);
+        }
+
         let (total_budgeted, budgeted_energies, child) =
-            self.budget_and_maybe_reproduce(mutation_number_source, environment);
+            self.budget_and_maybe_reproduce(mutation_number_source, environment, mate);
 
         self.expend_energy(total_budgeted);
+        let toxin_neutralized = self.detox(budgeted_energies.detox, environment.toxin_per_cell);
+
+        self.state.attack_energy = budgeted_energies.attack;
+        self.state.trade_energy = budgeted_energies.trade;
+        let mut context = BehaviorContext::new(environment, budgeted_energies);
+        let constants = self.constants;
+        for process in constants.process_order.processes() {
+            process.run(self, &mut context);
+        }
+        for behavior in constants.behaviors.iter() {
+            behavior.run(self, &mut context);
+        }
+        self.grow_older();
+
+        CellStepOutcome {
+            child,
+            food_eaten: context.food_eaten,
+            food_beyond_intake: context.food_beyond_intake,
+            protein_eaten: context.protein_eaten,
+            energy_expended: total_budgeted,
+            toxin_neutralized,
+        }
+    }
+
+    pub fn constants(&self) -> &CellConstants {
+        self.constants
+    }
+
+    pub fn genome(&self) -> &Genome {
+        self.genome
+    }
+
+    pub fn energy(&self) -> F32Positive {
+        *self.energy
+    }
+
+    pub fn health(&self) -> F32ZeroToOne {
+        *self.health
+    }
+
+    pub fn state(&self) -> &CellState {
+        self.state
+    }
+
+    /// For behaviors that change the cell in ways its own methods do not.
+    pub fn energy_mut(&mut self) -> &mut F32Positive {
+        self.energy
+    }
+
+    /// For behaviors that change the cell in ways its own methods do not.
+    pub fn state_mut(&mut self) -> &mut CellState {
+        self.state
+    }
+
+    /// Spends the attack energy budgeted during this step on damaging the victim and taking
+    /// some of its energy.
+    pub fn attack(&mut self, victim: &mut CellMut) {
+        let attack_energy = self.state.attack_energy - victim.constants.attack_defense;
+        self.state.attack_energy = 0.into();
+
+        victim.lose_health(attack_energy * self.constants.health_reduction_per_attack_energy, DeathCause::Attack);
+        let stolen_energy = (attack_energy * self.constants.attack_efficiency).min(*victim.energy);
+        *victim.energy -= stolen_energy;
+        *self.energy += stolen_energy;
+    }
+
+    /// Exchanges the trade energies budgeted during this step by this cell and its partner. Each
+    /// cell receives what the other offered, scaled by the giver's `trade_efficiency`, and pays
+    /// for it with access to its food: it owes the giver `food_access_per_trade_energy` food per
+    /// unit of energy received from its share next step. Each cell remembers what it got when
+    /// deciding whether to offer energy in its next trade.
+    pub fn trade(&mut self, partner: &mut CellMut) {
+        let given = self.state.trade_energy * self.constants.trade_efficiency;
+        let received = partner.state.trade_energy * partner.constants.trade_efficiency;
+        self.receive_trade(received, partner.state.id);
+        partner.receive_trade(given, self.state.id);
+    }
+
+    /// Takes the food access the cell owes from its trade, if any.
+    pub fn take_food_access_owed(&mut self) -> Option<(u64, F32Positive)> {
+        self.state.food_access_owed.take()
+    }
+
+    /// Counts food access delivered by the cell's last trading partner toward what that partner
+    /// gave it, in trade energy at the price the cell itself asks.
+    pub fn receive_food_access(&mut self, food: F32Positive) {
+        if self.constants.food_access_per_trade_energy.value() > 0.0 {
+            let energy = food / self.constants.food_access_per_trade_energy;
+            self.state.last_trade_received = Some(self.state.last_trade_received.unwrap_or(0.into()) + energy);
+        }
+    }
+
+    /// Gives the recipient `donation_fraction` of the cell's energy beyond its
+    /// `donation_threshold`, if the recipient has less energy than that threshold. The recipient
+    /// gets the donation scaled by the giver's `donation_efficiency`. Returns the energy given.
+    pub fn donate(&mut self, recipient: &mut CellMut) -> F32Positive {
+        if *recipient.energy >= self.genome.donation_threshold {
+            return 0.into();
+        }
+
+        let surplus = *self.energy - self.genome.donation_threshold;
+        let donation = surplus * self.genome.donation_fraction;
+        *self.energy -= donation;
+        *recipient.energy += donation * self.constants.donation_efficiency;
+        donation
+    }
+
+    /// Kills the cell with its chance of dying this step whatever its condition:
+    /// `death_probability`, plus `death_probability_per_health_lost` for each unit of health it
+    /// lacks. Draws nothing from `rng` unless that chance is above zero.
+    pub fn face_death_hazard<R: Rng>(&mut self, rng: &mut R) {
+        if self.health.value() <= 0.0 {
+            return;
+        }
+        let probability = self.constants.death_probability.value()
+            + self.constants.death_probability_per_health_lost.value() * (1.0 - self.health.value());
+        if probability > 0.0 && rng.gen_bool(probability.min(1.0) as f64) {
+            self.die_of(DeathCause::Hazard);
+        }
+    }
+
+    /// Loses health to crowding in an overfull world.
+    pub fn suffer_crowding(&mut self, health_reduction: F32ZeroToOne) {
+        self.lose_health(health_reduction, DeathCause::Crowding);
+    }
+
+    /// Takes all the cell's health, as when it is culled.
+    pub fn die_of(&mut self, cause: DeathCause) {
+        *self.health = 0.0.into();
+        self.state.death_cause = Some(cause);
+    }
+
+    /// Records `cause` as the cause of death if this takes the last of the cell's health.
+    fn lose_health(&mut self, health_reduction: F32ZeroToOne, cause: DeathCause) {
+        if self.health.value() <= 0.0 {
+            return;
+        }
+        *self.health -= health_reduction;
+        if self.health.value() <= 0.0 {
+            self.state.death_cause = Some(cause);
+        }
+    }
+
+    /// Takes back trade energy that found no trading partner this step.
+    pub fn withdraw_trade_offer(&mut self) {
+        *self.energy += self.state.trade_energy;
+        self.state.trade_energy = 0.into();
+    }
+
+    fn receive_trade(&mut self, energy: F32Positive, giver_id: u64) {
+        *self.energy += energy;
+        self.state.last_trade_received = Some(energy);
+        self.state.trade_energy = 0.into();
+        let food_access = energy * self.constants.food_access_per_trade_energy;
+        self.state.food_access_owed = (food_access.value() > 0.0).then_some((giver_id, food_access));
+    }
+
+    /// A cell offers its full trade energy unless its last partner gave it less than its
+    /// `trade_reciprocity_threshold`. Cells that have never traded always offer.
+    fn trade_energy_to_offer(&self) -> F32Positive {
+        match self.state.last_trade_received {
+            Some(received) if received < self.genome.trade_reciprocity_threshold => 0.into(),
+            _ => self.genome.attempted_trade_energy,
+        }
+    }
+
+    /// A cell goes dormant when its energy is below its `dormancy_threshold_energy` while the food
+    /// per cell is below its `wake_threshold_food`, and wakes as soon as the food per cell reaches
+    /// that threshold.
+    fn update_dormancy(&mut self, food_per_cell: F32Positive) {
+        if food_per_cell >= self.genome.wake_threshold_food {
+            self.state.dormant = false;
+        } else if *self.energy < self.genome.dormancy_threshold_energy {
+            self.state.dormant = true;
+        }
+    }
+
+    /// A dormant cell budgets nothing, so it neither eats, heals, attacks, trades, nor
+    /// reproduces. It still suffers entropy and grows older.
+    fn rest(&mut self, environment: &CellEnvironment) -> CellStepOutcome {
+        self.entropy(environment.stress);
+        self.state.attack_energy = 0.into();
+        self.state.trade_energy = 0.into();
+        self.grow_older();
+        CellStepOutcome {
+            child: None,
+            food_eaten: 0.into(),
+            food_beyond_intake: 0.into(),
+            protein_eaten: 0.into(),
+            energy_expended: 0.into(),
+            toxin_neutralized: 0.into(),
+        }
+    }
+
+    /// Every living cell spends `maintenance_energy` for each unit of its size, plus
+    /// `maintenance_energy_per_storage` for each unit of its `max_energy_storage`,
+    /// `maintenance_energy_per_attempted_energy` for each unit of its total attempted energy, and
+    /// `maintenance_energy_per_efficiency` for each unit of its eating and digestion
+    /// efficiencies, and `maintenance_energy_per_stress_tolerance` for each unit of its stress
+    /// tolerance, each step before budgeting anything else, or only
+    /// `dormant_maintenance_fraction` of that while dormant. A cell that cannot pay it all loses
+    /// health in proportion to the shortfall.
+    fn pay_maintenance(&mut self) {
+        let mut maintenance_energy = self.constants.maintenance_energy * self.genome.size
+            + self.genome.max_energy_storage * self.constants.maintenance_energy_per_storage
+            + self.genome.total_attempted_energy() * self.constants.maintenance_energy_per_attempted_energy
+            + (self.genome.eating_efficiency + self.genome.digestion_efficiency) * self.constants.maintenance_energy_per_efficiency
+            + self.genome.stress_tolerance * self.constants.maintenance_energy_per_stress_tolerance;
+        if self.state.dormant {
+            maintenance_energy = maintenance_energy * self.constants.dormant_maintenance_fraction;
+        }
+        let shortfall = maintenance_energy - *self.energy;
+        *self.energy -= maintenance_energy;
+        self.lose_health(shortfall * self.constants.health_reduction_per_maintenance_shortfall, DeathCause::Starvation);
+    }
+
+    /// A cell with no energy left to budget loses `health_reduction_from_zero_energy`, so cells
+    /// that run dry starve to death within a few steps unless they find energy.
+    fn starve(&mut self) {
+        if self.energy.value() <= 0.0 {
+            self.lose_health(self.constants.health_reduction_from_zero_energy, DeathCause::Starvation);
+        }
+    }
 
-        let food = self.eat(budgeted_energies.eating, environment.food_per_cell);
-        self.digest(food);
-        self.entropy();
-        self.heal(budgeted_energies.healing);
+    /// A cell loses `health_reduction_per_toxin` for each unit of toxin per cell beyond its
+    /// `toxin_tolerance`.
+    fn poison(&mut self, toxin_per_cell: F32Positive) {
+        self.lose_health((toxin_per_cell - self.constants.toxin_tolerance) * self.constants.health_reduction_per_toxin, DeathCause::Toxin);
+    }
+
+    /// Spends detox energy neutralizing toxin, `toxin_yield_from_detox` per unit, up to the toxin
+    /// per cell. Returns the toxin neutralized.
+    fn detox(&mut self, detox_energy: F32Positive, toxin_per_cell: F32Positive) -> F32Positive {
+        (detox_energy * self.constants.toxin_yield_from_detox).min(toxin_per_cell)
+    }
+
+    /// Folds this step's food per cell into the cell's exponential moving average of it, weighting
+    /// the new value by `food_memory_rate`. A cell's first step sets the memory outright.
+    fn remember_food(&mut self, food_per_cell: F32Positive) {
+        let rate = self.genome.food_memory_rate.value();
+        self.state.remembered_food = Some(match self.state.remembered_food {
+            Some(remembered) => F32Positive::clipped(rate * food_per_cell.value() + (1.0 - rate) * remembered.value()),
+            None => food_per_cell,
+        });
+    }
+
+    /// Moves the cell's internal state `state_update_rate` of the way toward its input: the mean
+    /// of its energy and food per cell, each saturating as x / (x + scale) with the cell's
+    /// `state_energy_scale` or `state_food_scale`, so that the input and the state run from 0 to 1.
+    fn update_internal_state(&mut self, food_per_cell: F32Positive) {
+        let input = (saturation(*self.energy, self.genome.state_energy_scale)
+            + saturation(food_per_cell, self.genome.state_food_scale)) / 2.0;
+        let rate = self.genome.state_update_rate.value();
+        self.state.internal_state = F32ZeroToOne::clipped((1.0 - rate) * self.state.internal_state.value() + rate * input);
+    }
 
-        (child, food)
+    /// Scales an energy the cell would like to spend by its `scarcity_spending_fraction` while the
+    /// food it remembers is below its `scarce_food_threshold`, so that it can save energy for
+    /// better times.
+    fn discretionary(&self, energy: F32Positive) -> F32Positive {
+        match self.state.remembered_food {
+            Some(remembered) if remembered < self.genome.scarce_food_threshold =>
+                energy * F32Positive::unchecked(self.genome.scarcity_spending_fraction.value()),
+            _ => energy,
+        }
     }
 
-    fn budget_and_maybe_reproduce(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment) -> (F32Positive, CellEnergies, Option<Cell>) {
-        let (total_budgeted, budgeted_energies) = self.budget_including_reproduction();
+    fn budget_and_maybe_reproduce(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment, mate: &mut dyn MateSource) -> (F32Positive, CellEnergies, Option<Cell>) {
+        let (total_budgeted, budgeted_energies) = self.budget_including_reproduction(environment);
         if self.can_reproduce(budgeted_energies.reproduction, environment) {
             let child
-                = self.reproduce(budgeted_energies.reproduction, mutation_number_source);
+                = self.reproduce(budgeted_energies.reproduction, mate, mutation_number_source);
             (total_budgeted, budgeted_energies, child)
         } else {
-            let (total_budgeted, budgeted_energies) = self.budget_excluding_reproduction();
+            let (total_budgeted, budgeted_energies) = self.budget_excluding_reproduction(environment);
             (total_budgeted, budgeted_energies, None)
         }
     }
 
     #[allow(unused_assignments)]
-    fn budget_including_reproduction(&self) -> (F32Positive, CellEnergies) {
+    fn budget_including_reproduction(&self, environment: &CellEnvironment) -> (F32Positive, CellEnergies) {
         let mut total_budgeted = 0.into(); // make Rust plugin shut up about uninitialized var
         let mut budgeted_energies = CellEnergies::new();
         (total_budgeted,
          [budgeted_energies.reproduction,
              budgeted_energies.eating,
-             budgeted_energies.healing]) =
-            budget(self.state.energy,
-                   &[self.params.child_threshold_energy,
-                       self.params.attempted_eating_energy,
-                       self.params.attempted_healing_energy]);
+             budgeted_energies.healing,
+             budgeted_energies.attack,
+             budgeted_energies.photosynthesis,
+             budgeted_energies.trade,
+             budgeted_energies.detox]) =
+            budget(*self.energy,
+                   &[self.reproduction_energy(),
+                       self.discretionary(self.eating_energy_at(environment.time_of_day)),
+                       self.discretionary(self.healing_energy()),
+                       self.discretionary(self.genome.attempted_attack_energy),
+                       self.discretionary(self.genome.attempted_photosynthesis_energy),
+                       self.discretionary(self.trade_energy_to_offer()),
+                       self.discretionary(self.genome.attempted_detox_energy)],
+                   &[self.genome.reproduction_priority,
+                       self.genome.eating_priority,
+                       self.genome.healing_priority,
+                       self.genome.attack_priority,
+                       self.genome.photosynthesis_priority,
+                       self.genome.trade_priority,
+                       self.genome.detox_priority]);
         (total_budgeted, budgeted_energies)
     }
 
     #[allow(unused_assignments)]
-    fn budget_excluding_reproduction(&mut self) -> (F32Positive, CellEnergies) {
+    fn budget_excluding_reproduction(&mut self, environment: &CellEnvironment) -> (F32Positive, CellEnergies) {
         let mut total_budgeted = 0.into(); // make Rust plugin shut up about uninitialized var
         let mut budgeted_energies = CellEnergies::new();
-        (total_budgeted, [budgeted_energies.eating, budgeted_energies.healing]) =
-            budget(self.state.energy,
-                   &[self.params.attempted_eating_energy,
-                       self.params.attempted_healing_energy]);
+        (total_budgeted,
+         [budgeted_energies.eating,
+             budgeted_energies.healing,
+             budgeted_energies.attack,
+             budgeted_energies.photosynthesis,
+             budgeted_energies.trade,
+             budgeted_energies.detox]) =
+            budget(*self.energy,
+                   &[self.discretionary(self.eating_energy_at(environment.time_of_day)),
+                       self.discretionary(self.healing_energy()),
+                       self.discretionary(self.genome.attempted_attack_energy),
+                       self.discretionary(self.genome.attempted_photosynthesis_energy),
+                       self.discretionary(self.trade_energy_to_offer()),
+                       self.discretionary(self.genome.attempted_detox_energy)],
+                   &[self.genome.eating_priority,
+                       self.genome.healing_priority,
+                       self.genome.attack_priority,
+                       self.genome.photosynthesis_priority,
+                       self.genome.trade_priority,
+                       self.genome.detox_priority]);
         budgeted_energies.reproduction = 0.into();
         (total_budgeted, budgeted_energies)
     }
 
+    fn eating_energy_at(&self, time_of_day: F32ZeroToOne) -> F32Positive {
+        if self.is_active_at(time_of_day) {
+            self.genome.attempted_eating_energy * internal_state_factor(self.state.internal_state, self.genome.eating_state_response)
+                * self.efforts().eating
+        } else {
+            0.into()
+        }
+    }
+
+    fn healing_energy(&self) -> F32Positive {
+        self.genome.attempted_healing_energy * internal_state_factor(self.state.internal_state, self.genome.healing_state_response)
+            * self.efforts().healing
+    }
+
+    /// The energy the cell would like to put toward a child, its `child_threshold_energy` scaled
+    /// by its reproduction effort.
+    fn reproduction_energy(&self) -> F32Positive {
+        F32Positive::clipped((self.genome.child_threshold_energy.value() * self.efforts().reproduction.value()).min(f32::MAX))
+    }
+
+    fn efforts(&self) -> Efforts {
+        efforts(self.constants, self.genome, *self.energy, *self.health, self.state)
+    }
+
+    /// A cell is active for `activity_fraction` of each day, starting at its `active_phase`.
+    fn is_active_at(&self, time_of_day: F32ZeroToOne) -> bool {
+        let time_since_active = (time_of_day.value() - self.genome.active_phase.value()).rem_euclid(1.0);
+        time_since_active < self.constants.activity_fraction.value()
+            || self.constants.activity_fraction.value() >= 1.0
+    }
+
     fn can_reproduce(&self, reproduction_energy: F32Positive, environment: &CellEnvironment) -> bool {
-        reproduction_energy >= self.params.child_threshold_energy
-            && environment.food_per_cell >= self.params.child_threshold_food
+        environment.reproduction_allowed
+            && reproduction_energy >= self.genome.child_threshold_energy
+            && environment.food_per_cell >= self.genome.child_threshold_food
+    }
+
+    /// Spends all of the cell's accumulated energy on children, one per `child_threshold_energy`.
+    /// Used at the end of a non-overlapping generation, when the parents are about to be replaced.
+    pub fn reproduce_from_accumulated_energy(&mut self, mate: &mut dyn MateSource, mutation_number_source: &mut dyn MutationNumberSource) -> Vec<Cell> {
+        let child_energy = self.genome.child_threshold_energy;
+        if child_energy.value() <= 0.0 {
+            return vec![];
+        }
+
+        let num_children = (*self.energy / child_energy).value().floor() as usize;
+        let mut children = Vec::with_capacity(num_children);
+        for _ in 0..num_children {
+            if let Some(child) = self.reproduce(child_energy, mate, mutation_number_source) {
+                children.push(child);
+            }
+            *self.energy -= child_energy;
+        }
+        children
     }
 
-    fn reproduce(&mut self, reproduction_energy: F32Positive, mutation_number_source: &mut dyn MutationNumberSource) -> Option<Cell> {
-        let mut child = self.clone();
-        child.mutate(mutation_number_source);
-        child.state.health = 1.0.into();
-        child.state.energy = reproduction_energy - self.constants.create_child_energy;
+    /// Creates a child for a generation built by selecting parents. The child starts with
+    /// `energy`, which costs the parent nothing, since a selected parent's energy measures its
+    /// fitness rather than being passed on.
+    pub fn create_selected_child(&mut self, energy: F32Positive, mate: &mut dyn MateSource, mutation_number_source: &mut dyn MutationNumberSource) -> Option<Cell> {
+        let mut child = self.reproduce(energy, mate, mutation_number_source)?;
+        child.energy = energy;
+        Some(child)
+    }
+
+    fn reproduce(&mut self, reproduction_energy: F32Positive, mate: &mut dyn MateSource, mutation_number_source: &mut dyn MutationNumberSource) -> Option<Cell> {
+        mutation_number_source.start_stream(self.state.id, self.state.num_children);
+        self.state.num_children += 1;
+        let mut child = Cell::from_parts(Rc::clone(self.constants), Rc::clone(self.genome), *self.energy, *self.health, *self.state);
+        mate.cross_over(&mut child.genome, self.constants.crossover_probability);
+        child.as_cell_mut().mutate(mutation_number_source);
+        child.health = 1.0.into();
+        child.energy = reproduction_energy - self.constants.create_child_energy * self.genome.size;
+        child.state.age = 0;
+        child.state.last_trade_received = None;
+        child.state.food_access_owed = None;
+        child.state.remembered_food = None;
+        child.state.internal_state = 0.0.into();
+        child.state.dormant = false;
+        child.state.death_cause = None;
+        child.state.parent_id = Some(self.state.id);
+        child.state.num_children = 0;
         Some(child)
     }
 
     fn mutate(&mut self, mutation_number_source: &mut dyn MutationNumberSource) {
-        self.params.attempted_eating_energy = mutation_number_source.mutate(
-            self.params.attempted_eating_energy, self.constants.attempted_eating_energy_mutation_stdev);
-        self.params.attempted_healing_energy = mutation_number_source.mutate(
-            self.params.attempted_healing_energy, self.constants.attempted_healing_energy_mutation_stdev);
-        self.params.child_threshold_energy = mutation_number_source.mutate(
-            self.params.child_threshold_energy, self.constants.child_threshold_energy_mutation_stdev);
-        self.params.child_threshold_food = mutation_number_source.mutate(
-            self.params.child_threshold_food, self.constants.child_threshold_food_mutation_stdev);
+        Genome::mutate(self.genome, self.constants, mutation_number_source);
+    }
+
+    /// Returns the food eaten, which is at most `max_food_intake` for each unit of the cell's
+    /// size, and any food beyond that the cell's eating energy would otherwise have gotten it.
+    /// Bigger and more efficient eaters also get more food for their eating energy.
+    fn eat(&mut self, eating_energy: F32Positive, food_per_cell: F32Positive) -> (F32Positive, F32Positive) {
+        let food = (eating_energy * self.constants.food_yield_from_eating * self.genome.eating_efficiency * self.genome.size)
+            .min(food_per_cell);
+        let eaten = food.min(self.constants.max_food_intake * self.genome.size);
+        (eaten, food - eaten)
+    }
+
+    /// Like `eat`, but for protein, with `protein_yield_from_eating` and the same intake limit.
+    fn eat_protein(&mut self, eating_energy: F32Positive, protein_per_cell: F32Positive) -> F32Positive {
+        (eating_energy * self.constants.protein_yield_from_eating * self.genome.size)
+            .min(protein_per_cell)
+            .min(self.constants.max_food_intake * self.genome.size)
+    }
+
+    fn digest(&mut self, food_amount: F32Positive, protein_amount: F32Positive) {
+        let age_efficiency = self.age_efficiency();
+        *self.energy += food_amount * age_efficiency * self.constants.energy_yield_from_digestion * self.genome.digestion_efficiency;
+        *self.energy += protein_amount * age_efficiency * self.constants.energy_yield_from_protein_digestion;
     }
 
-    fn eat(&mut self, eating_energy: F32Positive, food_per_cell: F32Positive) -> F32Positive {
-        (eating_energy * self.constants.food_yield_from_eating).min(food_per_cell)
+    fn photosynthesize(&mut self, photosynthesis_energy: F32Positive, light_per_cell: F32Positive) {
+        let light = (photosynthesis_energy * self.constants.light_yield_from_photosynthesis).min(light_per_cell);
+        *self.energy += light * self.constants.energy_yield_from_light;
     }
 
-    fn digest(&mut self, food_amount: F32Positive) {
-        self.state.energy += food_amount * self.constants.energy_yield_from_digestion;
+    /// Energy gained beyond the cell's `max_energy_storage` is lost, costing
+    /// `health_reduction_per_excess_energy` per unit.
+    fn spill_excess_energy(&mut self) {
+        let excess = *self.energy - self.genome.max_energy_storage;
+        *self.energy = self.energy.min(self.genome.max_energy_storage);
+        self.lose_health(excess * self.constants.health_reduction_per_excess_energy, DeathCause::ExcessEnergy);
     }
 
-    fn entropy(&mut self) {
-        self.state.health -= self.constants.health_reduction_from_entropy;
+    /// The cell loses `health_reduction_from_entropy`, scaled up with age by
+    /// `entropy_age_exponent` and by the environment's `stress` divided by one plus the cell's
+    /// `stress_tolerance`.
+    fn entropy(&mut self, stress: F32Positive) {
+        let age_factor = (self.state.age as f32).powf(self.constants.entropy_age_exponent.value());
+        let stress_factor = stress.value() / (1.0 + self.genome.stress_tolerance.value());
+        let health_reduction = F32ZeroToOne::clipped(self.constants.health_reduction_from_entropy.value() * age_factor * stress_factor);
+        self.lose_health(health_reduction, DeathCause::Entropy);
     }
 
     fn heal(&mut self, healing_energy: F32Positive) {
-        self.state.health += healing_energy * self.constants.health_increase_per_healing_energy;
+        *self.health += healing_energy * self.age_efficiency() * self.constants.health_increase_per_healing_energy;
+        if self.health.value() > 0.0 {
+            self.state.death_cause = None;
+        }
+    }
+
+    /// Digestion and healing lose `efficiency_decay_per_age` of their remaining efficiency with
+    /// each step of age.
+    fn age_efficiency(&self) -> F32Positive {
+        (1.0 - self.constants.efficiency_decay_per_age.value()).powf(self.state.age as f32).into()
+    }
+
+    fn grow_older(&mut self) {
+        self.state.age += 1;
+        if self.state.age >= self.constants.max_age {
+            self.die_of(DeathCause::OldAge);
+        }
     }
 
     fn expend_energy(&mut self, energy: F32Positive) {
-        self.state.energy -= energy;
-        self.state.health -= energy * self.constants.health_reduction_per_energy_expended;
+        *self.energy -= energy;
+        self.lose_health(energy * self.constants.health_reduction_per_energy_expended, DeathCause::EnergyExpenditure);
     }
 }
 
-fn budget<const N: usize>(available: F32Positive, desired: &[F32Positive; N]) -> (F32Positive, [F32Positive; N]) {
+/// How much an attempted energy is multiplied by at a cell's internal state: 1 at a state of 0,
+/// `response` at a state of 1, and in proportion between them.
+pub(crate) fn internal_state_factor(internal_state: F32ZeroToOne, response: F32Positive) -> F32Positive {
+    let state = internal_state.value();
+    F32Positive::clipped(1.0 - state + state * response.value())
+}
+
+/// How far `amount` is toward saturating, from 0 toward 1, being halfway there at `scale`.
+pub(crate) fn saturation(amount: F32Positive, scale: F32Positive) -> f32 {
+    let total = amount.value() + scale.value();
+    if total > 0.0 { amount.value() / total } else { 0.0 }
+}
+
+/// When the available energy cannot cover every desired energy, the budgeted energies are
+/// proportional to desired energy times priority, except that none exceeds its desired energy,
+/// with any excess shared among the rest. Equal priorities scale everything down by the same
+/// fraction. Energies with zero priority get only what is left after all the others are fully
+/// funded.
+fn budget<const N: usize>(available: F32Positive, desired: &[F32Positive; N], priorities: &[F32Positive; N]) -> (F32Positive, [F32Positive; N]) {
     let desired_sum = desired.iter().sum::<F32Positive>();
-    if available < desired_sum {
-        let reduction_factor = available / desired_sum;
-        let budgeted = desired.map(|item| { item * reduction_factor });
-        (available, budgeted)
-    } else {
-        (desired_sum, *desired)
+    if available >= desired_sum {
+        return (desired_sum, *desired);
+    }
+
+    let mut budgeted = [0.into(); N];
+    let mut funded = [false; N];
+    let mut remaining = available;
+    loop {
+        let unfunded = || (0..N).filter(|&i| !funded[i]);
+        let weighted_sum = unfunded().map(|i| desired[i] * priorities[i]).sum::<F32Positive>();
+        if weighted_sum.value() <= 0.0 {
+            let unfunded_sum = unfunded().map(|i| desired[i]).sum::<F32Positive>();
+            if unfunded_sum.value() > 0.0 {
+                let reduction_factor = remaining / unfunded_sum;
+                for i in unfunded() {
+                    budgeted[i] = desired[i] * reduction_factor;
+                }
+            }
+            break;
+        }
+
+        let reduction_factor = remaining / weighted_sum;
+        let newly_funded: Vec<usize> = unfunded()
+            .filter(|&i| (priorities[i] * reduction_factor).value() >= 1.0)
+            .collect();
+        if newly_funded.is_empty() {
+            for i in unfunded() {
+                budgeted[i] = desired[i] * (priorities[i] * reduction_factor);
+            }
+            break;
+        }
+        for i in newly_funded {
+            budgeted[i] = desired[i];
+            remaining -= desired[i];
+            funded[i] = true;
+        }
     }
+    (available, budgeted)
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CellConstants {
     pub create_child_energy: F32Positive,
     pub energy_yield_from_digestion: F32Positive,
     pub food_yield_from_eating: F32Positive,
     pub health_increase_per_healing_energy: F32ZeroToOnePerF32Positive,
     pub health_reduction_from_entropy: F32ZeroToOne,
+    /// Makes entropy a model of senescence, with cells losing `health_reduction_from_entropy`
+    /// times their age raised to this exponent each step. At 0, cells of every age lose the same.
+    #[serde(default)]
+    pub entropy_age_exponent: F32Positive,
+    pub health_reduction_from_zero_energy: F32ZeroToOne,
     pub health_reduction_per_energy_expended: F32ZeroToOnePerF32Positive,
     pub attempted_eating_energy_mutation_stdev: F32Positive,
     pub attempted_healing_energy_mutation_stdev: F32Positive,
     pub child_threshold_energy_mutation_stdev: F32Positive,
     pub child_threshold_food_mutation_stdev: F32Positive,
+    pub attempted_attack_energy_mutation_stdev: F32Positive,
+    pub attack_efficiency: F32Positive,
+    pub attack_defense: F32Positive,
+    pub health_reduction_per_attack_energy: F32ZeroToOnePerF32Positive,
+    pub attempted_photosynthesis_energy_mutation_stdev: F32Positive,
+    pub light_yield_from_photosynthesis: F32Positive,
+    pub energy_yield_from_light: F32Positive,
+    pub max_age: u32,
+    pub crossover_probability: F32ZeroToOne,
+    pub efficiency_decay_per_age: F32ZeroToOne,
+    pub activity_fraction: F32ZeroToOne,
+    pub active_phase_mutation_stdev: F32Positive,
+    pub attempted_trade_energy_mutation_stdev: F32Positive,
+    pub trade_reciprocity_threshold_mutation_stdev: F32Positive,
+    pub trade_efficiency: F32Positive,
+    /// Food from its next share that a trading cell lets its partner have per unit of trade
+    /// energy the partner gave it.
+    pub food_access_per_trade_energy: F32Positive,
+    pub food_memory_rate_mutation_stdev: F32Positive,
+    pub scarce_food_threshold_mutation_stdev: F32Positive,
+    pub scarcity_spending_fraction_mutation_stdev: F32Positive,
+    pub reproduction_priority_mutation_stdev: F32Positive,
+    pub eating_priority_mutation_stdev: F32Positive,
+    pub healing_priority_mutation_stdev: F32Positive,
+    pub attack_priority_mutation_stdev: F32Positive,
+    pub photosynthesis_priority_mutation_stdev: F32Positive,
+    pub trade_priority_mutation_stdev: F32Positive,
+    pub maintenance_energy: F32Positive,
+    pub health_reduction_per_maintenance_shortfall: F32ZeroToOnePerF32Positive,
+    pub max_energy_storage_mutation_stdev: F32Positive,
+    pub maintenance_energy_per_storage: F32Positive,
+    pub health_reduction_per_excess_energy: F32ZeroToOnePerF32Positive,
+    #[serde(default)]
+    pub process_order: ProcessOrder,
+    pub death_probability: F32ZeroToOne,
+    pub death_probability_per_health_lost: F32ZeroToOne,
+    pub attempted_detox_energy_mutation_stdev: F32Positive,
+    pub detox_priority_mutation_stdev: F32Positive,
+    pub toxin_tolerance: F32Positive,
+    pub health_reduction_per_toxin: F32ZeroToOnePerF32Positive,
+    pub toxin_yield_from_detox: F32Positive,
+    pub max_food_intake: F32Positive,
+    pub size_mutation_stdev: F32Positive,
+    pub donation_threshold_mutation_stdev: F32Positive,
+    pub donation_fraction_mutation_stdev: F32Positive,
+    pub donation_efficiency: F32ZeroToOne,
+    pub dormancy_threshold_energy_mutation_stdev: F32Positive,
+    pub wake_threshold_food_mutation_stdev: F32Positive,
+    pub dormant_maintenance_fraction: F32ZeroToOne,
+    pub protein_yield_from_eating: F32Positive,
+    pub energy_yield_from_protein_digestion: F32Positive,
+    pub protein_preference_mutation_stdev: F32Positive,
+    /// The upkeep of a cell's machinery for each process it attempts, charged whether or not
+    /// the process runs, so that strategies carrying more traits than they use cost more.
+    pub maintenance_energy_per_attempted_energy: F32Positive,
+    #[serde(default)]
+    pub eating_efficiency_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub digestion_efficiency_mutation_stdev: F32Positive,
+    /// The upkeep of a cell's eating and digestive machinery for each unit of its
+    /// `eating_efficiency` and `digestion_efficiency`, so that more efficient cells cost more.
+    #[serde(default)]
+    pub maintenance_energy_per_efficiency: F32Positive,
+    #[serde(default)]
+    pub state_update_rate_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub state_energy_scale_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub state_food_scale_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub eating_state_response_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub healing_state_response_mutation_stdev: F32Positive,
+    /// Whether cells' efforts come from their genes alone or from their brains too.
+    #[serde(default)]
+    pub controller: CellController,
+    /// The mutation stdev of each of the weights of cells' brains.
+    #[serde(default)]
+    pub brain_weight_mutation_stdev: F32Positive,
+    #[serde(default)]
+    pub stress_tolerance_mutation_stdev: F32Positive,
+    /// The upkeep of a cell's defenses against environmental stress for each unit of its
+    /// `stress_tolerance`.
+    #[serde(default)]
+    pub maintenance_energy_per_stress_tolerance: F32Positive,
+    #[serde(skip)]
+    pub behaviors: CellBehaviors,
 }
 
 impl CellConstants {
@@ -173,59 +854,205 @@ impl CellConstants {
         food_yield_from_eating: F32Positive::unchecked(0.0),
         health_increase_per_healing_energy: F32ZeroToOnePerF32Positive::unchecked(0.0),
         health_reduction_from_entropy: F32ZeroToOne::unchecked(0.0),
+        entropy_age_exponent: F32Positive::unchecked(0.0),
+        health_reduction_from_zero_energy: F32ZeroToOne::unchecked(0.0),
         health_reduction_per_energy_expended: F32ZeroToOnePerF32Positive::unchecked(0.0),
         attempted_eating_energy_mutation_stdev: F32Positive::unchecked(0.0),
         attempted_healing_energy_mutation_stdev: F32Positive::unchecked(0.0),
         child_threshold_energy_mutation_stdev: F32Positive::unchecked(0.0),
         child_threshold_food_mutation_stdev: F32Positive::unchecked(0.0),
+        attempted_attack_energy_mutation_stdev: F32Positive::unchecked(0.0),
+        attack_efficiency: F32Positive::unchecked(0.0),
+        attack_defense: F32Positive::unchecked(0.0),
+        health_reduction_per_attack_energy: F32ZeroToOnePerF32Positive::unchecked(0.0),
+        attempted_photosynthesis_energy_mutation_stdev: F32Positive::unchecked(0.0),
+        light_yield_from_photosynthesis: F32Positive::unchecked(0.0),
+        energy_yield_from_light: F32Positive::unchecked(0.0),
+        max_age: u32::MAX,
+        crossover_probability: F32ZeroToOne::unchecked(0.5),
+        efficiency_decay_per_age: F32ZeroToOne::unchecked(0.0),
+        activity_fraction: F32ZeroToOne::unchecked(1.0),
+        active_phase_mutation_stdev: F32Positive::unchecked(0.0),
+        attempted_trade_energy_mutation_stdev: F32Positive::unchecked(0.0),
+        trade_reciprocity_threshold_mutation_stdev: F32Positive::unchecked(0.0),
+        trade_efficiency: F32Positive::unchecked(0.0),
+        food_access_per_trade_energy: F32Positive::unchecked(0.0),
+        food_memory_rate_mutation_stdev: F32Positive::unchecked(0.0),
+        scarce_food_threshold_mutation_stdev: F32Positive::unchecked(0.0),
+        scarcity_spending_fraction_mutation_stdev: F32Positive::unchecked(0.0),
+        reproduction_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        eating_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        healing_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        attack_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        photosynthesis_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        trade_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        maintenance_energy: F32Positive::unchecked(0.0),
+        health_reduction_per_maintenance_shortfall: F32ZeroToOnePerF32Positive::unchecked(0.0),
+        max_energy_storage_mutation_stdev: F32Positive::unchecked(0.0),
+        maintenance_energy_per_storage: F32Positive::unchecked(0.0),
+        health_reduction_per_excess_energy: F32ZeroToOnePerF32Positive::unchecked(0.0),
+        process_order: ProcessOrder::DEFAULT,
+        death_probability: F32ZeroToOne::unchecked(0.0),
+        death_probability_per_health_lost: F32ZeroToOne::unchecked(0.0),
+        attempted_detox_energy_mutation_stdev: F32Positive::unchecked(0.0),
+        detox_priority_mutation_stdev: F32Positive::unchecked(0.0),
+        toxin_tolerance: F32Positive::unchecked(0.0),
+        health_reduction_per_toxin: F32ZeroToOnePerF32Positive::unchecked(0.0),
+        toxin_yield_from_detox: F32Positive::unchecked(0.0),
+        max_food_intake: F32Positive::unchecked(f32::MAX),
+        size_mutation_stdev: F32Positive::unchecked(0.0),
+        donation_threshold_mutation_stdev: F32Positive::unchecked(0.0),
+        donation_fraction_mutation_stdev: F32Positive::unchecked(0.0),
+        donation_efficiency: F32ZeroToOne::unchecked(1.0),
+        dormancy_threshold_energy_mutation_stdev: F32Positive::unchecked(0.0),
+        wake_threshold_food_mutation_stdev: F32Positive::unchecked(0.0),
+        dormant_maintenance_fraction: F32ZeroToOne::unchecked(0.1),
+        protein_yield_from_eating: F32Positive::unchecked(0.0),
+        energy_yield_from_protein_digestion: F32Positive::unchecked(0.0),
+        protein_preference_mutation_stdev: F32Positive::unchecked(0.0),
+        maintenance_energy_per_attempted_energy: F32Positive::unchecked(0.0),
+        eating_efficiency_mutation_stdev: F32Positive::unchecked(0.0),
+        digestion_efficiency_mutation_stdev: F32Positive::unchecked(0.0),
+        maintenance_energy_per_efficiency: F32Positive::unchecked(0.0),
+        state_update_rate_mutation_stdev: F32Positive::unchecked(0.0),
+        state_energy_scale_mutation_stdev: F32Positive::unchecked(0.0),
+        state_food_scale_mutation_stdev: F32Positive::unchecked(0.0),
+        eating_state_response_mutation_stdev: F32Positive::unchecked(0.0),
+        healing_state_response_mutation_stdev: F32Positive::unchecked(0.0),
+        controller: CellController::Fixed,
+        brain_weight_mutation_stdev: F32Positive::unchecked(0.0),
+        stress_tolerance_mutation_stdev: F32Positive::unchecked(0.0),
+        maintenance_energy_per_stress_tolerance: F32Positive::unchecked(0.0),
+        behaviors: CellBehaviors::NONE,
     };
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct CellParams {
-    pub attempted_eating_energy: F32Positive,
-    pub attempted_healing_energy: F32Positive,
-    pub child_threshold_energy: F32Positive,
-    pub child_threshold_food: F32Positive,
-}
-
-impl CellParams {
-    #[allow(dead_code)]
-    pub const DEFAULT: CellParams = CellParams {
-        attempted_eating_energy: F32Positive::unchecked(0.0),
-        attempted_healing_energy: F32Positive::unchecked(0.0),
-        child_threshold_energy: F32Positive::unchecked(f32::MAX),
-        child_threshold_food: F32Positive::unchecked(f32::MAX),
-    };
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Everything about a cell that changes as it lives except its energy and health, which are
+/// read by scans over the whole population often enough that `CellColumns` keeps each of them
+/// in a column of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CellState {
-    pub energy: F32Positive,
-    pub health: F32ZeroToOne,
+    pub id: u64,
+    pub parent_id: Option<u64>,
+    pub num_children: u32,
+    pub attack_energy: F32Positive,
+    pub trade_energy: F32Positive,
+    pub last_trade_received: Option<F32Positive>,
+    /// Food from the cell's next share that it owes the trading partner with the given id.
+    #[serde(default)]
+    pub food_access_owed: Option<(u64, F32Positive)>,
+    pub remembered_food: Option<F32Positive>,
+    /// A memory of the cell's energy and food per cell that modulates its eating and healing,
+    /// as evolved by its `state_*` genes.
+    #[serde(default)]
+    pub internal_state: F32ZeroToOne,
+    pub age: u32,
+    #[serde(default)]
+    pub dormant: bool,
+    #[serde(default)]
+    pub death_cause: Option<DeathCause>,
 }
 
 impl CellState {
     #[allow(dead_code)]
     pub const DEFAULT: CellState = CellState {
-        energy: F32Positive::unchecked(0.0),
-        health: F32ZeroToOne::unchecked(1.0),
+        id: 0,
+        parent_id: None,
+        num_children: 0,
+        attack_energy: F32Positive::unchecked(0.0),
+        trade_energy: F32Positive::unchecked(0.0),
+        last_trade_received: None,
+        food_access_owed: None,
+        remembered_food: None,
+        internal_state: F32ZeroToOne::unchecked(0.0),
+        age: 0,
+        dormant: false,
+        death_cause: None,
     };
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct CellEnvironment {
     pub food_per_cell: F32Positive,
+    pub protein_per_cell: F32Positive,
+    pub light_per_cell: F32Positive,
+    pub toxin_per_cell: F32Positive,
+    pub time_of_day: F32ZeroToOne,
+    pub reproduction_allowed: bool,
+    /// Multiplies the entropy damage cells suffer, less what their `stress_tolerance` resists.
+    pub stress: F32Positive,
 }
 
 impl CellEnvironment {
     #[allow(dead_code)]
-    pub const DEFAULT: CellEnvironment = CellEnvironment { food_per_cell: F32Positive::unchecked(0.0) };
+    pub const DEFAULT: CellEnvironment = CellEnvironment {
+        food_per_cell: F32Positive::unchecked(0.0),
+        protein_per_cell: F32Positive::unchecked(0.0),
+        light_per_cell: F32Positive::unchecked(0.0),
+        toxin_per_cell: F32Positive::unchecked(0.0),
+        time_of_day: F32ZeroToOne::unchecked(0.0),
+        reproduction_allowed: true,
+        stress: F32Positive::unchecked(1.0),
+    };
+}
+
+/// What a step's behaviors share: the cell's environment, the energy budgeted for each
+/// built-in process, and what the cell has eaten so far.
+pub struct BehaviorContext<'a> {
+    pub environment: &'a CellEnvironment,
+    energies: CellEnergies,
+    pub food_eaten: F32Positive,
+    /// Food the cell would have eaten but for its `max_food_intake`.
+    pub food_beyond_intake: F32Positive,
+    pub protein_eaten: F32Positive,
+}
+
+impl<'a> BehaviorContext<'a> {
+    fn new(environment: &'a CellEnvironment, energies: CellEnergies) -> Self {
+        BehaviorContext {
+            environment,
+            energies,
+            food_eaten: 0.into(),
+            food_beyond_intake: 0.into(),
+            protein_eaten: 0.into(),
+        }
+    }
+}
+
+impl CellBehavior for CellProcess {
+    fn name(&self) -> &str {
+        CellProcess::name(*self)
+    }
+
+    fn run(&self, cell: &mut CellMut, context: &mut BehaviorContext) {
+        let environment = context.environment;
+        match self {
+            CellProcess::Eat => {
+                let eating_energy = context.energies.eating;
+                let protein_eating_energy = eating_energy * cell.genome.protein_preference;
+                (context.food_eaten, context.food_beyond_intake) = cell.eat(eating_energy - protein_eating_energy, environment.food_per_cell);
+                context.protein_eaten = cell.eat_protein(protein_eating_energy, environment.protein_per_cell);
+                cell.digest(context.food_eaten, context.protein_eaten);
+                cell.spill_excess_energy();
+            }
+            CellProcess::Photosynthesize => {
+                cell.photosynthesize(context.energies.photosynthesis, environment.light_per_cell);
+                cell.spill_excess_energy();
+            }
+            CellProcess::Entropy => cell.entropy(environment.stress),
+            CellProcess::Heal => cell.heal(context.energies.healing),
+        }
+    }
 }
 
 struct CellEnergies {
     reproduction: F32Positive,
     eating: F32Positive,
     healing: F32Positive,
+    attack: F32Positive,
+    photosynthesis: F32Positive,
+    trade: F32Positive,
+    detox: F32Positive,
 }
 
 impl CellEnergies {
@@ -234,12 +1061,83 @@ impl CellEnergies {
             reproduction: 0.into(),
             eating: 0.into(),
             healing: 0.into(),
+            attack: 0.into(),
+            photosynthesis: 0.into(),
+            trade: 0.into(),
+            detox: 0.into(),
         }
     }
 }
 
+/// What took the last of a cell's health. Later losses in the same step are not recorded, but a
+/// cell that heals after losing all its health is alive again, with no cause of death.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    OldAge,
+    Entropy,
+    Starvation,
+    EnergyExpenditure,
+    Toxin,
+    ExcessEnergy,
+    Attack,
+    Crowding,
+    Hazard,
+    Culled,
+}
+
+impl fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DeathCause::OldAge => "old_age",
+            DeathCause::Entropy => "entropy",
+            DeathCause::Starvation => "starvation",
+            DeathCause::EnergyExpenditure => "energy_expenditure",
+            DeathCause::Toxin => "toxin",
+            DeathCause::ExcessEnergy => "excess_energy",
+            DeathCause::Attack => "attack",
+            DeathCause::Crowding => "crowding",
+            DeathCause::Hazard => "hazard",
+            DeathCause::Culled => "culled",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a cell's step produced and took from the world besides its own changes.
+pub struct CellStepOutcome {
+    pub child: Option<Cell>,
+    pub food_eaten: F32Positive,
+    /// Food the cell would have eaten but for its `max_food_intake`.
+    pub food_beyond_intake: F32Positive,
+    pub protein_eaten: F32Positive,
+    /// The energy spent on everything budgeted, which leaves toxin behind.
+    pub energy_expended: F32Positive,
+    pub toxin_neutralized: F32Positive,
+}
+
+/// Where a sexually reproducing cell finds a mate. It is only asked when the cell has a child, so
+/// that cells that do not reproduce cost nothing to mate.
+pub trait MateSource {
+    /// Crosses the child's genome over with that of a mate, if there is one.
+    fn cross_over(&mut self, child_genome: &mut Rc<Genome>, probability: F32ZeroToOne);
+}
+
+/// The mate source of asexually reproducing cells, whose children get only their parent's genes.
+pub struct NoMate;
+
+impl MateSource for NoMate {
+    fn cross_over(&mut self, _child_genome: &mut Rc<Genome>, _probability: F32ZeroToOne) {}
+}
+
 pub trait MutationNumberSource {
+    /// Called before the numbers for a cell's `child_index`th child are drawn, so sources can
+    /// draw them from a stream of their own.
+    fn start_stream(&mut self, _cell_id: u64, _child_index: u32) {}
+
     fn mutate(&mut self, value: F32Positive, stdev: F32Positive) -> F32Positive;
+
+    /// Called by `Genome::mutate` with the genome before and after mutating it.
+    fn record_mutation(&mut self, _before: &Genome, _after: &Genome) {}
 }
 
 pub struct NullMutationNumberSource {}
@@ -250,77 +1148,239 @@ impl NullMutationNumberSource {
     }
 }
 
+impl Default for NullMutationNumberSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MutationNumberSource for NullMutationNumberSource {
     fn mutate(&mut self, value: F32Positive, _stdev: F32Positive) -> F32Positive {
         value
     }
 }
 
+/// Draws the numbers for each child from its own random stream, keyed by the master seed, the
+/// parent's id, and how many children the parent has had. A child's mutations therefore do not
+/// depend on the order in which cells are stepped, or on how many other cells mutated first.
+#[derive(Serialize, Deserialize)]
 pub struct RandomMutationNumberSource {
-    rng: ThreadRng,
+    seed: u64,
+    rng: Pcg64,
+    #[serde(default)]
+    model: MutationModel,
+    #[serde(default)]
+    stats: MutationStats,
+}
+
+/// Counts of the work a mutation number source has done, so that runs can check that mutation
+/// behaves as configured. Gene changes are listed in `Genome::gene_names` order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MutationStats {
+    pub num_genomes_mutated: u64,
+    /// Samples drawn for mutated values, including rejected ones.
+    pub num_draws: u64,
+    /// Samples rejected for being negative and drawn again.
+    pub num_rejected_draws: u64,
+    pub total_gene_changes: Vec<f64>,
+}
+
+impl MutationStats {
+    fn record(&mut self, before: &Genome, after: &Genome) {
+        self.num_genomes_mutated += 1;
+        self.total_gene_changes.resize(Genome::gene_names().count(), 0.0);
+        for ((total, before), after) in self.total_gene_changes.iter_mut().zip(before.gene_values()).zip(after.gene_values()) {
+            *total += (after - before).abs() as f64;
+        }
+    }
+
+    /// The counts added since `earlier`, a copy of these stats taken before.
+    pub fn since(&self, earlier: &MutationStats) -> MutationStats {
+        MutationStats {
+            num_genomes_mutated: self.num_genomes_mutated - earlier.num_genomes_mutated,
+            num_draws: self.num_draws - earlier.num_draws,
+            num_rejected_draws: self.num_rejected_draws - earlier.num_rejected_draws,
+            total_gene_changes: self.total_gene_changes.iter().enumerate()
+                .map(|(index, total)| total - earlier.total_gene_changes.get(index).copied().unwrap_or(0.0))
+                .collect(),
+        }
+    }
+
+    /// The mean absolute change per mutation of each gene, in `Genome::gene_names` order.
+    pub fn mean_gene_changes(&self) -> Vec<f64> {
+        self.total_gene_changes.iter()
+            .map(|total| if self.num_genomes_mutated == 0 { 0.0 } else { total / self.num_genomes_mutated as f64 })
+            .collect()
+    }
 }
 
 impl RandomMutationNumberSource {
+    /// Seeded from the operating system's randomness, with the `entropy` feature.
+    #[cfg(feature = "entropy")]
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         RandomMutationNumberSource {
-            rng: rand::thread_rng(),
+            seed,
+            rng: Pcg64::seed_from_u64(seed),
+            model: MutationModel::Gaussian,
+            stats: MutationStats::default(),
         }
     }
+
+    pub fn with_model(mut self, model: MutationModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn stats(&self) -> &MutationStats {
+        &self.stats
+    }
+}
+
+/// The SplitMix64 finalizer, which scrambles similar inputs (such as consecutive ids) into
+/// unrelated outputs.
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(feature = "entropy")]
+impl Default for RandomMutationNumberSource {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MutationNumberSource for RandomMutationNumberSource {
+    fn start_stream(&mut self, cell_id: u64, child_index: u32) {
+        let key = split_mix(split_mix(self.seed ^ cell_id) ^ child_index as u64);
+        let stream = ((cell_id as u128) << 32) | child_index as u128;
+        self.rng = Pcg64::new(((split_mix(self.seed) as u128) << 64) | key as u128, stream);
+    }
+
     fn mutate(&mut self, value: F32Positive, stdev: F32Positive) -> F32Positive {
-        let normal = Normal::new(value.value(), stdev.value()).unwrap();
-        loop {
-            let mutated = normal.sample(&mut self.rng);
-            if mutated >= 0.0 {
-                return F32Positive::unchecked(mutated);
-            }
-        }
+        self.model.mutate(value, stdev, &mut self.rng, &mut self.stats)
+    }
+
+    fn record_mutation(&mut self, before: &Genome, after: &Genome) {
+        self.stats.record(before, after);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use crate::brain::BrainWeights;
 
     #[test]
     fn budgeting_adjusts_downward_proportionally() {
         let desired: [F32Positive; 2] = [10.into(), 5.into()];
 
-        let (total_budgeted, budgeted) = budget(7.5.into(), &desired);
+        let (total_budgeted, budgeted) = budget(7.5.into(), &desired, &[1.into(), 1.into()]);
 
         assert_eq!(total_budgeted, 7.5.into());
         assert_eq!(budgeted, [5.into(), 2.5.into()]);
     }
 
     #[test]
-    fn budgeting_leaves_satisfiable_requests_unchanged() {
-        let desired: [F32Positive; 2] = [10.into(), 5.into()];
+    fn budgeting_favors_higher_priorities() {
+        let desired: [F32Positive; 2] = [10.into(), 10.into()];
 
-        let (total_budgeted, budgeted) = budget(20.into(), &desired);
+        let (total_budgeted, budgeted) = budget(10.into(), &desired, &[3.into(), 1.into()]);
 
-        assert_eq!(total_budgeted, 15.into());
-        assert_eq!(budgeted, [10.into(), 5.into()]);
+        assert_eq!(total_budgeted, 10.into());
+        assert_eq!(budgeted, [7.5.into(), 2.5.into()]);
     }
 
     #[test]
-    fn new_cell_has_full_health() {
-        let cell = Cell::new(
-            &Rc::new(CellConstants::DEFAULT),
-            CellParams::DEFAULT);
+    fn budgeting_fully_funds_top_priorities_and_shares_the_rest() {
+        let desired: [F32Positive; 3] = [2.into(), 8.into(), 8.into()];
 
-        assert_eq!(cell.health(), 1.0.into());
+        let (_, budgeted) = budget(10.into(), &desired, &[10.into(), 1.into(), 3.into()]);
+
+        assert_eq!(budgeted, [2.into(), 2.into(), 6.into()]);
     }
 
     #[test]
-    fn cell_suffers_entropic_damage() {
-        let mut cell = Cell::new(
-            &Rc::new(CellConstants {
-                health_reduction_from_entropy: 0.25.into(),
-                ..CellConstants::DEFAULT
+    fn zero_priorities_get_only_leftovers() {
+        let desired: [F32Positive; 3] = [4.into(), 4.into(), 4.into()];
+
+        let (_, budgeted) = budget(6.into(), &desired, &[1.into(), 0.into(), 0.into()]);
+
+        assert_eq!(budgeted, [4.into(), 1.into(), 1.into()]);
+    }
+
+    #[test]
+    fn budgeting_leaves_satisfiable_requests_unchanged() {
+        let desired: [F32Positive; 2] = [10.into(), 5.into()];
+
+        let (total_budgeted, budgeted) = budget(20.into(), &desired, &[1.into(), 1.into()]);
+
+        assert_eq!(total_budgeted, 15.into());
+        assert_eq!(budgeted, [10.into(), 5.into()]);
+    }
+
+    proptest! {
+        #[test]
+        fn budgeting_never_exceeds_available_or_desired_energy(
+            available in 0.0_f32..100.0,
+            desired in prop::array::uniform4(0.0_f32..50.0),
+            priorities in prop::array::uniform4(0.0_f32..10.0),
+        ) {
+            let desired = desired.map(F32Positive::unchecked);
+
+            let (total_budgeted, budgeted) = budget(available.into(), &desired, &priorities.map(F32Positive::unchecked));
+
+            let budgeted_sum: f32 = budgeted.iter().map(F32Positive::value).sum();
+            prop_assert!(total_budgeted.value() <= available);
+            prop_assert!(budgeted_sum <= total_budgeted.value() * (1.0 + 1e-5) + 1e-5, "{} > {}", budgeted_sum, total_budgeted);
+            for (budgeted, desired) in budgeted.iter().zip(&desired) {
+                prop_assert!(budgeted.value() >= 0.0 && budgeted.value() <= desired.value() * (1.0 + 1e-5), "{} of {}", budgeted, desired);
+            }
+        }
+
+        #[test]
+        fn budgeting_with_equal_priorities_preserves_ratios(
+            available in 0.0_f32..100.0,
+            desired in prop::array::uniform4(0.01_f32..50.0),
+            priority in 0.01_f32..10.0,
+        ) {
+            let desired = desired.map(F32Positive::unchecked);
+            let desired_sum: f32 = desired.iter().map(F32Positive::value).sum();
+
+            let (_, budgeted) = budget(available.into(), &desired, &[priority.into(); 4]);
+
+            let fraction = (available / desired_sum).min(1.0);
+            for (budgeted, desired) in budgeted.iter().zip(&desired) {
+                let expected = desired.value() * fraction;
+                prop_assert!((budgeted.value() - expected).abs() <= expected * 1e-4 + 1e-6, "{} instead of {}", budgeted, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn new_cell_has_full_health() {
+        let cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome::DEFAULT);
+
+        assert_eq!(cell.health(), 1.0.into());
+    }
+
+    #[test]
+    fn cell_suffers_entropic_damage() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                health_reduction_from_entropy: 0.25.into(),
+                ..CellConstants::DEFAULT
             }),
-            CellParams::DEFAULT)
+            Genome::DEFAULT)
             .with_health(1.0.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
@@ -329,13 +1389,147 @@ mod tests {
         assert_eq!(cell.health(), 0.75.into());
     }
 
+    #[test]
+    fn entropic_damage_can_grow_with_age() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                health_reduction_from_entropy: 0.125.into(),
+                entropy_age_exponent: 2.0.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome::DEFAULT)
+            .with_health(1.0.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        for _ in 0..3 {
+            cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        }
+
+        assert_eq!(cell.health(), (1.0 - 0.125 * (0.0 + 1.0 + 4.0)).into());
+    }
+
+    #[test]
+    fn stress_multiplies_entropic_damage_less_what_cells_tolerate() {
+        let health_after_step = |stress: f32, stress_tolerance: f32| {
+            let mut cell = Cell::new(
+                &Rc::new(CellConstants {
+                    health_reduction_from_entropy: 0.125.into(),
+                    maintenance_energy_per_stress_tolerance: 1.0.into(),
+                    ..CellConstants::DEFAULT
+                }),
+                Genome { stress_tolerance: stress_tolerance.into(), ..Genome::DEFAULT })
+                .with_energy(10.into())
+                .with_health(1.0.into());
+            cell.step(&mut NullMutationNumberSource::new(), &CellEnvironment { stress: stress.into(), ..CellEnvironment::DEFAULT });
+            (cell.health().value(), cell.energy().value())
+        };
+
+        assert_eq!(health_after_step(1.0, 0.0), (0.875, 10.0));
+        assert_eq!(health_after_step(3.0, 0.0), (0.625, 10.0));
+        assert_eq!(health_after_step(3.0, 1.0), (0.8125, 9.0));
+    }
+
+    #[test]
+    fn process_order_changes_outcome() {
+        let step_with_order = |process_order: &str| {
+            let mut cell = Cell::new(
+                &Rc::new(CellConstants {
+                    health_reduction_from_entropy: 0.5.into(),
+                    health_increase_per_healing_energy: 0.5.into(),
+                    process_order: process_order.parse().unwrap(),
+                    ..CellConstants::DEFAULT
+                }),
+                Genome {
+                    attempted_healing_energy: 1.into(),
+                    ..Genome::DEFAULT
+                })
+                .with_health(0.75.into())
+                .with_energy(1.into());
+            cell.step(&mut NullMutationNumberSource::new(), &CellEnvironment::DEFAULT);
+            cell.health()
+        };
+
+        assert_eq!(step_with_order("eat,photosynthesize,entropy,heal"), 0.75.into());
+        assert_eq!(step_with_order("heal,eat,photosynthesize,entropy"), 0.5.into());
+    }
+
+    #[test]
+    fn added_behaviors_run_in_order_after_the_processes() {
+        struct Snacking;
+
+        impl CellBehavior for Snacking {
+            fn name(&self) -> &str {
+                "snacking"
+            }
+
+            fn run(&self, cell: &mut CellMut, _context: &mut BehaviorContext) {
+                *cell.energy_mut() += F32Positive::from(1.0);
+            }
+        }
+
+        struct Doubling;
+
+        impl CellBehavior for Doubling {
+            fn name(&self) -> &str {
+                "doubling"
+            }
+
+            fn run(&self, cell: &mut CellMut, context: &mut BehaviorContext) {
+                let energy = cell.energy() + context.food_eaten;
+                *cell.energy_mut() = energy * F32Positive::from(2.0);
+            }
+        }
+
+        let step_with_behaviors = |behaviors: CellBehaviors| {
+            let mut cell = Cell::new(
+                &Rc::new(CellConstants {
+                    food_yield_from_eating: 1.into(),
+                    behaviors,
+                    ..CellConstants::DEFAULT
+                }),
+                Genome {
+                    attempted_eating_energy: 1.into(),
+                    ..Genome::DEFAULT
+                })
+                .with_energy(10.into());
+            cell.step(&mut NullMutationNumberSource::new(), &CellEnvironment {
+                food_per_cell: 5.into(),
+                ..CellEnvironment::DEFAULT
+            });
+            cell.energy()
+        };
+
+        assert_eq!(step_with_behaviors(CellBehaviors::new(&[&Snacking, &Doubling])), 22.0.into());
+        assert_eq!(step_with_behaviors(CellBehaviors::new(&[&Doubling, &Snacking])), 21.0.into());
+    }
+
+    #[test]
+    fn toxin_beyond_tolerance_harms_cell() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                toxin_tolerance: 2.into(),
+                health_reduction_per_toxin: 0.125.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome::DEFAULT);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                toxin_per_cell: 4.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(cell.health(), 0.75.into());
+    }
+
     #[test]
     fn cell_uses_energy() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 5.25.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -349,9 +1543,9 @@ mod tests {
     fn cell_cannot_expend_energy_below_zero() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 11.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -361,11 +1555,74 @@ mod tests {
         assert_eq!(cell.energy(), 0.into());
     }
 
+    #[test]
+    fn cell_pays_maintenance_before_budgeting() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                maintenance_energy: 2.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 10.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.energy(), 0.into());
+        assert_eq!(cell.health(), 1.0.into());
+    }
+
+    #[test]
+    fn cell_that_cannot_pay_maintenance_loses_health() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                maintenance_energy: 3.into(),
+                health_reduction_per_maintenance_shortfall: 0.25.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome::DEFAULT)
+            .with_energy(1.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.energy(), 0.into());
+        assert_eq!(cell.health(), 0.5.into());
+    }
+
+    #[test]
+    fn cell_without_energy_starves() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                health_reduction_from_zero_energy: 0.4.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 1.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(1.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        assert_eq!(cell.health(), 1.0.into());
+        for _ in 0..3 {
+            assert!(cell.is_alive());
+            cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        }
+
+        assert!(!cell.is_alive());
+        assert_eq!(cell.death_cause(), Some(DeathCause::Starvation));
+    }
+
     #[test]
     fn cell_with_zero_health_is_dead() {
         let cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams::DEFAULT)
+            Genome::DEFAULT)
             .with_health(0.0.into())
             .with_energy(1.into());
 
@@ -376,7 +1633,7 @@ mod tests {
     fn cell_with_health_but_no_energy_is_alive() {
         let cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams::DEFAULT)
+            Genome::DEFAULT)
             .with_health(1.0.into())
             .with_energy(0.into());
 
@@ -390,14 +1647,14 @@ mod tests {
                 food_yield_from_eating: 1.5.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (_, food_eaten) = cell.step(
+        let CellStepOutcome { food_eaten, .. } = cell.step(
             &mut mutation_number_source, &CellEnvironment {
                 food_per_cell: 10.into(),
                 ..CellEnvironment::DEFAULT
@@ -406,6 +1663,30 @@ mod tests {
         assert_eq!(food_eaten, 3.into());
     }
 
+    #[test]
+    fn cell_cannot_eat_more_than_its_max_intake() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                food_yield_from_eating: 1.into(),
+                max_food_intake: 1.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 3.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let CellStepOutcome { food_eaten, food_beyond_intake, .. } = cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                food_per_cell: 2.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!((food_eaten, food_beyond_intake), (1.5.into(), 0.5.into()));
+    }
+
     #[test]
     fn cell_cannot_eat_more_food_than_is_available() {
         let mut cell = Cell::new(
@@ -413,14 +1694,14 @@ mod tests {
                 food_yield_from_eating: 1.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 3.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (_, food_eaten) = cell.step(
+        let CellStepOutcome { food_eaten, .. } = cell.step(
             &mut mutation_number_source, &CellEnvironment {
                 food_per_cell: 2.into(),
                 ..CellEnvironment::DEFAULT
@@ -436,9 +1717,9 @@ mod tests {
                 food_yield_from_eating: 0.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(5.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -459,9 +1740,9 @@ mod tests {
                 food_yield_from_eating: 0.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(5.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -483,9 +1764,9 @@ mod tests {
                 energy_yield_from_digestion: 1.5.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -499,6 +1780,102 @@ mod tests {
         assert_eq!(cell.energy(), 11.into());
     }
 
+    #[test]
+    fn cell_splits_eating_energy_between_food_and_protein() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                food_yield_from_eating: 1.into(),
+                energy_yield_from_digestion: 1.into(),
+                protein_yield_from_eating: 2.into(),
+                energy_yield_from_protein_digestion: 1.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 4.into(),
+                protein_preference: 0.25.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let CellStepOutcome { food_eaten, protein_eaten, .. } = cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                food_per_cell: 10.into(),
+                protein_per_cell: 10.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!((food_eaten, protein_eaten), (3.into(), 2.into()));
+        assert_eq!(cell.energy(), 12.0.into());
+    }
+
+    #[test]
+    fn energy_beyond_storage_capacity_is_lost_and_harmful() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                food_yield_from_eating: 1.into(),
+                energy_yield_from_digestion: 1.5.into(),
+                health_reduction_per_excess_energy: 0.125.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 2.into(),
+                max_energy_storage: 9.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                food_per_cell: 10.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(cell.energy(), 9.into());
+        assert_eq!(cell.health(), 0.75.into());
+    }
+
+    #[test]
+    fn storage_capacity_adds_to_maintenance() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                maintenance_energy: 1.into(),
+                maintenance_energy_per_storage: 0.25.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                max_energy_storage: 8.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(5.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.energy(), 2.into());
+    }
+
+    #[test]
+    fn attempted_energies_add_to_maintenance() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                maintenance_energy_per_attempted_energy: 0.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_attack_energy: 1.into(),
+                attempted_healing_energy: 3.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.energy(), 4.0.into());
+    }
+
     #[test]
     fn expending_eating_energy_reduces_health() {
         let mut cell = Cell::new(
@@ -506,9 +1883,9 @@ mod tests {
                 health_reduction_per_energy_expended: 0.125.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -525,9 +1902,9 @@ mod tests {
                 health_reduction_per_energy_expended: 1.0.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
@@ -544,9 +1921,9 @@ mod tests {
                 health_increase_per_healing_energy: 0.25.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_healing_energy: 1.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_health(0.5.into())
             .with_energy(10.into());
@@ -565,9 +1942,9 @@ mod tests {
                 health_reduction_per_energy_expended: 0.25.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_healing_energy: 1.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_health(0.5.into())
             .with_energy(10.into());
@@ -582,15 +1959,15 @@ mod tests {
     fn cell_with_insufficient_energy_does_not_reproduce() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 child_threshold_energy: 4.into(),
                 child_threshold_food: 0.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(3.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        let child = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT).child;
 
         assert_eq!(child, None);
     }
@@ -599,15 +1976,15 @@ mod tests {
     fn cell_with_insufficient_food_does_not_reproduce() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 child_threshold_energy: 0.into(),
                 child_threshold_food: 4.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(1.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(
+        let CellStepOutcome { child, .. } = cell.step(
             &mut mutation_number_source, &CellEnvironment {
                 food_per_cell: 3.into(),
                 ..CellEnvironment::DEFAULT
@@ -617,35 +1994,37 @@ mod tests {
     }
 
     #[test]
-    fn reproduction_clones_cell_params() {
+    fn reproduction_clones_cell_genome() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 child_threshold_energy: 1.into(),
                 child_threshold_food: 2.into(),
                 attempted_eating_energy: 3.into(),
                 attempted_healing_energy: 4.into(),
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(
+        let CellStepOutcome { child, .. } = cell.step(
             &mut mutation_number_source, &CellEnvironment {
                 food_per_cell: 10.into(),
                 ..CellEnvironment::DEFAULT
             });
 
         assert_ne!(child, None);
-        assert_eq!(child.unwrap().params, CellParams {
+        assert_eq!(*child.unwrap().genome, Genome {
             child_threshold_energy: 1.into(),
             child_threshold_food: 2.into(),
             attempted_eating_energy: 3.into(),
             attempted_healing_energy: 4.into(),
+            ..Genome::DEFAULT
         });
     }
 
     #[test]
-    fn reproduction_mutates_cell_params() {
+    fn reproduction_mutates_cell_genome() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants {
                 child_threshold_energy_mutation_stdev: 0.25.into(),
@@ -654,16 +2033,17 @@ mod tests {
                 attempted_healing_energy_mutation_stdev: 1.0.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 child_threshold_energy: 1.into(),
                 child_threshold_food: 2.into(),
                 attempted_eating_energy: 3.into(),
                 attempted_healing_energy: 4.into(),
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
 
         let mut mutation_number_source = AdditiveMutationNumberSource::new();
-        let (child, _) = cell.step(
+        let CellStepOutcome { child, .. } = cell.step(
             &mut mutation_number_source,
             &CellEnvironment {
                 food_per_cell: 10.into(),
@@ -671,11 +2051,12 @@ mod tests {
             });
 
         assert_ne!(child, None);
-        assert_eq!(child.unwrap().params, CellParams {
+        assert_eq!(*child.unwrap().genome, Genome {
             child_threshold_energy: 1.25.into(),
             child_threshold_food: 2.5.into(),
             attempted_eating_energy: 3.75.into(),
             attempted_healing_energy: 5.0.into(),
+            ..Genome::DEFAULT
         });
     }
 
@@ -686,38 +2067,150 @@ mod tests {
                 create_child_energy: 1.5.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 child_threshold_energy: 4.into(),
                 child_threshold_food: 0.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        let child = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT).child;
 
         assert_ne!(child, None);
-        assert_eq!(child.unwrap().state.energy, 2.5.into());
+        assert_eq!(child.unwrap().energy, 2.5.into());
         assert_eq!(cell.energy(), 6.into());
     }
 
+    #[test]
+    fn cell_donates_part_of_its_surplus_to_a_needier_cell() {
+        let constants = Rc::new(CellConstants {
+            donation_efficiency: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut donor = Cell::new(&constants, Genome {
+            donation_threshold: 4.into(),
+            donation_fraction: 0.25.into(),
+            ..Genome::DEFAULT
+        }).with_energy(12.into());
+        let mut needy = Cell::new(&constants, Genome::DEFAULT).with_energy(1.into());
+        let mut rich = Cell::new(&constants, Genome::DEFAULT).with_energy(4.into());
+
+        assert_eq!(donor.donate(&mut needy), 2.into());
+        assert_eq!((donor.energy(), needy.energy()), (10.into(), 2.into()));
+        assert_eq!(donor.donate(&mut rich), 0.into());
+        assert_eq!(rich.energy(), 4.into());
+    }
+
+    #[test]
+    fn bigger_cell_eats_and_costs_more() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                food_yield_from_eating: 1.into(),
+                max_food_intake: 1.5.into(),
+                maintenance_energy: 1.into(),
+                create_child_energy: 1.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 1.into(),
+                child_threshold_energy: 4.into(),
+                child_threshold_food: 0.into(),
+                size: 2.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(11.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let CellStepOutcome { child, food_eaten, .. } = cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                food_per_cell: 10.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(food_eaten, 2.into());
+        assert_eq!(child.unwrap().energy, 1.into());
+        assert_eq!(cell.energy(), 4.into());
+    }
+
+    #[test]
+    fn efficient_cell_gets_more_from_food_and_costs_more() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                food_yield_from_eating: 1.into(),
+                energy_yield_from_digestion: 1.into(),
+                maintenance_energy_per_efficiency: 0.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 1.into(),
+                eating_efficiency: 2.into(),
+                digestion_efficiency: 1.5.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+
+        let CellStepOutcome { food_eaten, .. } = cell.step(
+            &mut NullMutationNumberSource::new(), &CellEnvironment {
+                food_per_cell: 10.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(food_eaten, 2.0.into());
+        assert_eq!(cell.energy(), 10.25.into());
+    }
+
+    #[test]
+    fn dormant_cell_skips_eating_and_pays_less_maintenance_until_food_returns() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                energy_yield_from_digestion: 1.into(),
+                food_yield_from_eating: 1.into(),
+                maintenance_energy: 1.into(),
+                dormant_maintenance_fraction: 0.25.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_eating_energy: 1.into(),
+                dormancy_threshold_energy: 3.into(),
+                wake_threshold_food: 2.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(2.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        let environment = |food_per_cell: f32| CellEnvironment {
+            food_per_cell: food_per_cell.into(),
+            ..CellEnvironment::DEFAULT
+        };
+
+        let CellStepOutcome { food_eaten, .. } = cell.step(&mut mutation_number_source, &environment(1.0));
+        assert!(cell.is_dormant());
+        assert_eq!(food_eaten, 0.0.into());
+        assert_eq!(cell.energy(), 1.75.into());
+
+        let CellStepOutcome { food_eaten, .. } = cell.step(&mut mutation_number_source, &environment(5.0));
+        assert!(!cell.is_dormant());
+        assert_eq!(food_eaten, 0.75.into());
+        assert_eq!(cell.energy(), 0.75.into());
+    }
+
     #[test]
     fn child_starts_with_full_health() {
         let mut cell = Cell::new(
             &Rc::new(CellConstants::DEFAULT),
-            CellParams {
+            Genome {
                 child_threshold_energy: 1.into(),
                 child_threshold_food: 0.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_health(0.5.into())
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        let child = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT).child;
 
         assert_ne!(child, None);
-        assert_eq!(child.unwrap().state.health, 1.0.into());
+        assert_eq!(child.unwrap().health, 1.0.into());
     }
 
     #[test]
@@ -728,15 +2221,15 @@ mod tests {
                 health_reduction_per_energy_expended: 0.125.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 child_threshold_energy: 2.into(),
                 child_threshold_food: 0.into(),
-                ..CellParams::DEFAULT
+                ..Genome::DEFAULT
             })
             .with_energy(10.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, _) = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        let child = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT).child;
 
         assert_ne!(child, None);
         assert_eq!(cell.health(), 0.75.into());
@@ -751,17 +2244,18 @@ mod tests {
                 health_increase_per_healing_energy: 0.25.into(),
                 ..CellConstants::DEFAULT
             }),
-            CellParams {
+            Genome {
                 attempted_eating_energy: 2.into(),
                 attempted_healing_energy: 2.into(),
                 child_threshold_energy: 2.into(),
                 child_threshold_food: 0.into(),
+                ..Genome::DEFAULT
             })
             .with_health(0.25.into())
             .with_energy(2.into());
         let mut mutation_number_source = NullMutationNumberSource::new();
 
-        let (child, food_eaten) = cell.step(
+        let CellStepOutcome { child, food_eaten, .. } = cell.step(
             &mut mutation_number_source,
             &CellEnvironment {
                 food_per_cell: 10.into(),
@@ -774,6 +2268,458 @@ mod tests {
         assert_eq!(cell.energy(), 0.into());
     }
 
+    #[test]
+    fn cell_does_not_reproduce_when_reproduction_is_not_allowed() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                child_threshold_energy: 1.into(),
+                child_threshold_food: 0.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let CellStepOutcome { child, .. } = cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                reproduction_allowed: false,
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(child, None);
+        assert_eq!(cell.energy(), 10.into());
+    }
+
+    #[test]
+    fn cell_spends_accumulated_energy_on_children() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                create_child_energy: 0.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                child_threshold_energy: 3.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let children = cell.reproduce_from_accumulated_energy(&mut NoMate, &mut mutation_number_source);
+
+        assert_eq!(children.len(), 3);
+        assert!(children.iter().all(|child| child.energy() == 2.5.into()));
+        assert_eq!(cell.energy(), 1.into());
+    }
+
+    #[test]
+    fn cell_converts_light_to_energy() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                light_yield_from_photosynthesis: 2.into(),
+                energy_yield_from_light: 1.5.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_photosynthesis_energy: 1.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                light_per_cell: 10.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(cell.energy(), 12.into());
+    }
+
+    #[test]
+    fn cell_cannot_absorb_more_light_than_is_available() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                light_yield_from_photosynthesis: 2.into(),
+                energy_yield_from_light: 1.into(),
+                ..CellConstants::DEFAULT
+            }),
+            Genome {
+                attempted_photosynthesis_energy: 1.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                light_per_cell: 0.5.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(cell.energy(), 9.5.into());
+    }
+
+    #[test]
+    fn cell_ages_each_step() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome::DEFAULT);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.age(), 2);
+    }
+
+    #[test]
+    fn cell_dies_of_old_age() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants {
+                max_age: 2,
+                ..CellConstants::DEFAULT
+            }),
+            Genome::DEFAULT);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        assert!(cell.is_alive());
+        assert_eq!(cell.death_cause(), None);
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        assert!(!cell.is_alive());
+        assert_eq!(cell.death_cause(), Some(DeathCause::OldAge));
+    }
+
+    #[test]
+    fn digestion_and_healing_become_less_efficient_with_age() {
+        let constants = Rc::new(CellConstants {
+            energy_yield_from_digestion: 1.into(),
+            food_yield_from_eating: 1.into(),
+            health_increase_per_healing_energy: 0.5.into(),
+            efficiency_decay_per_age: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut cell = Cell::new(
+            &constants,
+            Genome {
+                attempted_eating_energy: 4.into(),
+                attempted_healing_energy: 1.into(),
+                ..Genome::DEFAULT
+            })
+            .with_health(0.5.into())
+            .with_energy(5.into());
+        cell.state.age = 1;
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment {
+            food_per_cell: 10.into(),
+            ..CellEnvironment::DEFAULT
+        });
+
+        assert_eq!(cell.energy(), 2.into());
+        assert_eq!(cell.health(), 0.75.into());
+    }
+
+    #[test]
+    fn child_starts_at_age_zero() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                child_threshold_energy: 1.into(),
+                child_threshold_food: 0.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        cell.state.age = 5;
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let child = cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT).child;
+
+        assert_eq!(child.unwrap().age(), 0);
+        assert_eq!(cell.age(), 6);
+    }
+
+    #[test]
+    fn cell_eats_only_during_its_active_phase() {
+        let constants = Rc::new(CellConstants {
+            activity_fraction: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let params = Genome {
+            attempted_eating_energy: 2.into(),
+            active_phase: 0.75.into(),
+            ..Genome::DEFAULT
+        };
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let mut awake_cell = Cell::new(&constants, params).with_energy(10.into());
+        awake_cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                time_of_day: 0.0.into(),
+                ..CellEnvironment::DEFAULT
+            });
+        let mut sleeping_cell = Cell::new(&constants, params).with_energy(10.into());
+        sleeping_cell.step(
+            &mut mutation_number_source, &CellEnvironment {
+                time_of_day: 0.5.into(),
+                ..CellEnvironment::DEFAULT
+            });
+
+        assert_eq!(awake_cell.energy(), 8.into());
+        assert_eq!(sleeping_cell.energy(), 10.into());
+    }
+
+    #[test]
+    fn cell_remembers_moving_average_of_food() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                food_memory_rate: 0.25.into(),
+                ..Genome::DEFAULT
+            });
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment {
+            food_per_cell: 8.into(),
+            ..CellEnvironment::DEFAULT
+        });
+        cell.step(&mut mutation_number_source, &CellEnvironment {
+            food_per_cell: 0.into(),
+            ..CellEnvironment::DEFAULT
+        });
+
+        assert_eq!(cell.state.remembered_food, Some(6.into()));
+    }
+
+    #[test]
+    fn cell_saves_energy_when_remembered_food_is_scarce() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                attempted_eating_energy: 4.into(),
+                scarce_food_threshold: 2.into(),
+                scarcity_spending_fraction: 0.25.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment {
+            food_per_cell: 1.into(),
+            ..CellEnvironment::DEFAULT
+        });
+        assert_eq!(cell.energy(), 9.into());
+
+        cell.step(&mut mutation_number_source, &CellEnvironment {
+            food_per_cell: 3.into(),
+            ..CellEnvironment::DEFAULT
+        });
+        assert_eq!(cell.energy(), 5.into());
+    }
+
+    #[test]
+    fn internal_state_modulates_eating_and_healing() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                attempted_eating_energy: 2.into(),
+                attempted_healing_energy: 2.into(),
+                state_update_rate: 1.0.into(),
+                state_energy_scale: 30.into(),
+                eating_state_response: 3.into(),
+                healing_state_response: 0.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(30.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.state.internal_state.value(), 0.25);
+        assert_eq!(cell.energy(), (30.0 - 2.0 * 1.5 - 2.0 * 0.75).into());
+    }
+
+    #[test]
+    fn net_controlled_cell_follows_its_brain() {
+        let genome = Genome {
+            attempted_healing_energy: 2.into(),
+            brain: BrainWeights {
+                healing: [0.0.into(), 0.5.into(), 0.5.into(), 0.5.into(), 0.5.into()],
+                ..BrainWeights::NEUTRAL
+            },
+            ..Genome::DEFAULT
+        };
+        let mut fixed_cell = Cell::new(&Rc::new(CellConstants::DEFAULT), genome)
+            .with_energy(10.into());
+        let mut net_cell = Cell::new(&Rc::new(CellConstants { controller: CellController::Net, ..CellConstants::DEFAULT }), genome)
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        fixed_cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        net_cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(fixed_cell.energy(), 8.0.into());
+        let healing_effort = 2.0 / (1.0 + 1.0_f32.exp());
+        assert!((net_cell.energy().value() - (10.0 - 2.0 * healing_effort)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cell_budgets_attack_energy() {
+        let mut cell = Cell::new(
+            &Rc::new(CellConstants::DEFAULT),
+            Genome {
+                attempted_attack_energy: 2.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(cell.attack_energy(), 2.into());
+        assert_eq!(cell.energy(), 8.into());
+    }
+
+    #[test]
+    fn attack_damages_victim_and_takes_its_energy() {
+        let constants = Rc::new(CellConstants {
+            attack_efficiency: 2.into(),
+            attack_defense: 1.into(),
+            health_reduction_per_attack_energy: 0.25.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut attacker = Cell::new(&constants, Genome::DEFAULT);
+        attacker.state.attack_energy = 3.into();
+        let mut victim = Cell::new(&constants, Genome::DEFAULT).with_energy(10.into());
+
+        attacker.attack(&mut victim);
+
+        assert_eq!(victim.health(), 0.5.into());
+        assert_eq!(victim.energy(), 6.into());
+        assert_eq!(attacker.energy(), 4.into());
+        assert_eq!(attacker.attack_energy(), 0.into());
+    }
+
+    #[test]
+    fn attacker_cannot_take_more_energy_than_victim_has() {
+        let constants = Rc::new(CellConstants {
+            attack_efficiency: 10.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut attacker = Cell::new(&constants, Genome::DEFAULT);
+        attacker.state.attack_energy = 1.into();
+        let mut victim = Cell::new(&constants, Genome::DEFAULT).with_energy(3.into());
+
+        attacker.attack(&mut victim);
+
+        assert_eq!(victim.energy(), 0.into());
+        assert_eq!(attacker.energy(), 3.into());
+    }
+
+    #[test]
+    fn trading_cells_exchange_energy() {
+        let constants = Rc::new(CellConstants {
+            trade_efficiency: 2.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut cell1 = Cell::new(&constants, Genome::DEFAULT).with_energy(1.into());
+        cell1.state.trade_energy = 3.into();
+        let mut cell2 = Cell::new(&constants, Genome::DEFAULT).with_energy(1.into());
+        cell2.state.trade_energy = 1.into();
+
+        cell1.trade(&mut cell2);
+
+        assert_eq!(cell1.energy(), 3.into());
+        assert_eq!(cell2.energy(), 7.into());
+        assert_eq!(cell1.trade_energy(), 0.into());
+        assert_eq!(cell2.trade_energy(), 0.into());
+    }
+
+    #[test]
+    fn trading_cells_owe_each_other_food_access() {
+        let constants = Rc::new(CellConstants {
+            trade_efficiency: 1.into(),
+            food_access_per_trade_energy: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut cell1 = Cell::new(&constants, Genome::DEFAULT).with_energy(1.into());
+        cell1.state.trade_energy = 4.into();
+        let mut cell2 = Cell::new(&constants, Genome::DEFAULT).with_energy(1.into());
+        cell2.state.trade_energy = 0.into();
+
+        cell1.trade(&mut cell2);
+
+        assert_eq!(cell2.state.food_access_owed, Some((cell1.id(), 2.into())));
+        assert_eq!(cell1.state.food_access_owed, None);
+    }
+
+    #[test]
+    fn cell_stops_offering_trade_energy_to_partners_that_do_not_reciprocate() {
+        let constants = Rc::new(CellConstants {
+            trade_efficiency: 1.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut cell = Cell::new(
+            &constants,
+            Genome {
+                attempted_trade_energy: 2.into(),
+                trade_reciprocity_threshold: 1.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(10.into());
+        let mut freeloader = Cell::new(&constants, Genome::DEFAULT);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        assert_eq!(cell.trade_energy(), 2.into());
+        cell.trade(&mut freeloader);
+        cell.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+
+        assert_eq!(freeloader.energy(), 2.into());
+        assert_eq!(cell.trade_energy(), 0.into());
+        assert_eq!(cell.energy(), 8.into());
+    }
+
+    #[test]
+    fn unmatched_trade_offer_is_withdrawn() {
+        let mut cell = Cell::new(&Rc::new(CellConstants::DEFAULT), Genome::DEFAULT).with_energy(1.into());
+        cell.state.trade_energy = 2.into();
+
+        cell.withdraw_trade_offer();
+
+        assert_eq!(cell.energy(), 3.into());
+        assert_eq!(cell.trade_energy(), 0.into());
+    }
+
+    #[test]
+    fn child_mutations_depend_only_on_seed_and_parentage() {
+        let mutate = |source: &mut RandomMutationNumberSource, cell_id, child_index| {
+            source.start_stream(cell_id, child_index);
+            source.mutate(1.into(), 1.into())
+        };
+        let mut source1 = RandomMutationNumberSource::with_seed(7);
+        let mut source2 = RandomMutationNumberSource::with_seed(7);
+
+        mutate(&mut source2, 3, 0);
+        mutate(&mut source2, 3, 1);
+
+        assert_eq!(mutate(&mut source1, 5, 0), mutate(&mut source2, 5, 0));
+        assert_ne!(mutate(&mut source1, 5, 0), mutate(&mut source1, 5, 1));
+        assert_ne!(mutate(&mut source1, 5, 0), mutate(&mut RandomMutationNumberSource::with_seed(8), 5, 0));
+    }
+
+    #[test]
+    fn parent_counts_its_children() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut cell = Cell::new(&constants, Genome { child_threshold_energy: 1.into(), ..Genome::DEFAULT })
+            .with_energy(2.into());
+
+        let children = cell.reproduce_from_accumulated_energy(&mut NoMate, &mut NullMutationNumberSource::new());
+
+        assert_eq!(cell.state.num_children, 2);
+        assert!(children.iter().all(|child| child.state.num_children == 0));
+    }
+
     pub struct AdditiveMutationNumberSource {}
 
     impl AdditiveMutationNumberSource {
@@ -787,4 +2733,73 @@ mod tests {
             value + stdev
         }
     }
+
+    fn arbitrary_genome() -> impl Strategy<Value=Genome> {
+        (prop::collection::vec(0.0_f32..20.0, Genome::F32_POSITIVE_GENES.len()),
+         prop::collection::vec(0.0_f32..=1.0, Genome::F32_ZERO_TO_ONE_GENES.len()))
+            .prop_map(|(positives, fractions)| {
+                let mut genome = Genome::DEFAULT;
+                for (gene, value) in Genome::F32_POSITIVE_GENES.iter().zip(positives) {
+                    *gene.value_mut(&mut genome) = value.into();
+                }
+                for (gene, value) in Genome::F32_ZERO_TO_ONE_GENES.iter().zip(fractions) {
+                    *gene.value_mut(&mut genome) = value.into();
+                }
+                genome
+            })
+    }
+
+    fn arbitrary_environment() -> impl Strategy<Value=CellEnvironment> {
+        (0.0_f32..100.0, 0.0_f32..100.0, 0.0_f32..100.0, 0.0_f32..10.0, 0.0_f32..=1.0, any::<bool>(), 0.0_f32..10.0)
+            .prop_map(|(food, protein, light, toxin, time_of_day, reproduction_allowed, stress)| CellEnvironment {
+                food_per_cell: food.into(),
+                protein_per_cell: protein.into(),
+                light_per_cell: light.into(),
+                toxin_per_cell: toxin.into(),
+                time_of_day: time_of_day.into(),
+                reproduction_allowed,
+                stress: stress.into(),
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn stepping_keeps_health_and_energy_in_their_domains(
+            genome in arbitrary_genome(),
+            energy in 0.0_f32..100.0,
+            health in 0.0_f32..=1.0,
+            environments in prop::collection::vec(arbitrary_environment(), 1..5),
+        ) {
+            let constants = Rc::new(CellConstants {
+                create_child_energy: 1.into(),
+                energy_yield_from_digestion: 0.8.into(),
+                food_yield_from_eating: 3.into(),
+                health_increase_per_healing_energy: 0.2.into(),
+                health_reduction_from_entropy: 0.1.into(),
+                health_reduction_from_zero_energy: 0.3.into(),
+                health_reduction_per_energy_expended: 0.01.into(),
+                light_yield_from_photosynthesis: 2.into(),
+                energy_yield_from_light: 0.5.into(),
+                maintenance_energy: 0.5.into(),
+                health_reduction_per_maintenance_shortfall: 0.1.into(),
+                maintenance_energy_per_storage: 0.01.into(),
+                health_reduction_per_excess_energy: 0.01.into(),
+                toxin_tolerance: 1.into(),
+                health_reduction_per_toxin: 0.05.into(),
+                toxin_yield_from_detox: 2.into(),
+                efficiency_decay_per_age: 0.01.into(),
+                ..CellConstants::DEFAULT
+            });
+            let mut cell = Cell::new(&constants, genome).with_energy(energy.into()).with_health(health.into());
+            let mut mutation_number_source = NullMutationNumberSource::new();
+
+            for environment in &environments {
+                let outcome = cell.step(&mut mutation_number_source, environment);
+                for cell in std::iter::once(&cell).chain(outcome.child.as_ref()) {
+                    prop_assert!((0.0..=1.0).contains(&cell.health().value()), "health {}", cell.health());
+                    prop_assert!(cell.energy().value() >= 0.0 && cell.energy().value().is_finite(), "energy {}", cell.energy());
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::mem;
+use std::rc::Rc;
+use crate::cell::{Cell, CellConstants, CellMut, CellState};
+use crate::genome::{Genome, GenomeInterner};
+use crate::number_types::{F32Positive, F32ZeroToOne};
+
+/// Struct-of-arrays storage for a population of cells: their constants, genomes, energies,
+/// healths, and the rest of their states are kept in separate vectors, so scans over one of
+/// them, such as finding dead cells by health or means of energy, touch only that part of
+/// memory. Cells are addressed by index: `get` reads one through a `CellView`, and
+/// `with_cell_mut` runs cell behavior on one in place through a `CellMut`.
+///
+/// Cells share their genomes, too: children get their parents' until a gene mutates or crosses
+/// over, and cells added or loaded get the copy of any other cell with the same genes, so that
+/// millions of cells with few distinct genomes hold few copies of them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "StoredCellColumns")]
+pub struct CellColumns {
+    constants: Vec<Rc<CellConstants>>,
+    genomes: Vec<Rc<Genome>>,
+    energies: Vec<F32Positive>,
+    healths: Vec<F32ZeroToOne>,
+    states: Vec<CellState>,
+    #[serde(skip)]
+    interner: GenomeInterner,
+}
+
+/// The columns as serialized, whose genomes are interned as they are loaded.
+#[derive(Deserialize)]
+struct StoredCellColumns {
+    constants: Vec<Rc<CellConstants>>,
+    genomes: Vec<Rc<Genome>>,
+    energies: Vec<F32Positive>,
+    healths: Vec<F32ZeroToOne>,
+    states: Vec<CellState>,
+}
+
+impl From<StoredCellColumns> for CellColumns {
+    fn from(stored: StoredCellColumns) -> Self {
+        let mut interner = GenomeInterner::new();
+        CellColumns {
+            constants: stored.constants,
+            genomes: stored.genomes.into_iter().map(|genome| interner.intern(genome)).collect(),
+            energies: stored.energies,
+            healths: stored.healths,
+            states: stored.states,
+            interner,
+        }
+    }
+}
+
+/// Compares the cells, not how their genomes happen to be shared.
+impl PartialEq for CellColumns {
+    fn eq(&self, other: &Self) -> bool {
+        self.constants == other.constants
+            && self.genomes == other.genomes
+            && self.energies == other.energies
+            && self.healths == other.healths
+            && self.states == other.states
+    }
+}
+
+impl CellColumns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.genomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.genomes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> CellView<'_> {
+        assert!(index < self.len(), "cell index {} out of range for {} cells", index, self.len());
+        CellView { columns: self, index }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=CellView<'_>> + '_ {
+        (0..self.len()).map(move |index| CellView { columns: self, index })
+    }
+
+    pub fn genomes(&self) -> &[Rc<Genome>] {
+        &self.genomes
+    }
+
+    pub fn energies(&self) -> &[F32Positive] {
+        &self.energies
+    }
+
+    pub fn healths(&self) -> &[F32ZeroToOne] {
+        &self.healths
+    }
+
+    pub fn states(&self) -> &[CellState] {
+        &self.states
+    }
+
+    pub fn is_alive(&self, index: usize) -> bool {
+        self.healths[index].value() > 0.0
+    }
+
+    /// Bytes allocated for the cells' genomes, counting each shared copy once along with its
+    /// reference counts, plus the column of pointers to them.
+    pub fn genome_memory_bytes(&self) -> usize {
+        let num_copies = self.genomes.iter().map(Rc::as_ptr).collect::<HashSet<_>>().len();
+        num_copies * (mem::size_of::<Genome>() + 2 * mem::size_of::<usize>())
+            + self.genomes.capacity() * mem::size_of::<Rc<Genome>>()
+    }
+
+    pub fn set_constants(&mut self, constants: &Rc<CellConstants>) {
+        for cell_constants in &mut self.constants {
+            *cell_constants = Rc::clone(constants);
+        }
+    }
+
+    pub fn push(&mut self, cell: Cell) {
+        let (constants, genome, energy, health, state) = cell.into_parts();
+        self.constants.push(constants);
+        self.genomes.push(self.interner.intern(genome));
+        self.energies.push(energy);
+        self.healths.push(health);
+        self.states.push(state);
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Cell {
+        Cell::from_parts(
+            self.constants.swap_remove(index),
+            self.genomes.swap_remove(index),
+            self.energies.swap_remove(index),
+            self.healths.swap_remove(index),
+            self.states.swap_remove(index))
+    }
+
+    pub fn with_cell_mut<R, F: FnOnce(&mut CellMut) -> R>(&mut self, index: usize, f: F) -> R {
+        f(&mut CellMut::new(&self.constants[index], &mut self.genomes[index], &mut self.energies[index], &mut self.healths[index], &mut self.states[index]))
+    }
+
+    /// Like `with_cell_mut`, but also lends out the genomes of all the other cells.
+    pub fn with_cell_mut_and_other_genomes<R, F: FnOnce(&mut CellMut, OtherGenomes) -> R>(&mut self, index: usize, f: F) -> R {
+        let (before, rest) = self.genomes.split_at_mut(index);
+        let (genome, after) = rest.split_first_mut().expect("cell index in range");
+        f(&mut CellMut::new(&self.constants[index], genome, &mut self.energies[index], &mut self.healths[index], &mut self.states[index]),
+          OtherGenomes { before, after })
+    }
+
+    pub fn with_two_cells_mut<R, F: FnOnce(&mut CellMut, &mut CellMut) -> R>(&mut self, index1: usize, index2: usize, f: F) -> R {
+        let (genome1, genome2) = two_mut(&mut self.genomes, index1, index2);
+        let (energy1, energy2) = two_mut(&mut self.energies, index1, index2);
+        let (health1, health2) = two_mut(&mut self.healths, index1, index2);
+        let (state1, state2) = two_mut(&mut self.states, index1, index2);
+        f(&mut CellMut::new(&self.constants[index1], genome1, energy1, health1, state1),
+          &mut CellMut::new(&self.constants[index2], genome2, energy2, health2, state2))
+    }
+}
+
+/// The genomes of every cell but one, indexed as if that cell had been removed.
+pub struct OtherGenomes<'a> {
+    before: &'a [Rc<Genome>],
+    after: &'a [Rc<Genome>],
+}
+
+impl<'a> OtherGenomes<'a> {
+    pub fn len(&self) -> usize {
+        self.before.len() + self.after.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> &'a Genome {
+        if index < self.before.len() { &self.before[index] } else { &self.after[index - self.before.len()] }
+    }
+}
+
+fn two_mut<T>(items: &mut [T], index1: usize, index2: usize) -> (&mut T, &mut T) {
+    assert_ne!(index1, index2);
+    if index1 < index2 {
+        let (left, right) = items.split_at_mut(index2);
+        (&mut left[index1], &mut right[0])
+    } else {
+        let (left, right) = items.split_at_mut(index1);
+        (&mut right[0], &mut left[index2])
+    }
+}
+
+impl From<Vec<Cell>> for CellColumns {
+    fn from(cells: Vec<Cell>) -> Self {
+        let mut columns = CellColumns::new();
+        columns.extend(cells);
+        columns
+    }
+}
+
+impl Extend<Cell> for CellColumns {
+    fn extend<I: IntoIterator<Item=Cell>>(&mut self, cells: I) {
+        for cell in cells {
+            self.push(cell);
+        }
+    }
+}
+
+/// Read-only access to one cell in a `CellColumns`, with the same accessors as `Cell`.
+#[derive(Clone, Copy)]
+pub struct CellView<'a> {
+    columns: &'a CellColumns,
+    index: usize,
+}
+
+impl<'a> CellView<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn constants(&self) -> &'a Rc<CellConstants> {
+        &self.columns.constants[self.index]
+    }
+
+    pub fn genome(&self) -> &'a Genome {
+        &self.columns.genomes[self.index]
+    }
+
+    pub fn state(&self) -> &'a CellState {
+        &self.columns.states[self.index]
+    }
+
+    pub fn id(&self) -> u64 {
+        self.state().id
+    }
+
+    pub fn health(&self) -> F32ZeroToOne {
+        self.columns.healths[self.index]
+    }
+
+    pub fn energy(&self) -> F32Positive {
+        self.columns.energies[self.index]
+    }
+
+    pub fn age(&self) -> u32 {
+        self.state().age
+    }
+
+    pub fn attack_energy(&self) -> F32Positive {
+        self.state().attack_energy
+    }
+
+    pub fn trade_energy(&self) -> F32Positive {
+        self.state().trade_energy
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.columns.is_alive(self.index)
+    }
+
+    pub fn to_cell(&self) -> Cell {
+        Cell::from_parts(Rc::clone(self.constants()), Rc::clone(&self.columns.genomes[self.index]), self.energy(), self.health(), *self.state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{CellEnvironment, NullMutationNumberSource};
+
+    #[test]
+    fn columns_round_trip_cells() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cell = Cell::new(
+            &constants,
+            Genome {
+                attempted_eating_energy: 2.0.into(),
+                ..Genome::DEFAULT
+            })
+            .with_energy(3.0.into())
+            .with_health(0.5.into());
+        let mut columns = CellColumns::from(vec![cell.clone()]);
+
+        assert_eq!(columns.get(0).to_cell(), cell);
+        assert_eq!(columns.get(0).energy(), 3.0.into());
+        assert_eq!(columns.healths()[0], 0.5.into());
+        assert_eq!(columns.swap_remove(0), cell);
+        assert!(columns.is_empty());
+    }
+
+    #[test]
+    fn cells_with_the_same_genes_share_a_genome() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let other_genome = Genome { size: 2.0.into(), ..Genome::DEFAULT };
+        let columns = CellColumns::from((0..6)
+            .map(|index| Cell::new(&constants, if index < 4 { Genome::DEFAULT } else { other_genome }))
+            .collect::<Vec<_>>());
+
+        let loaded: CellColumns = serde_json::from_str(&serde_json::to_string(&columns).unwrap()).unwrap();
+
+        for columns in [&columns, &loaded] {
+            assert!(Rc::ptr_eq(&columns.genomes[0], &columns.genomes[3]));
+            assert!(Rc::ptr_eq(&columns.genomes[4], &columns.genomes[5]));
+            assert_eq!(*columns.genomes[4], other_genome);
+        }
+        assert_eq!(loaded, columns);
+        assert!(columns.genome_memory_bytes() < 6 * mem::size_of::<Genome>() / 2);
+    }
+
+    #[test]
+    fn changes_made_through_cell_api_are_stored() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_from_entropy: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut columns = CellColumns::from(vec![
+            Cell::new(&constants, Genome::DEFAULT).with_health(0.5.into()),
+            Cell::new(&constants, Genome::DEFAULT),
+        ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        columns.with_two_cells_mut(1, 0, |cell1, cell2| {
+            cell1.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+            cell2.step(&mut mutation_number_source, &CellEnvironment::DEFAULT);
+        });
+
+        assert_eq!(columns.get(1).health(), 0.5.into());
+        assert_eq!(columns.get(1).age(), 1);
+        assert!(!columns.is_alive(0));
+    }
+}
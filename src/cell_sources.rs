@@ -0,0 +1,192 @@
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use crate::cell::{Cell, CellConstants};
+use crate::food_sources::WorldSummary;
+use crate::genome::Genome;
+use crate::number_types::F32Positive;
+
+/// Adds cells to the world from outside it each step, as `FoodSource`s add food, to model
+/// immigration and rescue effects. The cells arrive before any cell steps, and are not counted
+/// as births.
+#[typetag::serde]
+pub trait CellSource {
+    fn cells_this_step(&mut self, world: &WorldSummary) -> Vec<Cell>;
+}
+
+/// Where a cell source's immigrants come from. Each arrives with full health, `energy`, and the
+/// source's constants, which interventions that set constants do not change.
+#[derive(Serialize, Deserialize)]
+pub struct Immigrants {
+    genomes: ImmigrantGenomes,
+    energy: F32Positive,
+    constants: Rc<CellConstants>,
+    rng: Pcg64,
+}
+
+#[derive(Serialize, Deserialize)]
+enum ImmigrantGenomes {
+    /// Sampled as `Genome::sample` does. Boxed to keep the enum as small as a `Vec`.
+    Sampled { gene_means: Box<Genome>, gene_stdevs: Box<Genome> },
+    /// Copies of a saved population's genomes, chosen at random.
+    Saved(Vec<Genome>),
+}
+
+impl Immigrants {
+    pub fn sampled(gene_means: Genome, gene_stdevs: Genome, energy: F32Positive, constants: &Rc<CellConstants>, seed: u64) -> Self {
+        Self::new(ImmigrantGenomes::Sampled { gene_means: Box::new(gene_means), gene_stdevs: Box::new(gene_stdevs) }, energy, constants, seed)
+    }
+
+    /// Immigrants with the genomes of a saved population, such as one loaded with
+    /// `Snapshot::load`. There are none if `genomes` is empty.
+    pub fn saved(genomes: Vec<Genome>, energy: F32Positive, constants: &Rc<CellConstants>, seed: u64) -> Self {
+        Self::new(ImmigrantGenomes::Saved(genomes), energy, constants, seed)
+    }
+
+    fn new(genomes: ImmigrantGenomes, energy: F32Positive, constants: &Rc<CellConstants>, seed: u64) -> Self {
+        Immigrants {
+            genomes,
+            energy,
+            constants: Rc::clone(constants),
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+
+    fn draw(&mut self, count: usize) -> Vec<Cell> {
+        (0..count)
+            .filter_map(|_| {
+                let genome = match &self.genomes {
+                    ImmigrantGenomes::Sampled { gene_means, gene_stdevs } => Genome::sample(gene_means, gene_stdevs, &mut self.rng),
+                    ImmigrantGenomes::Saved(genomes) => *genomes.choose(&mut self.rng)?,
+                };
+                Some(Cell::new(&self.constants, genome).with_energy(self.energy))
+            })
+            .collect()
+    }
+}
+
+/// Adds `cells_per_step` cells each step on average, carrying fractions of a cell over to
+/// later steps, so that 0.1 adds one cell every ten steps.
+#[derive(Serialize, Deserialize)]
+pub struct ConstantCellSource {
+    immigrants: Immigrants,
+    cells_per_step: F32Positive,
+    cells_owed: f32,
+}
+
+impl ConstantCellSource {
+    pub fn new(immigrants: Immigrants, cells_per_step: F32Positive) -> Self {
+        ConstantCellSource {
+            immigrants,
+            cells_per_step,
+            cells_owed: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl CellSource for ConstantCellSource {
+    fn cells_this_step(&mut self, _world: &WorldSummary) -> Vec<Cell> {
+        self.cells_owed += self.cells_per_step.value();
+        let count = self.cells_owed.floor();
+        self.cells_owed -= count;
+        self.immigrants.draw(count as usize)
+    }
+}
+
+/// Adds `cells_per_pulse` cells at once every `period` steps, starting at `first_step`.
+#[derive(Serialize, Deserialize)]
+pub struct PulseCellSource {
+    immigrants: Immigrants,
+    cells_per_pulse: usize,
+    period: u32,
+    first_step: u32,
+}
+
+impl PulseCellSource {
+    pub fn new(immigrants: Immigrants, cells_per_pulse: usize, period: u32, first_step: u32) -> Self {
+        PulseCellSource {
+            immigrants,
+            cells_per_pulse,
+            period,
+            first_step,
+        }
+    }
+}
+
+#[typetag::serde]
+impl CellSource for PulseCellSource {
+    fn cells_this_step(&mut self, world: &WorldSummary) -> Vec<Cell> {
+        if world.step >= self.first_step && (world.step - self.first_step).is_multiple_of(self.period.max(1)) {
+            self.immigrants.draw(self.cells_per_pulse)
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Founds a new population in any step that ends with every cell dead, so that a run carries on
+/// through extinctions rather than stopping at the first.
+#[derive(Serialize, Deserialize)]
+pub struct ExtinctionReseeding {
+    founders: Immigrants,
+    num_founders: usize,
+}
+
+impl ExtinctionReseeding {
+    pub fn new(founders: Immigrants, num_founders: usize) -> Self {
+        ExtinctionReseeding {
+            founders,
+            num_founders,
+        }
+    }
+
+    pub(crate) fn founders(&mut self) -> Vec<Cell> {
+        self.founders.draw(self.num_founders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn immigrants() -> Immigrants {
+        let genome = Genome { attempted_eating_energy: 2.0.into(), ..Genome::ZERO };
+        Immigrants::sampled(genome, Genome::ZERO, 5.0.into(), &Rc::new(CellConstants::DEFAULT), 0)
+    }
+
+    fn summary(step: u32) -> WorldSummary {
+        WorldSummary { step, ..WorldSummary::EMPTY }
+    }
+
+    #[test]
+    fn constant_cell_source_carries_fractional_cells_over() {
+        let mut source = ConstantCellSource::new(immigrants(), 0.5.into());
+
+        let counts: Vec<usize> = (0..4).map(|step| source.cells_this_step(&summary(step)).len()).collect();
+
+        assert_eq!(counts, vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn pulse_cell_source_adds_cells_periodically() {
+        let mut source = PulseCellSource::new(immigrants(), 3, 2, 1);
+
+        let counts: Vec<usize> = (0..5).map(|step| source.cells_this_step(&summary(step)).len()).collect();
+
+        assert_eq!(counts, vec![0, 3, 0, 3, 0]);
+    }
+
+    #[test]
+    fn immigrants_have_the_configured_genomes_and_energy() {
+        let cells = immigrants().draw(2);
+        assert!(cells.iter().all(|cell| cell.genome().attempted_eating_energy == 2.0.into() && cell.energy() == 5.0.into()));
+
+        let saved = vec![Genome { attempted_healing_energy: 1.0.into(), ..Genome::ZERO }];
+        let mut immigrants = Immigrants::saved(saved.clone(), 1.0.into(), &Rc::new(CellConstants::DEFAULT), 0);
+        assert_eq!(immigrants.draw(1)[0].genome(), &saved[0]);
+        assert!(Immigrants::saved(vec![], 1.0.into(), &Rc::new(CellConstants::DEFAULT), 0).draw(2).is_empty());
+    }
+}
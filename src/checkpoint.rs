@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use crate::build_info::BuildInfo;
+use crate::cell::RandomMutationNumberSource;
+use crate::run_id::RunId;
+use crate::world::World;
+
+/// Everything needed to resume a run so that it continues exactly as if it had never stopped:
+/// the world (including food source and generation counters) and the mutation RNG state.
+/// Also records the build and run that saved it.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    #[serde(default)]
+    pub build_info: BuildInfo,
+    #[serde(default)]
+    pub run_id: Option<RunId>,
+    pub step: u32,
+    pub world: World,
+    pub mutation_number_source: RandomMutationNumberSource,
+}
+
+#[derive(Serialize)]
+struct CheckpointRef<'a> {
+    build_info: BuildInfo,
+    run_id: Option<RunId>,
+    step: u32,
+    world: &'a World,
+    mutation_number_source: &'a RandomMutationNumberSource,
+}
+
+pub fn save(path: &Path, step: u32, run_id: Option<RunId>, world: &World, mutation_number_source: &RandomMutationNumberSource) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    let checkpoint = CheckpointRef { build_info: BuildInfo::current(), run_id, step, world, mutation_number_source };
+    serde_json::to_writer(writer, &checkpoint)?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> io::Result<Checkpoint> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
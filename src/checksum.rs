@@ -0,0 +1,46 @@
+use crate::genome::Genome;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A hash of a population's genomes that is the same on every machine and Rust version, for
+/// checking that seeded runs stay identical without saving full snapshots. The genomes are
+/// sorted first, so the checksum does not depend on the order of the cells. Uses 64-bit FNV-1a
+/// over the bits of the gene values, since `std`'s hashers are not guaranteed to be stable.
+pub fn population_checksum<'a, I>(genomes: I) -> u64
+    where I: Iterator<Item=&'a Genome>,
+{
+    let mut gene_bits: Vec<Vec<u32>> = genomes
+        .map(|genome| genome.gene_values().map(f32::to_bits).collect())
+        .collect();
+    gene_bits.sort_unstable();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for bits in gene_bits.iter().flatten() {
+        for byte in bits.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_ignores_cell_order() {
+        let genome1 = Genome { attempted_eating_energy: 1.0.into(), ..Genome::ZERO };
+        let genome2 = Genome { attempted_eating_energy: 2.0.into(), ..Genome::ZERO };
+
+        assert_eq!(population_checksum([genome1, genome2].iter()), population_checksum([genome2, genome1].iter()));
+        assert_ne!(population_checksum([genome1, genome2].iter()), population_checksum([genome1, genome1].iter()));
+    }
+
+    #[test]
+    fn checksum_is_stable() {
+        assert_eq!(population_checksum([].iter()), FNV_OFFSET_BASIS);
+        assert_eq!(population_checksum([Genome::ZERO].iter()), 0x37027190f725c8c5);
+    }
+}
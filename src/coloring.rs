@@ -0,0 +1,213 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::cell_columns::CellView;
+use crate::genome::Genome;
+
+/// A gene or state value of a cell that can drive a color channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Property {
+    /// The gene at this position in `Genome::gene_names`.
+    Gene(usize),
+    Energy,
+    Health,
+    Age,
+}
+
+impl Property {
+    pub fn value(&self, cell: &CellView) -> f32 {
+        match self {
+            Property::Gene(index) => cell.genome().gene_values().nth(*index).unwrap_or(0.0),
+            Property::Energy => cell.energy().value(),
+            Property::Health => cell.health().value(),
+            Property::Age => cell.age() as f32,
+        }
+    }
+}
+
+impl FromStr for Property {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "energy" => Ok(Property::Energy),
+            "health" => Ok(Property::Health),
+            "age" => Ok(Property::Age),
+            _ => Genome::gene_names().position(|name| name == s)
+                .map(Property::Gene)
+                .ok_or_else(|| format!("unknown gene or state '{}' (expected a gene name, energy, health, or age)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Property::Gene(index) => write!(f, "{}", Genome::gene_names().nth(*index).unwrap_or("?")),
+            Property::Energy => write!(f, "energy"),
+            Property::Health => write!(f, "health"),
+            Property::Age => write!(f, "age"),
+        }
+    }
+}
+
+/// Maps a property's values from `min` to `max` onto 0 to 1, clamping values outside the range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale {
+    pub property: Property,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Scale {
+    pub fn fraction(&self, cell: &CellView) -> f32 {
+        let value = self.property.value(cell);
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How cells are colored in visual output: hue and brightness each follow a scaled property, so
+/// that the same rule gives the same colors in every view and every run. Hue runs from red at
+/// the bottom of its scale through green to blue at the top. With no hue property cells are gray,
+/// and with no brightness property they are fully bright.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorRule {
+    pub hue: Option<Scale>,
+    pub brightness: Option<Scale>,
+}
+
+impl ColorRule {
+    /// Hue by eating energy, brightness by health.
+    pub const DEFAULT: ColorRule = ColorRule {
+        hue: Some(Scale { property: Property::Gene(0), min: 0.0, max: 10.0 }),
+        brightness: Some(Scale { property: Property::Health, min: 0.0, max: 1.0 }),
+    };
+
+    pub fn color(&self, cell: &CellView) -> Rgb {
+        let brightness = self.brightness.map_or(1.0, |scale| scale.fraction(cell));
+        match self.hue {
+            Some(scale) => Rgb::from_hsv(scale.fraction(cell) * 240.0, 1.0, brightness),
+            None => Rgb::from_hsv(0.0, 0.0, brightness),
+        }
+    }
+}
+
+impl Default for ColorRule {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Parses rules such as `hue=attempted_eating_energy:0:10,brightness=health`. A property without
+/// a range is scaled from 0 to 1. Channels not listed are left uncolored.
+impl FromStr for ColorRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rule = ColorRule { hue: None, brightness: None };
+        for assignment in s.split(',').map(str::trim).filter(|assignment| !assignment.is_empty()) {
+            let (channel, scale) = assignment.split_once('=')
+                .ok_or_else(|| format!("expected channel=property, got '{}'", assignment))?;
+            let scale = parse_scale(scale.trim())?;
+            match channel.trim() {
+                "hue" => rule.hue = Some(scale),
+                "brightness" => rule.brightness = Some(scale),
+                channel => return Err(format!("unknown color channel '{}' (expected hue or brightness)", channel)),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for ColorRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let channels: Vec<String> = [("hue", self.hue), ("brightness", self.brightness)].iter()
+            .filter_map(|(channel, scale)| scale.map(|scale| format!("{}={}:{}:{}", channel, scale.property, scale.min, scale.max)))
+            .collect();
+        write!(f, "{}", channels.join(","))
+    }
+}
+
+fn parse_scale(s: &str) -> Result<Scale, String> {
+    let mut parts = s.split(':');
+    let property = parts.next().unwrap_or_default().parse()?;
+    let bound = |part: Option<&str>, default: f32| match part {
+        Some(part) => part.parse::<f32>().map_err(|_| format!("invalid range bound '{}' in '{}'", part, s)),
+        None => Ok(default),
+    };
+    let min = bound(parts.next(), 0.0)?;
+    let max = bound(parts.next(), 1.0)?;
+    if parts.next().is_some() || max <= min || max.is_nan() || min.is_nan() {
+        return Err(format!("expected property or property:min:max with min < max, got '{}'", s));
+    }
+    Ok(Scale { property, min, max })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Converts a hue in degrees and a saturation and value from 0 to 1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        let sector = (hue / 60.0).rem_euclid(6.0);
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+    use crate::cell_columns::CellColumns;
+
+    fn cells(cells: &[(Genome, f32)]) -> CellColumns {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        CellColumns::from(cells.iter()
+            .map(|&(genome, health)| Cell::new(&constants, genome).with_health(health.into()))
+            .collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn rule_maps_properties_to_hue_and_brightness() {
+        let rule: ColorRule = "hue=attempted_eating_energy:0:10,brightness=health".parse().unwrap();
+        let genome = Genome { attempted_eating_energy: 5.0.into(), ..Genome::DEFAULT };
+        let cells = cells(&[(genome, 1.0), (genome, 0.0), (Genome::DEFAULT, 1.0)]);
+
+        assert_eq!(rule, ColorRule::DEFAULT);
+        assert_eq!(rule.to_string().parse(), Ok(rule));
+        assert_eq!(rule.color(&cells.get(0)), Rgb(0, 255, 0));
+        assert_eq!(rule.color(&cells.get(1)), Rgb(0, 0, 0));
+        assert_eq!(rule.color(&cells.get(2)), Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn rule_without_hue_is_gray() {
+        let rule: ColorRule = "brightness=health".parse().unwrap();
+        let cells = cells(&[(Genome::DEFAULT, 0.5)]);
+
+        assert_eq!(rule.color(&cells.get(0)), Rgb(128, 128, 128));
+    }
+
+    #[test]
+    fn bad_rules_are_rejected() {
+        assert!("hue=nonsense".parse::<ColorRule>().is_err());
+        assert!("tint=health".parse::<ColorRule>().is_err());
+        assert!("hue=health:1:0".parse::<ColorRule>().is_err());
+    }
+}
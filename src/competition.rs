@@ -0,0 +1,151 @@
+use crate::cell::NullMutationNumberSource;
+use crate::genome::Genome;
+use crate::world::World;
+
+/// How one replicate of a competition between two genomes ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// One genome made up the whole population at the given step.
+    Fixed { winner: Competitor, step: u32 },
+    /// Every cell died at the same step.
+    Extinct,
+    /// Both genomes were still present after the maximum number of steps.
+    Coexisting,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Competitor {
+    A,
+    B,
+}
+
+/// Runs a world seeded with cells of two genomes until one of them takes over the population or
+/// `max_steps` pass. Mutation is off, so every cell keeps exactly one of the two genomes.
+pub fn run_replicate(world: &mut World, genome_a: &Genome, genome_b: &Genome, max_steps: u32) -> Outcome {
+    let mut mutation_number_source = NullMutationNumberSource::new();
+    for step in 0..=max_steps {
+        if step > 0 {
+            world.step(&mut mutation_number_source);
+        }
+
+        let num_a = world.cells().genomes().iter().filter(|genome| genome.as_ref() == genome_a).count();
+        let num_b = world.cells().genomes().iter().filter(|genome| genome.as_ref() == genome_b).count();
+        match (num_a, num_b) {
+            (0, 0) => return Outcome::Extinct,
+            (_, 0) => return Outcome::Fixed { winner: Competitor::A, step },
+            (0, _) => return Outcome::Fixed { winner: Competitor::B, step },
+            _ => {}
+        }
+    }
+    Outcome::Coexisting
+}
+
+/// Outcomes tallied over the replicates of a competition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompetitionStats {
+    pub wins_a: u32,
+    pub wins_b: u32,
+    pub extinctions: u32,
+    pub coexistences: u32,
+    fixation_steps_sum: u64,
+}
+
+impl CompetitionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Fixed { winner, step } => {
+                match winner {
+                    Competitor::A => self.wins_a += 1,
+                    Competitor::B => self.wins_b += 1,
+                }
+                self.fixation_steps_sum += step as u64;
+            }
+            Outcome::Extinct => self.extinctions += 1,
+            Outcome::Coexisting => self.coexistences += 1,
+        }
+    }
+
+    pub fn num_replicates(&self) -> u32 {
+        self.wins_a + self.wins_b + self.extinctions + self.coexistences
+    }
+
+    /// Mean step at which one genome took over, over the replicates in which one did.
+    pub fn mean_fixation_step(&self) -> Option<f64> {
+        let num_fixed = self.wins_a + self.wins_b;
+        if num_fixed == 0 {
+            None
+        } else {
+            Some(self.fixation_steps_sum as f64 / num_fixed as f64)
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    pub fn print(&self) {
+        let num_replicates = self.num_replicates().max(1) as f64;
+        for (label, count) in [
+            ("genome A wins", self.wins_a),
+            ("genome B wins", self.wins_b),
+            ("both extinct", self.extinctions),
+            ("no winner", self.coexistences),
+        ] {
+            println!("{}: {} ({:.1}%)", label, count, 100.0 * count as f64 / num_replicates);
+        }
+        match self.mean_fixation_step() {
+            Some(mean) => println!("mean steps to fixation: {:.1}", mean),
+            None => println!("mean steps to fixation: -"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+
+    fn world(genome_a: &Genome, genome_b: &Genome) -> World {
+        let constants = Rc::new(CellConstants {
+            health_reduction_per_energy_expended: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        World::new().with_cells(vec![
+            Cell::new(&constants, *genome_a).with_energy(10.into()),
+            Cell::new(&constants, *genome_b).with_energy(10.into()),
+        ])
+    }
+
+    #[test]
+    fn genome_that_outlives_the_other_wins() {
+        let genome_a = Genome::DEFAULT;
+        let genome_b = Genome { attempted_healing_energy: 1.into(), ..Genome::DEFAULT };
+
+        let outcome = run_replicate(&mut world(&genome_a, &genome_b), &genome_a, &genome_b, 10);
+
+        assert_eq!(outcome, Outcome::Fixed { winner: Competitor::A, step: 2 });
+    }
+
+    #[test]
+    fn identical_fates_coexist() {
+        let genome_a = Genome::DEFAULT;
+        let genome_b = Genome { active_phase: 0.5.into(), ..Genome::DEFAULT };
+
+        let outcome = run_replicate(&mut world(&genome_a, &genome_b), &genome_a, &genome_b, 10);
+
+        assert_eq!(outcome, Outcome::Coexisting);
+    }
+
+    #[test]
+    fn stats_tally_outcomes() {
+        let mut stats = CompetitionStats::new();
+        stats.record(Outcome::Fixed { winner: Competitor::A, step: 10 });
+        stats.record(Outcome::Fixed { winner: Competitor::B, step: 20 });
+        stats.record(Outcome::Coexisting);
+
+        assert_eq!((stats.wins_a, stats.wins_b, stats.coexistences), (1, 1, 1));
+        assert_eq!(stats.mean_fixation_step(), Some(15.0));
+    }
+}
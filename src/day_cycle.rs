@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use crate::number_types::F32ZeroToOne;
+
+/// Tracks the time of day in a repeating cycle of `length` steps, the first half of which is
+/// day and the second half night. A length of 0 means there is no cycle: it is always midnight
+/// of the first day, which counts as daytime.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DayCycle {
+    length: u32,
+    steps_into_day: u32,
+}
+
+impl DayCycle {
+    pub fn new(length: u32) -> Self {
+        DayCycle {
+            length,
+            steps_into_day: 0,
+        }
+    }
+
+    pub fn time_of_day(&self) -> F32ZeroToOne {
+        if self.length == 0 {
+            return 0.0.into();
+        }
+
+        F32ZeroToOne::unchecked(self.steps_into_day as f32 / self.length as f32)
+    }
+
+    pub fn is_day(&self) -> bool {
+        self.time_of_day().value() < 0.5
+    }
+
+    pub fn advance(&mut self) {
+        if self.length > 0 {
+            self.steps_into_day = (self.steps_into_day + 1) % self.length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_cycle_alternates_day_and_night() {
+        let mut cycle = DayCycle::new(4);
+        let mut days = vec![];
+        for _ in 0..6 {
+            days.push(cycle.is_day());
+            cycle.advance();
+        }
+        assert_eq!(days, [true, true, false, false, true, true]);
+    }
+
+    #[test]
+    fn day_cycle_reports_time_of_day() {
+        let mut cycle = DayCycle::new(4);
+        cycle.advance();
+        assert_eq!(cycle.time_of_day(), 0.25.into());
+    }
+
+    #[test]
+    fn zero_length_day_cycle_is_always_day() {
+        let mut cycle = DayCycle::new(0);
+        cycle.advance();
+        assert!(cycle.is_day());
+        assert_eq!(cycle.time_of_day(), 0.0.into());
+    }
+}
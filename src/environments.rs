@@ -0,0 +1,138 @@
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+use crate::food_sources::{BoomBustFoodSource, ConstantFoodSource, FoodSource, LinearlyDecliningFoodSource, LogisticFoodSource, RandomFoodSource, StochasticPulseFoodSource};
+use crate::number_types::F32Positive;
+
+/// Named patterns of food supply for common experiments. Each adds `food_per_step` per step on
+/// average (or, for `GradualDecline`, at the start, and for `Regrowing`, at most), so presets can
+/// be compared at equal richness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Environment {
+    /// The same food every step.
+    Stable,
+    /// Alternating booms of twice the food and busts of none, each `BOOM_BUST_PHASE_STEPS` long.
+    BoomBust,
+    /// Food declining steadily to none by the end of the run.
+    GradualDecline,
+    /// A random amount of food each step, from none to twice the mean.
+    Chaotic,
+    /// The given number of steps' worth of food at once (by default `PULSE_PERIOD_STEPS`), at
+    /// random times averaging that many steps apart.
+    StochasticPulsed(u32),
+    /// Food regrowing logistically toward `REGROWTH_CAPACITY_STEPS` steps' worth, fastest when
+    /// half grazed.
+    Regrowing,
+}
+
+impl Environment {
+    pub const BOOM_BUST_PHASE_STEPS: u32 = 500;
+    pub const PULSE_PERIOD_STEPS: u32 = 100;
+    pub const REGROWTH_CAPACITY_STEPS: u32 = 100;
+
+    /// The food source for a run of `steps` steps. Only `Chaotic` and `StochasticPulsed` draw
+    /// from `rng`, to seed their own randomness.
+    pub fn food_source<R: Rng>(&self, food_per_step: F32Positive, steps: u32, rng: &mut R) -> Box<dyn FoodSource> {
+        match self {
+            Environment::Stable => Box::new(ConstantFoodSource::new(food_per_step)),
+            Environment::BoomBust => Box::new(BoomBustFoodSource::new(
+                food_per_step * 2.0,
+                0.0.into(),
+                Self::BOOM_BUST_PHASE_STEPS,
+                Self::BOOM_BUST_PHASE_STEPS)),
+            Environment::GradualDecline => Box::new(LinearlyDecliningFoodSource::new(
+                food_per_step,
+                food_per_step / steps.max(1) as usize)),
+            Environment::Chaotic => Box::new(RandomFoodSource::new(food_per_step, rng.gen())),
+            Environment::StochasticPulsed(mean_period) => Box::new(StochasticPulseFoodSource::new(
+                food_per_step * *mean_period as f32,
+                F32Positive::unchecked(1.0 / *mean_period as f32),
+                rng.gen())),
+            Environment::Regrowing => {
+                // Logistic growth peaks at a quarter of the growth rate times the capacity.
+                let capacity = food_per_step * Self::REGROWTH_CAPACITY_STEPS as f32;
+                let growth_rate = F32Positive::unchecked(4.0 / Self::REGROWTH_CAPACITY_STEPS as f32);
+                Box::new(LogisticFoodSource::new(growth_rate, capacity, food_per_step))
+            }
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(Environment::Stable),
+            "boom-bust" => Ok(Environment::BoomBust),
+            "gradual-decline" => Ok(Environment::GradualDecline),
+            "chaotic" => Ok(Environment::Chaotic),
+            "stochastic-pulsed" => Ok(Environment::StochasticPulsed(Self::PULSE_PERIOD_STEPS)),
+            "regrowing" => Ok(Environment::Regrowing),
+            _ => match s.strip_prefix("stochastic-pulsed:").map(str::parse::<u32>) {
+                Some(Ok(mean_period)) if mean_period > 0 => Ok(Environment::StochasticPulsed(mean_period)),
+                Some(_) => Err(format!("mean period in '{}' is not a whole number of steps of at least 1", s)),
+                None => Err(format!("unknown environment '{}' (expected stable, boom-bust, gradual-decline, chaotic, stochastic-pulsed[:MEAN_PERIOD], or regrowing)", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Environment::Stable => write!(f, "stable"),
+            Environment::BoomBust => write!(f, "boom-bust"),
+            Environment::GradualDecline => write!(f, "gradual-decline"),
+            Environment::Chaotic => write!(f, "chaotic"),
+            Environment::StochasticPulsed(mean_period) => write!(f, "stochastic-pulsed:{}", mean_period),
+            Environment::Regrowing => write!(f, "regrowing"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+    use crate::food_sources::WorldSummary;
+
+    fn total_food(environment: Environment, steps: u32) -> f32 {
+        let mut source = environment.food_source(10.0.into(), steps, &mut Pcg64::seed_from_u64(0));
+        (0..steps).map(|step| source.food_this_step(&WorldSummary { step, ..WorldSummary::EMPTY }).value()).sum()
+    }
+
+    #[test]
+    fn presets_round_trip_through_their_names() {
+        for environment in [Environment::Stable, Environment::BoomBust, Environment::GradualDecline, Environment::Chaotic,
+                            Environment::StochasticPulsed(50), Environment::Regrowing] {
+            assert_eq!(environment.to_string().parse::<Environment>(), Ok(environment));
+        }
+        assert_eq!("stochastic-pulsed".parse(), Ok(Environment::StochasticPulsed(Environment::PULSE_PERIOD_STEPS)));
+        assert!("tropical".parse::<Environment>().is_err());
+        assert!("stochastic-pulsed:0".parse::<Environment>().is_err());
+    }
+
+    #[test]
+    fn periodic_presets_add_the_same_food_on_average() {
+        let steps = 2 * Environment::BOOM_BUST_PHASE_STEPS;
+        assert_eq!(total_food(Environment::BoomBust, steps), total_food(Environment::Stable, steps));
+    }
+
+    #[test]
+    fn regrowing_adds_at_most_the_food_per_step() {
+        let mut source = Environment::Regrowing.food_source(10.0.into(), 100, &mut Pcg64::seed_from_u64(0));
+        let with_food = |food: f32| WorldSummary { food: food.into(), ..WorldSummary::EMPTY };
+        assert_eq!(source.food_this_step(&with_food(500.0)), 10.0.into());
+        assert!(source.food_this_step(&with_food(100.0)).value() < 10.0);
+        assert_eq!(source.food_this_step(&with_food(1000.0)), 0.0.into());
+    }
+
+    #[test]
+    fn gradual_decline_runs_out_by_the_end() {
+        let mut source = Environment::GradualDecline.food_source(10.0.into(), 4, &mut Pcg64::seed_from_u64(0));
+        let food: Vec<f32> = (0..5).map(|_| source.food_this_step(&WorldSummary::EMPTY).value()).collect();
+        assert_eq!(food, vec![10.0, 7.5, 5.0, 2.5, 0.0]);
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use std::rc::Rc;
+use crate::cell::Cell;
+use crate::genome::Genome;
+use crate::species;
+use crate::world::{World, WorldObserver, WorldTotals};
+
+/// The state of a run at one step, with what happened over the epoch since the previous
+/// snapshot, for analysis by library users, the command line, and dashboards alike.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EpochSnapshot {
+    pub first_step: u32,
+    pub step: u32,
+    pub population: PopulationStats,
+    pub genes: Vec<GeneStats>,
+    pub diversity: Diversity,
+    pub flows: ResourceFlows,
+    pub deaths: DeathCauses,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PopulationStats {
+    pub num_cells: usize,
+    pub num_born: u64,
+    pub num_died: u64,
+    pub mean_health: f32,
+    pub mean_energy: f32,
+    pub mean_age: f32,
+    pub max_age: u32,
+}
+
+/// The mean and standard deviation of one gene over a set of genomes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GeneStats {
+    pub name: &'static str,
+    pub mean: f64,
+    pub stdev: f64,
+}
+
+/// How many species the cells cluster into at the snapshot's species threshold, and the size of
+/// the largest.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diversity {
+    pub num_species: usize,
+    pub largest_species_size: usize,
+}
+
+/// Food and protein added to and taken out of the world over the epoch, with the food, protein,
+/// and toxin left at its end.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResourceFlows {
+    pub food_added: f64,
+    pub food_eaten: f64,
+    pub food_beyond_intake: f64,
+    pub food_decayed: f64,
+    pub food: f32,
+    pub protein_added: f64,
+    pub protein_eaten: f64,
+    pub protein: f32,
+    pub toxin: f32,
+}
+
+/// Deaths over an epoch by cause. Cells that reach their `max_age` die of old age, and the
+/// living parents of a replaced generation are counted as replaced. Any other death is from
+/// health lost, whether to starvation, entropy, toxin, attack, a death hazard, or culling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DeathCauses {
+    pub old_age: u64,
+    pub health_lost: u64,
+    pub replaced: u64,
+}
+
+impl EpochSnapshot {
+    /// Takes a snapshot of `world` at `step` for the epoch that started at `first_step` with
+    /// `start_totals`, during which `deaths` died.
+    pub fn take(world: &World, first_step: u32, step: u32, start_totals: &WorldTotals, deaths: DeathCauses,
+                species_threshold: f32) -> Self {
+        let totals = world.totals();
+        let species = species::cluster(world.cells().genomes().iter().map(Rc::as_ref), species_threshold);
+        EpochSnapshot {
+            first_step,
+            step,
+            population: PopulationStats {
+                num_cells: world.num_cells(),
+                num_born: totals.num_born - start_totals.num_born,
+                num_died: totals.num_died - start_totals.num_died,
+                mean_health: world.mean_health(),
+                mean_energy: world.mean_energy(),
+                mean_age: world.mean_age(),
+                max_age: world.max_age(),
+            },
+            genes: gene_stats(world.cells().genomes().iter().map(Rc::as_ref)),
+            diversity: Diversity {
+                num_species: species.len(),
+                largest_species_size: species.first().map_or(0, |species| species.size),
+            },
+            flows: ResourceFlows {
+                food_added: totals.food_added - start_totals.food_added,
+                food_eaten: totals.food_eaten - start_totals.food_eaten,
+                food_beyond_intake: totals.food_beyond_intake - start_totals.food_beyond_intake,
+                food_decayed: totals.food_decayed - start_totals.food_decayed,
+                food: world.food().value(),
+                protein_added: totals.protein_added - start_totals.protein_added,
+                protein_eaten: totals.protein_eaten - start_totals.protein_eaten,
+                protein: world.protein().value(),
+                toxin: world.toxin().value(),
+            },
+            deaths,
+        }
+    }
+}
+
+/// The stats of each gene, in `Genome::gene_names` order, over the genomes. They are all 0 if
+/// there are no genomes.
+pub fn gene_stats<'a, I>(genomes: I) -> Vec<GeneStats>
+    where I: Iterator<Item=&'a Genome>,
+{
+    let num_genes = Genome::gene_names().count();
+    let mut means = vec![0.0_f64; num_genes];
+    let mut squared_deviation_sums = vec![0.0_f64; num_genes];
+    let mut count = 0;
+    for genome in genomes {
+        count += 1;
+        for ((mean, squared_deviation_sum), value) in means.iter_mut().zip(&mut squared_deviation_sums).zip(genome.gene_values()) {
+            let value = value as f64;
+            let deviation = value - *mean;
+            *mean += deviation / count as f64;
+            *squared_deviation_sum += deviation * (value - *mean);
+        }
+    }
+    Genome::gene_names().zip(means).zip(squared_deviation_sums)
+        .map(|((name, mean), squared_deviation_sum)| GeneStats {
+            name,
+            mean,
+            stdev: if count == 0 { 0.0 } else { (squared_deviation_sum / count as f64).sqrt() },
+        })
+        .collect()
+}
+
+impl DeathCauses {
+    pub fn total(&self) -> u64 {
+        self.old_age + self.health_lost + self.replaced
+    }
+}
+
+impl WorldObserver for DeathCauses {
+    fn on_death(&mut self, cell: &Cell) {
+        if cell.is_alive() {
+            self.replaced += 1;
+        } else if cell.age() >= cell.constants().max_age {
+            self.old_age += 1;
+        } else {
+            self.health_lost += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gene_stats_give_mean_and_stdev_of_each_gene() {
+        let genomes = [1.0, 3.0].map(|eating: f32| Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT });
+
+        let stats = gene_stats(genomes.iter());
+
+        assert_eq!(stats.len(), Genome::gene_names().count());
+        assert_eq!(stats[0], GeneStats { name: "attempted_eating_energy", mean: 2.0, stdev: 1.0 });
+        assert_eq!((stats[1].mean, stats[1].stdev), (Genome::DEFAULT.attempted_healing_energy.value() as f64, 0.0));
+        assert_eq!(gene_stats([].iter())[0], GeneStats { name: "attempted_eating_energy", mean: 0.0, stdev: 0.0 });
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tracing::{debug, info};
+use tracing_subscriber::filter::LevelFilter;
+use crate::async_writer::AsyncWriter;
+use crate::cell::Cell;
+use crate::world::{World, WorldObserver};
+
+/// Emits a `tracing` event for each birth and death (at debug level) and a summary of each step
+/// (at info level), so that a run that went wrong can be examined event by event afterward.
+/// Deaths carry their cause: the `DeathCause` of a cell with no health left, or `replaced` for
+/// a living parent replaced by its generation's offspring.
+pub struct EventLog {
+    step: u32,
+}
+
+impl EventLog {
+    /// Starts counting steps from `first_step`.
+    pub fn new(first_step: u32) -> Self {
+        EventLog { step: first_step }
+    }
+}
+
+impl WorldObserver for EventLog {
+    fn before_step(&mut self, _world: &World) {
+        self.step += 1;
+    }
+
+    fn after_step(&mut self, world: &World, num_born: usize, num_died: usize) {
+        info!(step = self.step, num_born, num_died, num_cells = world.num_cells(),
+              mean_energy = world.mean_energy(), mean_health = world.mean_health(), food = world.food().value(),
+              "step");
+    }
+
+    fn on_birth(&mut self, cell: &Cell) {
+        debug!(step = self.step, id = cell.id(), parent_id = cell.parent_id(), energy = cell.energy().value(),
+               "birth");
+    }
+
+    fn on_death(&mut self, cell: &Cell) {
+        let cause = match cell.death_cause() {
+            Some(cause) => cause.to_string(),
+            None => "replaced".to_string(),
+        };
+        debug!(step = self.step, id = cell.id(), age = cell.age(), energy = cell.energy().value(), cause,
+               "death");
+    }
+}
+
+/// Sends events up to `level` to `path`, or to standard error if there is none. Events go to the
+/// file on a background thread, so that logging every birth and death doesn't hold up the run,
+/// until the returned `EventLogFile` is flushed or dropped.
+pub fn init(level: LevelFilter, path: Option<&Path>) -> io::Result<Option<EventLogFile>> {
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+    match path {
+        Some(path) => {
+            let file = EventLogFile(Arc::new(Mutex::new(AsyncWriter::new(File::create(path)?))));
+            let writer = file.0.clone();
+            builder.with_ansi(false).with_writer(move || SharedWriter(writer.clone())).try_init()
+                .map_err(io::Error::other)?;
+            Ok(Some(file))
+        }
+        None => {
+            builder.with_writer(io::stderr).try_init().map_err(io::Error::other)?;
+            Ok(None)
+        }
+    }
+}
+
+/// The event log file. Dropping it writes the events logged so far, even if the run panicked.
+pub struct EventLogFile(Arc<Mutex<AsyncWriter>>);
+
+impl EventLogFile {
+    /// Waits until the events logged so far have been written, returning the first error in
+    /// writing any of them.
+    pub fn flush(&self) -> io::Result<()> {
+        lock(&self.0).flush()
+    }
+}
+
+impl Drop for EventLogFile {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+struct SharedWriter(Arc<Mutex<AsyncWriter>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        lock(&self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        lock(&self.0).flush()
+    }
+}
+
+fn lock(writer: &Mutex<AsyncWriter>) -> MutexGuard<'_, AsyncWriter> {
+    writer.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::cell::{CellConstants, RandomMutationNumberSource};
+    use crate::genome::Genome;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logs_births_deaths_and_step_summaries() {
+        let parent_genome = Genome {
+            child_threshold_energy: 4.0.into(),
+            child_threshold_food: 0.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&Rc::new(CellConstants::DEFAULT), parent_genome).with_energy(10.0.into()),
+            Cell::new(&Rc::new(CellConstants { max_age: 1, ..CellConstants::DEFAULT }), Genome::DEFAULT),
+        ]);
+        world.add_observer(Rc::new(RefCell::new(EventLog::new(5))));
+        let mut mutation_number_source = RandomMutationNumberSource::with_seed(0);
+
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt().with_max_level(LevelFilter::DEBUG).with_ansi(false)
+            .without_time().with_writer(move || writer.clone()).finish();
+        tracing::subscriber::with_default(subscriber, || world.step(&mut mutation_number_source));
+
+        let events = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(events.lines().collect::<Vec<_>>(), vec![
+            "DEBUG evo2::event_log: birth step=6 id=2 parent_id=0 energy=4.0",
+            "DEBUG evo2::event_log: death step=6 id=1 age=1 energy=0.0 cause=\"old_age\"",
+            " INFO evo2::event_log: step step=6 num_born=1 num_died=1 num_cells=2 mean_energy=5.0 mean_health=1.0 food=0.0",
+        ]);
+    }
+}
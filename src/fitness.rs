@@ -0,0 +1,360 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use crate::cell::Cell;
+use crate::genome::Genome;
+use crate::world::{World, WorldObserver};
+
+/// Collects the realized lifetime reproductive success of cells as they die, i.e. how many
+/// children each had, together with their genes. Its report says how offspring counts are
+/// distributed and how strongly each gene goes with having more of them, which is selection on
+/// that gene as actually measured in the run. Reports cover the deaths since the last `reset`.
+#[derive(Clone, Debug, Default)]
+pub struct LifetimeSuccess {
+    offspring_counts: Vec<u32>,
+    gene_values: Vec<Vec<f32>>,
+}
+
+/// The distribution of lifetime offspring counts over a set of dead cells, with the correlation
+/// of each gene (in `Genome::gene_names` order) with offspring count. A correlation is 0 if the
+/// gene or the offspring count did not vary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitnessReport {
+    pub num_deaths: usize,
+    pub mean_offspring: f64,
+    pub stdev_offspring: f64,
+    pub max_offspring: u32,
+    pub childless_fraction: f64,
+    pub gini_offspring: f64,
+    pub gene_correlations: Vec<f64>,
+}
+
+impl LifetimeSuccess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn num_deaths(&self) -> usize {
+        self.offspring_counts.len()
+    }
+
+    pub fn record(&mut self, genome: &Genome, num_children: u32) {
+        self.offspring_counts.push(num_children);
+        self.gene_values.push(genome.gene_values().collect());
+    }
+
+    pub fn reset(&mut self) {
+        self.offspring_counts.clear();
+        self.gene_values.clear();
+    }
+
+    pub fn report(&self) -> FitnessReport {
+        let counts: Vec<f64> = self.offspring_counts.iter().map(|&count| count as f64).collect();
+        let (mean_offspring, variance) = mean_and_variance(&counts);
+        let gene_correlations = (0..Genome::gene_names().count())
+            .map(|gene_index| {
+                let values: Vec<f64> = self.gene_values.iter().map(|values| values[gene_index] as f64).collect();
+                correlation(&values, &counts)
+            })
+            .collect();
+        FitnessReport {
+            num_deaths: counts.len(),
+            mean_offspring,
+            stdev_offspring: variance.sqrt(),
+            max_offspring: self.offspring_counts.iter().copied().max().unwrap_or(0),
+            childless_fraction: if counts.is_empty() { 0.0 } else {
+                self.offspring_counts.iter().filter(|&&count| count == 0).count() as f64 / counts.len() as f64
+            },
+            gini_offspring: OffspringCounts::from_counts(&self.offspring_counts).gini(),
+            gene_correlations,
+        }
+    }
+}
+
+impl WorldObserver for LifetimeSuccess {
+    fn on_death(&mut self, cell: &Cell) {
+        self.record(cell.genome(), cell.num_children());
+    }
+}
+
+impl FitnessReport {
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "  lifetime offspring of {} dead cells: mean {:.3}, stdev {:.3}, max {}, childless {:.1}%, gini {:.3}",
+                 self.num_deaths, self.mean_offspring, self.stdev_offspring, self.max_offspring, 100.0 * self.childless_fraction,
+                 self.gini_offspring)?;
+        let correlations: Vec<String> = Genome::gene_names().zip(&self.gene_correlations)
+            .map(|(name, correlation)| format!("{}: {:+.3}", name, correlation))
+            .collect();
+        writeln!(writer, "  correlation with offspring: {}", correlations.join(", "))
+    }
+}
+
+/// Counts the cells that have died with each lifetime offspring count, for summarizing reproductive
+/// skew over a whole run in little space however many cells die.
+#[derive(Clone, Debug, Default)]
+pub struct OffspringCounts {
+    num_cells_by_count: Vec<u64>,
+}
+
+/// The distribution of lifetime offspring counts over the cells that died. The Gini coefficient
+/// measures reproductive skew: 0 if every cell had as many children, approaching 1 as fewer and
+/// fewer cells have all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct OffspringStats {
+    pub num_deaths: u64,
+    pub mean: f64,
+    pub variance: f64,
+    pub max: u32,
+    pub gini: f64,
+}
+
+impl OffspringCounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_counts(counts: &[u32]) -> Self {
+        let mut offspring_counts = Self::new();
+        for &count in counts {
+            offspring_counts.record(count);
+        }
+        offspring_counts
+    }
+
+    pub fn record(&mut self, num_children: u32) {
+        let index = num_children as usize;
+        if index >= self.num_cells_by_count.len() {
+            self.num_cells_by_count.resize(index + 1, 0);
+        }
+        self.num_cells_by_count[index] += 1;
+    }
+
+    /// None if no cells have died.
+    pub fn stats(&self) -> Option<OffspringStats> {
+        let num_deaths: u64 = self.num_cells_by_count.iter().sum();
+        if num_deaths == 0 {
+            return None;
+        }
+        let n = num_deaths as f64;
+        let mean = self.counts_and_cells().map(|(count, cells)| count * cells).sum::<f64>() / n;
+        let variance = self.counts_and_cells().map(|(count, cells)| (count - mean).powi(2) * cells).sum::<f64>() / n;
+        Some(OffspringStats {
+            num_deaths,
+            mean,
+            variance,
+            max: self.num_cells_by_count.len().saturating_sub(1) as u32,
+            gini: self.gini(),
+        })
+    }
+
+    /// The Gini coefficient of the counts, Σ(2i - n - 1)·xᵢ / (n·Σx) over the counts xᵢ sorted
+    /// ascending with ranks i from 1 to n, taking each run of equal counts at once. 0 if there
+    /// were no children.
+    fn gini(&self) -> f64 {
+        let (mut num_below, mut weighted_sum, mut total) = (0.0, 0.0, 0.0);
+        let n: f64 = self.num_cells_by_count.iter().sum::<u64>() as f64;
+        for (count, cells) in self.counts_and_cells() {
+            weighted_sum += count * cells * (2.0 * num_below + cells - n);
+            total += count * cells;
+            num_below += cells;
+        }
+        if total == 0.0 { 0.0 } else { weighted_sum / (n * total) }
+    }
+
+    fn counts_and_cells(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.num_cells_by_count.iter().enumerate()
+            .filter(|(_, &cells)| cells > 0)
+            .map(|(count, &cells)| (count as f64, cells as f64))
+    }
+}
+
+impl WorldObserver for OffspringCounts {
+    fn on_death(&mut self, cell: &Cell) {
+        self.record(cell.num_children());
+    }
+}
+
+impl OffspringStats {
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "#   lifetime offspring of {} dead cells: mean {:.3}, variance {:.3}, max {}, gini {:.3}",
+                 self.num_deaths, self.mean, self.variance, self.max, self.gini)
+    }
+}
+
+/// Measures selection on the living population each time it is sampled, relating each gene to
+/// two fitness proxies: the children each cell has had since the last sample (or since its birth,
+/// if later), and its energy now. Unlike `LifetimeSuccess`, it needn't wait for cells to die.
+#[derive(Clone, Debug, Default)]
+pub struct SelectionAnalysis {
+    children_at_last_sample: HashMap<u64, u32>,
+}
+
+/// How each gene (in `Genome::gene_names` order) went with fitness over a sampling interval.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectionReport {
+    pub step: u32,
+    pub num_cells: usize,
+    pub mean_offspring: f64,
+    pub genes: Vec<GeneSelection>,
+}
+
+/// A gene's correlations with the fitness proxies, and its selection differential: the covariance
+/// of the gene with offspring relative to the mean, which is how far selection alone would move
+/// the gene's mean in a generation. The differential is 0 if no cell had children.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneSelection {
+    pub name: &'static str,
+    pub offspring_correlation: f64,
+    pub energy_correlation: f64,
+    pub selection_differential: f64,
+}
+
+impl SelectionAnalysis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sample(&mut self, step: u32, world: &World) -> SelectionReport {
+        let cells = world.cells();
+        let offspring: Vec<f64> = cells.states().iter()
+            .map(|state| (state.num_children - self.children_at_last_sample.get(&state.id).copied().unwrap_or(0)) as f64)
+            .collect();
+        let energies: Vec<f64> = cells.energies().iter().map(|energy| energy.value() as f64).collect();
+        self.children_at_last_sample = cells.states().iter().map(|state| (state.id, state.num_children)).collect();
+
+        let (mean_offspring, _) = mean_and_variance(&offspring);
+        let relative_fitnesses: Vec<f64> = offspring.iter()
+            .map(|count| if mean_offspring > 0.0 { count / mean_offspring } else { 0.0 })
+            .collect();
+        let genes = Genome::gene_names().enumerate()
+            .map(|(gene_index, name)| {
+                let values: Vec<f64> = cells.genomes().iter()
+                    .map(|genome| genome.gene_values().nth(gene_index).unwrap() as f64)
+                    .collect();
+                GeneSelection {
+                    name,
+                    offspring_correlation: correlation(&values, &offspring),
+                    energy_correlation: correlation(&values, &energies),
+                    selection_differential: covariance(&values, &relative_fitnesses),
+                }
+            })
+            .collect();
+        SelectionReport { step, num_cells: cells.len(), mean_offspring, genes }
+    }
+}
+
+impl SelectionReport {
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "  selection on {} cells, {:.3} offspring each since last sample (r offspring, r energy, differential):",
+                 self.num_cells, self.mean_offspring)?;
+        for gene in &self.genes {
+            writeln!(writer, "    {}: {:+.3}, {:+.3}, {:+.4}",
+                     gene.name, gene.offspring_correlation, gene.energy_correlation, gene.selection_differential)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_csv_header<W: Write>(writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "step,gene,offspring_correlation,energy_correlation,selection_differential")
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for gene in &self.genes {
+            writeln!(writer, "{},{},{},{},{}",
+                     self.step, gene.name, gene.offspring_correlation, gene.energy_correlation, gene.selection_differential)?;
+        }
+        Ok(())
+    }
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance)
+}
+
+fn covariance(xs: &[f64], ys: &[f64]) -> f64 {
+    if xs.is_empty() {
+        return 0.0;
+    }
+    let (mean_x, _) = mean_and_variance(xs);
+    let (mean_y, _) = mean_and_variance(ys);
+    xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>() / xs.len() as f64
+}
+
+fn correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let (_, variance_x) = mean_and_variance(xs);
+    let (_, variance_y) = mean_and_variance(ys);
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return 0.0;
+    }
+    covariance(xs, ys) / (variance_x * variance_y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{CellConstants, CellState};
+
+    #[test]
+    fn report_summarizes_offspring_and_their_gene_correlations() {
+        let mut success = LifetimeSuccess::new();
+        for (eating, num_children) in [(1.0, 0), (2.0, 1), (3.0, 2), (4.0, 5)] {
+            success.record(&Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT }, num_children);
+        }
+
+        let report = success.report();
+
+        assert_eq!((report.num_deaths, report.mean_offspring, report.max_offspring), (4, 2.0, 5));
+        assert_eq!(report.childless_fraction, 0.25);
+        assert!(report.gene_correlations[0] > 0.9);
+        assert_eq!(report.gene_correlations[1], 0.0);
+
+        assert_eq!(report.gini_offspring, 0.5);
+
+        success.reset();
+        assert_eq!(success.report().num_deaths, 0);
+    }
+
+    #[test]
+    fn offspring_stats_measure_reproductive_skew() {
+        assert_eq!(OffspringCounts::new().stats(), None);
+        assert_eq!(OffspringCounts::from_counts(&[2, 2, 2]).stats().unwrap().gini, 0.0);
+        assert_eq!(OffspringCounts::from_counts(&[0, 0, 0]).stats().unwrap().gini, 0.0);
+
+        let stats = OffspringCounts::from_counts(&[0, 0, 0, 4]).stats().unwrap();
+
+        assert_eq!((stats.num_deaths, stats.mean, stats.variance, stats.max), (4, 1.0, 3.0, 4));
+        assert_eq!(stats.gini, 0.75);
+    }
+
+    fn world(cells: &[(f32, u32)]) -> World {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        World::new().with_cells(cells.iter()
+            .map(|&(eating, num_children)| {
+                let genome = Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT };
+                Cell::from_parts(constants.clone(), Rc::new(genome), 0.0.into(), 1.0.into(), CellState { num_children, ..CellState::DEFAULT })
+            })
+            .collect())
+    }
+
+    #[test]
+    fn selection_counts_only_offspring_since_the_last_sample() {
+        let mut analysis = SelectionAnalysis::new();
+        analysis.sample(10, &world(&[(1.0, 3), (2.0, 2), (3.0, 1), (4.0, 0)]));
+
+        let report = analysis.sample(20, &world(&[(1.0, 3), (2.0, 2), (3.0, 3), (4.0, 4)]));
+
+        assert_eq!((report.step, report.num_cells, report.mean_offspring), (20, 4, 1.5));
+        let eating = report.genes[0];
+        assert_eq!(eating.name, "attempted_eating_energy");
+        assert!(eating.offspring_correlation > 0.9);
+        assert!(eating.selection_differential > 0.0);
+        assert!(report.genes[1..].iter().all(|gene| gene.selection_differential == 0.0));
+    }
+}
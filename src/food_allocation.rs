@@ -0,0 +1,205 @@
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::brain::efforts;
+use crate::cell::internal_state_factor;
+use crate::cell_columns::{CellColumns, CellView};
+use crate::number_types::F32Positive;
+
+/// Divides the world's food among its cells each step. A cell can eat no more than its share,
+/// which it also senses as its food per cell, e.g. in deciding whether to go dormant. The shares
+/// add up to at most the food, so contention never overdraws it. Protein is always shared
+/// equally.
+#[typetag::serde]
+pub trait FoodAllocation {
+    /// The share of `food` for each of the cells, in order. Only allocations that draw random
+    /// numbers use `rng`.
+    fn shares(&mut self, food: F32Positive, cells: &CellColumns, rng: &mut Pcg64) -> Vec<F32Positive>;
+}
+
+/// Every cell gets the same share.
+#[derive(Serialize, Deserialize)]
+pub struct EqualShares;
+
+#[typetag::serde]
+impl FoodAllocation for EqualShares {
+    fn shares(&mut self, food: F32Positive, cells: &CellColumns, _rng: &mut Pcg64) -> Vec<F32Positive> {
+        vec![food / cells.len(); cells.len()]
+    }
+}
+
+/// Cells get shares in proportion to the food their attempted eating energy would get them, so
+/// that cells trying harder to eat get more, or equal shares if no cell is trying.
+#[derive(Serialize, Deserialize)]
+pub struct ProportionalShares;
+
+#[typetag::serde]
+impl FoodAllocation for ProportionalShares {
+    fn shares(&mut self, food: F32Positive, cells: &CellColumns, _rng: &mut Pcg64) -> Vec<F32Positive> {
+        weighted_shares(food, cells.iter().map(|cell| food_wanted(&cell).value()).collect())
+    }
+}
+
+/// Cells come to the food one at a time in a random order drawn each step. Each can eat all the
+/// food the cells before it left, and leaves what its attempted eating energy would not get it.
+#[derive(Serialize, Deserialize)]
+pub struct FirstComeShares;
+
+#[typetag::serde]
+impl FoodAllocation for FirstComeShares {
+    fn shares(&mut self, food: F32Positive, cells: &CellColumns, rng: &mut Pcg64) -> Vec<F32Positive> {
+        let mut order: Vec<usize> = (0..cells.len()).collect();
+        order.shuffle(rng);
+        let mut shares = vec![0.into(); cells.len()];
+        let mut remaining = food;
+        for index in order {
+            shares[index] = remaining;
+            remaining -= food_wanted(&cells.get(index)).min(remaining);
+        }
+        shares
+    }
+}
+
+/// Scramble competition, in which every cell grabs what it can and effort has diminishing
+/// returns: cells get shares in proportion to the food their attempted eating energy would get
+/// them raised to `exponent`. An exponent of 1 is `ProportionalShares`, and of 0, `EqualShares`.
+#[derive(Serialize, Deserialize)]
+pub struct ScrambleShares {
+    exponent: f32,
+}
+
+impl ScrambleShares {
+    pub fn new(exponent: f32) -> Self {
+        ScrambleShares { exponent }
+    }
+}
+
+#[typetag::serde]
+impl FoodAllocation for ScrambleShares {
+    fn shares(&mut self, food: F32Positive, cells: &CellColumns, _rng: &mut Pcg64) -> Vec<F32Positive> {
+        weighted_shares(food, cells.iter().map(|cell| food_wanted(&cell).value().powf(self.exponent)).collect())
+    }
+}
+
+/// The food the cell's attempted eating energy would get it, up to its intake limit.
+fn food_wanted(cell: &CellView) -> F32Positive {
+    let (constants, genome) = (cell.constants(), cell.genome());
+    let eating_energy = genome.attempted_eating_energy * internal_state_factor(cell.state().internal_state, genome.eating_state_response)
+        * efforts(constants, genome, cell.energy(), cell.health(), cell.state()).eating;
+    let food_eating_energy = eating_energy - eating_energy * genome.protein_preference;
+    (food_eating_energy * constants.food_yield_from_eating * genome.eating_efficiency * genome.size)
+        .min(constants.max_food_intake * genome.size)
+}
+
+fn weighted_shares(food: F32Positive, weights: Vec<f32>) -> Vec<F32Positive> {
+    let total_weight: f32 = weights.iter().sum();
+    if total_weight <= 0.0 || !total_weight.is_finite() {
+        return vec![food / weights.len(); weights.len()];
+    }
+    weights.iter()
+        .map(|weight| F32Positive::clipped(food.value() * (weight / total_weight)))
+        .collect()
+}
+
+/// The food allocations that can be chosen on the command line, by the names `equal`,
+/// `proportional`, `first-come`, and `scramble:EXPONENT`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FoodAllocationPolicy {
+    Equal,
+    Proportional,
+    FirstCome,
+    Scramble(f32),
+}
+
+impl FoodAllocationPolicy {
+    pub fn allocation(self) -> Box<dyn FoodAllocation> {
+        match self {
+            FoodAllocationPolicy::Equal => Box::new(EqualShares),
+            FoodAllocationPolicy::Proportional => Box::new(ProportionalShares),
+            FoodAllocationPolicy::FirstCome => Box::new(FirstComeShares),
+            FoodAllocationPolicy::Scramble(exponent) => Box::new(ScrambleShares::new(exponent)),
+        }
+    }
+}
+
+impl FromStr for FoodAllocationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "equal" => Ok(FoodAllocationPolicy::Equal),
+            "proportional" => Ok(FoodAllocationPolicy::Proportional),
+            "first-come" => Ok(FoodAllocationPolicy::FirstCome),
+            _ => match s.strip_prefix("scramble:").map(str::parse::<f32>) {
+                Some(Ok(exponent)) if exponent >= 0.0 && exponent.is_finite() => Ok(FoodAllocationPolicy::Scramble(exponent)),
+                Some(_) => Err(format!("scramble exponent in '{}' is not a number of at least 0", s)),
+                None => Err(format!("unknown food allocation '{}' (expected equal, proportional, first-come, or scramble:EXPONENT)", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for FoodAllocationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoodAllocationPolicy::Equal => write!(f, "equal"),
+            FoodAllocationPolicy::Proportional => write!(f, "proportional"),
+            FoodAllocationPolicy::FirstCome => write!(f, "first-come"),
+            FoodAllocationPolicy::Scramble(exponent) => write!(f, "scramble:{}", exponent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+    use crate::genome::Genome;
+
+    fn cells(eating_energies: &[f32]) -> CellColumns {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: 1.0.into(),
+            max_food_intake: 10.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        CellColumns::from(eating_energies.iter()
+            .map(|&eating| Cell::new(&constants, Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT }))
+            .collect::<Vec<_>>())
+    }
+
+    fn shares(allocation: &mut dyn FoodAllocation, food: f32, eating_energies: &[f32]) -> Vec<f32> {
+        allocation.shares(food.into(), &cells(eating_energies), &mut Pcg64::seed_from_u64(0)).iter()
+            .map(|share| share.value())
+            .collect()
+    }
+
+    #[test]
+    fn policies_parse_from_their_names() {
+        for name in ["equal", "proportional", "first-come", "scramble:0.5"] {
+            assert_eq!(name.parse::<FoodAllocationPolicy>().unwrap().to_string(), name);
+        }
+        for bad in ["fair", "scramble", "scramble:-1", "scramble:x"] {
+            assert!(bad.parse::<FoodAllocationPolicy>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn shares_follow_attempted_eating() {
+        assert_eq!(shares(&mut EqualShares, 12.0, &[1.0, 2.0, 3.0]), vec![4.0, 4.0, 4.0]);
+        assert_eq!(shares(&mut ProportionalShares, 12.0, &[1.0, 2.0, 3.0]), vec![2.0, 4.0, 6.0]);
+        assert_eq!(shares(&mut ProportionalShares, 12.0, &[0.0, 0.0]), vec![6.0, 6.0]);
+        assert_eq!(shares(&mut ScrambleShares::new(0.0), 12.0, &[1.0, 2.0, 3.0]), vec![4.0, 4.0, 4.0]);
+        assert_eq!(shares(&mut ScrambleShares::new(0.5), 12.0, &[1.0, 4.0]), vec![4.0, 8.0]);
+    }
+
+    #[test]
+    fn first_comers_leave_the_rest_to_later_cells() {
+        let mut shares = shares(&mut FirstComeShares, 5.0, &[2.0, 2.0, 2.0]);
+        shares.sort_by(|a, b| b.total_cmp(a));
+        assert_eq!(shares, vec![5.0, 3.0, 1.0]);
+    }
+}
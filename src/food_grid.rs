@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use crate::food_sources::{FoodSource, WorldSummary};
+use crate::number_types::{F32Positive, F32ZeroToOne};
+use crate::topology::Topology;
+
+/// Food spread over a grid of tiles, for worlds in which cells have locations. Food sources
+/// deposit food on particular tiles, and each step every tile passes `diffusion_rate` of its food
+/// to its four neighbors in equal shares, so that deposits spread out into gradients. On a
+/// bounded grid, shares that would cross the edge stay on their tile, so diffusion conserves food
+/// with either topology.
+#[derive(Serialize, Deserialize)]
+pub struct FoodGrid {
+    width: usize,
+    height: usize,
+    food: Vec<F32Positive>,
+    diffusion_rate: F32ZeroToOne,
+    #[serde(default)]
+    topology: Topology,
+    sources: Vec<LocatedFoodSource>,
+}
+
+/// A food source that deposits all its food on one tile.
+#[derive(Serialize, Deserialize)]
+pub struct LocatedFoodSource {
+    pub x: usize,
+    pub y: usize,
+    pub source: Box<dyn FoodSource>,
+}
+
+impl FoodGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        FoodGrid {
+            width,
+            height,
+            food: vec![0.into(); width * height],
+            diffusion_rate: 0.0.into(),
+            topology: Topology::Bounded,
+            sources: vec![],
+        }
+    }
+
+    pub fn with_diffusion_rate(mut self, diffusion_rate: F32ZeroToOne) -> Self {
+        self.diffusion_rate = diffusion_rate;
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_sources(mut self, sources: Vec<LocatedFoodSource>) -> Self {
+        for source in &sources {
+            assert!(source.x < self.width && source.y < self.height,
+                    "food source at ({}, {}) is outside the {}x{} grid", source.x, source.y, self.width, self.height);
+        }
+        self.sources = sources;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn food_at(&self, x: usize, y: usize) -> F32Positive {
+        self.food[self.index(x, y)]
+    }
+
+    pub fn total_food(&self) -> F32Positive {
+        self.food.iter().sum()
+    }
+
+    pub fn deposit(&mut self, x: usize, y: usize, amount: F32Positive) {
+        let index = self.index(x, y);
+        self.food[index] += amount;
+    }
+
+    /// Takes up to `amount` food from the tile, returning the amount taken.
+    pub fn take(&mut self, x: usize, y: usize, amount: F32Positive) -> F32Positive {
+        let index = self.index(x, y);
+        let taken = amount.min(self.food[index]);
+        self.food[index] -= taken;
+        taken
+    }
+
+    /// Adds this step's food from each source to its tile, then diffuses.
+    pub fn step(&mut self, world: &WorldSummary) {
+        for index in 0..self.sources.len() {
+            let LocatedFoodSource { x, y, ref mut source } = self.sources[index];
+            let amount = source.food_this_step(world);
+            self.deposit(x, y, amount);
+        }
+        self.diffuse();
+    }
+
+    pub fn diffuse(&mut self) {
+        let rate = self.diffusion_rate.value();
+        if rate <= 0.0 {
+            return;
+        }
+
+        let mut diffused = vec![0.0_f32; self.food.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let food = self.food_at(x, y).value();
+                let share = food * rate / 4.0;
+                let mut kept = food * (1.0 - rate);
+                for neighbor in self.topology.neighbors(x, y, self.width, self.height) {
+                    match neighbor {
+                        Some((neighbor_x, neighbor_y)) => diffused[self.index(neighbor_x, neighbor_y)] += share,
+                        None => kept += share,
+                    }
+                }
+                diffused[self.index(x, y)] += kept;
+            }
+        }
+        self.food = diffused.into_iter().map(F32Positive::clipped).collect();
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside the {}x{} grid", x, y, self.width, self.height);
+        y * self.width + x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::food_sources::ConstantFoodSource;
+
+    #[test]
+    fn food_diffuses_to_neighbors() {
+        let mut grid = FoodGrid::new(3, 3).with_diffusion_rate(0.5.into());
+        grid.deposit(1, 1, 8.0.into());
+
+        grid.diffuse();
+
+        assert_eq!(grid.food_at(1, 1), 4.0.into());
+        assert_eq!(grid.food_at(0, 1), 1.0.into());
+        assert_eq!(grid.food_at(1, 2), 1.0.into());
+        assert_eq!(grid.food_at(0, 0), 0.0.into());
+    }
+
+    #[test]
+    fn diffusion_at_edges_conserves_food() {
+        let mut grid = FoodGrid::new(2, 1).with_diffusion_rate(1.0.into());
+        grid.deposit(0, 0, 8.0.into());
+
+        grid.diffuse();
+
+        assert_eq!(grid.food_at(0, 0), 6.0.into());
+        assert_eq!(grid.food_at(1, 0), 2.0.into());
+        assert_eq!(grid.total_food(), 8.0.into());
+    }
+
+    #[test]
+    fn toroidal_diffusion_wraps_around_edges() {
+        let mut grid = FoodGrid::new(3, 1).with_diffusion_rate(1.0.into()).with_topology(Topology::Toroidal);
+        grid.deposit(0, 0, 8.0.into());
+
+        grid.diffuse();
+
+        assert_eq!(grid.food_at(0, 0), 4.0.into());
+        assert_eq!(grid.food_at(1, 0), 2.0.into());
+        assert_eq!(grid.food_at(2, 0), 2.0.into());
+    }
+
+    #[test]
+    fn sources_deposit_at_their_locations() {
+        let mut grid = FoodGrid::new(4, 4).with_sources(vec![
+            LocatedFoodSource { x: 3, y: 0, source: Box::new(ConstantFoodSource::new(5.0.into())) },
+        ]);
+
+        grid.step(&WorldSummary::EMPTY);
+
+        assert_eq!(grid.food_at(3, 0), 5.0.into());
+        assert_eq!(grid.take(3, 0, 2.0.into()), 2.0.into());
+        assert_eq!(grid.take(3, 0, 10.0.into()), 3.0.into());
+        assert_eq!(grid.total_food(), 0.0.into());
+    }
+}
@@ -1,9 +1,35 @@
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Poisson};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use crate::day_cycle::DayCycle;
 use crate::number_types::F32Positive;
 
+#[typetag::serde]
 pub trait FoodSource {
-    fn food_this_step(&mut self) -> F32Positive;
+    fn food_this_step(&mut self, world: &WorldSummary) -> F32Positive;
 }
 
+/// The state of the world that food sources can respond to, as of the start of the step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldSummary {
+    /// The number of steps the world has taken before this one.
+    pub step: u32,
+    pub num_cells: usize,
+    /// The food in the world before any is added for this step, or for protein sources, the protein.
+    pub food: F32Positive,
+}
+
+impl WorldSummary {
+    #[allow(dead_code)]
+    pub const EMPTY: WorldSummary = WorldSummary {
+        step: 0,
+        num_cells: 0,
+        food: F32Positive::unchecked(0.0),
+    };
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct ConstantFoodSource {
     food_per_step: F32Positive,
 }
@@ -16,12 +42,14 @@ impl ConstantFoodSource {
     }
 }
 
+#[typetag::serde]
 impl FoodSource for ConstantFoodSource {
-    fn food_this_step(&mut self) -> F32Positive {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
         self.food_per_step
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct LinearlyGrowingFoodSource {
     next_food: F32Positive,
     food_increase_per_step: F32Positive,
@@ -36,14 +64,209 @@ impl LinearlyGrowingFoodSource {
     }
 }
 
+#[typetag::serde]
 impl FoodSource for LinearlyGrowingFoodSource {
-    fn food_this_step(&mut self) -> F32Positive {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
         let result = self.next_food;
         self.next_food += self.food_increase_per_step;
         result
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct DayNightFoodSource {
+    day_food_per_step: F32Positive,
+    night_food_per_step: F32Positive,
+    day_cycle: DayCycle,
+}
+
+impl DayNightFoodSource {
+    pub fn new(day_food_per_step: F32Positive, night_food_per_step: F32Positive, day_length: u32) -> Self {
+        DayNightFoodSource {
+            day_food_per_step,
+            night_food_per_step,
+            day_cycle: DayCycle::new(day_length),
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for DayNightFoodSource {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
+        let result = if self.day_cycle.is_day() { self.day_food_per_step } else { self.night_food_per_step };
+        self.day_cycle.advance();
+        result
+    }
+}
+
+/// Adds food that shrinks by a fixed amount each step until it runs out.
+#[derive(Serialize, Deserialize)]
+pub struct LinearlyDecliningFoodSource {
+    next_food: F32Positive,
+    food_decrease_per_step: F32Positive,
+}
+
+impl LinearlyDecliningFoodSource {
+    pub fn new(starting_food: F32Positive, food_decrease_per_step: F32Positive) -> Self {
+        LinearlyDecliningFoodSource {
+            next_food: starting_food,
+            food_decrease_per_step,
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for LinearlyDecliningFoodSource {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
+        let result = self.next_food;
+        self.next_food = F32Positive::clipped(self.next_food.value() - self.food_decrease_per_step.value());
+        result
+    }
+}
+
+/// Alternates between runs of plentiful and scarce food.
+#[derive(Serialize, Deserialize)]
+pub struct BoomBustFoodSource {
+    boom_food_per_step: F32Positive,
+    bust_food_per_step: F32Positive,
+    boom_steps: u32,
+    bust_steps: u32,
+    step: u32,
+}
+
+impl BoomBustFoodSource {
+    pub fn new(boom_food_per_step: F32Positive, bust_food_per_step: F32Positive, boom_steps: u32, bust_steps: u32) -> Self {
+        BoomBustFoodSource {
+            boom_food_per_step,
+            bust_food_per_step,
+            boom_steps,
+            bust_steps,
+            step: 0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for BoomBustFoodSource {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
+        let result = if self.step < self.boom_steps { self.boom_food_per_step } else { self.bust_food_per_step };
+        self.step = (self.step + 1) % (self.boom_steps + self.bust_steps).max(1);
+        result
+    }
+}
+
+/// Adds an unpredictable amount of food each step, drawn uniformly from zero to twice the mean.
+#[derive(Serialize, Deserialize)]
+pub struct RandomFoodSource {
+    mean_food_per_step: F32Positive,
+    rng: Pcg64,
+}
+
+impl RandomFoodSource {
+    pub fn new(mean_food_per_step: F32Positive, seed: u64) -> Self {
+        RandomFoodSource {
+            mean_food_per_step,
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for RandomFoodSource {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
+        self.mean_food_per_step * F32Positive::unchecked(self.rng.gen_range(0.0..=2.0))
+    }
+}
+
+/// Scales another food source's output down as the population approaches a carrying capacity,
+/// reaching zero at or above it, so that populations grow logistically rather than exponentially.
+#[derive(Serialize, Deserialize)]
+pub struct DensityDependentFoodSource {
+    source: Box<dyn FoodSource>,
+    carrying_capacity: usize,
+}
+
+impl DensityDependentFoodSource {
+    pub fn new(source: Box<dyn FoodSource>, carrying_capacity: usize) -> Self {
+        DensityDependentFoodSource {
+            source,
+            carrying_capacity,
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for DensityDependentFoodSource {
+    fn food_this_step(&mut self, world: &WorldSummary) -> F32Positive {
+        let food = self.source.food_this_step(world);
+        let crowding = world.num_cells as f32 / self.carrying_capacity.max(1) as f32;
+        food * F32Positive::clipped(1.0 - crowding)
+    }
+}
+
+/// Regrows the world's food toward `capacity` logistically, adding `growth_rate` times the food
+/// times the fraction of `capacity` still unfilled. Food below `seed_food` regrows as if it were
+/// `seed_food`, so that a world grazed bare can recover. With a `seed_food` of 0, overharvesting
+/// can collapse the food supply for good.
+#[derive(Serialize, Deserialize)]
+pub struct LogisticFoodSource {
+    growth_rate: F32Positive,
+    capacity: F32Positive,
+    seed_food: F32Positive,
+}
+
+impl LogisticFoodSource {
+    pub fn new(growth_rate: F32Positive, capacity: F32Positive, seed_food: F32Positive) -> Self {
+        LogisticFoodSource {
+            growth_rate,
+            capacity,
+            seed_food,
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for LogisticFoodSource {
+    fn food_this_step(&mut self, world: &WorldSummary) -> F32Positive {
+        if self.capacity.value() <= 0.0 {
+            return 0.0.into();
+        }
+        let unfilled = F32Positive::clipped(1.0 - world.food.value() / self.capacity.value());
+        self.growth_rate * world.food.max(self.seed_food) * unfilled
+    }
+}
+
+/// Adds food in bursts of `food_per_pulse` at random times, with a Poisson-distributed number
+/// of bursts each step averaging `pulses_per_step`, so that the wait for the next burst is
+/// unpredictable.
+#[derive(Serialize, Deserialize)]
+pub struct StochasticPulseFoodSource {
+    food_per_pulse: F32Positive,
+    pulses_per_step: F32Positive,
+    rng: Pcg64,
+}
+
+impl StochasticPulseFoodSource {
+    pub fn new(food_per_pulse: F32Positive, pulses_per_step: F32Positive, seed: u64) -> Self {
+        StochasticPulseFoodSource {
+            food_per_pulse,
+            pulses_per_step,
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl FoodSource for StochasticPulseFoodSource {
+    fn food_this_step(&mut self, _world: &WorldSummary) -> F32Positive {
+        if self.pulses_per_step.value() <= 0.0 {
+            return 0.0.into();
+        }
+        let num_pulses: f32 = Poisson::new(self.pulses_per_step.value()).unwrap().sample(&mut self.rng);
+        self.food_per_pulse * num_pulses
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,8 +274,68 @@ mod tests {
     #[test]
     fn linearly_growing_food_source_grows_linearly() {
         let mut source = LinearlyGrowingFoodSource::new(100.0.into(), 10.0.into());
-        assert_eq!(source.food_this_step(), 100.0.into());
-        assert_eq!(source.food_this_step(), 110.0.into());
-        assert_eq!(source.food_this_step(), 120.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 100.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 110.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 120.0.into());
+    }
+
+    #[test]
+    fn day_night_food_source_varies_with_time_of_day() {
+        let mut source = DayNightFoodSource::new(10.0.into(), 1.0.into(), 2);
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 10.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 1.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 10.0.into());
+    }
+
+    #[test]
+    fn linearly_declining_food_source_declines_to_zero() {
+        let mut source = LinearlyDecliningFoodSource::new(10.0.into(), 6.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 10.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 4.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary::EMPTY), 0.0.into());
+    }
+
+    #[test]
+    fn boom_bust_food_source_alternates_booms_and_busts() {
+        let mut source = BoomBustFoodSource::new(10.0.into(), 1.0.into(), 2, 1);
+        let food: Vec<f32> = (0..6).map(|_| source.food_this_step(&WorldSummary::EMPTY).value()).collect();
+        assert_eq!(food, vec![10.0, 10.0, 1.0, 10.0, 10.0, 1.0]);
+    }
+
+    #[test]
+    fn density_dependent_food_source_diminishes_with_population() {
+        let mut source = DensityDependentFoodSource::new(Box::new(ConstantFoodSource::new(10.0.into())), 100);
+        assert_eq!(source.food_this_step(&WorldSummary { num_cells: 0, ..WorldSummary::EMPTY }), 10.0.into());
+        assert_eq!(source.food_this_step(&WorldSummary { num_cells: 25, ..WorldSummary::EMPTY }), 7.5.into());
+        assert_eq!(source.food_this_step(&WorldSummary { num_cells: 150, ..WorldSummary::EMPTY }), 0.0.into());
+    }
+
+    #[test]
+    fn logistic_food_source_regrows_fastest_when_half_full() {
+        let mut source = LogisticFoodSource::new(0.5.into(), 100.0.into(), 2.0.into());
+        let with_food = |food: f32| WorldSummary { food: food.into(), ..WorldSummary::EMPTY };
+        assert_eq!(source.food_this_step(&with_food(0.0)), 1.0.into());
+        assert_eq!(source.food_this_step(&with_food(25.0)), 9.375.into());
+        assert_eq!(source.food_this_step(&with_food(50.0)), 12.5.into());
+        assert_eq!(source.food_this_step(&with_food(75.0)), 9.375.into());
+        assert_eq!(source.food_this_step(&with_food(120.0)), 0.0.into());
+    }
+
+    #[test]
+    fn logistic_food_source_without_seed_food_collapses_when_grazed_bare() {
+        let mut source = LogisticFoodSource::new(0.5.into(), 100.0.into(), 0.0.into());
+        let with_food = |food: f32| WorldSummary { food: food.into(), ..WorldSummary::EMPTY };
+        assert!(source.food_this_step(&with_food(1.0)).value() > 0.0);
+        assert_eq!(source.food_this_step(&with_food(0.0)), 0.0.into());
+    }
+
+    #[test]
+    fn stochastic_pulse_food_source_adds_whole_pulses_at_the_mean_rate() {
+        let mut source = StochasticPulseFoodSource::new(10.0.into(), 0.1.into(), 0);
+        let food: Vec<f32> = (0..10_000).map(|_| source.food_this_step(&WorldSummary::EMPTY).value()).collect();
+        assert!(food.iter().all(|food| food % 10.0 == 0.0));
+        assert!(food.iter().filter(|food| **food == 0.0).count() > 8_000);
+        let mean = food.iter().sum::<f32>() / food.len() as f32;
+        assert!((mean - 1.0).abs() < 0.1, "{}", mean);
     }
 }
@@ -0,0 +1,940 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use crate::brain::BrainWeights;
+use crate::cell::{CellConstants, MutationNumberSource};
+use crate::number_types::{F32Positive, F32ZeroToOne};
+use crate::units::Unit;
+
+/// The evolvable parameters of a cell. Each field is a gene, listed in one of the `*_GENES`
+/// tables so that mutation, sampling, and reporting can iterate over every gene. A new gene
+/// needs a field here, an entry in the matching table, and a mutation stdev in `CellConstants`
+/// (which `Units::constants` rescales along with the gene).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub attempted_eating_energy: F32Positive,
+    pub attempted_healing_energy: F32Positive,
+    pub child_threshold_energy: F32Positive,
+    pub child_threshold_food: F32Positive,
+    pub attempted_attack_energy: F32Positive,
+    pub attempted_photosynthesis_energy: F32Positive,
+    pub active_phase: F32ZeroToOne,
+    pub attempted_trade_energy: F32Positive,
+    pub trade_reciprocity_threshold: F32Positive,
+    pub food_memory_rate: F32ZeroToOne,
+    pub scarce_food_threshold: F32Positive,
+    pub scarcity_spending_fraction: F32ZeroToOne,
+    pub reproduction_priority: F32Positive,
+    pub eating_priority: F32Positive,
+    pub healing_priority: F32Positive,
+    pub attack_priority: F32Positive,
+    pub photosynthesis_priority: F32Positive,
+    pub trade_priority: F32Positive,
+    pub max_energy_storage: F32Positive,
+    pub attempted_detox_energy: F32Positive,
+    pub detox_priority: F32Positive,
+    pub size: F32Positive,
+    pub donation_threshold: F32Positive,
+    pub donation_fraction: F32ZeroToOne,
+    pub dormancy_threshold_energy: F32Positive,
+    pub wake_threshold_food: F32Positive,
+    /// The fraction of the cell's eating energy spent on protein rather than food, trading off
+    /// its efficiency at eating one nutrient against the other.
+    pub protein_preference: F32ZeroToOne,
+    /// Multiplies the food the cell gets for its eating energy.
+    pub eating_efficiency: F32Positive,
+    /// Multiplies the energy the cell gets from digesting food.
+    pub digestion_efficiency: F32Positive,
+    /// How far the cell's internal state moves toward its inputs each step, from not at all to
+    /// all the way.
+    pub state_update_rate: F32ZeroToOne,
+    /// The energy at which the cell's energy input to its internal state is halfway to saturating.
+    pub state_energy_scale: F32Positive,
+    /// The food per cell at which the cell's food input to its internal state is halfway to
+    /// saturating.
+    pub state_food_scale: F32Positive,
+    /// Multiplies the cell's attempted eating energy when its internal state is 1.
+    pub eating_state_response: F32Positive,
+    /// Multiplies the cell's attempted healing energy when its internal state is 1.
+    pub healing_state_response: F32Positive,
+    /// Divides the environmental stress that multiplies the cell's entropy damage, at an upkeep.
+    #[serde(default)]
+    pub stress_tolerance: F32Positive,
+    /// The weights of the cell's brain, which only `CellController::Net` cells use.
+    #[serde(default)]
+    pub brain: BrainWeights,
+}
+
+impl Genome {
+    #[allow(dead_code)]
+    pub const DEFAULT: Genome = Genome {
+        attempted_eating_energy: F32Positive::unchecked(0.0),
+        attempted_healing_energy: F32Positive::unchecked(0.0),
+        child_threshold_energy: F32Positive::unchecked(f32::MAX),
+        child_threshold_food: F32Positive::unchecked(f32::MAX),
+        attempted_attack_energy: F32Positive::unchecked(0.0),
+        attempted_photosynthesis_energy: F32Positive::unchecked(0.0),
+        active_phase: F32ZeroToOne::unchecked(0.0),
+        attempted_trade_energy: F32Positive::unchecked(0.0),
+        trade_reciprocity_threshold: F32Positive::unchecked(0.0),
+        food_memory_rate: F32ZeroToOne::unchecked(1.0),
+        scarce_food_threshold: F32Positive::unchecked(0.0),
+        scarcity_spending_fraction: F32ZeroToOne::unchecked(1.0),
+        reproduction_priority: F32Positive::unchecked(1.0),
+        eating_priority: F32Positive::unchecked(1.0),
+        healing_priority: F32Positive::unchecked(1.0),
+        attack_priority: F32Positive::unchecked(1.0),
+        photosynthesis_priority: F32Positive::unchecked(1.0),
+        trade_priority: F32Positive::unchecked(1.0),
+        max_energy_storage: F32Positive::unchecked(f32::MAX),
+        attempted_detox_energy: F32Positive::unchecked(0.0),
+        detox_priority: F32Positive::unchecked(1.0),
+        size: F32Positive::unchecked(1.0),
+        donation_threshold: F32Positive::unchecked(f32::MAX),
+        donation_fraction: F32ZeroToOne::unchecked(0.0),
+        dormancy_threshold_energy: F32Positive::unchecked(0.0),
+        wake_threshold_food: F32Positive::unchecked(0.0),
+        protein_preference: F32ZeroToOne::unchecked(0.0),
+        eating_efficiency: F32Positive::unchecked(1.0),
+        digestion_efficiency: F32Positive::unchecked(1.0),
+        state_update_rate: F32ZeroToOne::unchecked(0.0),
+        state_energy_scale: F32Positive::unchecked(f32::MAX),
+        state_food_scale: F32Positive::unchecked(f32::MAX),
+        eating_state_response: F32Positive::unchecked(1.0),
+        healing_state_response: F32Positive::unchecked(1.0),
+        stress_tolerance: F32Positive::unchecked(0.0),
+        brain: BrainWeights::NEUTRAL,
+    };
+
+    pub const ZERO: Genome = Genome {
+        attempted_eating_energy: F32Positive::unchecked(0.0),
+        attempted_healing_energy: F32Positive::unchecked(0.0),
+        child_threshold_energy: F32Positive::unchecked(0.0),
+        child_threshold_food: F32Positive::unchecked(0.0),
+        attempted_attack_energy: F32Positive::unchecked(0.0),
+        attempted_photosynthesis_energy: F32Positive::unchecked(0.0),
+        active_phase: F32ZeroToOne::unchecked(0.0),
+        attempted_trade_energy: F32Positive::unchecked(0.0),
+        trade_reciprocity_threshold: F32Positive::unchecked(0.0),
+        food_memory_rate: F32ZeroToOne::unchecked(0.0),
+        scarce_food_threshold: F32Positive::unchecked(0.0),
+        scarcity_spending_fraction: F32ZeroToOne::unchecked(0.0),
+        reproduction_priority: F32Positive::unchecked(0.0),
+        eating_priority: F32Positive::unchecked(0.0),
+        healing_priority: F32Positive::unchecked(0.0),
+        attack_priority: F32Positive::unchecked(0.0),
+        photosynthesis_priority: F32Positive::unchecked(0.0),
+        trade_priority: F32Positive::unchecked(0.0),
+        max_energy_storage: F32Positive::unchecked(0.0),
+        attempted_detox_energy: F32Positive::unchecked(0.0),
+        detox_priority: F32Positive::unchecked(0.0),
+        size: F32Positive::unchecked(0.0),
+        donation_threshold: F32Positive::unchecked(0.0),
+        donation_fraction: F32ZeroToOne::unchecked(0.0),
+        dormancy_threshold_energy: F32Positive::unchecked(0.0),
+        wake_threshold_food: F32Positive::unchecked(0.0),
+        protein_preference: F32ZeroToOne::unchecked(0.0),
+        eating_efficiency: F32Positive::unchecked(0.0),
+        digestion_efficiency: F32Positive::unchecked(0.0),
+        state_update_rate: F32ZeroToOne::unchecked(0.0),
+        state_energy_scale: F32Positive::unchecked(0.0),
+        state_food_scale: F32Positive::unchecked(0.0),
+        eating_state_response: F32Positive::unchecked(0.0),
+        healing_state_response: F32Positive::unchecked(0.0),
+        stress_tolerance: F32Positive::unchecked(0.0),
+        brain: BrainWeights::ZERO,
+    };
+
+    pub const F32_POSITIVE_GENES: [Gene<F32Positive>; 29] = [
+        Gene {
+            name: "attempted_eating_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_eating_energy,
+            value_mut: |genome| &mut genome.attempted_eating_energy,
+            mutation_stdev: |constants| constants.attempted_eating_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attempted_healing_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_healing_energy,
+            value_mut: |genome| &mut genome.attempted_healing_energy,
+            mutation_stdev: |constants| constants.attempted_healing_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "child_threshold_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.child_threshold_energy,
+            value_mut: |genome| &mut genome.child_threshold_energy,
+            mutation_stdev: |constants| constants.child_threshold_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "child_threshold_food",
+            unit: Unit::Food,
+            value: |genome| genome.child_threshold_food,
+            value_mut: |genome| &mut genome.child_threshold_food,
+            mutation_stdev: |constants| constants.child_threshold_food_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attempted_attack_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_attack_energy,
+            value_mut: |genome| &mut genome.attempted_attack_energy,
+            mutation_stdev: |constants| constants.attempted_attack_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attempted_photosynthesis_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_photosynthesis_energy,
+            value_mut: |genome| &mut genome.attempted_photosynthesis_energy,
+            mutation_stdev: |constants| constants.attempted_photosynthesis_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attempted_trade_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_trade_energy,
+            value_mut: |genome| &mut genome.attempted_trade_energy,
+            mutation_stdev: |constants| constants.attempted_trade_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "trade_reciprocity_threshold",
+            unit: Unit::Energy,
+            value: |genome| genome.trade_reciprocity_threshold,
+            value_mut: |genome| &mut genome.trade_reciprocity_threshold,
+            mutation_stdev: |constants| constants.trade_reciprocity_threshold_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "scarce_food_threshold",
+            unit: Unit::Food,
+            value: |genome| genome.scarce_food_threshold,
+            value_mut: |genome| &mut genome.scarce_food_threshold,
+            mutation_stdev: |constants| constants.scarce_food_threshold_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "reproduction_priority",
+            unit: Unit::None,
+            value: |genome| genome.reproduction_priority,
+            value_mut: |genome| &mut genome.reproduction_priority,
+            mutation_stdev: |constants| constants.reproduction_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "eating_priority",
+            unit: Unit::None,
+            value: |genome| genome.eating_priority,
+            value_mut: |genome| &mut genome.eating_priority,
+            mutation_stdev: |constants| constants.eating_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "healing_priority",
+            unit: Unit::None,
+            value: |genome| genome.healing_priority,
+            value_mut: |genome| &mut genome.healing_priority,
+            mutation_stdev: |constants| constants.healing_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attack_priority",
+            unit: Unit::None,
+            value: |genome| genome.attack_priority,
+            value_mut: |genome| &mut genome.attack_priority,
+            mutation_stdev: |constants| constants.attack_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "photosynthesis_priority",
+            unit: Unit::None,
+            value: |genome| genome.photosynthesis_priority,
+            value_mut: |genome| &mut genome.photosynthesis_priority,
+            mutation_stdev: |constants| constants.photosynthesis_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "trade_priority",
+            unit: Unit::None,
+            value: |genome| genome.trade_priority,
+            value_mut: |genome| &mut genome.trade_priority,
+            mutation_stdev: |constants| constants.trade_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "max_energy_storage",
+            unit: Unit::Energy,
+            value: |genome| genome.max_energy_storage,
+            value_mut: |genome| &mut genome.max_energy_storage,
+            mutation_stdev: |constants| constants.max_energy_storage_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "attempted_detox_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.attempted_detox_energy,
+            value_mut: |genome| &mut genome.attempted_detox_energy,
+            mutation_stdev: |constants| constants.attempted_detox_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "detox_priority",
+            unit: Unit::None,
+            value: |genome| genome.detox_priority,
+            value_mut: |genome| &mut genome.detox_priority,
+            mutation_stdev: |constants| constants.detox_priority_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "size",
+            unit: Unit::None,
+            value: |genome| genome.size,
+            value_mut: |genome| &mut genome.size,
+            mutation_stdev: |constants| constants.size_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "donation_threshold",
+            unit: Unit::Energy,
+            value: |genome| genome.donation_threshold,
+            value_mut: |genome| &mut genome.donation_threshold,
+            mutation_stdev: |constants| constants.donation_threshold_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "dormancy_threshold_energy",
+            unit: Unit::Energy,
+            value: |genome| genome.dormancy_threshold_energy,
+            value_mut: |genome| &mut genome.dormancy_threshold_energy,
+            mutation_stdev: |constants| constants.dormancy_threshold_energy_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "wake_threshold_food",
+            unit: Unit::Food,
+            value: |genome| genome.wake_threshold_food,
+            value_mut: |genome| &mut genome.wake_threshold_food,
+            mutation_stdev: |constants| constants.wake_threshold_food_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "eating_efficiency",
+            unit: Unit::None,
+            value: |genome| genome.eating_efficiency,
+            value_mut: |genome| &mut genome.eating_efficiency,
+            mutation_stdev: |constants| constants.eating_efficiency_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "digestion_efficiency",
+            unit: Unit::None,
+            value: |genome| genome.digestion_efficiency,
+            value_mut: |genome| &mut genome.digestion_efficiency,
+            mutation_stdev: |constants| constants.digestion_efficiency_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "state_energy_scale",
+            unit: Unit::Energy,
+            value: |genome| genome.state_energy_scale,
+            value_mut: |genome| &mut genome.state_energy_scale,
+            mutation_stdev: |constants| constants.state_energy_scale_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "state_food_scale",
+            unit: Unit::Food,
+            value: |genome| genome.state_food_scale,
+            value_mut: |genome| &mut genome.state_food_scale,
+            mutation_stdev: |constants| constants.state_food_scale_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "eating_state_response",
+            unit: Unit::None,
+            value: |genome| genome.eating_state_response,
+            value_mut: |genome| &mut genome.eating_state_response,
+            mutation_stdev: |constants| constants.eating_state_response_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "healing_state_response",
+            unit: Unit::None,
+            value: |genome| genome.healing_state_response,
+            value_mut: |genome| &mut genome.healing_state_response,
+            mutation_stdev: |constants| constants.healing_state_response_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "stress_tolerance",
+            unit: Unit::None,
+            value: |genome| genome.stress_tolerance,
+            value_mut: |genome| &mut genome.stress_tolerance,
+            mutation_stdev: |constants| constants.stress_tolerance_mutation_stdev,
+            circular: false,
+        },
+    ];
+
+    pub const F32_ZERO_TO_ONE_GENES: [Gene<F32ZeroToOne>; 21] = [
+        Gene {
+            name: "active_phase",
+            unit: Unit::None,
+            value: |genome| genome.active_phase,
+            value_mut: |genome| &mut genome.active_phase,
+            mutation_stdev: |constants| constants.active_phase_mutation_stdev,
+            circular: true,
+        },
+        Gene {
+            name: "food_memory_rate",
+            unit: Unit::None,
+            value: |genome| genome.food_memory_rate,
+            value_mut: |genome| &mut genome.food_memory_rate,
+            mutation_stdev: |constants| constants.food_memory_rate_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "scarcity_spending_fraction",
+            unit: Unit::None,
+            value: |genome| genome.scarcity_spending_fraction,
+            value_mut: |genome| &mut genome.scarcity_spending_fraction,
+            mutation_stdev: |constants| constants.scarcity_spending_fraction_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "donation_fraction",
+            unit: Unit::None,
+            value: |genome| genome.donation_fraction,
+            value_mut: |genome| &mut genome.donation_fraction,
+            mutation_stdev: |constants| constants.donation_fraction_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "protein_preference",
+            unit: Unit::None,
+            value: |genome| genome.protein_preference,
+            value_mut: |genome| &mut genome.protein_preference,
+            mutation_stdev: |constants| constants.protein_preference_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "state_update_rate",
+            unit: Unit::None,
+            value: |genome| genome.state_update_rate,
+            value_mut: |genome| &mut genome.state_update_rate,
+            mutation_stdev: |constants| constants.state_update_rate_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_eating_bias",
+            unit: Unit::None,
+            value: |genome| genome.brain.eating[0],
+            value_mut: |genome| &mut genome.brain.eating[0],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_eating_energy",
+            unit: Unit::None,
+            value: |genome| genome.brain.eating[1],
+            value_mut: |genome| &mut genome.brain.eating[1],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_eating_health",
+            unit: Unit::None,
+            value: |genome| genome.brain.eating[2],
+            value_mut: |genome| &mut genome.brain.eating[2],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_eating_food",
+            unit: Unit::None,
+            value: |genome| genome.brain.eating[3],
+            value_mut: |genome| &mut genome.brain.eating[3],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_eating_age",
+            unit: Unit::None,
+            value: |genome| genome.brain.eating[4],
+            value_mut: |genome| &mut genome.brain.eating[4],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_healing_bias",
+            unit: Unit::None,
+            value: |genome| genome.brain.healing[0],
+            value_mut: |genome| &mut genome.brain.healing[0],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_healing_energy",
+            unit: Unit::None,
+            value: |genome| genome.brain.healing[1],
+            value_mut: |genome| &mut genome.brain.healing[1],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_healing_health",
+            unit: Unit::None,
+            value: |genome| genome.brain.healing[2],
+            value_mut: |genome| &mut genome.brain.healing[2],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_healing_food",
+            unit: Unit::None,
+            value: |genome| genome.brain.healing[3],
+            value_mut: |genome| &mut genome.brain.healing[3],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_healing_age",
+            unit: Unit::None,
+            value: |genome| genome.brain.healing[4],
+            value_mut: |genome| &mut genome.brain.healing[4],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_reproduction_bias",
+            unit: Unit::None,
+            value: |genome| genome.brain.reproduction[0],
+            value_mut: |genome| &mut genome.brain.reproduction[0],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_reproduction_energy",
+            unit: Unit::None,
+            value: |genome| genome.brain.reproduction[1],
+            value_mut: |genome| &mut genome.brain.reproduction[1],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_reproduction_health",
+            unit: Unit::None,
+            value: |genome| genome.brain.reproduction[2],
+            value_mut: |genome| &mut genome.brain.reproduction[2],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_reproduction_food",
+            unit: Unit::None,
+            value: |genome| genome.brain.reproduction[3],
+            value_mut: |genome| &mut genome.brain.reproduction[3],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+        Gene {
+            name: "brain_reproduction_age",
+            unit: Unit::None,
+            value: |genome| genome.brain.reproduction[4],
+            value_mut: |genome| &mut genome.brain.reproduction[4],
+            mutation_stdev: |constants| constants.brain_weight_mutation_stdev,
+            circular: false,
+        },
+    ];
+
+    pub const NUM_GENES: usize = Self::F32_POSITIVE_GENES.len() + Self::F32_ZERO_TO_ONE_GENES.len();
+
+    /// Mutates the genome, copying it first if it is shared, but only once a gene changes, so
+    /// that a child whose genes all come through unchanged keeps sharing its parent's genome.
+    pub fn mutate(genome: &mut Rc<Genome>, constants: &CellConstants, mutation_number_source: &mut dyn MutationNumberSource) {
+        let before = Rc::clone(genome);
+        for gene in &Self::F32_POSITIVE_GENES {
+            gene.mutate(genome, constants, mutation_number_source);
+        }
+        for gene in &Self::F32_ZERO_TO_ONE_GENES {
+            gene.mutate(genome, constants, mutation_number_source);
+        }
+        mutation_number_source.record_mutation(&before, genome);
+    }
+
+    /// Replaces each gene with the mate's version of it with the given probability. Genes the two
+    /// share draw nothing. Like `mutate`, copies the genome only once a gene changes.
+    pub fn cross_over<R: Rng>(genome: &mut Rc<Genome>, mate: &Genome, probability: F32ZeroToOne, rng: &mut R) {
+        for gene in &Self::F32_POSITIVE_GENES {
+            gene.cross_over(genome, mate, probability, rng);
+        }
+        for gene in &Self::F32_ZERO_TO_ONE_GENES {
+            gene.cross_over(genome, mate, probability, rng);
+        }
+    }
+
+    /// Samples each gene from a normal distribution. Samples outside a gene's domain are clipped.
+    pub fn sample<R: Rng>(means: &Genome, stdevs: &Genome, rng: &mut R) -> Genome {
+        let mut genome = *means;
+        for gene in &Self::F32_POSITIVE_GENES {
+            gene.sample(&mut genome, stdevs, rng);
+        }
+        for gene in &Self::F32_ZERO_TO_ONE_GENES {
+            gene.sample(&mut genome, stdevs, rng);
+        }
+        genome
+    }
+
+    /// The sum of the energies the cell attempts to spend each step on its processes.
+    pub fn total_attempted_energy(&self) -> F32Positive {
+        self.attempted_eating_energy
+            + self.attempted_healing_energy
+            + self.attempted_attack_energy
+            + self.attempted_photosynthesis_energy
+            + self.attempted_trade_energy
+            + self.attempted_detox_energy
+    }
+
+    pub fn gene_names() -> impl Iterator<Item=&'static str> {
+        Self::F32_POSITIVE_GENES.iter().map(|gene| gene.name)
+            .chain(Self::F32_ZERO_TO_ONE_GENES.iter().map(|gene| gene.name))
+    }
+
+    pub fn gene_values(&self) -> impl Iterator<Item=f32> + '_ {
+        Self::F32_POSITIVE_GENES.iter().map(|gene| gene.value(self).into())
+            .chain(Self::F32_ZERO_TO_ONE_GENES.iter().map(|gene| gene.value(self).into()))
+    }
+
+    /// The bits of the gene values, in `gene_names` order, which are equal exactly when the
+    /// genomes are.
+    pub fn gene_bits(&self) -> [u32; Self::NUM_GENES] {
+        let mut bits = [0; Self::NUM_GENES];
+        for (bits, value) in bits.iter_mut().zip(self.gene_values()) {
+            *bits = value.to_bits();
+        }
+        bits
+    }
+
+    /// Copies this genome with the genes listed in `spec`, e.g.
+    /// `"attempted_eating_energy=1.5,active_phase=0.25"`, set to the given values.
+    pub fn with_gene_values(&self, spec: &str) -> Result<Genome, String> {
+        let mut genome = *self;
+        for assignment in spec.split(',').map(str::trim).filter(|assignment| !assignment.is_empty()) {
+            let (name, value) = assignment.split_once('=')
+                .ok_or_else(|| format!("expected gene=value, got '{}'", assignment))?;
+            let (name, value) = (name.trim(), value.trim());
+            let value: f32 = value.parse()
+                .map_err(|_| format!("invalid value '{}' for gene {}", value, name))?;
+            if let Some(gene) = Self::F32_POSITIVE_GENES.iter().find(|gene| gene.name == name) {
+                gene.set_checked(&mut genome, value, value >= 0.0)?;
+            } else if let Some(gene) = Self::F32_ZERO_TO_ONE_GENES.iter().find(|gene| gene.name == name) {
+                gene.set_checked(&mut genome, value, (0.0..=1.0).contains(&value))?;
+            } else {
+                return Err(format!("unknown gene '{}'", name));
+            }
+        }
+        Ok(genome)
+    }
+}
+
+pub trait GeneValue: Copy + Into<f32> {
+    fn mutated(self, stdev: F32Positive, mutation_number_source: &mut dyn MutationNumberSource) -> Self;
+
+    fn clipped(value: f32) -> Self;
+}
+
+impl GeneValue for F32Positive {
+    fn mutated(self, stdev: F32Positive, mutation_number_source: &mut dyn MutationNumberSource) -> Self {
+        mutation_number_source.mutate(self, stdev)
+    }
+
+    fn clipped(value: f32) -> Self {
+        F32Positive::clipped(value)
+    }
+}
+
+impl GeneValue for F32ZeroToOne {
+    fn mutated(self, stdev: F32Positive, mutation_number_source: &mut dyn MutationNumberSource) -> Self {
+        F32ZeroToOne::clipped(mutation_number_source.mutate(self.into(), stdev).value())
+    }
+
+    fn clipped(value: f32) -> Self {
+        F32ZeroToOne::clipped(value)
+    }
+}
+
+pub struct Gene<T: GeneValue> {
+    pub name: &'static str,
+    pub unit: Unit,
+    value: fn(&Genome) -> T,
+    value_mut: fn(&mut Genome) -> &mut T,
+    mutation_stdev: fn(&CellConstants) -> F32Positive,
+    /// Whether the gene's values run around a circle from 0 to 1, as times of day do, so that
+    /// mutation and sampling wrap past either end instead of clipping to it.
+    circular: bool,
+}
+
+impl<T: GeneValue> Gene<T> {
+    pub fn value(&self, genome: &Genome) -> T {
+        (self.value)(genome)
+    }
+
+    pub fn value_mut<'a>(&self, genome: &'a mut Genome) -> &'a mut T {
+        (self.value_mut)(genome)
+    }
+
+    fn set_checked(&self, genome: &mut Genome, value: f32, in_domain: bool) -> Result<(), String> {
+        if !in_domain {
+            return Err(format!("value {} is out of range for gene {}", value, self.name));
+        }
+        *self.value_mut(genome) = T::clipped(value);
+        Ok(())
+    }
+
+    fn mutate(&self, genome: &mut Rc<Genome>, constants: &CellConstants, mutation_number_source: &mut dyn MutationNumberSource) {
+        let stdev = (self.mutation_stdev)(constants);
+        if stdev.value() == 0.0 {
+            // Draws nothing, so that genes added with no mutation leave the random streams alone.
+            return;
+        }
+
+        let value = self.value(genome);
+        let mutated = if self.circular {
+            // Mutates the value a turn up, so that it stays positive however it mutates down.
+            let mutated = mutation_number_source.mutate(F32Positive::clipped(1.0 + value.into()), stdev);
+            T::clipped(mutated.value().rem_euclid(1.0))
+        } else {
+            value.mutated(stdev, mutation_number_source)
+        };
+        if mutated.into().to_bits() != value.into().to_bits() {
+            *self.value_mut(Rc::make_mut(genome)) = mutated;
+        }
+    }
+
+    fn cross_over<R: Rng>(&self, genome: &mut Rc<Genome>, mate: &Genome, probability: F32ZeroToOne, rng: &mut R) {
+        if self.value(mate).into() == self.value(genome).into() {
+            return;
+        }
+
+        if rng.gen_bool(probability.value() as f64) {
+            *self.value_mut(Rc::make_mut(genome)) = self.value(mate);
+        }
+    }
+
+    fn sample<R: Rng>(&self, genome: &mut Genome, stdevs: &Genome, rng: &mut R) {
+        let value = self.value_mut(genome);
+        let stdev: f32 = self.value(stdevs).into();
+        if stdev > 0.0 {
+            let normal = Normal::new((*value).into(), stdev).unwrap();
+            let sampled: f32 = normal.sample(rng);
+            *value = T::clipped(if self.circular { sampled.rem_euclid(1.0) } else { sampled });
+        }
+    }
+}
+
+/// Shares one copy of each distinct genome, found by its `gene_bits`, so that a population of
+/// millions of cells with few distinct genomes holds few copies of them. It keeps only weak
+/// references, so that a genome is freed once no cell has it, and drops those now and then.
+#[derive(Clone, Debug, Default)]
+pub struct GenomeInterner {
+    genomes: HashMap<[u32; Genome::NUM_GENES], Weak<Genome>>,
+    prune_at: usize,
+}
+
+impl GenomeInterner {
+    const MIN_PRUNE_AT: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `genome`, which becomes the shared copy if there is none.
+    pub fn intern(&mut self, genome: Rc<Genome>) -> Rc<Genome> {
+        let interned = self.genomes.entry(genome.gene_bits()).or_default();
+        if let Some(shared) = interned.upgrade() {
+            return shared;
+        }
+
+        *interned = Rc::downgrade(&genome);
+        if self.genomes.len() >= self.prune_at {
+            self.genomes.retain(|_, interned| interned.strong_count() > 0);
+            self.prune_at = (2 * self.genomes.len()).max(Self::MIN_PRUNE_AT);
+        }
+        genome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn genome_lists_every_gene() {
+        let genome = Genome {
+            attempted_eating_energy: 1.0.into(),
+            active_phase: 0.5.into(),
+            ..Genome::DEFAULT
+        };
+        let genes: Vec<(&str, f32)> = Genome::gene_names().zip(genome.gene_values()).collect();
+        assert_eq!(genes.len(), 50);
+        assert!(genes.contains(&("attempted_eating_energy", 1.0)));
+        assert!(genes.contains(&("active_phase", 0.5)));
+    }
+
+    #[test]
+    fn sampling_with_zero_stdevs_copies_means() {
+        let means = Genome {
+            attempted_eating_energy: 2.0.into(),
+            ..Genome::DEFAULT
+        };
+        let genome = Genome::sample(&means, &Genome::ZERO, &mut rand::thread_rng());
+        assert_eq!(genome, means);
+    }
+
+    #[test]
+    fn genes_without_mutation_stdev_draw_nothing() {
+        struct CountingMutationNumberSource {
+            num_draws: usize,
+        }
+
+        impl MutationNumberSource for CountingMutationNumberSource {
+            fn mutate(&mut self, value: F32Positive, _stdev: F32Positive) -> F32Positive {
+                self.num_draws += 1;
+                value
+            }
+        }
+
+        let constants = CellConstants {
+            attempted_eating_energy_mutation_stdev: 0.5.into(),
+            ..CellConstants::DEFAULT
+        };
+        let mut source = CountingMutationNumberSource { num_draws: 0 };
+        let mut genome = Rc::new(Genome::DEFAULT);
+        Genome::mutate(&mut genome, &constants, &mut source);
+        assert_eq!(source.num_draws, 1);
+
+        let mate = Genome { active_phase: 0.25.into(), ..*genome };
+        let mut rng = Pcg64::seed_from_u64(0);
+        let mut expected_rng = rng.clone();
+        Genome::cross_over(&mut genome, &mate, 0.5.into(), &mut rng);
+        expected_rng.gen_bool(0.5);
+        assert!(rng == expected_rng);
+    }
+
+    #[test]
+    fn mutating_zero_to_one_gene_clips_it() {
+        let mut genome = Rc::new(Genome {
+            food_memory_rate: 0.75.into(),
+            ..Genome::DEFAULT
+        });
+        let constants = CellConstants {
+            food_memory_rate_mutation_stdev: 0.5.into(),
+            ..CellConstants::DEFAULT
+        };
+        Genome::mutate(&mut genome, &constants, &mut AddingMutationNumberSource {});
+        assert_eq!(genome.food_memory_rate, 1.0.into());
+    }
+
+    #[test]
+    fn active_phase_mutates_past_midnight() {
+        let mut genome = Rc::new(Genome {
+            active_phase: 0.875.into(),
+            ..Genome::DEFAULT
+        });
+        let constants = CellConstants {
+            active_phase_mutation_stdev: 0.25.into(),
+            ..CellConstants::DEFAULT
+        };
+        Genome::mutate(&mut genome, &constants, &mut AddingMutationNumberSource {});
+        assert_eq!(genome.active_phase, 0.125.into());
+
+        let means = Genome { active_phase: 0.95.into(), ..Genome::DEFAULT };
+        let stdevs = Genome { active_phase: 0.1.into(), ..Genome::ZERO };
+        let mut rng = Pcg64::seed_from_u64(0);
+        let phases: Vec<f32> = (0..100).map(|_| Genome::sample(&means, &stdevs, &mut rng).active_phase.value()).collect();
+        assert!(phases.iter().any(|&phase| phase < 0.5));
+        assert!(!phases.contains(&1.0));
+    }
+
+    #[test]
+    fn crossover_takes_genes_from_mate() {
+        let mut genome = Rc::new(Genome {
+            attempted_eating_energy: 1.0.into(),
+            active_phase: 0.25.into(),
+            ..Genome::DEFAULT
+        });
+        let mate = Genome {
+            attempted_eating_energy: 2.0.into(),
+            active_phase: 0.5.into(),
+            ..Genome::DEFAULT
+        };
+        Genome::cross_over(&mut genome, &mate, 1.0.into(), &mut Pcg64::seed_from_u64(0));
+        assert_eq!(*genome, mate);
+    }
+
+    #[test]
+    fn shared_genome_is_copied_only_when_a_gene_changes() {
+        let parent = Rc::new(Genome::DEFAULT);
+        let unmutating = CellConstants::DEFAULT;
+        let mutating = CellConstants {
+            attempted_eating_energy_mutation_stdev: 0.5.into(),
+            ..CellConstants::DEFAULT
+        };
+
+        let mut child = Rc::clone(&parent);
+        Genome::mutate(&mut child, &unmutating, &mut AddingMutationNumberSource {});
+        Genome::cross_over(&mut child, &parent, 1.0.into(), &mut Pcg64::seed_from_u64(0));
+        assert!(Rc::ptr_eq(&child, &parent));
+
+        Genome::mutate(&mut child, &mutating, &mut AddingMutationNumberSource {});
+        assert!(!Rc::ptr_eq(&child, &parent));
+        assert_eq!((child.attempted_eating_energy, parent.attempted_eating_energy), (0.5.into(), 0.0.into()));
+    }
+
+    #[test]
+    fn interning_shares_genomes_with_the_same_genes() {
+        let mut interner = GenomeInterner::new();
+        let first = interner.intern(Rc::new(Genome::DEFAULT));
+        let other = interner.intern(Rc::new(Genome { size: 2.0.into(), ..Genome::DEFAULT }));
+
+        assert!(Rc::ptr_eq(&interner.intern(Rc::new(Genome::DEFAULT)), &first));
+        assert!(!Rc::ptr_eq(&other, &first));
+
+        drop(first);
+        let after_drop = Rc::new(Genome::DEFAULT);
+        assert!(Rc::ptr_eq(&interner.intern(Rc::clone(&after_drop)), &after_drop));
+    }
+
+    #[test]
+    fn gene_values_override_genome() {
+        let genome = Genome::DEFAULT.with_gene_values("attempted_eating_energy=1.5, active_phase=0.25").unwrap();
+        assert_eq!(genome, Genome {
+            attempted_eating_energy: 1.5.into(),
+            active_phase: 0.25.into(),
+            ..Genome::DEFAULT
+        });
+    }
+
+    #[test]
+    fn gene_values_are_checked() {
+        assert_eq!(Genome::DEFAULT.with_gene_values("eating=1"), Err("unknown gene 'eating'".to_string()));
+        assert_eq!(Genome::DEFAULT.with_gene_values("active_phase=2"), Err("value 2 is out of range for gene active_phase".to_string()));
+        assert!(Genome::DEFAULT.with_gene_values("attempted_eating_energy").is_err());
+    }
+
+    struct AddingMutationNumberSource {}
+
+    impl MutationNumberSource for AddingMutationNumberSource {
+        fn mutate(&mut self, value: F32Positive, stdev: F32Positive) -> F32Positive {
+            value + stdev
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::mem;
+
+/// How much of a history to keep in memory: the most recent `recent` values in full, plus up to
+/// `older` values downsampled from the ones before them by keeping every `downsample_factor`th.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetentionPolicy {
+    pub recent: usize,
+    pub older: usize,
+    pub downsample_factor: usize,
+}
+
+impl RetentionPolicy {
+    pub const fn keep_last(recent: usize) -> Self {
+        RetentionPolicy {
+            recent,
+            older: 0,
+            downsample_factor: 1,
+        }
+    }
+}
+
+/// A sequence of values recorded during a run whose memory use is bounded by a `RetentionPolicy`.
+pub struct History<T> {
+    policy: RetentionPolicy,
+    recent: VecDeque<T>,
+    older: VecDeque<T>,
+    num_retired: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        History {
+            policy,
+            recent: VecDeque::with_capacity(policy.recent),
+            older: VecDeque::with_capacity(policy.older),
+            num_retired: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.policy.recent == 0 {
+            self.retire(value);
+            return;
+        }
+
+        if self.recent.len() == self.policy.recent {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.retire(oldest);
+            }
+        }
+        self.recent.push_back(value);
+    }
+
+    fn retire(&mut self, value: T) {
+        if self.policy.older == 0 {
+            return;
+        }
+
+        if self.num_retired.is_multiple_of(self.policy.downsample_factor.max(1)) {
+            if self.older.len() == self.policy.older {
+                self.older.pop_front();
+            }
+            self.older.push_back(value);
+        }
+        self.num_retired += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.older.len() + self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn latest(&self) -> Option<&T> {
+        self.recent.back().or_else(|| self.older.back())
+    }
+
+    /// Iterates from the oldest retained value to the latest.
+    pub fn iter(&self) -> impl Iterator<Item=&T> + '_ {
+        self.older.iter().chain(self.recent.iter())
+    }
+
+    /// Bytes allocated for the retained values, including spare capacity.
+    pub fn memory_bytes(&self) -> usize {
+        (self.older.capacity() + self.recent.capacity()) * mem::size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_only_recent_values() {
+        let mut history = History::new(RetentionPolicy::keep_last(2));
+        for value in 0..5 {
+            history.push(value);
+        }
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(history.latest(), Some(&4));
+    }
+
+    #[test]
+    fn history_downsamples_older_values() {
+        let mut history = History::new(RetentionPolicy {
+            recent: 2,
+            older: 2,
+            downsample_factor: 2,
+        });
+        for value in 0..10 {
+            history.push(value);
+        }
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), vec![4, 6, 8, 9]);
+    }
+
+    #[test]
+    fn history_memory_is_bounded_by_policy() {
+        let mut history = History::new(RetentionPolicy {
+            recent: 4,
+            older: 4,
+            downsample_factor: 3,
+        });
+        for value in 0..1000_u64 {
+            history.push(value);
+        }
+        assert_eq!(history.len(), 8);
+        assert!(history.memory_bytes() < 1000 * mem::size_of::<u64>());
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::number_types::F32ZeroToOne;
+
+/// A change to make to a running world after a given number of steps, for perturbation-response
+/// experiments. Scripts list one per line as `<step> <action>`, with `#` starting a comment:
+///
+/// ```text
+/// 500 kill 0.5
+/// 1000 add 100 attempted_eating_energy=3,child_threshold_energy=5
+/// 1500 set maintenance_energy 0.2
+/// 2000 dump population.2000.json
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intervention {
+    pub step: u32,
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Kill this fraction of the cells, chosen at random.
+    Kill(F32ZeroToOne),
+    /// Add cells with the gene means, overridden by any gene=value pairs in `genes`.
+    Add { count: usize, genes: String },
+    /// Set a cell constant, named as its command-line option, for every cell from now on.
+    Set { constant: String, value: String },
+    /// Write a population dump to this file.
+    Dump(PathBuf),
+}
+
+/// Reads a script, returning its interventions in step order. Interventions at the same step
+/// keep their order in the script.
+pub fn load(path: &Path) -> Result<Vec<Intervention>, String> {
+    let script = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+    parse(&script).map_err(|message| format!("{}: {}", path.display(), message))
+}
+
+pub fn parse(script: &str) -> Result<Vec<Intervention>, String> {
+    let mut interventions = vec![];
+    for (line_index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        interventions.push(line.parse().map_err(|message| format!("line {}: {}", line_index + 1, message))?);
+    }
+    interventions.sort_by_key(|intervention: &Intervention| intervention.step);
+    Ok(interventions)
+}
+
+impl FromStr for Intervention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (step, action) = s.trim().split_once(char::is_whitespace).unwrap_or((s.trim(), ""));
+        let step = step.parse()
+            .map_err(|_| format!("expected a step number to start '{}'", s))?;
+        Ok(Intervention { step, action: action.parse()? })
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        Ok(match words[..] {
+            ["kill", fraction] => Action::Kill(fraction.parse::<f32>().ok()
+                .filter(|fraction| (0.0..=1.0).contains(fraction))
+                .ok_or_else(|| format!("kill fraction must be between 0 and 1 (got {})", fraction))?
+                .into()),
+            ["add", count] | ["add", count, _] => Action::Add {
+                count: count.parse().map_err(|_| format!("invalid cell count '{}'", count))?,
+                genes: words.get(2).copied().unwrap_or_default().to_string(),
+            },
+            ["set", constant, value] => Action::Set { constant: constant.to_string(), value: value.to_string() },
+            ["dump", path] => Action::Dump(PathBuf::from(path)),
+            _ => return Err(format!(
+                "expected kill <fraction>, add <count> [<gene>=<value>,...], set <constant> <value>, or dump <file>, got '{}'",
+                words.join(" "))),
+        })
+    }
+}
+
+impl fmt::Display for Intervention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.step, self.action)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Kill(fraction) => write!(f, "kill {}", fraction),
+            Action::Add { count, genes } if genes.is_empty() => write!(f, "add {}", count),
+            Action::Add { count, genes } => write!(f, "add {} {}", count, genes),
+            Action::Set { constant, value } => write!(f, "set {} {}", constant, value),
+            Action::Dump(path) => write!(f, "dump {}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_lists_interventions_in_step_order() {
+        let interventions = parse("
+            # perturbation experiment
+            1000 add 100 attempted_eating_energy=3
+            500 kill 0.5
+            1000 set maintenance_energy 0.2  # after the add
+            2000 dump population.json
+        ").unwrap();
+
+        assert_eq!(interventions, vec![
+            Intervention { step: 500, action: Action::Kill(0.5.into()) },
+            Intervention { step: 1000, action: Action::Add { count: 100, genes: "attempted_eating_energy=3".to_string() } },
+            Intervention { step: 1000, action: Action::Set { constant: "maintenance_energy".to_string(), value: "0.2".to_string() } },
+            Intervention { step: 2000, action: Action::Dump(PathBuf::from("population.json")) },
+        ]);
+        assert_eq!(interventions[1].to_string(), "1000 add 100 attempted_eating_energy=3");
+    }
+
+    #[test]
+    fn bad_lines_are_reported_by_number() {
+        assert_eq!(parse("10 kill 0.5\n20 kill 2").unwrap_err(), "line 2: kill fraction must be between 0 and 1 (got 2)");
+        assert!(parse("later kill 0.5").is_err());
+        assert!(parse("10 explode").is_err());
+    }
+}
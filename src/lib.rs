@@ -1,7 +1,61 @@
 extern crate core;
 
+pub mod async_writer;
+pub mod audit;
+pub mod bench;
+pub mod brain;
+pub mod build_info;
 pub mod cell;
+pub mod cell_columns;
+pub mod cell_sources;
+pub mod checkpoint;
+pub mod checksum;
+pub mod coloring;
+pub mod competition;
+pub mod day_cycle;
+pub mod environments;
+pub mod epoch_snapshot;
+#[cfg(feature = "cli")]
+pub mod event_log;
+pub mod fitness;
+pub mod food_allocation;
+pub mod food_grid;
 pub mod food_sources;
+pub mod genome;
+pub mod history;
+pub mod interventions;
+pub mod light_sources;
+#[cfg(feature = "cli")]
 pub mod main_support;
+pub mod mutation_models;
 pub mod number_types;
+pub mod phylogeny;
+pub mod population_dump;
+pub mod process_order;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "cli")]
+pub mod repl;
+#[cfg(feature = "sqlite")]
+pub mod results_db;
+pub mod run_id;
+pub mod run_report;
+pub mod scenarios;
+pub mod simulation;
+pub mod simulation_config;
+pub mod snapshot_diff;
+pub mod sparklines;
+pub mod species;
+pub mod stop_conditions;
+pub mod stress;
+pub mod sweep;
+pub mod topology;
+pub mod trait_heatmap;
+#[cfg(feature = "cli")]
+pub mod tui;
+pub mod units;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "web")]
+pub mod web_dashboard;
 pub mod world;
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use crate::day_cycle::DayCycle;
+use crate::number_types::F32Positive;
+
+/// Supplies the light shared among all cells each step. Unlike food, light that no cell
+/// absorbs is not carried over to the next step.
+#[typetag::serde]
+pub trait LightSource {
+    fn light_this_step(&mut self) -> F32Positive;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConstantLightSource {
+    light_per_step: F32Positive,
+}
+
+impl ConstantLightSource {
+    pub fn new(light_per_step: F32Positive) -> Self {
+        ConstantLightSource {
+            light_per_step
+        }
+    }
+}
+
+#[typetag::serde]
+impl LightSource for ConstantLightSource {
+    fn light_this_step(&mut self) -> F32Positive {
+        self.light_per_step
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DayNightLightSource {
+    day_light_per_step: F32Positive,
+    night_light_per_step: F32Positive,
+    day_cycle: DayCycle,
+}
+
+impl DayNightLightSource {
+    pub fn new(day_light_per_step: F32Positive, night_light_per_step: F32Positive, day_length: u32) -> Self {
+        DayNightLightSource {
+            day_light_per_step,
+            night_light_per_step,
+            day_cycle: DayCycle::new(day_length),
+        }
+    }
+}
+
+#[typetag::serde]
+impl LightSource for DayNightLightSource {
+    fn light_this_step(&mut self) -> F32Positive {
+        let result = if self.day_cycle.is_day() { self.day_light_per_step } else { self.night_light_per_step };
+        self.day_cycle.advance();
+        result
+    }
+}
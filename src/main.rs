@@ -1,6 +1,9 @@
-use clap::Parser;
-use evo2::main_support::{create_and_run_world, Args};
+use std::env;
+use std::ffi::OsString;
+use evo2::main_support::{run_command, Command};
 
 fn main() {
-    create_and_run_world(&Args::parse());
+    let command_line: Vec<OsString> = env::args_os().collect();
+    let command = Command::try_parse_with_scenario_from(command_line.iter().cloned()).unwrap_or_else(|err| err.exit());
+    run_command(&command, &command_line);
 }
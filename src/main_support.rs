@@ -1,169 +1,2324 @@
-use clap::Parser;
+use clap::{CommandFactory, ErrorKind, FromArgMatches, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rand_distr::Normal;
+use rand_pcg::Pcg64;
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use crate::cell::{CellConstants, RandomMutationNumberSource};
-use crate::food_sources::ConstantFoodSource;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tracing_subscriber::filter::LevelFilter;
+use crate::async_writer::AsyncWriter;
+use crate::audit::Audit;
+use crate::brain::{BrainWeights, CellController};
+use crate::bench;
+use crate::build_info::BuildInfo;
+use crate::cell::{Cell, CellConstants, RandomMutationNumberSource};
+use crate::cell_sources::{CellSource, ConstantCellSource, ExtinctionReseeding, Immigrants, PulseCellSource};
+use crate::checkpoint;
+use crate::checksum;
+use crate::competition;
+use crate::coloring::ColorRule;
+use crate::competition::CompetitionStats;
+use crate::environments::Environment;
+use crate::event_log;
+use crate::event_log::EventLog;
+use crate::fitness::{LifetimeSuccess, OffspringCounts, SelectionAnalysis, SelectionReport};
+use crate::food_allocation::FoodAllocationPolicy;
+use crate::food_sources::{ConstantFoodSource, DayNightFoodSource, DensityDependentFoodSource, FoodSource};
+use crate::genome::Genome;
+use crate::history::RetentionPolicy;
+use crate::interventions;
+use crate::interventions::{Action, Intervention};
+use crate::light_sources::{DayNightLightSource, LightSource};
+use crate::mutation_models::MutationModel;
+use crate::number_types::{F32Positive, F32ZeroToOne};
+use crate::population_dump;
+use crate::process_order::ProcessOrder;
+use crate::repl;
+#[cfg(feature = "sqlite")]
+use crate::results_db::ResultsDb;
+use crate::sparklines::GeneTraces;
+use crate::simulation;
+use crate::simulation::RunSummary;
+use crate::run_id::RunId;
+use crate::run_report::RunReport;
+use crate::scenarios::Scenario;
+use crate::snapshot_diff;
+use crate::snapshot_diff::Snapshot;
+use crate::species;
+use crate::stop_conditions::StopCondition;
+use crate::stress::StressPattern;
+use crate::sweep;
+use crate::sweep::SweepAxis;
+use crate::trait_heatmap::TraitHeatmap;
+use crate::tui;
+use crate::units::Units;
+#[cfg(feature = "web")]
+use crate::web_dashboard;
 use crate::world;
-use crate::world::World;
+use crate::world::{CorpseRecycling, Crowding, DeathCounts, Generations, ReproductionMode, World, WorldObserver};
 
+/// Carries out the command parsed from `command_line`, which a sweep parses again with each
+/// configuration's values added.
+pub fn run_command(command: &Command, command_line: &[OsString]) {
+    match command {
+        Command::Run(args) => create_and_run_world(args),
+        Command::Resume(resume_args) => run_resumed_world(resume_args),
+        Command::Compete(compete_args) => run_competition(compete_args),
+        Command::Sweep(sweep_args) => run_sweep(command_line, sweep_args),
+        Command::Inspect(inspect_args) => run_inspect(inspect_args),
+        Command::Diff(diff_args) => run_diff(diff_args),
+        Command::Bench(bench_args) => run_bench(bench_args),
+    }
+}
+
+/// Runs a new world as `args` describe, exiting with a usage error if they are out of range.
 pub fn create_and_run_world(args: &Args) {
-    let cell_constants = Rc::new(CellConstants {
+    exit_if_invalid(args.validate());
+    let (mut world, mut mutation_number_source) = create_world_and_mutation_number_source(args);
+    run_with_interface(Some(&args.world), &args.run, &mut world, &mut mutation_number_source, 0, args.run.run_id.unwrap_or_else(RunId::generate));
+}
+
+fn exit_if_invalid(validated: Result<(), String>) {
+    if let Err(message) = validated {
+        clap::Error::raw(clap::ErrorKind::ValueValidation, message + "\n").exit();
+    }
+}
+
+/// Resumes the checkpoint's run. Interventions can only kill cells and dump the population,
+/// since adding cells and setting cell constants start from world options the checkpoint lacks.
+fn run_resumed_world(resume_args: &ResumeArgs) {
+    exit_if_invalid(resume_args.run.validate());
+    let checkpoint = checkpoint::load(&resume_args.checkpoint)
+        .unwrap_or_else(|err| panic!("Cannot load checkpoint {}: {}", resume_args.checkpoint.display(), err));
+    if checkpoint.build_info != BuildInfo::current() {
+        println!("# Resuming checkpoint saved by {}", checkpoint.build_info);
+    }
+    let run_args = &resume_args.run;
+    let run_id = run_args.run_id.or(checkpoint.run_id).unwrap_or_else(RunId::generate);
+    let mut world = checkpoint.world;
+    world.set_audit(run_args.audit);
+    let mut mutation_number_source = checkpoint.mutation_number_source;
+    run_with_interface(None, run_args, &mut world, &mut mutation_number_source, checkpoint.step, run_id);
+}
+
+/// Runs the world that `args` describe without printing anything, and returns its summary, for
+/// outer optimizers such as parameter tuning scripts that call evo2 as a library. Build `args`
+/// from `Args::DEFAULT` or parse them with `Args::try_parse_with_scenario_from`. The run ends after
+/// `args.run.steps` steps, once every cell has died, or when `args.run.stop_when` is met; intervention
+/// scripts and outputs such as checkpoints are ignored.
+///
+/// The summary is a `RunReport`, the one `--summary-json` saves, with the run's population
+/// extremes, extinction step, offspring stats, and survivors' gene stats. It is not a
+/// `simulation::RunSummary`, the leaner summary of throughput runs and sweeps, which has only
+/// the run's counts and food totals.
+pub fn evaluate(args: &Args) -> Result<RunReport, String> {
+    args.validate()?;
+    let (mut world, mut mutation_number_source) = create_world_and_mutation_number_source(args);
+    let options = RunOptions {
+        run_id: args.run.run_id,
+        print_every: 0,
+        stop_condition: args.run.stop_when.clone(),
+        ..RunOptions::DEFAULT
+    };
+    let output = Rc::new(RefCell::new(AsyncWriter::new(io::sink())));
+    let mut effects = StepEffects::start(&mut world, 0, args.run.steps, &options, output, None);
+    while effects.step(&mut world, &mut mutation_number_source) {}
+    Ok(effects.finish(&mut world, &mutation_number_source))
+}
+
+/// The world and mutation number source that `args` describe, for front ends other than the
+/// command line's.
+pub fn create_world_and_mutation_number_source(args: &Args) -> (World, RandomMutationNumberSource) {
+    let units = units(&args.world);
+    let cell_constants = cell_constants(&args.world, &units);
+    let mut rng = rng(&args.world);
+    let mut world = create_world(&args.world, args.run.steps, &units, &cell_constants, &mut rng);
+    world.set_audit(args.run.audit);
+    (world, RandomMutationNumberSource::with_seed(rng.gen()).with_model(args.world.mutation_model.clone()))
+}
+
+fn units(args: &WorldArgs) -> Units {
+    Units {
+        energy: args.energy_scale,
+        food: args.food_scale,
+        light: args.light_scale,
+    }
+}
+
+fn cell_constants(args: &WorldArgs, units: &Units) -> Rc<CellConstants> {
+    Rc::new(units.constants(&unscaled_cell_constants(args)))
+}
+
+fn unscaled_cell_constants(args: &WorldArgs) -> CellConstants {
+    CellConstants {
         create_child_energy: args.create_child_energy.into(),
         energy_yield_from_digestion: args.energy_yield_from_digestion.into(),
         food_yield_from_eating: args.food_yield_from_eating.into(),
         health_increase_per_healing_energy: args.health_increase_per_healing_energy.into(),
         health_reduction_from_entropy: args.health_reduction_from_entropy.into(),
+        entropy_age_exponent: args.entropy_age_exponent.into(),
+        health_reduction_from_zero_energy: args.health_reduction_from_zero_energy.into(),
         health_reduction_per_energy_expended: args.health_reduction_per_energy_expended.into(),
+        attack_efficiency: args.attack_efficiency.into(),
+        attack_defense: args.attack_defense.into(),
+        health_reduction_per_attack_energy: args.health_reduction_per_attack_energy.into(),
+        max_age: args.max_age,
+        crossover_probability: args.crossover_probability.into(),
+        efficiency_decay_per_age: args.efficiency_decay_per_age.into(),
+        activity_fraction: args.activity_fraction.into(),
+        light_yield_from_photosynthesis: args.light_yield_from_photosynthesis.into(),
+        energy_yield_from_light: args.energy_yield_from_light.into(),
+        trade_efficiency: args.trade_efficiency.into(),
+        food_access_per_trade_energy: args.food_access_per_trade_energy.into(),
+        maintenance_energy: args.maintenance_energy.into(),
+        health_reduction_per_maintenance_shortfall: args.health_reduction_per_maintenance_shortfall.into(),
+        maintenance_energy_per_storage: args.maintenance_energy_per_storage.into(),
+        maintenance_energy_per_attempted_energy: args.maintenance_energy_per_attempted_energy.into(),
+        maintenance_energy_per_efficiency: args.maintenance_energy_per_efficiency.into(),
+        maintenance_energy_per_stress_tolerance: args.maintenance_energy_per_stress_tolerance.into(),
+        health_reduction_per_excess_energy: args.health_reduction_per_excess_energy.into(),
+        process_order: args.process_order,
+        controller: args.cell_controller,
+        death_probability: args.death_probability.into(),
+        death_probability_per_health_lost: args.death_probability_per_health_lost.into(),
+        toxin_tolerance: args.toxin_tolerance.into(),
+        health_reduction_per_toxin: args.health_reduction_per_toxin.into(),
+        toxin_yield_from_detox: args.toxin_yield_from_detox.into(),
+        max_food_intake: args.max_food_intake.into(),
+        donation_efficiency: args.donation_efficiency.into(),
+        dormant_maintenance_fraction: args.dormant_maintenance_fraction.into(),
+        protein_yield_from_eating: args.protein_yield_from_eating.into(),
+        energy_yield_from_protein_digestion: args.energy_yield_from_protein_digestion.into(),
         ..CellConstants::DEFAULT
+    }
+}
+
+/// Sets the cell constant configured by the command-line option `name`, e.g.
+/// maintenance_energy for --maintenance-energy, checking the value against its domain.
+fn set_cell_constant(constants: &mut CellConstants, name: &str, value: &str) -> Result<(), String> {
+    let non_negative = |value| checked_constant(name, value, DomainChecks::non_negative);
+    let zero_to_one = |value| checked_constant(name, value, DomainChecks::zero_to_one);
+    match name.replace('-', "_").as_str() {
+        "create_child_energy" => constants.create_child_energy = non_negative(value)?.into(),
+        "energy_yield_from_digestion" => constants.energy_yield_from_digestion = non_negative(value)?.into(),
+        "food_yield_from_eating" => constants.food_yield_from_eating = non_negative(value)?.into(),
+        "health_increase_per_healing_energy" => constants.health_increase_per_healing_energy = zero_to_one(value)?.into(),
+        "health_reduction_from_entropy" => constants.health_reduction_from_entropy = zero_to_one(value)?.into(),
+        "entropy_age_exponent" => constants.entropy_age_exponent = non_negative(value)?.into(),
+        "health_reduction_from_zero_energy" => constants.health_reduction_from_zero_energy = zero_to_one(value)?.into(),
+        "health_reduction_per_energy_expended" => constants.health_reduction_per_energy_expended = zero_to_one(value)?.into(),
+        "attack_efficiency" => constants.attack_efficiency = non_negative(value)?.into(),
+        "attack_defense" => constants.attack_defense = non_negative(value)?.into(),
+        "health_reduction_per_attack_energy" => constants.health_reduction_per_attack_energy = zero_to_one(value)?.into(),
+        "max_age" => constants.max_age = value.parse().map_err(|_| format!("{} must be a whole number of steps (got {})", name, value))?,
+        "crossover_probability" => constants.crossover_probability = zero_to_one(value)?.into(),
+        "efficiency_decay_per_age" => constants.efficiency_decay_per_age = zero_to_one(value)?.into(),
+        "activity_fraction" => constants.activity_fraction = zero_to_one(value)?.into(),
+        "light_yield_from_photosynthesis" => constants.light_yield_from_photosynthesis = non_negative(value)?.into(),
+        "energy_yield_from_light" => constants.energy_yield_from_light = non_negative(value)?.into(),
+        "trade_efficiency" => constants.trade_efficiency = non_negative(value)?.into(),
+        "food_access_per_trade_energy" => constants.food_access_per_trade_energy = non_negative(value)?.into(),
+        "maintenance_energy" => constants.maintenance_energy = non_negative(value)?.into(),
+        "health_reduction_per_maintenance_shortfall" => constants.health_reduction_per_maintenance_shortfall = zero_to_one(value)?.into(),
+        "maintenance_energy_per_storage" => constants.maintenance_energy_per_storage = non_negative(value)?.into(),
+        "maintenance_energy_per_attempted_energy" => constants.maintenance_energy_per_attempted_energy = non_negative(value)?.into(),
+        "maintenance_energy_per_efficiency" => constants.maintenance_energy_per_efficiency = non_negative(value)?.into(),
+        "maintenance_energy_per_stress_tolerance" => constants.maintenance_energy_per_stress_tolerance = non_negative(value)?.into(),
+        "health_reduction_per_excess_energy" => constants.health_reduction_per_excess_energy = zero_to_one(value)?.into(),
+        "process_order" => constants.process_order = value.parse()?,
+        "cell_controller" => constants.controller = value.parse()?,
+        "death_probability" => constants.death_probability = zero_to_one(value)?.into(),
+        "death_probability_per_health_lost" => constants.death_probability_per_health_lost = zero_to_one(value)?.into(),
+        "toxin_tolerance" => constants.toxin_tolerance = non_negative(value)?.into(),
+        "health_reduction_per_toxin" => constants.health_reduction_per_toxin = zero_to_one(value)?.into(),
+        "toxin_yield_from_detox" => constants.toxin_yield_from_detox = non_negative(value)?.into(),
+        "max_food_intake" => constants.max_food_intake = non_negative(value)?.into(),
+        "donation_efficiency" => constants.donation_efficiency = zero_to_one(value)?.into(),
+        "dormant_maintenance_fraction" => constants.dormant_maintenance_fraction = zero_to_one(value)?.into(),
+        "protein_yield_from_eating" => constants.protein_yield_from_eating = non_negative(value)?.into(),
+        "energy_yield_from_protein_digestion" => constants.energy_yield_from_protein_digestion = non_negative(value)?.into(),
+        _ => return Err(format!("unknown cell constant '{}'", name)),
+    }
+    Ok(())
+}
+
+fn checked_constant(name: &str, value: &str, check: fn(&mut DomainChecks, &str, f32)) -> Result<f32, String> {
+    let number: f32 = value.parse().map_err(|_| format!("invalid value '{}' for {}", value, name))?;
+    let mut checks = DomainChecks::new();
+    checks.finite(name, number);
+    check(&mut checks, name, number);
+    checks.into_result().map(|()| number)
+}
+
+fn rng(args: &WorldArgs) -> Pcg64 {
+    match args.seed {
+        Some(seed) => Pcg64::seed_from_u64(seed),
+        None => Pcg64::from_entropy(),
+    }
+}
+
+fn run_with_interface(world_args: Option<&WorldArgs>, run_args: &RunArgs, world: &mut World, mutation_number_source: &mut RandomMutationNumberSource, first_step: u32, run_id: RunId) {
+    let options = run_options(world_args, run_args, run_id);
+    let event_log = if run_args.log_level == LevelFilter::OFF {
+        None
+    } else {
+        let path = run_args.log_file.as_ref().map(|path| run_id.expand(path));
+        let file = event_log::init(run_args.log_level, path.as_deref())
+            .unwrap_or_else(|err| panic!("Cannot start event log: {}", err));
+        let event_log = Rc::new(RefCell::new(EventLog::new(first_step)));
+        world.add_observer(event_log.clone());
+        Some((event_log, file.zip(path)))
+    };
+
+    let heatmap = run_args.heatmap.as_ref().map(|path| {
+        let path = run_id.expand(path);
+        let heatmap = trait_heatmap(world_args.unwrap_or(&WorldArgs::DEFAULT), run_args, &path, first_step)
+            .unwrap_or_else(|err| panic!("Cannot write heatmap to {}: {}", path.display(), err));
+        let heatmap = Rc::new(RefCell::new(heatmap));
+        world.add_observer(heatmap.clone());
+        (path, heatmap)
+    });
+
+    run_in_mode(world_args, run_args, world, mutation_number_source, first_step, &options);
+
+    if let Some((event_log, file)) = event_log {
+        world.remove_observer(&event_log);
+        if let Some((file, path)) = file {
+            file.flush().unwrap_or_else(|err| panic!("Cannot write event log to {}: {}", path.display(), err));
+        }
+    }
+    if let Some((path, heatmap)) = heatmap {
+        world.remove_observer(&heatmap);
+        heatmap.borrow_mut().finish()
+            .unwrap_or_else(|err| panic!("Cannot write heatmap to {}: {}", path.display(), err));
+    }
+}
+
+const DEFAULT_HEATMAP_GENE: &str = "child_threshold_energy";
+
+fn trait_heatmap(world_args: &WorldArgs, run_args: &RunArgs, path: &Path, first_step: u32) -> io::Result<TraitHeatmap<AsyncWriter>> {
+    let gene = run_args.heatmap_gene.as_deref().unwrap_or(DEFAULT_HEATMAP_GENE);
+    let gene_index = Genome::gene_names().position(|name| name == gene).expect("validated gene name");
+    let max = run_args.heatmap_max.unwrap_or_else(|| {
+        let mean = units(world_args).genome(&gene_means(world_args)).gene_values().nth(gene_index).unwrap();
+        if mean == 0.0 { 1.0 } else { 2.0 * mean }
+    });
+    TraitHeatmap::new(AsyncWriter::new(File::create(path)?), gene_index, run_args.heatmap_min, max,
+                      run_args.heatmap_bins, run_args.heatmap_every, first_step)
+}
+
+fn run_in_mode(world_args: Option<&WorldArgs>, run_args: &RunArgs, world: &mut World, mutation_number_source: &mut RandomMutationNumberSource, first_step: u32, options: &RunOptions) {
+    if run_args.interactive {
+        let mut planner = InterventionPlanner::new(world_args, options.run_id.unwrap_or_else(RunId::generate));
+        repl::run(io::stdin().lock(), &mut io::stdout().lock(), world, mutation_number_source, first_step, &mut planner, options.run_id)
+            .unwrap_or_else(|err| panic!("Interactive session failed: {}", err));
+        return;
+    }
+    if run_args.throughput {
+        run_throughput(world, mutation_number_source, first_step, run_args.steps, options);
+        return;
+    }
+    if run_args.tui {
+        tui::run(world, mutation_number_source, first_step, run_args.steps, options)
+            .unwrap_or_else(|err| panic!("Terminal dashboard failed: {}", err));
+        return;
+    }
+    #[cfg(feature = "web")]
+    if let Some(address) = &run_args.serve {
+        web_dashboard::serve(world, mutation_number_source, first_step, run_args.steps, options, address)
+            .unwrap_or_else(|err| panic!("Web dashboard at {} failed: {}", address, err));
+        return;
+    }
+    run_with_options(world, mutation_number_source, first_step, run_args.steps, options);
+}
+
+fn run_bench(bench_args: &BenchArgs) {
+    let mut world = bench::scenario_world(bench_args.cells, 1.0);
+    let result = bench::time_steps(&mut world, bench_args.steps);
+    println!("# {}", BuildInfo::current());
+    println!("{} steps starting with {} cells in {:.3}s: {:.1} steps/s, {:.0} cell-steps/s ({} cells at end)",
+             result.num_steps,
+             bench_args.cells,
+             result.elapsed.as_secs_f64(),
+             result.steps_per_second(),
+             result.cell_steps_per_second(),
+             world.num_cells());
+}
+
+fn create_world(args: &WorldArgs, steps: u32, units: &Units, cell_constants: &Rc<CellConstants>, rng: &mut Pcg64) -> World {
+    let cells = world::generate_cells(
+        args.cells,
+        initial_energies(args, units),
+        initial_healths(args),
+        &units.genome(&gene_means(args)),
+        &units.genome(&gene_stdevs(args)),
+        cell_constants,
+        rng,
+    );
+    create_world_with_cells(args, steps, units, cell_constants, cells, rng)
+}
+
+fn initial_energies(args: &WorldArgs, units: &Units) -> Normal<f32> {
+    Normal::new(units.energy(args.initial_energy_mean), units.energy(args.initial_energy_stdev)).unwrap()
+}
+
+fn initial_healths(args: &WorldArgs) -> Normal<f32> {
+    Normal::new(args.initial_health_mean, args.initial_health_stdev).unwrap()
+}
+
+fn create_world_with_cells(args: &WorldArgs, steps: u32, units: &Units, cell_constants: &Rc<CellConstants>, cells: Vec<Cell>, rng: &mut Pcg64) -> World {
+    let cell_sources = cell_sources(args, units, cell_constants, rng);
+    let mut world = World::new()
+        .with_cells(cells)
+        .with_food(units.food(args.initial_food).into())
+        .with_food_decay_rate(args.food_decay_rate.into())
+        .with_toxin_per_energy_expended(args.toxin_per_energy_expended.into())
+        .with_corpse_recycling(CorpseRecycling {
+            food_per_energy: (units.food(args.corpse_food_per_energy) / units.energy(1.0)).into(),
+            food_per_size: units.food(args.corpse_food_per_size).into(),
+        })
+        .with_food_sources(vec![food_source(args, steps, units, rng)])
+        .with_food_allocation(args.food_allocation.allocation())
+        .with_protein(units.food(args.initial_protein).into())
+        .with_protein_sources(vec![Box::new(ConstantFoodSource::new(units.food(args.added_protein).into()))])
+        .with_light_sources(vec![light_source(args, units)])
+        .with_cell_sources(cell_sources)
+        .with_day_length(args.day_length)
+        .with_generations(generations(args, units))
+        .with_reproduction_mode(args.reproduction_mode)
+        .with_max_cells(args.max_cells, args.crowding);
+    if args.reseed_on_extinction {
+        let founders = immigrants(args, units, cell_constants, args.reseed_from.as_deref(), rng.gen());
+        world = world.with_extinction_reseeding(ExtinctionReseeding::new(founders, args.cells));
+    }
+    if let Some(stress) = args.stress {
+        world = world.with_stress_source(stress.source(steps, rng.gen()));
+    }
+    world.with_seed(rng.gen())
+}
+
+fn run_competition(compete_args: &CompeteArgs) {
+    let args = &compete_args.world;
+    exit_if_invalid(args.validate());
+    let units = units(args);
+    let cell_constants = cell_constants(args, &units);
+    let (genome_a, genome_b) = competing_genomes(args, compete_args)
+        .unwrap_or_else(|message| clap::Error::raw(clap::ErrorKind::ValueValidation, message + "\n").exit());
+    let (genome_a, genome_b) = (units.genome(&genome_a), units.genome(&genome_b));
+    let mut rng = rng(args);
+
+    println!("# {}", BuildInfo::current());
+    println!("# {} replicates of {} cells of each genome for up to {} steps",
+             compete_args.replicates, args.cells / 2, compete_args.max_steps);
+    let mut stats = CompetitionStats::new();
+    for _ in 0..compete_args.replicates {
+        let mut cells = world::generate_cells(args.cells / 2, initial_energies(args, &units), initial_healths(args), &genome_a, &Genome::ZERO, &cell_constants, &mut rng);
+        cells.append(&mut world::generate_cells(args.cells / 2, initial_energies(args, &units), initial_healths(args), &genome_b, &Genome::ZERO, &cell_constants, &mut rng));
+        cells.shuffle(&mut rng);
+        let mut world = create_world_with_cells(args, compete_args.max_steps, &units, &cell_constants, cells, &mut rng);
+        stats.record(competition::run_replicate(&mut world, &genome_a, &genome_b, compete_args.max_steps));
+    }
+    stats.print();
+}
+
+/// Runs every configuration of the sweep to the end in throughput mode and writes one row of
+/// results per configuration.
+fn run_sweep(command_line: &[OsString], sweep_args: &SweepArgs) {
+    let configurations = sweep::configurations(&sweep_args.axes);
+    let configuration_args: Vec<Args> = configurations.iter()
+        .map(|values| {
+            let args = sweep_configuration_args(command_line, &sweep_args.axes, values).unwrap_or_else(|err| err.exit());
+            if let Err(message) = args.validate() {
+                clap::Error::raw(clap::ErrorKind::ValueValidation, format!("{}: {}\n", values.join(" "), message)).exit();
+            }
+            if args.run.steps == u32::MAX {
+                clap::Error::raw(clap::ErrorKind::MissingRequiredArgument, "sweep needs --steps\n").exit();
+            }
+            args
+        })
+        .collect();
+
+    println!("# {}", BuildInfo::current());
+    println!("# {} configurations on {} threads", configurations.len(), sweep_args.threads);
+    let results: Vec<Mutex<Option<SweepResult>>> = configurations.iter().map(|_| Mutex::new(None)).collect();
+    let next_index = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..sweep_args.threads.clamp(1, configurations.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                if index >= configuration_args.len() {
+                    break;
+                }
+                let result = run_sweep_configuration(&configuration_args[index]);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
     });
+    let results: Vec<SweepResult> = results.into_iter()
+        .map(|result| result.into_inner().unwrap().expect("every configuration was run"))
+        .collect();
+
+    match &sweep_args.summary {
+        Some(path) => {
+            let path = path.as_path();
+            File::create(path)
+                .and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    write_sweep_table(&mut writer, &sweep_args.axes, &configurations, &results)?;
+                    writer.flush()
+                })
+                .unwrap_or_else(|err| panic!("Cannot write sweep summary to {}: {}", path.display(), err));
+        }
+        None => write_sweep_table(&mut io::stdout().lock(), &sweep_args.axes, &configurations, &results)
+            .unwrap_or_else(|err| panic!("Cannot write sweep summary: {}", err)),
+    }
+}
+
+fn run_inspect(inspect_args: &InspectArgs) {
+    let snapshot = Snapshot::load(&inspect_args.file)
+        .unwrap_or_else(|err| panic!("Cannot load {}: {}", inspect_args.file.display(), err));
+    snapshot.write_summary(&mut io::stdout().lock(), inspect_args.species_threshold)
+        .unwrap_or_else(|err| panic!("Cannot write summary: {}", err));
+}
+
+fn run_diff(diff_args: &DiffArgs) {
+    let load = |path: &Path| Snapshot::load(path)
+        .unwrap_or_else(|err| panic!("Cannot load {}: {}", path.display(), err));
+    let diff = snapshot_diff::diff(&load(&diff_args.before), &load(&diff_args.after), diff_args.species_threshold);
+    diff.write(&mut io::stdout().lock())
+        .unwrap_or_else(|err| panic!("Cannot write diff: {}", err));
+}
+
+/// The options of a run of one sweep configuration: the sweep's command line with the
+/// configuration's values added, overriding any given there.
+fn sweep_configuration_args(command_line: &[OsString], axes: &[SweepAxis], values: &[&str]) -> Result<Args, clap::Error> {
+    let overrides = axes.iter().zip(values)
+        .map(|(axis, value)| OsString::from(format!("{}={}", axis.flag(), value)));
+    match Command::try_parse_with_scenario_from(command_line.iter().cloned().chain(overrides))? {
+        Command::Sweep(sweep_args) => Ok(sweep_args.args),
+        _ => Err(clap::Error::raw(ErrorKind::MissingSubcommand, "expected a sweep command line\n")),
+    }
+}
+
+struct SweepResult {
+    run_id: RunId,
+    summary: RunSummary,
+    mean_health: f32,
+    mean_energy: f32,
+}
+
+fn run_sweep_configuration(args: &Args) -> SweepResult {
+    let (mut world, mut mutation_number_source) = create_world_and_mutation_number_source(args);
+    let summary = simulation::run_summarized(&mut world, &mut mutation_number_source, 0, args.run.steps);
+    SweepResult {
+        run_id: args.run.run_id.unwrap_or_else(RunId::generate),
+        summary,
+        mean_health: world.mean_health(),
+        mean_energy: world.mean_energy(),
+    }
+}
+
+fn write_sweep_table<W: Write>(writer: &mut W, axes: &[SweepAxis], configurations: &[Vec<&str>], results: &[SweepResult]) -> io::Result<()> {
+    let axis_names: Vec<&str> = axes.iter().map(|axis| axis.name.as_str()).collect();
+    writeln!(writer, "{},run_id,steps,born,died,cells,food_added,food_eaten,mean_health,mean_energy", axis_names.join(","))?;
+    for (values, result) in configurations.iter().zip(results) {
+        writeln!(writer, "{},{},{},{},{},{},{},{},{},{}",
+                 values.join(","),
+                 result.run_id,
+                 result.summary.num_steps,
+                 result.summary.num_born,
+                 result.summary.num_died,
+                 result.summary.num_cells,
+                 result.summary.food_added,
+                 result.summary.food_eaten,
+                 result.mean_health,
+                 result.mean_energy)?;
+    }
+    Ok(())
+}
+
+/// The two genomes to compete: the gene means with the genes listed for each one overridden.
+fn competing_genomes(args: &WorldArgs, compete_args: &CompeteArgs) -> Result<(Genome, Genome), String> {
+    let genome_a = gene_means(args).with_gene_values(&compete_args.genome_a)
+        .map_err(|message| format!("--genome-a: {}", message))?;
+    let genome_b = gene_means(args).with_gene_values(&compete_args.genome_b)
+        .map_err(|message| format!("--genome-b: {}", message))?;
+    if genome_a == genome_b {
+        return Err("--genome-a and --genome-b must differ".to_string());
+    }
+    if args.cells < 2 {
+        return Err("--cells must be at least 2 to seed both genomes".to_string());
+    }
+    Ok((genome_a, genome_b))
+}
+
+fn gene_means(args: &WorldArgs) -> Genome {
+    Genome {
+        attempted_eating_energy: args.attempted_eating_energy_mean.into(),
+        attempted_healing_energy: args.attempted_healing_energy_mean.into(),
+        child_threshold_energy: args.child_threshold_energy_mean.into(),
+        child_threshold_food: args.child_threshold_food_mean.into(),
+        attempted_attack_energy: args.attempted_attack_energy_mean.into(),
+        attempted_photosynthesis_energy: args.attempted_photosynthesis_energy_mean.into(),
+        active_phase: args.active_phase_mean.into(),
+        attempted_trade_energy: args.attempted_trade_energy_mean.into(),
+        trade_reciprocity_threshold: args.trade_reciprocity_threshold_mean.into(),
+        food_memory_rate: args.food_memory_rate_mean.into(),
+        scarce_food_threshold: args.scarce_food_threshold_mean.into(),
+        scarcity_spending_fraction: args.scarcity_spending_fraction_mean.into(),
+        reproduction_priority: args.reproduction_priority_mean.into(),
+        eating_priority: args.eating_priority_mean.into(),
+        healing_priority: args.healing_priority_mean.into(),
+        attack_priority: args.attack_priority_mean.into(),
+        photosynthesis_priority: args.photosynthesis_priority_mean.into(),
+        trade_priority: args.trade_priority_mean.into(),
+        max_energy_storage: args.max_energy_storage_mean.into(),
+        attempted_detox_energy: args.attempted_detox_energy_mean.into(),
+        detox_priority: args.detox_priority_mean.into(),
+        size: args.size_mean.into(),
+        donation_threshold: args.donation_threshold_mean.into(),
+        donation_fraction: args.donation_fraction_mean.into(),
+        dormancy_threshold_energy: args.dormancy_threshold_energy_mean.into(),
+        wake_threshold_food: args.wake_threshold_food_mean.into(),
+        protein_preference: args.protein_preference_mean.into(),
+        eating_efficiency: args.eating_efficiency_mean.into(),
+        digestion_efficiency: args.digestion_efficiency_mean.into(),
+        state_update_rate: args.state_update_rate_mean.into(),
+        state_energy_scale: args.state_energy_scale_mean.into(),
+        state_food_scale: args.state_food_scale_mean.into(),
+        eating_state_response: args.eating_state_response_mean.into(),
+        healing_state_response: args.healing_state_response_mean.into(),
+        stress_tolerance: args.stress_tolerance_mean.into(),
+        brain: BrainWeights::NEUTRAL,
+    }
+}
+
+fn gene_stdevs(args: &WorldArgs) -> Genome {
+    Genome {
+        attempted_eating_energy: args.attempted_eating_energy_stdev.into(),
+        attempted_healing_energy: args.attempted_healing_energy_stdev.into(),
+        child_threshold_energy: args.child_threshold_energy_stdev.into(),
+        child_threshold_food: args.child_threshold_food_stdev.into(),
+        attempted_attack_energy: args.attempted_attack_energy_stdev.into(),
+        attempted_photosynthesis_energy: args.attempted_photosynthesis_energy_stdev.into(),
+        active_phase: F32ZeroToOne::clipped(args.active_phase_stdev),
+        attempted_trade_energy: args.attempted_trade_energy_stdev.into(),
+        trade_reciprocity_threshold: args.trade_reciprocity_threshold_stdev.into(),
+        food_memory_rate: F32ZeroToOne::clipped(args.food_memory_rate_stdev),
+        scarce_food_threshold: args.scarce_food_threshold_stdev.into(),
+        scarcity_spending_fraction: F32ZeroToOne::clipped(args.scarcity_spending_fraction_stdev),
+        reproduction_priority: args.reproduction_priority_stdev.into(),
+        eating_priority: args.eating_priority_stdev.into(),
+        healing_priority: args.healing_priority_stdev.into(),
+        attack_priority: args.attack_priority_stdev.into(),
+        photosynthesis_priority: args.photosynthesis_priority_stdev.into(),
+        trade_priority: args.trade_priority_stdev.into(),
+        max_energy_storage: args.max_energy_storage_stdev.into(),
+        attempted_detox_energy: args.attempted_detox_energy_stdev.into(),
+        detox_priority: args.detox_priority_stdev.into(),
+        size: args.size_stdev.into(),
+        donation_threshold: args.donation_threshold_stdev.into(),
+        donation_fraction: F32ZeroToOne::clipped(args.donation_fraction_stdev),
+        dormancy_threshold_energy: args.dormancy_threshold_energy_stdev.into(),
+        wake_threshold_food: args.wake_threshold_food_stdev.into(),
+        protein_preference: F32ZeroToOne::clipped(args.protein_preference_stdev),
+        eating_efficiency: args.eating_efficiency_stdev.into(),
+        digestion_efficiency: args.digestion_efficiency_stdev.into(),
+        state_update_rate: F32ZeroToOne::clipped(args.state_update_rate_stdev),
+        state_energy_scale: args.state_energy_scale_stdev.into(),
+        state_food_scale: args.state_food_scale_stdev.into(),
+        eating_state_response: args.eating_state_response_stdev.into(),
+        healing_state_response: args.healing_state_response_stdev.into(),
+        stress_tolerance: args.stress_tolerance_stdev.into(),
+        brain: BrainWeights::uniform(F32ZeroToOne::clipped(args.brain_weight_stdev)),
+    }
+}
+
+fn food_source(args: &WorldArgs, steps: u32, units: &Units, rng: &mut Pcg64) -> Box<dyn FoodSource> {
+    let source: Box<dyn FoodSource> = match args.environment {
+        Some(environment) => environment.food_source(units.food(args.added_food).into(), steps, rng),
+        None => Box::new(DayNightFoodSource::new(
+            units.food(args.added_food).into(),
+            units.food(args.night_added_food.unwrap_or(args.added_food)).into(),
+            args.day_length)),
+    };
+    if args.carrying_capacity == 0 {
+        source
+    } else {
+        Box::new(DensityDependentFoodSource::new(source, args.carrying_capacity))
+    }
+}
+
+/// None unless --immigrants-per-step is set, so that runs without immigration draw no seed for
+/// it from `rng`.
+fn cell_sources(args: &WorldArgs, units: &Units, cell_constants: &Rc<CellConstants>, rng: &mut Pcg64) -> Vec<Box<dyn CellSource>> {
+    if args.immigrants_per_step == 0.0 {
+        return vec![];
+    }
+    let immigrants = immigrants(args, units, cell_constants, args.immigrants_from.as_deref(), rng.gen());
+    let source: Box<dyn CellSource> = if args.immigration_period == 0 {
+        Box::new(ConstantCellSource::new(immigrants, args.immigrants_per_step.into()))
+    } else {
+        let cells_per_pulse = (args.immigrants_per_step * args.immigration_period as f32).round() as usize;
+        Box::new(PulseCellSource::new(immigrants, cells_per_pulse, args.immigration_period, 0))
+    };
+    vec![source]
+}
+
+/// Cells with the initial energy mean and the genomes saved in `path`, if any, or else sampled
+/// from the gene means and stdevs.
+fn immigrants(args: &WorldArgs, units: &Units, cell_constants: &Rc<CellConstants>, path: Option<&Path>, seed: u64) -> Immigrants {
+    let energy = F32Positive::clipped(units.energy(args.initial_energy_mean));
+    match path {
+        Some(path) => {
+            let snapshot = Snapshot::load(path)
+                .unwrap_or_else(|err| panic!("Cannot load cells from {}: {}", path.display(), err));
+            Immigrants::saved(snapshot.genomes, energy, cell_constants, seed)
+        }
+        None => Immigrants::sampled(units.genome(&gene_means(args)), units.genome(&gene_stdevs(args)), energy, cell_constants, seed),
+    }
+}
+
+fn light_source(args: &WorldArgs, units: &Units) -> Box<dyn LightSource> {
+    Box::new(DayNightLightSource::new(
+        units.light(args.light).into(),
+        units.light(args.night_light.unwrap_or(args.light)).into(),
+        args.day_length))
+}
+
+fn generations(args: &WorldArgs, units: &Units) -> Generations {
+    if args.generation_steps == 0 {
+        Generations::Overlapping
+    } else if args.select_by_energy {
+        Generations::Selected {
+            steps_per_generation: args.generation_steps,
+            population_size: args.cells,
+            child_energy: F32Positive::clipped(units.energy(args.initial_energy_mean)),
+        }
+    } else {
+        Generations::NonOverlapping { steps_per_generation: args.generation_steps }
+    }
+}
+
+fn run_options(world_args: Option<&WorldArgs>, run_args: &RunArgs, run_id: RunId) -> RunOptions {
+    let interventions = run_args.interventions.as_ref().map(|path| interventions::load(path)
+        .and_then(|interventions| schedule_interventions(world_args, run_id, interventions))
+        .unwrap_or_else(|message| clap::Error::raw(clap::ErrorKind::ValueValidation, message + "\n").exit()));
+    RunOptions {
+        run_id: Some(run_id),
+        checkpoints: run_args.checkpoint_file.as_ref().map(|path| OutputSchedule {
+            path: run_id.expand(path),
+            every: run_args.checkpoint_every,
+        }),
+        population_dumps: run_args.dump_population.as_ref().map(|path| OutputSchedule {
+            path: run_id.expand(path),
+            every: run_args.dump_population_every,
+        }),
+        summary_json: run_args.summary_json.as_ref().map(|path| run_id.expand(path)),
+        #[cfg(feature = "sqlite")]
+        db: run_args.db.as_ref().map(|path| OutputSchedule {
+            path: run_id.expand(path),
+            every: run_args.db_cells_every,
+        }),
+        sparkline_every: run_args.sparkline_every,
+        sparkline_width: run_args.sparkline_width,
+        sparkline_history: run_args.sparkline_history,
+        sparkline_downsample: run_args.sparkline_downsample,
+        species_every: run_args.species_every,
+        species_threshold: run_args.species_threshold,
+        checksum_every: run_args.checksum_every,
+        fitness_every: run_args.fitness_every,
+        selection_every: run_args.selection_every,
+        selection_csv: run_args.selection_csv.as_ref().map(|path| run_id.expand(path)),
+        print_every: if run_args.quiet { 0 } else { run_args.print_every },
+        progress: run_args.progress,
+        frame_rate: run_args.frame_rate,
+        color_rule: run_args.color_by,
+        stop_condition: run_args.stop_when.clone(),
+        interventions,
+    }
+}
+
+/// Works out each intervention's change up front, so that a script that cannot be carried out
+/// fails before the run starts.
+fn schedule_interventions(args: Option<&WorldArgs>, run_id: RunId, interventions: Vec<Intervention>) -> Result<Vec<ScheduledChange>, String> {
+    let mut planner = InterventionPlanner::new(args, run_id);
+    interventions.into_iter()
+        .map(|intervention| {
+            let change = planner.change(&intervention.action)
+                .map_err(|message| format!("{}: {}", intervention, message))?;
+            Ok(ScheduledChange { intervention, change })
+        })
+        .collect()
+}
+
+/// Works out the changes that intervention actions make to a world configured by `args`.
+/// Constants set by actions accumulate, starting from the command line's; added cells get the
+/// constants in effect when they are added. A world resumed from a checkpoint has no `args`, so
+/// actions can only kill cells in it or dump it.
+pub struct InterventionPlanner<'a> {
+    args: Option<&'a WorldArgs>,
+    run_id: RunId,
+    units: Units,
+    unscaled_constants: CellConstants,
+    constants: Rc<CellConstants>,
+    rng: Pcg64,
+}
+
+impl<'a> InterventionPlanner<'a> {
+    pub fn new(args: Option<&'a WorldArgs>, run_id: RunId) -> Self {
+        let known_args = args.unwrap_or(&WorldArgs::DEFAULT);
+        let units = units(known_args);
+        InterventionPlanner {
+            args,
+            run_id,
+            units,
+            unscaled_constants: unscaled_cell_constants(known_args),
+            constants: cell_constants(known_args, &units),
+            rng: rng(known_args),
+        }
+    }
+
+    pub fn change(&mut self, action: &Action) -> Result<WorldChange, String> {
+        Ok(match action {
+            Action::Kill(fraction) => WorldChange::Kill(*fraction),
+            Action::Add { count, genes } => {
+                let args = self.world_args()?;
+                let genome = gene_means(args).with_gene_values(genes)?;
+                WorldChange::AddCells(world::generate_cells(
+                    *count, initial_energies(args, &self.units), initial_healths(args),
+                    &self.units.genome(&genome), &Genome::ZERO, &self.constants, &mut self.rng))
+            }
+            Action::Set { constant, value } => {
+                self.world_args()?;
+                set_cell_constant(&mut self.unscaled_constants, constant, value)?;
+                self.constants = Rc::new(self.units.constants(&self.unscaled_constants));
+                WorldChange::SetCellConstants(Rc::clone(&self.constants))
+            }
+            Action::Dump(path) => WorldChange::Dump(self.run_id.expand(path)),
+        })
+    }
+
+    fn world_args(&self) -> Result<&'a WorldArgs, String> {
+        self.args.ok_or_else(|| "cannot add cells or set cell constants in a resumed run, whose checkpoint keeps no world options for them to start from".to_string())
+    }
+}
+
+pub struct RunOptions {
+    /// Recorded in the stats header, checkpoints, and JSON population dumps.
+    pub run_id: Option<RunId>,
+    pub checkpoints: Option<OutputSchedule>,
+    pub population_dumps: Option<OutputSchedule>,
+    /// File to write the end-of-run summary to as JSON.
+    pub summary_json: Option<PathBuf>,
+    /// Results database to record every step's stats in, with cell snapshots on its schedule.
+    #[cfg(feature = "sqlite")]
+    pub db: Option<OutputSchedule>,
+    pub sparkline_every: u32,
+    pub sparkline_width: usize,
+    pub sparkline_history: usize,
+    pub sparkline_downsample: usize,
+    pub species_every: u32,
+    pub species_threshold: f32,
+    pub checksum_every: u32,
+    pub fitness_every: u32,
+    pub selection_every: u32,
+    /// File to export each selection report to as CSV rows, one per gene.
+    pub selection_csv: Option<PathBuf>,
+    pub print_every: u32,
+    pub progress: bool,
+    pub frame_rate: u32,
+    pub color_rule: ColorRule,
+    pub stop_condition: Option<StopCondition>,
+    /// Changes from an intervention script, in step order.
+    pub interventions: Option<Vec<ScheduledChange>>,
+}
+
+/// An intervention from a script, with the change it makes worked out.
+pub struct ScheduledChange {
+    pub intervention: Intervention,
+    pub change: WorldChange,
+}
+
+pub enum WorldChange {
+    Kill(F32ZeroToOne),
+    AddCells(Vec<Cell>),
+    SetCellConstants(Rc<CellConstants>),
+    Dump(PathBuf),
+}
+
+impl RunOptions {
+    pub const DEFAULT: RunOptions = RunOptions {
+        run_id: None,
+        checkpoints: None,
+        population_dumps: None,
+        summary_json: None,
+        #[cfg(feature = "sqlite")]
+        db: None,
+        sparkline_every: 0,
+        sparkline_width: 40,
+        sparkline_history: 0,
+        sparkline_downsample: 10,
+        species_every: 0,
+        species_threshold: 1.0,
+        checksum_every: 0,
+        fitness_every: 0,
+        selection_every: 0,
+        selection_csv: None,
+        print_every: 1,
+        progress: false,
+        frame_rate: 20,
+        color_rule: ColorRule::DEFAULT,
+        stop_condition: None,
+        interventions: None,
+    };
+}
+
+/// A file written at the end of a run and, if `every` is nonzero, every `every` steps.
+pub struct OutputSchedule {
+    pub path: PathBuf,
+    pub every: u32,
+}
+
+pub fn run(world: &mut World, steps: u32) {
+    run_with_options(world, &mut RandomMutationNumberSource::new(), 0, steps, &RunOptions::DEFAULT);
+}
+
+pub fn run_with_options(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+                        first_step: u32, steps: u32, options: &RunOptions) {
+    run_to_output(world, mutation_number_source, first_step, steps, options, Rc::new(RefCell::new(AsyncWriter::stdout())));
+}
+
+fn run_to_output(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+                 first_step: u32, steps: u32, options: &RunOptions, output: Output) {
+    print_above(&None, &output, |out| write_stats_header(out, options.run_id));
+
+    let progress_bar = if options.progress { Some(progress_bar(first_step, steps)) } else { None };
+    let stats_printer = Rc::new(RefCell::new(StatsPrinter::new(first_step, options.print_every, progress_bar.clone(), output.clone())));
+    stats_printer.borrow().print(world, 0, 0);
+    world.add_observer(stats_printer.clone());
+
+    let mut effects = StepEffects::start(world, first_step, steps, options, output.clone(), progress_bar.clone());
+    while effects.step(world, mutation_number_source) {
+        if let Some(progress_bar) = &progress_bar {
+            progress_bar.set_position((effects.current_step() - first_step) as u64);
+            progress_bar.set_message(format!("{} cells", world.num_cells()));
+        }
+    }
+
+    if let Some(progress_bar) = &progress_bar {
+        progress_bar.finish_and_clear();
+    }
+    world.remove_observer(&stats_printer);
+    stats_printer.borrow().finish(world);
+    let report = effects.finish(world, mutation_number_source);
+    print_above(&None, &output, |out| {
+        write_totals(out, world)?;
+        report.write(out)?;
+        out.flush()
+    });
+}
+
+/// Everything a run does as it steps besides stepping the world and showing its progress: the
+/// interventions, reports, checkpoints, population dumps, results database, and run summary its
+/// `RunOptions` ask for, and its stop condition. Every run loop steps through one, so that the
+/// options mean the same whether the run prints stats or plots them in a dashboard.
+pub(crate) struct StepEffects<'a> {
+    options: &'a RunOptions,
+    #[cfg(feature = "sqlite")]
+    first_step: u32,
+    steps: u32,
+    step: u32,
+    output: Output,
+    progress_bar: Option<ProgressBar>,
+    stop_condition: Option<StopCondition>,
+    offspring_counts: Rc<RefCell<OffspringCounts>>,
+    #[cfg(feature = "sqlite")]
+    results_db: Option<ResultsDb>,
+    gene_traces: GeneTraces,
+    lifetime_success: LifetimeSuccess,
+    selection_analysis: SelectionAnalysis,
+    selection_csv: Option<BufWriter<File>>,
+    report: RunReport,
+}
+
+impl<'a> StepEffects<'a> {
+    /// Starts the effects of a run from `first_step` to `steps`, printing its reports to `output`
+    /// above the progress bar, if there is one.
+    pub(crate) fn start(world: &mut World, first_step: u32, steps: u32, options: &'a RunOptions,
+                        output: Output, progress_bar: Option<ProgressBar>) -> Self {
+        let offspring_counts = Rc::new(RefCell::new(OffspringCounts::new()));
+        world.add_observer(offspring_counts.clone());
+        apply_interventions(world, first_step, options, &progress_bar, &output);
+        StepEffects {
+            options,
+            #[cfg(feature = "sqlite")]
+            first_step,
+            steps,
+            step: first_step,
+            output,
+            progress_bar,
+            stop_condition: options.stop_condition.clone(),
+            offspring_counts,
+            #[cfg(feature = "sqlite")]
+            results_db: options.db.as_ref().map(|db| open_results_db(&db.path, options.run_id, first_step)),
+            gene_traces: GeneTraces::new(RetentionPolicy {
+                recent: options.sparkline_width,
+                older: options.sparkline_history,
+                downsample_factor: options.sparkline_downsample,
+            }),
+            lifetime_success: LifetimeSuccess::new(),
+            selection_analysis: SelectionAnalysis::new(),
+            selection_csv: options.selection_csv.as_ref().map(|path| {
+                File::create(path)
+                    .map(BufWriter::new)
+                    .and_then(|mut writer| SelectionReport::write_csv_header(&mut writer).map(|()| writer))
+                    .unwrap_or_else(|err| panic!("Cannot write selection reports to {}: {}", path.display(), err))
+            }),
+            report: RunReport::start(options.run_id, first_step, world),
+        }
+    }
+
+    /// The step the world is at.
+    pub(crate) fn current_step(&self) -> u32 {
+        self.step
+    }
+
+    /// Steps the world and has the step's effects, unless the run has already ended. Returns
+    /// whether the run goes on, which it doesn't once it reaches its last step, every cell has
+    /// died, or its stop condition is met.
+    pub(crate) fn step(&mut self, world: &mut World, mutation_number_source: &mut RandomMutationNumberSource) -> bool {
+        if self.step >= self.steps || world.num_cells() == 0 {
+            return false;
+        }
+
+        let options = self.options;
+        #[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+        let (num_created, num_died) = if options.fitness_every > 0 {
+            world.step_observed(mutation_number_source, &mut self.lifetime_success)
+        } else {
+            world.step(mutation_number_source)
+        };
+        self.step += 1;
+        let step = self.step;
+        apply_interventions(world, step, options, &self.progress_bar, &self.output);
+        self.report.record(step, world);
+
+        #[cfg(feature = "sqlite")]
+        if let (Some(results_db), Some(db)) = (&mut self.results_db, &options.db) {
+            record_results(results_db, &db.path, step, num_created, num_died, db.every > 0 && step.is_multiple_of(db.every), world);
+        }
+
+        if options.sparkline_every > 0 {
+            self.gene_traces.record(world.cells().genomes().iter().map(Rc::as_ref));
+            if step.is_multiple_of(options.sparkline_every) {
+                print_above(&self.progress_bar, &self.output, |out| self.gene_traces.write(out));
+            }
+        }
+
+        if options.species_every > 0 && step.is_multiple_of(options.species_every) {
+            let species = species::cluster(world.cells().genomes().iter().map(Rc::as_ref), options.species_threshold);
+            print_above(&self.progress_bar, &self.output, |out| species::write(out, &species));
+        }
+
+        if options.fitness_every > 0 && step.is_multiple_of(options.fitness_every) {
+            let report = self.lifetime_success.report();
+            print_above(&self.progress_bar, &self.output, |out| report.write(out));
+            self.lifetime_success.reset();
+        }
+
+        if options.selection_every > 0 && step.is_multiple_of(options.selection_every) {
+            let report = self.selection_analysis.sample(step, world);
+            print_above(&self.progress_bar, &self.output, |out| report.write(out));
+            if let (Some(writer), Some(path)) = (&mut self.selection_csv, &options.selection_csv) {
+                report.write_csv(writer)
+                    .unwrap_or_else(|err| panic!("Cannot write selection reports to {}: {}", path.display(), err));
+            }
+        }
+
+        if options.checksum_every > 0 && step.is_multiple_of(options.checksum_every) {
+            let checksum = checksum::population_checksum(world.cells().genomes().iter().map(Rc::as_ref));
+            print_above(&self.progress_bar, &self.output, |out| writeln!(out, "  genome checksum: {:016x}", checksum));
+        }
+
+        if let Some(checkpoints) = &options.checkpoints {
+            if step.is_multiple_of(checkpoints.every) {
+                save_checkpoint(&checkpoints.path, step, options.run_id, world, mutation_number_source);
+            }
+        }
+
+        if let Some(population_dumps) = &options.population_dumps {
+            if step.is_multiple_of(population_dumps.every) {
+                save_population_dump(&population_dump::path_for_step(&population_dumps.path, step), step, options.run_id, world);
+            }
+        }
+
+        if self.stop_condition.as_mut().is_some_and(|condition| condition.should_stop(step, world)) {
+            print_above(&self.progress_bar, &self.output, |out| writeln!(out, "Stop condition met at step {}", step));
+            return false;
+        }
+        true
+    }
+
+    /// Has the end-of-run effects and returns the run's report.
+    pub(crate) fn finish(mut self, world: &mut World, mutation_number_source: &RandomMutationNumberSource) -> RunReport {
+        let options = self.options;
+        let step = self.step;
+        world.remove_observer(&self.offspring_counts);
+        if self.lifetime_success.num_deaths() > 0 {
+            print_above(&None, &self.output, |out| self.lifetime_success.report().write(out));
+        }
+        self.report.finish(step, world);
+        self.report.record_offspring(&self.offspring_counts.borrow());
+        if let (Some(mut writer), Some(path)) = (self.selection_csv, &options.selection_csv) {
+            writer.flush().unwrap_or_else(|err| panic!("Cannot write selection reports to {}: {}", path.display(), err));
+        }
+        if let Some(path) = &options.summary_json {
+            self.report.save(path).unwrap_or_else(|err| panic!("Cannot write run summary to {}: {}", path.display(), err));
+        }
+
+        if let Some(checkpoints) = &options.checkpoints {
+            save_checkpoint(&checkpoints.path, step, options.run_id, world, mutation_number_source);
+        }
+
+        if let Some(population_dumps) = &options.population_dumps {
+            save_population_dump(&population_dumps.path, step, options.run_id, world);
+        }
+
+        #[cfg(feature = "sqlite")]
+        if let (Some(mut results_db), Some(db)) = (self.results_db, &options.db) {
+            let snapshot_taken = step > self.first_step && db.every > 0 && step.is_multiple_of(db.every);
+            let recorded = if snapshot_taken { Ok(()) } else { results_db.record_cells(step, world) };
+            recorded.and_then(|()| results_db.finish())
+                .unwrap_or_else(|err| panic!("Cannot write results to {}: {}", db.path.display(), err));
+        }
+        self.report
+    }
+}
+
+/// Makes the changes scheduled for this step.
+fn apply_interventions(world: &mut World, step: u32, options: &RunOptions, progress_bar: &Option<ProgressBar>, output: &Output) {
+    for scheduled in options.interventions.iter().flatten().filter(|scheduled| scheduled.intervention.step == step) {
+        print_above(progress_bar, output, |out| writeln!(out, "  intervention: {}", scheduled.intervention));
+        match &scheduled.change {
+            WorldChange::Kill(fraction) => {
+                world.cull(*fraction);
+            }
+            WorldChange::AddCells(cells) => world.add_cells(cells.clone()),
+            WorldChange::SetCellConstants(constants) => world.set_cell_constants(constants),
+            WorldChange::Dump(path) => save_population_dump(path, step, options.run_id, world),
+        }
+    }
+}
+
+/// Runs without computing any per-step stats, printing only the birth, death, and food totals at the
+/// end, and the interventions as they are made. Checkpoints and population dumps are written only
+/// at the end.
+pub fn run_throughput(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+                      first_step: u32, steps: u32, options: &RunOptions) -> RunSummary {
+    let output: Output = Rc::new(RefCell::new(AsyncWriter::stdout()));
+    print_above(&None, &output, |out| write_build_and_run(out, options.run_id));
+    apply_interventions(world, first_step, options, &None, &output);
+    let summary = simulation::run_summarized_with(world, mutation_number_source, first_step, steps.saturating_sub(first_step),
+                                                  |step, world| apply_interventions(world, step, options, &None, &output));
+    print_above(&None, &output, |out| out.flush());
+    println!("{}: +{} -{} -> {} (food added: {}, eaten: {})",
+             summary.last_step(), summary.num_born, summary.num_died, summary.num_cells, summary.food_added, summary.food_eaten);
+    if summary.num_extinctions > 0 {
+        println!("# extinctions: {}", summary.num_extinctions);
+    }
+
+    if let Some(checkpoints) = &options.checkpoints {
+        save_checkpoint(&checkpoints.path, summary.last_step(), options.run_id, world, mutation_number_source);
+    }
+    if let Some(population_dumps) = &options.population_dumps {
+        save_population_dump(&population_dumps.path, summary.last_step(), options.run_id, world);
+    }
+    summary
+}
+
+fn progress_bar(first_step: u32, steps: u32) -> ProgressBar {
+    if steps == u32::MAX {
+        let progress_bar = ProgressBar::new_spinner();
+        progress_bar.set_style(ProgressStyle::with_template("{spinner} step {pos} {msg} [{elapsed}]").unwrap());
+        return progress_bar;
+    }
+
+    let progress_bar = ProgressBar::new(steps.saturating_sub(first_step) as u64);
+    progress_bar.set_style(ProgressStyle::with_template("{bar:40} step {pos}/{len} {msg} [{elapsed}, eta {eta}]").unwrap());
+    progress_bar
+}
+
+/// A run's standard output, which is written on a background thread so that printing stats
+/// every step doesn't slow stepping down to the speed of the terminal.
+type Output = Rc<RefCell<AsyncWriter>>;
+
+/// Prints to `output` without garbling the progress bar, if there is one, by waiting until the
+/// output has been written before the bar is redrawn.
+fn print_above<F: FnOnce(&mut AsyncWriter) -> io::Result<()>>(progress_bar: &Option<ProgressBar>, output: &Output, print: F) {
+    let mut output = output.borrow_mut();
+    let printed = match progress_bar {
+        Some(progress_bar) => progress_bar.suspend(|| print(&mut output).and_then(|()| output.flush())),
+        None => print(&mut output),
+    };
+    printed.unwrap_or_else(|err| panic!("Cannot write output: {}", err));
+}
+
+pub(crate) fn save_checkpoint(path: &Path, step: u32, run_id: Option<RunId>, world: &World, mutation_number_source: &RandomMutationNumberSource) {
+    checkpoint::save(path, step, run_id, world, mutation_number_source)
+        .unwrap_or_else(|err| panic!("Cannot save checkpoint {}: {}", path.display(), err));
+}
+
+#[cfg(feature = "sqlite")]
+fn open_results_db(path: &Path, run_id: Option<RunId>, first_step: u32) -> ResultsDb {
+    ResultsDb::open(path, run_id, first_step)
+        .unwrap_or_else(|err| panic!("Cannot open results database {}: {}", path.display(), err))
+}
+
+#[cfg(feature = "sqlite")]
+fn record_results(results_db: &mut ResultsDb, path: &Path, step: u32, num_born: usize, num_died: usize,
+                  record_cells: bool, world: &World) {
+    results_db.record_step(step, num_born, num_died, world)
+        .and_then(|()| if record_cells { results_db.record_cells(step, world) } else { Ok(()) })
+        .unwrap_or_else(|err| panic!("Cannot write results to {}: {}", path.display(), err));
+}
+
+pub(crate) fn save_population_dump(path: &Path, step: u32, run_id: Option<RunId>, world: &World) {
+    population_dump::save(path, step, run_id, world)
+        .unwrap_or_else(|err| panic!("Cannot dump population to {}: {}", path.display(), err));
+}
+
+fn write_stats_header<W: Write>(writer: &mut W, run_id: Option<RunId>) -> io::Result<()> {
+    write_build_and_run(writer, run_id)?;
+    writeln!(writer, "<step>: +<born> -<died> -> <cells> (h: <mean_cell_health>, e: <mean_cell_energy>, a: <mean_cell_age>/<max_cell_age>, f: <total_food>)[ d: <cause> <died_of_cause>/<total_died_of_cause>, ...]")
+}
+
+fn write_build_and_run<W: Write>(writer: &mut W, run_id: Option<RunId>) -> io::Result<()> {
+    writeln!(writer, "# {}", BuildInfo::current())?;
+    if let Some(run_id) = run_id {
+        writeln!(writer, "# run {}", run_id)?;
+    }
+    Ok(())
+}
+
+fn write_totals<W: Write>(writer: &mut W, world: &World) -> io::Result<()> {
+    let totals = world.totals();
+    writeln!(writer, "# totals: +{} -{} (food added: {}, eaten: {}, decayed: {})",
+             totals.num_born, totals.num_died, totals.food_added, totals.food_eaten, totals.food_decayed)?;
+    if totals.food_from_corpses > 0.0 {
+        writeln!(writer, "# food from corpses: {}", totals.food_from_corpses)?;
+    }
+    if totals.num_immigrated > 0 {
+        writeln!(writer, "# immigrants: {}", totals.num_immigrated)?;
+    }
+    if totals.num_extinctions > 0 {
+        writeln!(writer, "# extinctions: {}", totals.num_extinctions)?;
+    }
+    if totals.food_beyond_intake > 0.0 {
+        writeln!(writer, "# food beyond cells' max intake: {}", totals.food_beyond_intake)?;
+    }
+    if totals.protein_added > 0.0 {
+        writeln!(writer, "# protein added: {}, eaten: {}, decayed: {}", totals.protein_added, totals.protein_eaten, totals.protein_decayed)?;
+    }
+    if totals.energy_donated > 0.0 {
+        writeln!(writer, "# energy donated to kin: {}", totals.energy_donated)?;
+    }
+    if world.toxin().value() > 0.0 {
+        writeln!(writer, "# toxin: {}", world.toxin())?;
+    }
+    if totals.ages_at_death.count > 0 {
+        let ages = &totals.ages_at_death;
+        writeln!(writer, "# age at death: mean {:.1}, stdev {:.1}, max {}", ages.mean(), ages.stdev(), ages.max)?;
+    }
+    if totals.deaths.total() > 0 {
+        writeln!(writer, "# deaths by cause: {}", totals.deaths.by_cause().iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(cause, count)| format!("{} {}", cause, count))
+            .collect::<Vec<_>>().join(", "))?;
+    }
+    Ok(())
+}
+
+/// Prints a line of population stats every `print_every` steps, and at the end of the run if the
+/// last step's line was not printed. Lines go above the progress bar, if there is one. Each line
+/// ends with the step's deaths by cause, next to the run's, for every cause that has killed any.
+pub struct StatsPrinter {
+    step: u32,
+    print_every: u32,
+    last_printed_step: u32,
+    last_counts: (usize, usize),
+    deaths_before_step: DeathCounts,
+    step_deaths: DeathCounts,
+    progress_bar: Option<ProgressBar>,
+    output: Output,
+}
+
+impl StatsPrinter {
+    fn new(first_step: u32, print_every: u32, progress_bar: Option<ProgressBar>, output: Output) -> Self {
+        StatsPrinter {
+            step: first_step,
+            print_every,
+            last_printed_step: first_step,
+            last_counts: (0, 0),
+            deaths_before_step: DeathCounts::default(),
+            step_deaths: DeathCounts::default(),
+            progress_bar,
+            output,
+        }
+    }
+
+    pub fn print(&self, world: &World, num_created: usize, num_died: usize) {
+        print_above(&self.progress_bar, &self.output, |out| write_stats(out, world, self.step, num_created, num_died, &self.step_deaths));
+    }
+
+    pub fn finish(&self, world: &World) {
+        if self.last_printed_step != self.step {
+            self.print(world, self.last_counts.0, self.last_counts.1);
+        }
+    }
+}
+
+impl WorldObserver for StatsPrinter {
+    fn before_step(&mut self, world: &World) {
+        self.deaths_before_step = world.totals().deaths;
+    }
+
+    fn after_step(&mut self, world: &World, num_born: usize, num_died: usize) {
+        self.step += 1;
+        self.last_counts = (num_born, num_died);
+        self.step_deaths = world.totals().deaths - self.deaths_before_step;
+        if self.print_every > 0 && self.step.is_multiple_of(self.print_every) {
+            self.print(world, num_born, num_died);
+            self.last_printed_step = self.step;
+        }
+    }
+}
+
+fn write_stats<W: Write>(writer: &mut W, world: &World, step: u32, num_created: usize, num_died: usize, step_deaths: &DeathCounts) -> io::Result<()> {
+    writeln!(writer, "{}: +{} -{} -> {} (h: {}, e: {}, a: {}/{}, f: {}){}",
+             step,
+             num_created,
+             num_died,
+             world.num_cells(),
+             world.mean_health(),
+             world.mean_energy(),
+             world.mean_age(),
+             world.max_age(),
+             world.food(),
+             format_deaths(step_deaths, &world.totals().deaths)
+    )
+}
+
+fn format_deaths(step_deaths: &DeathCounts, total_deaths: &DeathCounts) -> String {
+    let causes: Vec<String> = step_deaths.by_cause().iter().zip(total_deaths.by_cause())
+        .filter(|(_, (_, total))| *total > 0)
+        .map(|((cause, count), (_, total))| format!("{} {}/{}", cause, count, total))
+        .collect();
+    if causes.is_empty() { String::new() } else { format!(" d: {}", causes.join(", ")) }
+}
+
+/// The command line: a subcommand, or with none, the options of `run`.
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    #[clap(flatten)]
+    pub args: Args,
+}
+
+/// The options of a run of a new world.
+#[derive(clap::Args, Clone)]
+pub struct Args {
+    /// Named set of options to start from: eat_then_starve, eat_and_reproduce_then_starve, malthus, or boom_bust; options given alongside it override its settings
+    #[clap(long)]
+    pub scenario: Option<String>,
+
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    #[clap(flatten)]
+    pub run: RunArgs,
+}
+
+/// Options that configure a new world.
+#[derive(clap::Args, Clone)]
+pub struct WorldArgs {
+    /// Random number seed, for reproducible runs
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Rescale every energy in the configuration by this factor, which should not change the dynamics
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.energy_scale)]
+    pub energy_scale: f32,
+
+    /// Rescale every amount of food in the configuration by this factor
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_scale)]
+    pub food_scale: f32,
+
+    /// Rescale every amount of light in the configuration by this factor
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.light_scale)]
+    pub light_scale: f32,
+
+    /// Initial world food
+    #[clap(short('f'), long, default_value_t = WorldArgs::DEFAULT.initial_food)]
+    pub initial_food: f32,
+
+    /// World food added per step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.added_food)]
+    pub added_food: f32,
+
+    /// Pattern of added food over the run: stable, boom-bust, gradual-decline, chaotic, stochastic-pulsed[:MEAN_PERIOD], or regrowing (defaults to --added-food by day and --night-added-food by night)
+    #[clap(long)]
+    pub environment: Option<Environment>,
+
+    /// Environmental stress multiplying cells' entropy damage over the run: constant:STRESS, ramp:START:END, sine:MEAN:AMPLITUDE:PERIOD, or random-walk:START:STDEV (defaults to a constant 1)
+    #[clap(long)]
+    pub stress: Option<StressPattern>,
+
+    /// Initial world protein, a second nutrient that cells eat according to their protein preference
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.initial_protein)]
+    pub initial_protein: f32,
+
+    /// World protein added per step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.added_protein)]
+    pub added_protein: f32,
+
+    /// Cells immigrating into the world per step on average, with the initial energy mean and full health; fractions of a cell carry over to later steps
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.immigrants_per_step)]
+    pub immigrants_per_step: f32,
+
+    /// Steps between pulses of immigrants, each bringing this many steps' worth at once, or 0 for a steady trickle
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.immigration_period)]
+    pub immigration_period: u32,
+
+    /// Checkpoint or population dump whose genomes immigrants copy, instead of sampling the gene means and stdevs
+    #[clap(long, parse(from_os_str))]
+    pub immigrants_from: Option<PathBuf>,
+
+    /// When every cell dies, found a new population of --cells cells, with the initial energy mean and full health, and carry on; the summary counts the extinctions
+    #[clap(long)]
+    pub reseed_on_extinction: bool,
+
+    /// Checkpoint or population dump, such as an earlier checkpoint of the run, whose genomes reseeded founders copy, instead of sampling the gene means and stdevs
+    #[clap(long, parse(from_os_str))]
+    pub reseed_from: Option<PathBuf>,
+
+    /// Fraction of uneaten world food and protein that spoils each step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_decay_rate)]
+    pub food_decay_rate: f32,
+
+    /// Food a dead cell leaves behind per unit of the energy it died with
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.corpse_food_per_energy)]
+    pub corpse_food_per_energy: f32,
+
+    /// Food a dead cell leaves behind per unit of its size, however little energy it died with
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.corpse_food_per_size)]
+    pub corpse_food_per_size: f32,
+
+    /// Population at which added food falls to zero, shrinking linearly from the full amount at zero cells (0 for no limit)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.carrying_capacity)]
+    pub carrying_capacity: usize,
+
+    /// How cells share the food: equal, proportional to the food their attempted eating energy would get them, first-come in a random order each step, or scramble:EXPONENT, proportional to that food raised to the exponent
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_allocation)]
+    pub food_allocation: FoodAllocationPolicy,
+
+    /// Most cells the world holds before crowding sets in (0 for no limit)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.max_cells)]
+    pub max_cells: usize,
+
+    /// What crowding does to a world over --max-cells: cull, which removes the weakest cells over the limit, or damage, which takes health from every cell
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.crowding)]
+    pub crowding: Crowding,
+
+    /// How cells reproduce: asexual or sexual
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.reproduction_mode)]
+    pub reproduction_mode: ReproductionMode,
+
+    /// Steps per non-overlapping generation (0 for overlapping generations)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.generation_steps)]
+    pub generation_steps: u32,
+
+    /// Replace each non-overlapping generation like a genetic algorithm: with --cells children of parents drawn in proportion to their energy, each starting with --initial-energy-mean
+    #[clap(long)]
+    pub select_by_energy: bool,
+
+    /// World light per step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.light)]
+    pub light: f32,
+
+    /// Steps per day/night cycle (0 for no cycle)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.day_length)]
+    pub day_length: u32,
+
+    /// World food added per step at night (defaults to the daytime amount)
+    #[clap(long)]
+    pub night_added_food: Option<f32>,
+
+    /// World light per step at night (defaults to the daytime amount)
+    #[clap(long)]
+    pub night_light: Option<f32>,
+
+    /// Initial number of cells
+    #[clap(short('n'), long, default_value_t = WorldArgs::DEFAULT.cells)]
+    pub cells: usize,
+
+    /// Mean of cell eating energies
+    #[clap(short('E'), long, default_value_t = WorldArgs::DEFAULT.attempted_eating_energy_mean)]
+    pub attempted_eating_energy_mean: f32,
+
+    /// Standard deviation of cell eating energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_eating_energy_stdev)]
+    pub attempted_eating_energy_stdev: f32,
+
+    /// Mean of cell healing energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_healing_energy_mean)]
+    pub attempted_healing_energy_mean: f32,
+
+    /// Standard deviation of cell healing energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_healing_energy_stdev)]
+    pub attempted_healing_energy_stdev: f32,
+
+    /// Mean of cell attack energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_attack_energy_mean)]
+    pub attempted_attack_energy_mean: f32,
+
+    /// Standard deviation of cell attack energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_attack_energy_stdev)]
+    pub attempted_attack_energy_stdev: f32,
+
+    /// Mean of cell photosynthesis energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_photosynthesis_energy_mean)]
+    pub attempted_photosynthesis_energy_mean: f32,
+
+    /// Standard deviation of cell photosynthesis energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_photosynthesis_energy_stdev)]
+    pub attempted_photosynthesis_energy_stdev: f32,
+
+    /// Mean of the times of day at which cells become active
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.active_phase_mean)]
+    pub active_phase_mean: f32,
+
+    /// Standard deviation of the times of day at which cells become active
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.active_phase_stdev)]
+    pub active_phase_stdev: f32,
+
+    /// Mean of cell trade energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_trade_energy_mean)]
+    pub attempted_trade_energy_mean: f32,
+
+    /// Standard deviation of cell trade energies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_trade_energy_stdev)]
+    pub attempted_trade_energy_stdev: f32,
+
+    /// Mean of the trade energies cells must receive to keep trading
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.trade_reciprocity_threshold_mean)]
+    pub trade_reciprocity_threshold_mean: f32,
+
+    /// Standard deviation of the trade energies cells must receive to keep trading
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.trade_reciprocity_threshold_stdev)]
+    pub trade_reciprocity_threshold_stdev: f32,
+
+    /// Mean of the weights cells give the latest food per cell in their memories of it
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_memory_rate_mean)]
+    pub food_memory_rate_mean: f32,
+
+    /// Standard deviation of the weights cells give the latest food per cell in their memories of it
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_memory_rate_stdev)]
+    pub food_memory_rate_stdev: f32,
+
+    /// Mean of the remembered foods per cell below which cells consider food scarce
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.scarce_food_threshold_mean)]
+    pub scarce_food_threshold_mean: f32,
+
+    /// Standard deviation of the remembered foods per cell below which cells consider food scarce
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.scarce_food_threshold_stdev)]
+    pub scarce_food_threshold_stdev: f32,
+
+    /// Mean of the fractions of their usual energies cells spend while food is scarce
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.scarcity_spending_fraction_mean)]
+    pub scarcity_spending_fraction_mean: f32,
+
+    /// Standard deviation of the fractions of their usual energies cells spend while food is scarce
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.scarcity_spending_fraction_stdev)]
+    pub scarcity_spending_fraction_stdev: f32,
+
+    /// Mean of the priorities cells give reproduction energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.reproduction_priority_mean)]
+    pub reproduction_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give reproduction energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.reproduction_priority_stdev)]
+    pub reproduction_priority_stdev: f32,
+
+    /// Mean of the priorities cells give eating energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_priority_mean)]
+    pub eating_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give eating energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_priority_stdev)]
+    pub eating_priority_stdev: f32,
+
+    /// Mean of the priorities cells give healing energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.healing_priority_mean)]
+    pub healing_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give healing energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.healing_priority_stdev)]
+    pub healing_priority_stdev: f32,
+
+    /// Mean of the priorities cells give attack energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attack_priority_mean)]
+    pub attack_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give attack energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attack_priority_stdev)]
+    pub attack_priority_stdev: f32,
+
+    /// Mean of the priorities cells give photosynthesis energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.photosynthesis_priority_mean)]
+    pub photosynthesis_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give photosynthesis energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.photosynthesis_priority_stdev)]
+    pub photosynthesis_priority_stdev: f32,
+
+    /// Mean of the priorities cells give trade energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.trade_priority_mean)]
+    pub trade_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give trade energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.trade_priority_stdev)]
+    pub trade_priority_stdev: f32,
+
+    /// Mean of the most energy cells can store; energy gained beyond it is lost (default: unlimited)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.max_energy_storage_mean)]
+    pub max_energy_storage_mean: f32,
+
+    /// Standard deviation of the most energy cells can store
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.max_energy_storage_stdev)]
+    pub max_energy_storage_stdev: f32,
+
+    /// Mean of the energies cells would like to spend neutralizing toxin each step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_detox_energy_mean)]
+    pub attempted_detox_energy_mean: f32,
+
+    /// Standard deviation of the energies cells would like to spend neutralizing toxin each step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attempted_detox_energy_stdev)]
+    pub attempted_detox_energy_stdev: f32,
+
+    /// Mean of the priorities cells give detox energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.detox_priority_mean)]
+    pub detox_priority_mean: f32,
+
+    /// Standard deviation of the priorities cells give detox energy when they cannot afford everything
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.detox_priority_stdev)]
+    pub detox_priority_stdev: f32,
+
+    /// Mean of cell sizes, which scale the food cells get for their eating energy, their maximum food intake, their maintenance energy, and the energy it takes to create each of their children
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.size_mean)]
+    pub size_mean: f32,
+
+    /// Standard deviation of cell sizes
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.size_stdev)]
+    pub size_stdev: f32,
+
+    /// Mean of the energies above which cells donate to their kin (default: never donate)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.donation_threshold_mean)]
+    pub donation_threshold_mean: f32,
+
+    /// Standard deviation of the energies above which cells donate to their kin
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.donation_threshold_stdev)]
+    pub donation_threshold_stdev: f32,
+
+    /// Mean of the fractions of their energy above the donation threshold that cells give each step to their neediest kin
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.donation_fraction_mean)]
+    pub donation_fraction_mean: f32,
+
+    /// Standard deviation of the fractions of their energy above the donation threshold that cells give to their kin
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.donation_fraction_stdev)]
+    pub donation_fraction_stdev: f32,
+
+    /// Mean of the energies below which cells go dormant while food is scarce (default: never go dormant)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.dormancy_threshold_energy_mean)]
+    pub dormancy_threshold_energy_mean: f32,
+
+    /// Standard deviation of the energies below which cells go dormant
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.dormancy_threshold_energy_stdev)]
+    pub dormancy_threshold_energy_stdev: f32,
+
+    /// Mean of the food per cell below which cells may go dormant, and at which dormant cells wake
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.wake_threshold_food_mean)]
+    pub wake_threshold_food_mean: f32,
+
+    /// Standard deviation of the food per cell at which dormant cells wake
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.wake_threshold_food_stdev)]
+    pub wake_threshold_food_stdev: f32,
+
+    /// Mean of the fractions of their eating energy that cells spend on protein rather than food
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.protein_preference_mean)]
+    pub protein_preference_mean: f32,
 
-    let mut world = create_world(args, &cell_constants);
+    /// Standard deviation of the fractions of their eating energy that cells spend on protein
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.protein_preference_stdev)]
+    pub protein_preference_stdev: f32,
 
-    run(&mut world, args.steps);
-}
+    /// Mean of cell eating efficiencies, which multiply the food cells get for their eating energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_efficiency_mean)]
+    pub eating_efficiency_mean: f32,
 
-fn create_world(args: &Args, cell_constants: &Rc<CellConstants>) -> World {
-    World::new()
-        .with_cells(world::generate_cells(
-            args.cells,
-            Normal::new(args.initial_energy_mean, args.initial_energy_stdev).unwrap(),
-            Normal::new(args.attempted_eating_energy_mean, args.attempted_eating_energy_stdev).unwrap(),
-            Normal::new(args.attempted_healing_energy_mean, args.attempted_healing_energy_stdev).unwrap(),
-            Normal::new(args.child_threshold_energy_mean, args.child_threshold_energy_stdev).unwrap(),
-            Normal::new(args.child_threshold_food_mean, args.child_threshold_food_stdev).unwrap(),
-            cell_constants,
-        ))
-        .with_food(args.initial_food.into())
-        .with_food_sources(vec![
-            Box::new(ConstantFoodSource::new(args.added_food.into()))
-        ])
-}
+    /// Standard deviation of cell eating efficiencies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_efficiency_stdev)]
+    pub eating_efficiency_stdev: f32,
 
-pub fn run(world: &mut World, steps: u32) {
-    print_stats_header();
+    /// Mean of cell digestion efficiencies, which multiply the energy cells get from digesting food
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.digestion_efficiency_mean)]
+    pub digestion_efficiency_mean: f32,
 
-    let mut step = 0;
-    print_stats(world, step, 0, 0);
+    /// Standard deviation of cell digestion efficiencies
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.digestion_efficiency_stdev)]
+    pub digestion_efficiency_stdev: f32,
 
-    let mut mutation_number_source = RandomMutationNumberSource::new();
-    while step < steps && world.num_cells() > 0 {
-        let (num_created, num_died) = world.step(&mut mutation_number_source);
-        step += 1;
-        print_stats(world, step, num_created, num_died);
-    }
-}
+    /// Mean of the fractions of the way cells' internal states move toward their energy and food inputs each step
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_update_rate_mean)]
+    pub state_update_rate_mean: f32,
 
-fn print_stats_header() {
-    println!("<step>: +<born> -<died> -> <cells> (h: <mean_cell_health>, e: <mean_cell_energy>, f: <total_food>)");
-}
+    /// Standard deviation of the rates at which cells' internal states follow their inputs
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_update_rate_stdev)]
+    pub state_update_rate_stdev: f32,
 
-fn print_stats(world: &World, step: u32, num_created: usize, num_died: usize) {
-    println!("{}: +{} -{} -> {} (h: {}, e: {}, f: {})",
-             step,
-             num_created,
-             num_died,
-             world.num_cells(),
-             world.mean_health(),
-             world.mean_energy(),
-             world.food()
-    );
-}
+    /// Mean of the energies at which cells' energy inputs to their internal states are half saturated
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_energy_scale_mean)]
+    pub state_energy_scale_mean: f32,
 
-#[derive(Parser)]
-#[clap(author, version, about, long_about = None)]
-pub struct Args {
-    /// Number of steps
-    #[clap(short('s'), long, default_value_t = Args::DEFAULT.steps)]
-    pub steps: u32,
+    /// Standard deviation of the energies at which energy inputs to internal states are half saturated
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_energy_scale_stdev)]
+    pub state_energy_scale_stdev: f32,
 
-    /// Initial world food
-    #[clap(short('f'), long, default_value_t = Args::DEFAULT.initial_food)]
-    pub initial_food: f32,
+    /// Mean of the food per cell at which cells' food inputs to their internal states are half saturated
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_food_scale_mean)]
+    pub state_food_scale_mean: f32,
 
-    /// World food added per step
-    #[clap(long, default_value_t = Args::DEFAULT.added_food)]
-    pub added_food: f32,
+    /// Standard deviation of the food per cell at which food inputs to internal states are half saturated
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.state_food_scale_stdev)]
+    pub state_food_scale_stdev: f32,
 
-    /// Initial number of cells
-    #[clap(short('n'), long, default_value_t = Args::DEFAULT.cells)]
-    pub cells: usize,
+    /// Mean of the factors multiplying cells' attempted eating energies at an internal state of 1
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_state_response_mean)]
+    pub eating_state_response_mean: f32,
 
-    /// Mean of cell eating energies
-    #[clap(short('E'), long, default_value_t = Args::DEFAULT.attempted_eating_energy_mean)]
-    pub attempted_eating_energy_mean: f32,
+    /// Standard deviation of the factors multiplying eating energies at an internal state of 1
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.eating_state_response_stdev)]
+    pub eating_state_response_stdev: f32,
 
-    /// Standard deviation of cell eating energies
-    #[clap(long, default_value_t = Args::DEFAULT.attempted_eating_energy_stdev)]
-    pub attempted_eating_energy_stdev: f32,
+    /// Mean of the factors multiplying cells' attempted healing energies at an internal state of 1
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.healing_state_response_mean)]
+    pub healing_state_response_mean: f32,
 
-    /// Mean of cell healing energies
-    #[clap(long, default_value_t = Args::DEFAULT.attempted_healing_energy_mean)]
-    pub attempted_healing_energy_mean: f32,
+    /// Standard deviation of the factors multiplying healing energies at an internal state of 1
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.healing_state_response_stdev)]
+    pub healing_state_response_stdev: f32,
 
-    /// Standard deviation of cell healing energies
-    #[clap(long, default_value_t = Args::DEFAULT.attempted_healing_energy_stdev)]
-    pub attempted_healing_energy_stdev: f32,
+    /// Mean of the tolerances dividing the environmental stress on cells' entropy damage
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.stress_tolerance_mean)]
+    pub stress_tolerance_mean: f32,
+
+    /// Standard deviation of cells' stress tolerances
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.stress_tolerance_stdev)]
+    pub stress_tolerance_stdev: f32,
+
+    /// Standard deviation of the weights of cells' brains, as genes from 0 to 1 around the neutral 0.5
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.brain_weight_stdev)]
+    pub brain_weight_stdev: f32,
 
     /// Mean of child threshold energies
-    #[clap(short('C'), long, default_value_t = Args::DEFAULT.child_threshold_energy_mean)]
+    #[clap(short('C'), long, default_value_t = WorldArgs::DEFAULT.child_threshold_energy_mean)]
     pub child_threshold_energy_mean: f32,
 
     /// Standard deviation of child threshold energies
-    #[clap(long, default_value_t = Args::DEFAULT.child_threshold_energy_stdev)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.child_threshold_energy_stdev)]
     pub child_threshold_energy_stdev: f32,
 
     /// Mean of child threshold foods
-    #[clap(long, default_value_t = Args::DEFAULT.child_threshold_food_mean)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.child_threshold_food_mean)]
     pub child_threshold_food_mean: f32,
 
     /// Standard deviation of child threshold foods
-    #[clap(long, default_value_t = Args::DEFAULT.child_threshold_food_stdev)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.child_threshold_food_stdev)]
     pub child_threshold_food_stdev: f32,
 
     /// Energy cost of creating a child
-    #[clap(long, default_value_t = Args::DEFAULT.create_child_energy)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.create_child_energy)]
     pub create_child_energy: f32,
 
     /// Energy gained per unit food
-    #[clap(short('D'), long, default_value_t = Args::DEFAULT.energy_yield_from_digestion)]
+    #[clap(short('D'), long, default_value_t = WorldArgs::DEFAULT.energy_yield_from_digestion)]
     pub energy_yield_from_digestion: f32,
 
     /// Food gained per unit eating energy
-    #[clap(short('F'), long, default_value_t = Args::DEFAULT.food_yield_from_eating)]
+    #[clap(short('F'), long, default_value_t = WorldArgs::DEFAULT.food_yield_from_eating)]
     pub food_yield_from_eating: f32,
 
+    /// Energy gained per unit protein
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.energy_yield_from_protein_digestion)]
+    pub energy_yield_from_protein_digestion: f32,
+
+    /// Protein gained per unit eating energy spent on protein
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.protein_yield_from_eating)]
+    pub protein_yield_from_eating: f32,
+
     /// Health increase per energy expended
-    #[clap(long, default_value_t = Args::DEFAULT.health_increase_per_healing_energy)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_increase_per_healing_energy)]
     pub health_increase_per_healing_energy: f32,
 
     /// Health reduction due to entropy
-    #[clap(long, default_value_t = Args::DEFAULT.health_reduction_from_entropy)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_from_entropy)]
     pub health_reduction_from_entropy: f32,
 
+    /// Exponent of the age that multiplies the health reduction due to entropy, so that above 0 cells lose health faster as they age
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.entropy_age_exponent)]
+    pub entropy_age_exponent: f32,
+
+    /// Health reduction per step for cells with no energy left
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_from_zero_energy)]
+    pub health_reduction_from_zero_energy: f32,
+
     /// Health reduction per energy expended
-    #[clap(long, default_value_t = Args::DEFAULT.health_reduction_per_energy_expended)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_per_energy_expended)]
     pub health_reduction_per_energy_expended: f32,
 
+    /// Prey energy taken per attack energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attack_efficiency)]
+    pub attack_efficiency: f32,
+
+    /// Attack energy absorbed by the defenses of prey
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.attack_defense)]
+    pub attack_defense: f32,
+
+    /// Prey health reduction per attack energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_per_attack_energy)]
+    pub health_reduction_per_attack_energy: f32,
+
+    /// Light absorbed per unit photosynthesis energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.light_yield_from_photosynthesis)]
+    pub light_yield_from_photosynthesis: f32,
+
+    /// Energy gained per unit light
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.energy_yield_from_light)]
+    pub energy_yield_from_light: f32,
+
+    /// Age at which cells die
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.max_age)]
+    pub max_age: u32,
+
+    /// Probability that a sexually produced child takes each gene from its parent's mate
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.crossover_probability)]
+    pub crossover_probability: f32,
+
+    /// How genes mutate: gaussian, uniform, log-normal, macro:PROBABILITY:SCALE for Gaussian mutations that are SCALE times larger with PROBABILITY, or a weighted mix such as 0.9*gaussian+0.1*log-normal
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.mutation_model)]
+    pub mutation_model: MutationModel,
+
+    /// Fraction of digestion and healing efficiency lost per step of age
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.efficiency_decay_per_age)]
+    pub efficiency_decay_per_age: f32,
+
+    /// Fraction of each day during which a cell is active and eats
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.activity_fraction)]
+    pub activity_fraction: f32,
+
+    /// Energy received by a trading partner per unit trade energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.trade_efficiency)]
+    pub trade_efficiency: f32,
+
+    /// Food from its next share that a trading cell lets its partner have per unit trade energy
+    /// the partner gave it
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.food_access_per_trade_energy)]
+    pub food_access_per_trade_energy: f32,
+
+    /// Energy every cell spends each step just to stay alive
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.maintenance_energy)]
+    pub maintenance_energy: f32,
+
+    /// Health reduction per unit of maintenance energy a cell cannot pay
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_per_maintenance_shortfall)]
+    pub health_reduction_per_maintenance_shortfall: f32,
+
+    /// Maintenance energy each cell spends per step per unit of its max energy storage
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.maintenance_energy_per_storage)]
+    pub maintenance_energy_per_storage: f32,
+
+    /// Maintenance energy each cell spends per step per unit of the energies it attempts to spend on eating, healing, attack, photosynthesis, trade, and detox, penalizing unused traits
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.maintenance_energy_per_attempted_energy)]
+    pub maintenance_energy_per_attempted_energy: f32,
+
+    /// Energy each cell spends each step per unit of its eating and digestion efficiencies, so that evolving more efficient metabolisms has a cost
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.maintenance_energy_per_efficiency)]
+    pub maintenance_energy_per_efficiency: f32,
+
+    /// Energy each cell spends each step for each unit of its stress tolerance
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.maintenance_energy_per_stress_tolerance)]
+    pub maintenance_energy_per_stress_tolerance: f32,
+
+    /// Health reduction per unit of energy gained beyond a cell's max energy storage
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_per_excess_energy)]
+    pub health_reduction_per_excess_energy: f32,
+
+    /// Order in which cells eat, photosynthesize, suffer entropy, and heal each step, e.g. "heal,entropy,eat,photosynthesize" to heal before anything else
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.process_order)]
+    pub process_order: ProcessOrder,
+
+    /// What sets how hard cells try at eating, healing, and reproducing: fixed for their genes alone, or net for their genes scaled by evolved brains that sense their energy, health, food, and age
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.cell_controller)]
+    pub cell_controller: CellController,
+
+    /// Probability that a cell dies at random in any step, whatever its health
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.death_probability)]
+    pub death_probability: f32,
+
+    /// Increase in a cell's probability of dying at random per unit of health it has lost
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.death_probability_per_health_lost)]
+    pub death_probability_per_health_lost: f32,
+
+    /// Toxin, measured in energy units, left in the world per unit of energy cells expend
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.toxin_per_energy_expended)]
+    pub toxin_per_energy_expended: f32,
+
+    /// Toxin per cell that cells can bear without harm
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.toxin_tolerance)]
+    pub toxin_tolerance: f32,
+
+    /// Health reduction per unit of toxin per cell beyond the toxin tolerance
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.health_reduction_per_toxin)]
+    pub health_reduction_per_toxin: f32,
+
+    /// Toxin neutralized per unit detox energy
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.toxin_yield_from_detox)]
+    pub toxin_yield_from_detox: f32,
+
+    /// Most food a cell can eat in one step, whatever its eating energy (default: unlimited)
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.max_food_intake)]
+    pub max_food_intake: f32,
+
+    /// Fraction of donated energy that reaches the recipient
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.donation_efficiency)]
+    pub donation_efficiency: f32,
+
+    /// Fraction of their maintenance energy that dormant cells pay
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.dormant_maintenance_fraction)]
+    pub dormant_maintenance_fraction: f32,
+
     /// Mean of cell initial energies
-    #[clap(short('e'), long, default_value_t = Args::DEFAULT.initial_energy_mean)]
+    #[clap(short('e'), long, default_value_t = WorldArgs::DEFAULT.initial_energy_mean)]
     pub initial_energy_mean: f32,
 
     /// Standard deviation of cell initial energies
-    #[clap(long, default_value_t = Args::DEFAULT.initial_energy_stdev)]
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.initial_energy_stdev)]
     pub initial_energy_stdev: f32,
+
+    /// Mean of cell initial healths, clipped to between 0 and 1
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.initial_health_mean)]
+    pub initial_health_mean: f32,
+
+    /// Standard deviation of cell initial healths
+    #[clap(long, default_value_t = WorldArgs::DEFAULT.initial_health_stdev)]
+    pub initial_health_stdev: f32,
 }
 
-impl Args {
+/// Options that configure a run and its output, whether of a new world or a resumed one.
+#[derive(clap::Args, Clone)]
+pub struct RunArgs {
+    /// Number of steps
+    #[clap(short('s'), long, default_value_t = RunArgs::DEFAULT.steps)]
+    pub steps: u32,
+
+    /// Condition for ending the run early, e.g. "cells > 10000 or (stable(attempted_eating_energy, 500, 0.01) and step > 5000)"
+    #[clap(long)]
+    pub stop_when: Option<StopCondition>,
+
+    /// Script of changes to make to the world at given steps, one per line, e.g. "500 kill 0.5", "1000 add 100 attempted_eating_energy=3", "1500 set maintenance_energy 0.2", or "2000 dump population.json"
+    #[clap(long, parse(from_os_str))]
+    pub interventions: Option<PathBuf>,
+
+    /// ID recorded in every output of the run, and substituted for {run} in output file names (default: random)
+    #[clap(long)]
+    pub run_id: Option<RunId>,
+
+    /// File to save checkpoints to ({run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub checkpoint_file: Option<PathBuf>,
+
+    /// Steps between checkpoints (0 to checkpoint only at the end of the run)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.checkpoint_every)]
+    pub checkpoint_every: u32,
+
+    /// File to write every cell's genome and state to at the end of the run (JSON if it ends in .json, else CSV; {run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub dump_population: Option<PathBuf>,
+
+    /// Steps between extra population dumps, written with the step in the file name (0 for only at the end)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.dump_population_every)]
+    pub dump_population_every: u32,
+
+    /// File to write the end-of-run summary to as JSON ({run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub summary_json: Option<PathBuf>,
+
+    /// Most detailed events to log: off, error, warn, info for step summaries, or debug for births and deaths with their causes
+    #[clap(long, default_value_t = RunArgs::DEFAULT.log_level)]
+    pub log_level: LevelFilter,
+
+    /// File to write logged events to instead of standard error ({run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub log_file: Option<PathBuf>,
+
+    /// Check each step that food, protein, and toxin balance and that no energy comes from nowhere: off, log to log imbalances as warnings, or panic to stop at the first
+    #[clap(long, default_value_t = RunArgs::DEFAULT.audit)]
+    pub audit: Audit,
+
+    /// CSV file to write a histogram of --heatmap-gene's values to every --heatmap-every steps, as a matrix of steps by bins for trait heatmaps ({run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub heatmap: Option<PathBuf>,
+
+    /// Gene to bin for --heatmap (defaults to child_threshold_energy)
+    #[clap(long)]
+    pub heatmap_gene: Option<String>,
+
+    /// Number of --heatmap bins
+    #[clap(long, default_value_t = RunArgs::DEFAULT.heatmap_bins)]
+    pub heatmap_bins: usize,
+
+    /// Lower edge of the --heatmap bins, in the units population dumps record
+    #[clap(long, default_value_t = RunArgs::DEFAULT.heatmap_min)]
+    pub heatmap_min: f32,
+
+    /// Upper edge of the --heatmap bins, in the units population dumps record (defaults to twice the gene's mean, or 1 for a mean of 0)
+    #[clap(long)]
+    pub heatmap_max: Option<f32>,
+
+    /// Steps between --heatmap rows
+    #[clap(long, default_value_t = RunArgs::DEFAULT.heatmap_every)]
+    pub heatmap_every: u32,
+
+    /// SQLite file to record every step's stats and snapshots of every cell in, shared by any number of runs ({run} is replaced by the run ID)
+    #[cfg(feature = "sqlite")]
+    #[clap(long, parse(from_os_str))]
+    pub db: Option<PathBuf>,
+
+    /// Steps between cell snapshots in the results database (0 for only at the end)
+    #[cfg(feature = "sqlite")]
+    #[clap(long, default_value_t = RunArgs::DEFAULT.db_cells_every)]
+    pub db_cells_every: u32,
+
+    /// Steps between printed stats
+    #[clap(long, default_value_t = RunArgs::DEFAULT.print_every)]
+    pub print_every: u32,
+
+    /// Print stats only at the start and end of the run
+    #[clap(short('q'), long)]
+    pub quiet: bool,
+
+    /// Read commands from standard input to step, inspect, and change the world, instead of running it for --steps steps; type help for the commands
+    #[clap(long)]
+    pub interactive: bool,
+
+    /// Plot population dynamics in a terminal dashboard instead of printing stats
+    #[clap(long)]
+    pub tui: bool,
+
+    /// Serve a web dashboard charting the run live at this address, e.g. 127.0.0.1:8080 (or 0.0.0.0:8080 to watch from other machines), instead of printing stats
+    #[cfg(feature = "web")]
+    #[clap(long)]
+    pub serve: Option<String>,
+
+    /// Target terminal dashboard frames per second; steps between frames adapt to keep up
+    #[clap(long, default_value_t = RunArgs::DEFAULT.frame_rate)]
+    pub frame_rate: u32,
+
+    /// Compute no per-step stats at all and print only birth and death totals at the end, for sweeps
+    #[clap(long)]
+    pub throughput: bool,
+
+    /// How the terminal dashboard colors cells, e.g. "hue=attempted_eating_energy:0:10,brightness=health" (properties are gene names, energy, health, or age; ranges default to 0:1)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.color_by)]
+    pub color_by: ColorRule,
+
+    /// Show a progress bar with the step, population, and estimated time remaining
+    #[clap(long)]
+    pub progress: bool,
+
+    /// Steps between gene trace sparklines (0 for none)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.sparkline_every)]
+    pub sparkline_every: u32,
+
+    /// Number of recent steps shown in gene trace sparklines
+    #[clap(long, default_value_t = RunArgs::DEFAULT.sparkline_width)]
+    pub sparkline_width: usize,
+
+    /// Number of downsampled older points kept to the left of the recent ones in sparklines
+    #[clap(long, default_value_t = RunArgs::DEFAULT.sparkline_history)]
+    pub sparkline_history: usize,
+
+    /// Older sparkline points keep one of every this many steps
+    #[clap(long, default_value_t = RunArgs::DEFAULT.sparkline_downsample)]
+    pub sparkline_downsample: usize,
+
+    /// Steps between species reports (0 for none)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.species_every)]
+    pub species_every: u32,
+
+    /// Greatest distance between gene values of cells of the same species
+    #[clap(long, default_value_t = RunArgs::DEFAULT.species_threshold)]
+    pub species_threshold: f32,
+
+    /// Steps between logged checksums of the population's genomes, for verifying that seeded runs match (0 for none)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.checksum_every)]
+    pub checksum_every: u32,
+
+    /// Steps between reports of the lifetime offspring counts of the cells that died since the last report, and how each gene correlates with them (0 for none)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.fitness_every)]
+    pub fitness_every: u32,
+
+    /// Steps between reports of how each gene of the living cells correlates with their offspring since the last report and with their energy, and of each gene's selection differential (0 for none)
+    #[clap(long, default_value_t = RunArgs::DEFAULT.selection_every)]
+    pub selection_every: u32,
+
+    /// File to also write the selection reports to as CSV ({run} is replaced by the run ID)
+    #[clap(long, parse(from_os_str))]
+    pub selection_csv: Option<PathBuf>,
+}
+
+/// What to do. With no subcommand, `run`, with its options.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a new world for --steps steps.
+    Run(Args),
+
+    /// Resume a run from a checkpoint, for --steps more steps. The world is the checkpoint's, so
+    /// only options that configure the run and its output are taken, and interventions can only
+    /// kill cells and dump the population.
+    Resume(ResumeArgs),
+
+    /// Compete two genomes, starting from equal numbers of cells with mutation off, and report
+    /// how often each takes over the population. Options that configure the world apply as usual.
+    Compete(CompeteArgs),
+
+    /// Run every combination of values of the swept options, each in throughput mode for
+    /// --steps steps, and write a table with a row of results for each. The other options given
+    /// apply to every run.
+    Sweep(SweepArgs),
+
+    /// Summarize a checkpoint or population dump: its step, population size, gene
+    /// distributions, and species.
+    Inspect(InspectArgs),
+
+    /// Compare two checkpoints or population dumps, e.g. of two runs or of one run at two
+    /// steps, reporting how their population sizes, gene distributions, species, and lineages
+    /// differ.
+    Diff(DiffArgs),
+
+    /// Run a fixed benchmark scenario and report steps per second.
+    Bench(BenchArgs),
+}
+
+#[derive(clap::Args)]
+pub struct ResumeArgs {
+    /// Checkpoint file to resume from
+    #[clap(parse(from_os_str))]
+    pub checkpoint: PathBuf,
+
+    #[clap(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(clap::Args)]
+pub struct CompeteArgs {
+    /// First genome, as gene=value pairs separated by commas; unlisted genes take the gene means
+    #[clap(long)]
+    pub genome_a: String,
+
+    /// Second genome, in the same form as the first
+    #[clap(long)]
+    pub genome_b: String,
+
+    /// Number of independent runs
+    #[clap(long, default_value_t = 20)]
+    pub replicates: u32,
+
+    /// Steps after which a run with both genomes still present ends with no winner
+    #[clap(long, default_value_t = 10_000)]
+    pub max_steps: u32,
+
+    #[clap(flatten)]
+    pub world: WorldArgs,
+}
+
+#[derive(clap::Args)]
+pub struct SweepArgs {
+    /// Option to vary, as name=values, e.g. added_food=0,5,10 or added_food=0:10:2.5 (start:stop:step); repeat for each option to vary
+    #[clap(long = "sweep", required = true)]
+    pub axes: Vec<SweepAxis>,
+
+    /// Number of configurations to run at once
+    #[clap(long, default_value_t = 1)]
+    pub threads: usize,
+
+    /// CSV file to write the results table to, instead of printing it
+    #[clap(long, parse(from_os_str))]
+    pub summary: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub args: Args,
+}
+
+#[derive(clap::Args)]
+pub struct InspectArgs {
+    /// Checkpoint or population dump (.json or .csv)
+    #[clap(parse(from_os_str))]
+    pub file: PathBuf,
+
+    /// Greatest distance between gene values of cells of the same species
+    #[clap(long, default_value_t = RunOptions::DEFAULT.species_threshold)]
+    pub species_threshold: f32,
+}
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Earlier or reference checkpoint or population dump (.json or .csv)
+    #[clap(parse(from_os_str))]
+    pub before: PathBuf,
+
+    /// Later or compared checkpoint or population dump
+    #[clap(parse(from_os_str))]
+    pub after: PathBuf,
+
+    /// Greatest distance between gene values of cells of the same species
+    #[clap(long, default_value_t = RunOptions::DEFAULT.species_threshold)]
+    pub species_threshold: f32,
+}
+
+#[derive(clap::Args)]
+pub struct BenchArgs {
+    /// Number of cells the benchmark world starts with
+    #[clap(long, default_value_t = 10_000)]
+    pub cells: usize,
+
+    /// Number of steps to time
+    #[clap(long, default_value_t = 100)]
+    pub steps: u32,
+}
+
+impl WorldArgs {
     #[allow(dead_code)]
-    pub const DEFAULT: Args = Args {
-        steps: u32::MAX,
+    pub const DEFAULT: WorldArgs = WorldArgs {
+        seed: None,
+        energy_scale: Units::BASE.energy,
+        food_scale: Units::BASE.food,
+        light_scale: Units::BASE.light,
         initial_food: 0.0,
         added_food: 0.0,
+        initial_protein: 0.0,
+        added_protein: 0.0,
+        immigrants_per_step: 0.0,
+        immigration_period: 0,
+        immigrants_from: None,
+        reseed_on_extinction: false,
+        reseed_from: None,
+        environment: None,
+        stress: None,
+        food_decay_rate: 0.0,
+        corpse_food_per_energy: 0.0,
+        corpse_food_per_size: 0.0,
+        carrying_capacity: 0,
+        food_allocation: FoodAllocationPolicy::Equal,
+        max_cells: 0,
+        crowding: Crowding::CullWeakest,
+        light: 0.0,
+        day_length: 0,
+        night_added_food: None,
+        night_light: None,
+        reproduction_mode: ReproductionMode::Asexual,
+        generation_steps: 0,
+        select_by_energy: false,
         cells: 100,
         attempted_eating_energy_mean: 0.0,
         attempted_eating_energy_stdev: 0.0,
         attempted_healing_energy_mean: 0.0,
         attempted_healing_energy_stdev: 0.0,
+        attempted_attack_energy_mean: 0.0,
+        attempted_attack_energy_stdev: 0.0,
+        attempted_photosynthesis_energy_mean: 0.0,
+        attempted_photosynthesis_energy_stdev: 0.0,
+        active_phase_mean: 0.0,
+        active_phase_stdev: 0.0,
+        attempted_trade_energy_mean: 0.0,
+        attempted_trade_energy_stdev: 0.0,
+        trade_reciprocity_threshold_mean: 0.0,
+        trade_reciprocity_threshold_stdev: 0.0,
+        food_memory_rate_mean: Genome::DEFAULT.food_memory_rate.value(),
+        food_memory_rate_stdev: 0.0,
+        scarce_food_threshold_mean: Genome::DEFAULT.scarce_food_threshold.value(),
+        scarce_food_threshold_stdev: 0.0,
+        scarcity_spending_fraction_mean: Genome::DEFAULT.scarcity_spending_fraction.value(),
+        scarcity_spending_fraction_stdev: 0.0,
+        reproduction_priority_mean: Genome::DEFAULT.reproduction_priority.value(),
+        reproduction_priority_stdev: 0.0,
+        eating_priority_mean: Genome::DEFAULT.eating_priority.value(),
+        eating_priority_stdev: 0.0,
+        healing_priority_mean: Genome::DEFAULT.healing_priority.value(),
+        healing_priority_stdev: 0.0,
+        attack_priority_mean: Genome::DEFAULT.attack_priority.value(),
+        attack_priority_stdev: 0.0,
+        photosynthesis_priority_mean: Genome::DEFAULT.photosynthesis_priority.value(),
+        photosynthesis_priority_stdev: 0.0,
+        trade_priority_mean: Genome::DEFAULT.trade_priority.value(),
+        trade_priority_stdev: 0.0,
+        max_energy_storage_mean: f32::MAX,
+        max_energy_storage_stdev: 0.0,
+        attempted_detox_energy_mean: Genome::DEFAULT.attempted_detox_energy.value(),
+        attempted_detox_energy_stdev: 0.0,
+        detox_priority_mean: Genome::DEFAULT.detox_priority.value(),
+        detox_priority_stdev: 0.0,
+        size_mean: Genome::DEFAULT.size.value(),
+        size_stdev: 0.0,
+        donation_threshold_mean: f32::MAX,
+        donation_threshold_stdev: 0.0,
+        donation_fraction_mean: Genome::DEFAULT.donation_fraction.value(),
+        donation_fraction_stdev: 0.0,
+        dormancy_threshold_energy_mean: Genome::DEFAULT.dormancy_threshold_energy.value(),
+        dormancy_threshold_energy_stdev: 0.0,
+        wake_threshold_food_mean: Genome::DEFAULT.wake_threshold_food.value(),
+        wake_threshold_food_stdev: 0.0,
+        protein_preference_mean: Genome::DEFAULT.protein_preference.value(),
+        protein_preference_stdev: 0.0,
+        eating_efficiency_mean: Genome::DEFAULT.eating_efficiency.value(),
+        eating_efficiency_stdev: 0.0,
+        digestion_efficiency_mean: Genome::DEFAULT.digestion_efficiency.value(),
+        digestion_efficiency_stdev: 0.0,
+        state_update_rate_mean: Genome::DEFAULT.state_update_rate.value(),
+        state_update_rate_stdev: 0.0,
+        state_energy_scale_mean: Genome::DEFAULT.state_energy_scale.value(),
+        state_energy_scale_stdev: 0.0,
+        state_food_scale_mean: Genome::DEFAULT.state_food_scale.value(),
+        state_food_scale_stdev: 0.0,
+        eating_state_response_mean: Genome::DEFAULT.eating_state_response.value(),
+        eating_state_response_stdev: 0.0,
+        healing_state_response_mean: Genome::DEFAULT.healing_state_response.value(),
+        healing_state_response_stdev: 0.0,
+        stress_tolerance_mean: Genome::DEFAULT.stress_tolerance.value(),
+        stress_tolerance_stdev: 0.0,
+        brain_weight_stdev: 0.0,
         child_threshold_energy_mean: f32::MAX,
         child_threshold_energy_stdev: 0.0,
         child_threshold_food_mean: 0.0,
@@ -171,10 +2326,572 @@ impl Args {
         create_child_energy: CellConstants::DEFAULT.create_child_energy.value(),
         energy_yield_from_digestion: CellConstants::DEFAULT.energy_yield_from_digestion.value(),
         food_yield_from_eating: CellConstants::DEFAULT.food_yield_from_eating.value(),
+        energy_yield_from_protein_digestion: CellConstants::DEFAULT.energy_yield_from_protein_digestion.value(),
+        protein_yield_from_eating: CellConstants::DEFAULT.protein_yield_from_eating.value(),
         health_increase_per_healing_energy: CellConstants::DEFAULT.health_increase_per_healing_energy.value(),
         health_reduction_from_entropy: CellConstants::DEFAULT.health_reduction_from_entropy.value(),
+        entropy_age_exponent: CellConstants::DEFAULT.entropy_age_exponent.value(),
+        health_reduction_from_zero_energy: CellConstants::DEFAULT.health_reduction_from_zero_energy.value(),
         health_reduction_per_energy_expended: CellConstants::DEFAULT.health_reduction_per_energy_expended.value(),
+        attack_efficiency: CellConstants::DEFAULT.attack_efficiency.value(),
+        attack_defense: CellConstants::DEFAULT.attack_defense.value(),
+        health_reduction_per_attack_energy: CellConstants::DEFAULT.health_reduction_per_attack_energy.value(),
+        max_age: CellConstants::DEFAULT.max_age,
+        crossover_probability: CellConstants::DEFAULT.crossover_probability.value(),
+        mutation_model: MutationModel::Gaussian,
+        efficiency_decay_per_age: CellConstants::DEFAULT.efficiency_decay_per_age.value(),
+        activity_fraction: CellConstants::DEFAULT.activity_fraction.value(),
+        trade_efficiency: CellConstants::DEFAULT.trade_efficiency.value(),
+        food_access_per_trade_energy: CellConstants::DEFAULT.food_access_per_trade_energy.value(),
+        maintenance_energy: CellConstants::DEFAULT.maintenance_energy.value(),
+        health_reduction_per_maintenance_shortfall: CellConstants::DEFAULT.health_reduction_per_maintenance_shortfall.value(),
+        maintenance_energy_per_storage: CellConstants::DEFAULT.maintenance_energy_per_storage.value(),
+        maintenance_energy_per_attempted_energy: CellConstants::DEFAULT.maintenance_energy_per_attempted_energy.value(),
+        maintenance_energy_per_efficiency: CellConstants::DEFAULT.maintenance_energy_per_efficiency.value(),
+        maintenance_energy_per_stress_tolerance: CellConstants::DEFAULT.maintenance_energy_per_stress_tolerance.value(),
+        health_reduction_per_excess_energy: CellConstants::DEFAULT.health_reduction_per_excess_energy.value(),
+        process_order: CellConstants::DEFAULT.process_order,
+        cell_controller: CellConstants::DEFAULT.controller,
+        death_probability: CellConstants::DEFAULT.death_probability.value(),
+        death_probability_per_health_lost: CellConstants::DEFAULT.death_probability_per_health_lost.value(),
+        toxin_per_energy_expended: 0.0,
+        toxin_tolerance: CellConstants::DEFAULT.toxin_tolerance.value(),
+        health_reduction_per_toxin: CellConstants::DEFAULT.health_reduction_per_toxin.value(),
+        toxin_yield_from_detox: CellConstants::DEFAULT.toxin_yield_from_detox.value(),
+        max_food_intake: CellConstants::DEFAULT.max_food_intake.value(),
+        donation_efficiency: CellConstants::DEFAULT.donation_efficiency.value(),
+        dormant_maintenance_fraction: CellConstants::DEFAULT.dormant_maintenance_fraction.value(),
+        light_yield_from_photosynthesis: CellConstants::DEFAULT.light_yield_from_photosynthesis.value(),
+        energy_yield_from_light: CellConstants::DEFAULT.energy_yield_from_light.value(),
         initial_energy_mean: 100.0,
         initial_energy_stdev: 0.0,
+        initial_health_mean: 1.0,
+        initial_health_stdev: 0.0,
+    };
+}
+
+impl RunArgs {
+    #[allow(dead_code)]
+    pub const DEFAULT: RunArgs = RunArgs {
+        steps: u32::MAX,
+        stop_when: None,
+        interventions: None,
+        run_id: None,
+        checkpoint_file: None,
+        checkpoint_every: 0,
+        dump_population: None,
+        dump_population_every: 0,
+        summary_json: None,
+        log_level: LevelFilter::OFF,
+        log_file: None,
+        audit: Audit::Off,
+        heatmap: None,
+        heatmap_gene: None,
+        heatmap_bins: 20,
+        heatmap_min: 0.0,
+        heatmap_max: None,
+        heatmap_every: 1,
+        #[cfg(feature = "sqlite")]
+        db: None,
+        #[cfg(feature = "sqlite")]
+        db_cells_every: 0,
+        print_every: RunOptions::DEFAULT.print_every,
+        quiet: false,
+        interactive: false,
+        tui: false,
+        #[cfg(feature = "web")]
+        serve: None,
+        frame_rate: RunOptions::DEFAULT.frame_rate,
+        color_by: RunOptions::DEFAULT.color_rule,
+        throughput: false,
+        progress: RunOptions::DEFAULT.progress,
+        sparkline_every: RunOptions::DEFAULT.sparkline_every,
+        sparkline_width: RunOptions::DEFAULT.sparkline_width,
+        sparkline_history: RunOptions::DEFAULT.sparkline_history,
+        sparkline_downsample: RunOptions::DEFAULT.sparkline_downsample,
+        species_every: RunOptions::DEFAULT.species_every,
+        species_threshold: RunOptions::DEFAULT.species_threshold,
+        checksum_every: RunOptions::DEFAULT.checksum_every,
+        fitness_every: RunOptions::DEFAULT.fitness_every,
+        selection_every: RunOptions::DEFAULT.selection_every,
+        selection_csv: None,
+    };
+}
+
+impl Args {
+    #[allow(dead_code)]
+    pub const DEFAULT: Args = Args {
+        scenario: None,
+        world: WorldArgs::DEFAULT,
+        run: RunArgs::DEFAULT,
     };
+
+    /// Parses the command line as the options of `run`, for front ends that only run new worlds,
+    /// starting from the options of its `--scenario`, if any.
+    pub fn parse_with_scenario() -> Args {
+        Self::try_parse_with_scenario_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    pub fn try_parse_with_scenario_from<I, T>(argv: I) -> Result<Args, clap::Error>
+        where I: IntoIterator<Item=T>, T: Into<OsString> + Clone {
+        match Command::try_parse_with_scenario_from(argv)? {
+            Command::Run(args) => Ok(args),
+            _ => Err(clap::Error::raw(ErrorKind::UnrecognizedSubcommand, "expected the options of run\n")),
+        }
+    }
+
+    /// Checks values that parse but lie outside the domains of the quantities they configure,
+    /// so that a bad flag is reported by name before the world is built, rather than by a panic
+    /// partway through building or running it.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut checks = DomainChecks::new();
+        self.world.check(&mut checks);
+        self.run.check(&mut checks);
+        checks.into_result()
+    }
+}
+
+impl WorldArgs {
+    /// Checks the world options as `Args::validate` does.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut checks = DomainChecks::new();
+        self.check(&mut checks);
+        checks.into_result()
+    }
+
+    fn check(&self, checks: &mut DomainChecks) {
+        checks.finite("--initial-energy-mean", self.initial_energy_mean);
+        checks.non_negative("--initial-energy-stdev", self.initial_energy_stdev);
+        checks.finite("--initial-energy-stdev", self.initial_energy_stdev);
+        checks.zero_to_one("--initial-health-mean", self.initial_health_mean);
+        checks.non_negative("--initial-health-stdev", self.initial_health_stdev);
+        checks.finite("--initial-health-stdev", self.initial_health_stdev);
+        for (flag, value) in [
+            ("--attempted-eating-energy-mean", self.attempted_eating_energy_mean),
+            ("--attempted-eating-energy-stdev", self.attempted_eating_energy_stdev),
+            ("--attempted-healing-energy-mean", self.attempted_healing_energy_mean),
+            ("--attempted-healing-energy-stdev", self.attempted_healing_energy_stdev),
+            ("--attempted-attack-energy-mean", self.attempted_attack_energy_mean),
+            ("--attempted-attack-energy-stdev", self.attempted_attack_energy_stdev),
+            ("--attempted-photosynthesis-energy-mean", self.attempted_photosynthesis_energy_mean),
+            ("--attempted-photosynthesis-energy-stdev", self.attempted_photosynthesis_energy_stdev),
+            ("--active-phase-stdev", self.active_phase_stdev),
+            ("--attempted-trade-energy-mean", self.attempted_trade_energy_mean),
+            ("--attempted-trade-energy-stdev", self.attempted_trade_energy_stdev),
+            ("--trade-reciprocity-threshold-mean", self.trade_reciprocity_threshold_mean),
+            ("--trade-reciprocity-threshold-stdev", self.trade_reciprocity_threshold_stdev),
+            ("--food-memory-rate-stdev", self.food_memory_rate_stdev),
+            ("--scarce-food-threshold-mean", self.scarce_food_threshold_mean),
+            ("--scarce-food-threshold-stdev", self.scarce_food_threshold_stdev),
+            ("--scarcity-spending-fraction-stdev", self.scarcity_spending_fraction_stdev),
+            ("--reproduction-priority-mean", self.reproduction_priority_mean),
+            ("--reproduction-priority-stdev", self.reproduction_priority_stdev),
+            ("--eating-priority-mean", self.eating_priority_mean),
+            ("--eating-priority-stdev", self.eating_priority_stdev),
+            ("--healing-priority-mean", self.healing_priority_mean),
+            ("--healing-priority-stdev", self.healing_priority_stdev),
+            ("--attack-priority-mean", self.attack_priority_mean),
+            ("--attack-priority-stdev", self.attack_priority_stdev),
+            ("--photosynthesis-priority-mean", self.photosynthesis_priority_mean),
+            ("--photosynthesis-priority-stdev", self.photosynthesis_priority_stdev),
+            ("--trade-priority-mean", self.trade_priority_mean),
+            ("--trade-priority-stdev", self.trade_priority_stdev),
+            ("--max-energy-storage-mean", self.max_energy_storage_mean),
+            ("--max-energy-storage-stdev", self.max_energy_storage_stdev),
+            ("--attempted-detox-energy-mean", self.attempted_detox_energy_mean),
+            ("--attempted-detox-energy-stdev", self.attempted_detox_energy_stdev),
+            ("--detox-priority-mean", self.detox_priority_mean),
+            ("--detox-priority-stdev", self.detox_priority_stdev),
+            ("--size-mean", self.size_mean),
+            ("--size-stdev", self.size_stdev),
+            ("--donation-threshold-mean", self.donation_threshold_mean),
+            ("--donation-threshold-stdev", self.donation_threshold_stdev),
+            ("--donation-fraction-stdev", self.donation_fraction_stdev),
+            ("--dormancy-threshold-energy-mean", self.dormancy_threshold_energy_mean),
+            ("--dormancy-threshold-energy-stdev", self.dormancy_threshold_energy_stdev),
+            ("--wake-threshold-food-mean", self.wake_threshold_food_mean),
+            ("--wake-threshold-food-stdev", self.wake_threshold_food_stdev),
+            ("--protein-preference-stdev", self.protein_preference_stdev),
+            ("--eating-efficiency-mean", self.eating_efficiency_mean),
+            ("--eating-efficiency-stdev", self.eating_efficiency_stdev),
+            ("--digestion-efficiency-mean", self.digestion_efficiency_mean),
+            ("--digestion-efficiency-stdev", self.digestion_efficiency_stdev),
+            ("--state-update-rate-stdev", self.state_update_rate_stdev),
+            ("--state-energy-scale-mean", self.state_energy_scale_mean),
+            ("--state-energy-scale-stdev", self.state_energy_scale_stdev),
+            ("--state-food-scale-mean", self.state_food_scale_mean),
+            ("--state-food-scale-stdev", self.state_food_scale_stdev),
+            ("--eating-state-response-mean", self.eating_state_response_mean),
+            ("--eating-state-response-stdev", self.eating_state_response_stdev),
+            ("--healing-state-response-mean", self.healing_state_response_mean),
+            ("--healing-state-response-stdev", self.healing_state_response_stdev),
+            ("--brain-weight-stdev", self.brain_weight_stdev),
+            ("--stress-tolerance-mean", self.stress_tolerance_mean),
+            ("--stress-tolerance-stdev", self.stress_tolerance_stdev),
+            ("--child-threshold-energy-mean", self.child_threshold_energy_mean),
+            ("--child-threshold-energy-stdev", self.child_threshold_energy_stdev),
+            ("--child-threshold-food-mean", self.child_threshold_food_mean),
+            ("--child-threshold-food-stdev", self.child_threshold_food_stdev),
+            ("--create-child-energy", self.create_child_energy),
+            ("--energy-yield-from-digestion", self.energy_yield_from_digestion),
+            ("--food-yield-from-eating", self.food_yield_from_eating),
+            ("--energy-yield-from-protein-digestion", self.energy_yield_from_protein_digestion),
+            ("--protein-yield-from-eating", self.protein_yield_from_eating),
+            ("--attack-efficiency", self.attack_efficiency),
+            ("--attack-defense", self.attack_defense),
+            ("--light-yield-from-photosynthesis", self.light_yield_from_photosynthesis),
+            ("--energy-yield-from-light", self.energy_yield_from_light),
+            ("--trade-efficiency", self.trade_efficiency),
+            ("--food-access-per-trade-energy", self.food_access_per_trade_energy),
+            ("--maintenance-energy", self.maintenance_energy),
+            ("--maintenance-energy-per-storage", self.maintenance_energy_per_storage),
+            ("--maintenance-energy-per-attempted-energy", self.maintenance_energy_per_attempted_energy),
+            ("--maintenance-energy-per-efficiency", self.maintenance_energy_per_efficiency),
+            ("--maintenance-energy-per-stress-tolerance", self.maintenance_energy_per_stress_tolerance),
+            ("--entropy-age-exponent", self.entropy_age_exponent),
+            ("--toxin-per-energy-expended", self.toxin_per_energy_expended),
+            ("--corpse-food-per-energy", self.corpse_food_per_energy),
+            ("--corpse-food-per-size", self.corpse_food_per_size),
+            ("--toxin-tolerance", self.toxin_tolerance),
+            ("--toxin-yield-from-detox", self.toxin_yield_from_detox),
+            ("--max-food-intake", self.max_food_intake),
+            ("--initial-food", self.initial_food),
+            ("--added-food", self.added_food),
+            ("--initial-protein", self.initial_protein),
+            ("--added-protein", self.added_protein),
+            ("--immigrants-per-step", self.immigrants_per_step),
+            ("--night-added-food", self.night_added_food.unwrap_or(0.0)),
+            ("--light", self.light),
+            ("--night-light", self.night_light.unwrap_or(0.0)),
+        ] {
+            checks.non_negative(flag, value);
+        }
+
+        for (flag, value) in [
+            ("--active-phase-mean", self.active_phase_mean),
+            ("--food-memory-rate-mean", self.food_memory_rate_mean),
+            ("--scarcity-spending-fraction-mean", self.scarcity_spending_fraction_mean),
+            ("--donation-fraction-mean", self.donation_fraction_mean),
+            ("--protein-preference-mean", self.protein_preference_mean),
+            ("--state-update-rate-mean", self.state_update_rate_mean),
+            ("--donation-efficiency", self.donation_efficiency),
+            ("--dormant-maintenance-fraction", self.dormant_maintenance_fraction),
+            ("--health-reduction-from-entropy", self.health_reduction_from_entropy),
+            ("--health-reduction-from-zero-energy", self.health_reduction_from_zero_energy),
+            ("--crossover-probability", self.crossover_probability),
+            ("--efficiency-decay-per-age", self.efficiency_decay_per_age),
+            ("--activity-fraction", self.activity_fraction),
+            ("--food-decay-rate", self.food_decay_rate),
+            ("--health-increase-per-healing-energy", self.health_increase_per_healing_energy),
+            ("--health-reduction-per-energy-expended", self.health_reduction_per_energy_expended),
+            ("--health-reduction-per-attack-energy", self.health_reduction_per_attack_energy),
+            ("--health-reduction-per-maintenance-shortfall", self.health_reduction_per_maintenance_shortfall),
+            ("--health-reduction-per-excess-energy", self.health_reduction_per_excess_energy),
+            ("--death-probability", self.death_probability),
+            ("--death-probability-per-health-lost", self.death_probability_per_health_lost),
+            ("--health-reduction-per-toxin", self.health_reduction_per_toxin),
+        ] {
+            checks.zero_to_one(flag, value);
+        }
+
+        for (flag, value) in [
+            ("--energy-scale", self.energy_scale),
+            ("--food-scale", self.food_scale),
+            ("--light-scale", self.light_scale),
+        ] {
+            checks.positive(flag, value);
+            checks.finite(flag, value);
+        }
+    }
+}
+
+impl RunArgs {
+    /// Checks the run options as `Args::validate` does.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut checks = DomainChecks::new();
+        self.check(&mut checks);
+        checks.into_result()
+    }
+
+    fn check(&self, checks: &mut DomainChecks) {
+        checks.non_negative("--species-threshold", self.species_threshold);
+        if let Some(gene) = &self.heatmap_gene {
+            if !Genome::gene_names().any(|name| name == gene) {
+                checks.errors.push(format!("--heatmap-gene: unknown gene '{}'", gene));
+            }
+        }
+        if self.heatmap_max.is_some_and(|max| max <= self.heatmap_min) {
+            checks.errors.push("--heatmap-max must be above --heatmap-min".to_string());
+        }
+        if self.audit == Audit::Log && self.log_level < LevelFilter::WARN {
+            checks.errors.push("--audit log needs --log-level warn or more detailed".to_string());
+        }
+    }
+}
+
+impl Command {
+    /// Parses the command line, starting the options of `run` or `sweep` from those of its
+    /// `--scenario`, if any.
+    pub fn parse_with_scenario() -> Command {
+        Self::try_parse_with_scenario_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    pub fn try_parse_with_scenario_from<I, T>(argv: I) -> Result<Command, clap::Error>
+        where I: IntoIterator<Item=T>, T: Into<OsString> + Clone {
+        let argv: Vec<OsString> = argv.into_iter().map(Into::into).collect();
+        let cli = Self::try_parse_overriding_from(argv.iter().cloned())?;
+        let scenario = match cli.scenario() {
+            Some(name) => Scenario::find(name).map_err(|message| clap::Error::raw(ErrorKind::ValueValidation, message))?,
+            None => return Ok(cli.into_command()),
+        };
+        // Scenario options go first, so that the options given override them. They follow the
+        // subcommand, if there is one, which options cannot precede.
+        let options_start = if cli.command.is_some() { 2 } else { 1 };
+        Self::try_parse_overriding_from(argv[..options_start].iter().cloned()
+            .chain(scenario.args.iter().map(OsString::from))
+            .chain(argv[options_start..].iter().cloned()))
+            .map(Cli::into_command)
+    }
+
+    /// Parses the command line, letting later options override earlier ones.
+    fn try_parse_overriding_from(argv: impl Iterator<Item=OsString>) -> Result<Cli, clap::Error> {
+        let matches = Cli::command()
+            .args_override_self(true)
+            .try_get_matches_from(argv)?;
+        Cli::from_arg_matches(&matches)
+    }
+}
+
+impl Cli {
+    fn scenario(&self) -> Option<&str> {
+        match &self.command {
+            None => self.args.scenario.as_deref(),
+            Some(Command::Run(args)) => args.scenario.as_deref(),
+            Some(Command::Sweep(sweep_args)) => sweep_args.args.scenario.as_deref(),
+            Some(_) => None,
+        }
+    }
+
+    fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Run(self.args))
+    }
+}
+
+struct DomainChecks {
+    errors: Vec<String>,
+}
+
+impl DomainChecks {
+    fn new() -> Self {
+        DomainChecks { errors: vec![] }
+    }
+
+    fn non_negative(&mut self, flag: &str, value: f32) {
+        if value.is_nan() || value < 0.0 {
+            self.errors.push(format!("{} must not be negative (got {})", flag, value));
+        }
+    }
+
+    fn positive(&mut self, flag: &str, value: f32) {
+        if value.is_nan() || value <= 0.0 {
+            self.errors.push(format!("{} must be greater than 0 (got {})", flag, value));
+        }
+    }
+
+    fn zero_to_one(&mut self, flag: &str, value: f32) {
+        if !(0.0..=1.0).contains(&value) {
+            self.errors.push(format!("{} must be between 0 and 1 (got {})", flag, value));
+        }
+    }
+
+    fn finite(&mut self, flag: &str, value: f32) {
+        if !value.is_finite() {
+            self.errors.push(format!("{} must be a finite number (got {})", flag, value));
+        }
+    }
+
+    fn into_result(self) -> Result<(), String> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors.join("\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn default_args_are_valid() {
+        assert_eq!(Args::DEFAULT.validate(), Ok(()));
+    }
+
+    #[test]
+    fn deaths_are_formatted_for_causes_that_have_killed_any() {
+        let step_deaths = DeathCounts { starvation: 2, ..DeathCounts::default() };
+        let total_deaths = DeathCounts { starvation: 5, attack: 3, ..DeathCounts::default() };
+
+        assert_eq!(format_deaths(&step_deaths, &total_deaths), " d: starvation 2/5, attack 0/3");
+        assert_eq!(format_deaths(&DeathCounts::default(), &DeathCounts::default()), "");
+    }
+
+    #[test]
+    fn out_of_domain_args_are_reported_by_flag() {
+        let args = Args {
+            world: WorldArgs {
+                health_increase_per_healing_energy: 1.5,
+                initial_energy_stdev: -1.0,
+                attempted_eating_energy_mean: f32::NAN,
+                ..WorldArgs::DEFAULT
+            },
+            run: RunArgs { species_threshold: -1.0, ..RunArgs::DEFAULT },
+            ..Args::DEFAULT
+        };
+
+        let message = args.validate().unwrap_err();
+
+        assert!(message.contains("--health-increase-per-healing-energy must be between 0 and 1 (got 1.5)"));
+        assert!(message.contains("--initial-energy-stdev must not be negative (got -1)"));
+        assert!(message.contains("--attempted-eating-energy-mean must not be negative (got NaN)"));
+        assert!(message.contains("--species-threshold must not be negative (got -1)"));
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn printed_output(steps: u32, options: &RunOptions) -> String {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new().with_cells((0..3)
+            .map(|_| Cell::new(&constants, Genome::DEFAULT).with_energy(100.0.into()))
+            .collect());
+        let buffer = SharedBuffer::default();
+        run_to_output(&mut world, &mut RandomMutationNumberSource::with_seed(0), 0, steps, options,
+                      Rc::new(RefCell::new(AsyncWriter::new(buffer.clone()))));
+        let printed = buffer.0.lock().unwrap().clone();
+        String::from_utf8(printed).unwrap()
+    }
+
+    fn printed_steps(output: &str) -> Vec<u32> {
+        output.lines()
+            .filter_map(|line| line.split_once(": +"))
+            .filter_map(|(step, _)| step.parse().ok())
+            .collect()
+    }
+
+    #[test]
+    fn stats_are_printed_every_print_every_steps_and_at_the_end() {
+        let output = printed_output(8, &RunOptions { print_every: 3, ..RunOptions::DEFAULT });
+
+        assert_eq!(printed_steps(&output), vec![0, 3, 6, 8]);
+        assert!(output.contains("\n# summary: 8 steps (0-8)"));
+    }
+
+    #[test]
+    fn quiet_runs_print_only_the_first_and_last_stats() {
+        let args = Args::try_parse_with_scenario_from(["evo2", "--quiet", "--print-every", "3"]).unwrap();
+        let options = run_options(Some(&args.world), &args.run, RunId::generate());
+
+        assert_eq!(options.print_every, 0);
+        assert_eq!(printed_steps(&printed_output(8, &options)), vec![0, 8]);
+    }
+
+    #[test]
+    fn progress_bar_is_kept_out_of_the_output() {
+        let with_progress_bar = printed_output(8, &RunOptions { print_every: 3, progress: true, ..RunOptions::DEFAULT });
+
+        assert_eq!(with_progress_bar, printed_output(8, &RunOptions { print_every: 3, ..RunOptions::DEFAULT }));
+    }
+
+    #[test]
+    fn evaluate_summarizes_a_seeded_run() {
+        let args = Args {
+            world: WorldArgs { cells: 10, seed: Some(0), initial_energy_mean: 100.0, ..WorldArgs::DEFAULT },
+            run: RunArgs { steps: 5, ..RunArgs::DEFAULT },
+            ..Args::DEFAULT
+        };
+
+        let report = evaluate(&args).unwrap();
+
+        assert_eq!((report.last_step, report.num_cells, report.extinction_step), (5, 10, None));
+        assert_eq!(report.genes.len(), Genome::gene_names().count());
+        assert_eq!(evaluate(&args), Ok(report));
+        assert!(evaluate(&Args { world: WorldArgs { initial_energy_stdev: -1.0, ..args.world.clone() }, ..args }).is_err());
+    }
+
+    #[test]
+    fn resumed_runs_cannot_add_cells_or_set_constants() {
+        let script = |script| interventions::parse(script).unwrap();
+        let run_id = RunId::generate();
+
+        assert!(schedule_interventions(None, run_id, script("10 kill 0.5\n20 dump population.json")).is_ok());
+        assert!(schedule_interventions(Some(&WorldArgs::DEFAULT), run_id, script("10 add 3\n20 set max_age 100")).is_ok());
+        let scheduled = schedule_interventions(None, run_id, script("10 add 3"));
+        assert!(scheduled.is_err_and(|message| message.starts_with("10 add 3: cannot add cells or set cell constants in a resumed run")));
+        assert!(schedule_interventions(None, run_id, script("20 set max_age 100")).is_err());
+    }
+
+    #[test]
+    fn cell_constants_are_set_by_option_name() {
+        let mut constants = CellConstants::DEFAULT;
+
+        set_cell_constant(&mut constants, "maintenance_energy", "0.5").unwrap();
+        set_cell_constant(&mut constants, "max-age", "100").unwrap();
+
+        assert_eq!((constants.maintenance_energy, constants.max_age), (0.5.into(), 100));
+        assert_eq!(set_cell_constant(&mut constants, "crossover_probability", "2"),
+                   Err("crossover_probability must be between 0 and 1 (got 2)".to_string()));
+        assert!(set_cell_constant(&mut constants, "no_such_constant", "1").is_err());
+    }
+
+    #[test]
+    fn sweep_values_override_the_base_command_line() {
+        let base_args: Vec<OsString> = ["evo2", "sweep", "--sweep", "added_food=2", "--sweep", "seed=7", "--cells", "5", "--added-food", "1"]
+            .iter().map(OsString::from).collect();
+        let axes: Vec<SweepAxis> = vec!["added_food=2".parse().unwrap(), "seed=7".parse().unwrap()];
+
+        let args = sweep_configuration_args(&base_args, &axes, &["2", "7"]).unwrap();
+
+        assert_eq!((args.world.cells, args.world.added_food, args.world.seed), (5, 2.0, Some(7)));
+        assert!(sweep_configuration_args(&base_args, &["no_such_option=1".parse().unwrap()], &["1"]).is_err());
+    }
+
+    #[test]
+    fn options_given_override_the_scenario() {
+        let args = Args::try_parse_with_scenario_from(["evo2", "--scenario", "malthus", "--added-food", "20"]).unwrap();
+
+        assert_eq!((args.world.cells, args.world.maintenance_energy, args.world.added_food), (1, 0.5, 20.0));
+        assert!(Args::try_parse_with_scenario_from(["evo2", "--scenario", "utopia"]).is_err());
+    }
+
+    #[test]
+    fn subcommands_take_only_the_options_that_apply_to_them() {
+        let command = Command::try_parse_with_scenario_from(["evo2", "resume", "run.json", "--steps", "10"]).unwrap();
+        assert!(matches!(&command, Command::Resume(resume_args)
+            if resume_args.checkpoint == Path::new("run.json") && resume_args.run.steps == 10));
+
+        let command = Command::try_parse_with_scenario_from(["evo2", "bench", "--cells", "50"]).unwrap();
+        assert!(matches!(&command, Command::Bench(BenchArgs { cells: 50, steps: 100 })));
+
+        let command = Command::try_parse_with_scenario_from(["evo2", "--cells", "5", "--cells", "6"]).unwrap();
+        assert!(matches!(&command, Command::Run(args) if args.world.cells == 6));
+
+        assert!(Command::try_parse_with_scenario_from(["evo2", "--steps", "10", "resume", "run.json"]).is_err());
+        assert!(Command::try_parse_with_scenario_from(["evo2", "resume", "run.json", "--cells", "5"]).is_err());
+        assert!(Command::try_parse_with_scenario_from(["evo2", "bench", "--added-food", "1"]).is_err());
+        assert!(Command::try_parse_with_scenario_from(["evo2", "inspect"]).is_err());
+        assert!(Args::try_parse_with_scenario_from(["evo2", "bench"]).is_err());
+    }
 }
@@ -0,0 +1,207 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal, StandardNormal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::cell::MutationStats;
+use crate::number_types::F32Positive;
+
+/// How a `RandomMutationNumberSource` draws a gene's mutated value from its current value and
+/// its mutation stdev. Every model keeps values at or above 0.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum MutationModel {
+    /// Normal noise with the stdev, drawn again while the value would be negative.
+    #[default]
+    Gaussian,
+    /// Uniform noise with the stdev, so at most √3 times it either way, drawn again while the
+    /// value would be negative.
+    Uniform,
+    /// Multiplication by e^N(0, σ), where σ is the stdev relative to the value. Small mutations
+    /// are about the size of Gaussian ones, but large ones scale the value rather than shifting
+    /// it, so a value of 0 stays 0.
+    LogNormal,
+    /// Gaussian, except that a mutation is a macromutation, with `scale` times the stdev, with
+    /// `probability`.
+    Macro { probability: f32, scale: f32 },
+    /// Each mutation by one of the models, chosen at random in proportion to its weight.
+    Composite(Vec<(f32, MutationModel)>),
+}
+
+impl MutationModel {
+    pub fn mutate<R: Rng>(&self, value: F32Positive, stdev: F32Positive, rng: &mut R, stats: &mut MutationStats) -> F32Positive {
+        match self {
+            MutationModel::Gaussian => Self::mutate_gaussian(value, stdev.value(), rng, stats),
+            MutationModel::Uniform => {
+                let half_width = stdev.value() * 3.0_f32.sqrt();
+                Self::redraw_while_negative(stats, || value.value() + half_width * (rng.gen::<f32>() * 2.0 - 1.0))
+            }
+            MutationModel::LogNormal => {
+                if value.value() == 0.0 {
+                    return value;
+                }
+                let sigma = stdev.value() / value.value();
+                let z: f32 = StandardNormal.sample(rng);
+                stats.num_draws += 1;
+                F32Positive::clipped((value.value() * (sigma * z).exp()).min(f32::MAX))
+            }
+            MutationModel::Macro { probability, scale } => {
+                let stdev = if rng.gen_bool(*probability as f64) { stdev.value() * scale } else { stdev.value() };
+                Self::mutate_gaussian(value, stdev, rng, stats)
+            }
+            MutationModel::Composite(models) => {
+                let total_weight: f32 = models.iter().map(|(weight, _)| weight).sum();
+                let mut remaining = rng.gen::<f32>() * total_weight;
+                for (weight, model) in models {
+                    if remaining < *weight {
+                        return model.mutate(value, stdev, rng, stats);
+                    }
+                    remaining -= weight;
+                }
+                match models.iter().rev().find(|(weight, _)| *weight > 0.0) {
+                    Some((_, model)) => model.mutate(value, stdev, rng, stats),
+                    None => value,
+                }
+            }
+        }
+    }
+
+    fn mutate_gaussian<R: Rng>(value: F32Positive, stdev: f32, rng: &mut R, stats: &mut MutationStats) -> F32Positive {
+        let normal = Normal::new(value.value(), stdev).unwrap();
+        Self::redraw_while_negative(stats, || normal.sample(rng))
+    }
+
+    fn redraw_while_negative<F: FnMut() -> f32>(stats: &mut MutationStats, mut draw: F) -> F32Positive {
+        loop {
+            let mutated = draw();
+            stats.num_draws += 1;
+            if mutated >= 0.0 {
+                return F32Positive::unchecked(mutated);
+            }
+            stats.num_rejected_draws += 1;
+        }
+    }
+}
+
+/// Parses `gaussian`, `uniform`, `log-normal`, `macro:PROBABILITY:SCALE`, or a composite of
+/// those as `WEIGHT*MODEL+WEIGHT*MODEL...`, e.g. `0.9*gaussian+0.1*log-normal`.
+impl FromStr for MutationModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('+') || s.contains('*') {
+            return parse_composite(s);
+        }
+        match s {
+            "gaussian" => Ok(MutationModel::Gaussian),
+            "uniform" => Ok(MutationModel::Uniform),
+            "log-normal" => Ok(MutationModel::LogNormal),
+            _ => match s.strip_prefix("macro:").and_then(|params| params.split_once(':')) {
+                Some((probability, scale)) => {
+                    let probability = parse_number(probability, "macromutation probability")?;
+                    if probability > 1.0 {
+                        return Err(format!("macromutation probability {} is above 1", probability));
+                    }
+                    let scale = parse_number(scale, "macromutation scale")?;
+                    Ok(MutationModel::Macro { probability, scale })
+                }
+                None => Err(format!("unknown mutation model '{}' (expected gaussian, uniform, log-normal, \
+                                     macro:PROBABILITY:SCALE, or WEIGHT*MODEL+WEIGHT*MODEL...)", s)),
+            }
+        }
+    }
+}
+
+fn parse_composite(s: &str) -> Result<MutationModel, String> {
+    let models = s.split('+')
+        .map(|part| {
+            let (weight, model) = part.split_once('*')
+                .ok_or_else(|| format!("composite mutation model part '{}' is not WEIGHT*MODEL", part))?;
+            Ok((parse_number(weight, "mutation model weight")?, model.parse()?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if models.iter().all(|(weight, _)| *weight == 0.0) {
+        return Err(format!("composite mutation model '{}' has no positive weight", s));
+    }
+    Ok(MutationModel::Composite(models))
+}
+
+fn parse_number(s: &str, name: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => Ok(value),
+        _ => Err(format!("{} '{}' is not a number of at least 0", name, s)),
+    }
+}
+
+impl fmt::Display for MutationModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationModel::Gaussian => write!(f, "gaussian"),
+            MutationModel::Uniform => write!(f, "uniform"),
+            MutationModel::LogNormal => write!(f, "log-normal"),
+            MutationModel::Macro { probability, scale } => write!(f, "macro:{}:{}", probability, scale),
+            MutationModel::Composite(models) => {
+                for (index, (weight, model)) in models.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "+")?;
+                    }
+                    write!(f, "{}*{}", weight, model)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    fn mutations(model: &MutationModel, value: f32, stdev: f32) -> Vec<f32> {
+        let mut rng = Pcg64::seed_from_u64(0);
+        let mut stats = MutationStats::default();
+        (0..1000).map(|_| model.mutate(value.into(), stdev.into(), &mut rng, &mut stats).value()).collect()
+    }
+
+    #[test]
+    fn models_parse_from_their_names() {
+        for name in ["gaussian", "uniform", "log-normal", "macro:0.01:10", "0.9*gaussian+0.1*macro:0.5:20"] {
+            assert_eq!(name.parse::<MutationModel>().unwrap().to_string(), name);
+        }
+        assert_eq!("0.5*uniform+0.5*log-normal".parse(),
+                   Ok(MutationModel::Composite(vec![(0.5, MutationModel::Uniform), (0.5, MutationModel::LogNormal)])));
+        for bad in ["cauchy", "macro:2:10", "macro:0.1", "uniform+gaussian", "0*uniform", "-1*uniform+1*gaussian"] {
+            assert!(bad.parse::<MutationModel>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn uniform_jitter_stays_within_its_range() {
+        let values = mutations(&MutationModel::Uniform, 10.0, 1.0);
+        assert!(values.iter().all(|value| (value - 10.0).abs() <= 3.0_f32.sqrt()));
+    }
+
+    #[test]
+    fn log_normal_mutation_scales_values() {
+        assert!(mutations(&MutationModel::LogNormal, 0.0, 1.0).iter().all(|value| *value == 0.0));
+        let values = mutations(&MutationModel::LogNormal, 0.5, 1.0);
+        assert!(values.iter().all(|value| *value > 0.0));
+        assert!(values.iter().any(|value| *value > 5.0));
+    }
+
+    #[test]
+    fn macromutations_jump_farther() {
+        let max_change = |model| mutations(&model, 100.0, 1.0).iter()
+            .map(|value| (value - 100.0).abs())
+            .fold(0.0, f32::max);
+        assert!(max_change(MutationModel::Macro { probability: 0.0, scale: 10.0 }) < 5.0);
+        assert!(max_change(MutationModel::Macro { probability: 0.1, scale: 10.0 }) > 10.0);
+    }
+
+    #[test]
+    fn composite_uses_only_weighted_models() {
+        let model = MutationModel::Composite(vec![(0.0, MutationModel::Gaussian), (1.0, MutationModel::LogNormal)]);
+        assert!(mutations(&model, 0.0, 1.0).iter().all(|value| *value == 0.0));
+    }
+}
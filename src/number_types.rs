@@ -1,9 +1,13 @@
+use serde::{Deserialize, Serialize};
 use std::convert::From;
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct F32Positive {
     value: f32,
 }
@@ -29,6 +33,20 @@ impl F32Positive {
     pub fn min(self, other: F32Positive) -> F32Positive {
         Self::unchecked(self.value.min(other.value))
     }
+
+    pub fn max(self, other: F32Positive) -> F32Positive {
+        Self::unchecked(self.value.max(other.value))
+    }
+
+    /// Adds without overflowing to infinity.
+    pub fn saturating_add(self, other: F32Positive) -> F32Positive {
+        Self::unchecked((self.value + other.value).min(f32::MAX))
+    }
+
+    /// Subtracts without going below 0, like `-`.
+    pub fn saturating_sub(self, other: F32Positive) -> F32Positive {
+        Self::clipped(self.value - other.value)
+    }
 }
 
 impl fmt::Display for F32Positive {
@@ -115,7 +133,135 @@ impl<'a> Sum<&'a Self> for F32Positive {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+/// Scales by a plain factor, clipping a negative result to 0 like `saturating_sub`.
+impl Mul<f32> for F32Positive {
+    type Output = Self;
+
+    fn mul(self, other: f32) -> Self::Output {
+        Self::clipped(self.value() * other)
+    }
+}
+
+impl Mul<F32ZeroToOne> for F32Positive {
+    type Output = Self;
+
+    fn mul(self, other: F32ZeroToOne) -> Self::Output {
+        Self::unchecked(self.value() * other.value())
+    }
+}
+
+impl Div<usize> for F32Positive {
+    type Output = Self;
+
+    fn div(self, other: usize) -> Self::Output {
+        Self::unchecked(self.value() / other as f32)
+    }
+}
+
+/// Compares with the plain number on the left, so that `F32Positive == x.into()` still infers `x`.
+impl PartialEq<F32Positive> for f32 {
+    fn eq(&self, other: &F32Positive) -> bool {
+        *self == other.value()
+    }
+}
+
+impl PartialOrd<F32Positive> for f32 {
+    fn partial_cmp(&self, other: &F32Positive) -> Option<Ordering> {
+        self.partial_cmp(&other.value())
+    }
+}
+
+/// Hashes the bits of the value, with -0 hashed as 0 so that equal values hash alike.
+impl Hash for F32Positive {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.value() + 0.0).to_bits().hash(state);
+    }
+}
+
+/// A non-negative amount kept in `f64`, for pools that many small per-cell amounts are added to
+/// and taken from each step, which would be rounded away in `f32` once the pool is large.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct F64Positive {
+    value: f64,
+}
+
+impl F64Positive {
+    pub const fn unchecked(value: f64) -> Self {
+        Self { value }
+    }
+
+    pub fn clipped(value: f64) -> Self {
+        Self { value: value.max(0.0) }
+    }
+
+    pub const fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+impl fmt::Display for F64Positive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl From<F32Positive> for F64Positive {
+    fn from(num: F32Positive) -> Self {
+        Self::unchecked(num.value() as f64)
+    }
+}
+
+impl From<F64Positive> for F32Positive {
+    fn from(num: F64Positive) -> Self {
+        Self::unchecked(num.value() as f32)
+    }
+}
+
+impl AddAssign<F32Positive> for F64Positive {
+    fn add_assign(&mut self, other: F32Positive) {
+        *self = Self::unchecked(self.value() + other.value() as f64);
+    }
+}
+
+impl SubAssign<F32Positive> for F64Positive {
+    fn sub_assign(&mut self, other: F32Positive) {
+        *self = Self::clipped(self.value() - other.value() as f64);
+    }
+}
+
+/// The type of the world's food, toxin, and protein pools: `F64Positive` with the `f64` feature,
+/// else `F32Positive`. Either way, amounts go in and come out as `F32Positive`.
+///
+/// The feature widens only the pools. A cell's energy and health stay in `f32` with or without
+/// it: each is a single cell's amount, changed by a handful of terms a step, so it doesn't
+/// accumulate the rounding error that a pool summing every cell's amounts does. The `World::mean_*`
+/// scans, the other place where a large population's values add up, sum them in `f64` with
+/// `compensated_sum` regardless of the feature.
+#[cfg(feature = "f64")]
+pub type PoolAmount = F64Positive;
+#[cfg(not(feature = "f64"))]
+pub type PoolAmount = F32Positive;
+
+/// Sums the values in `f64` with Neumaier's compensated summation, which keeps the rounding
+/// error from growing with the number of values.
+pub fn compensated_sum<I: Iterator<Item=f64>>(values: I) -> f64 {
+    let mut sum = 0.0_f64;
+    let mut compensation = 0.0_f64;
+    for value in values {
+        let next_sum = sum + value;
+        compensation += if sum.abs() >= value.abs() {
+            (sum - next_sum) + value
+        } else {
+            (value - next_sum) + sum
+        };
+        sum = next_sum;
+    }
+    sum + compensation
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct F32ZeroToOne {
     value: f32,
 }
@@ -130,6 +276,8 @@ impl F32ZeroToOne {
         Self { value }
     }
 
+    /// Clamps to 0 to 1, taking NaN to 0 as `F32Positive::clipped` does, which `clamp` would not.
+    #[allow(clippy::manual_clamp)]
     pub fn clipped(value: f32) -> Self {
         Self { value: value.max(0.0).min(1.0) }
     }
@@ -137,6 +285,16 @@ impl F32ZeroToOne {
     pub const fn value(&self) -> f32 {
         self.value
     }
+
+    /// Adds without going above 1, like `+`.
+    pub fn saturating_add(self, other: F32ZeroToOne) -> F32ZeroToOne {
+        Self::clipped(self.value + other.value)
+    }
+
+    /// Subtracts without going below 0, like `-`.
+    pub fn saturating_sub(self, other: F32ZeroToOne) -> F32ZeroToOne {
+        Self::clipped(self.value - other.value)
+    }
 }
 
 impl fmt::Display for F32ZeroToOne {
@@ -157,19 +315,112 @@ impl From<f32> for F32ZeroToOne {
     }
 }
 
+impl From<F32ZeroToOne> for F32Positive {
+    fn from(num: F32ZeroToOne) -> Self {
+        F32Positive::unchecked(num.value())
+    }
+}
+
+impl Add for F32ZeroToOne {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        self.saturating_add(other)
+    }
+}
+
 impl AddAssign for F32ZeroToOne {
     fn add_assign(&mut self, other: Self) {
-        *self = Self::clipped(self.value() + other.value());
+        *self = self.saturating_add(other);
+    }
+}
+
+impl Sub for F32ZeroToOne {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.saturating_sub(other)
     }
 }
 
 impl SubAssign for F32ZeroToOne {
     fn sub_assign(&mut self, other: Self) {
-        *self = Self::clipped(self.value() - other.value());
+        *self = self.saturating_sub(other);
+    }
+}
+
+impl Mul for F32ZeroToOne {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Self::unchecked(self.value() * other.value())
+    }
+}
+
+impl Mul<F32Positive> for F32ZeroToOne {
+    type Output = F32Positive;
+
+    fn mul(self, other: F32Positive) -> Self::Output {
+        other * self
+    }
+}
+
+/// Scales by a plain factor, clipping the result to 0 to 1 like the saturating helpers.
+impl Mul<f32> for F32ZeroToOne {
+    type Output = Self;
+
+    fn mul(self, other: f32) -> Self::Output {
+        Self::clipped(self.value() * other)
+    }
+}
+
+impl Div<usize> for F32ZeroToOne {
+    type Output = Self;
+
+    fn div(self, other: usize) -> Self::Output {
+        Self::unchecked(self.value() / other as f32)
+    }
+}
+
+/// Fractions sum to an `F32Positive`, since their sum can exceed 1.
+impl Sum<F32ZeroToOne> for F32Positive {
+    fn sum<I>(iter: I) -> Self
+        where I: Iterator<Item=F32ZeroToOne>,
+    {
+        iter.fold(Self::unchecked(0.0), |a, b| a + b.into())
+    }
+}
+
+impl<'a> Sum<&'a F32ZeroToOne> for F32Positive {
+    fn sum<I>(iter: I) -> Self
+        where I: Iterator<Item=&'a F32ZeroToOne>,
+    {
+        iter.fold(Self::unchecked(0.0), |a, b| a + (*b).into())
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+/// Compares with the plain number on the left, so that `F32ZeroToOne == x.into()` still infers `x`.
+impl PartialEq<F32ZeroToOne> for f32 {
+    fn eq(&self, other: &F32ZeroToOne) -> bool {
+        *self == other.value()
+    }
+}
+
+impl PartialOrd<F32ZeroToOne> for f32 {
+    fn partial_cmp(&self, other: &F32ZeroToOne) -> Option<Ordering> {
+        self.partial_cmp(&other.value())
+    }
+}
+
+/// Hashes the bits of the value, with -0 hashed as 0 so that equal values hash alike.
+impl Hash for F32ZeroToOne {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.value() + 0.0).to_bits().hash(state);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct F32ZeroToOnePerF32Positive {
     value: f32,
 }
@@ -184,6 +435,8 @@ impl F32ZeroToOnePerF32Positive {
         Self { value }
     }
 
+    /// Clamps to 0 to 1, taking NaN to 0 as `F32Positive::clipped` does, which `clamp` would not.
+    #[allow(clippy::manual_clamp)]
     pub fn clipped(value: f32) -> Self {
         Self { value: value.max(0.0).min(1.0) }
     }
@@ -230,6 +483,7 @@ impl Mul<F32ZeroToOnePerF32Positive> for F32Positive {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn f32_positive_clips() {
@@ -251,10 +505,109 @@ mod tests {
         assert_eq!(nums.iter().sum::<F32Positive>(), 3.0.into());
     }
 
+    #[test]
+    fn f64_positive_accumulates_what_f32_would_lose() {
+        let mut pool = F64Positive::from(F32Positive::from(1.0e8));
+        pool += 1.0.into();
+        pool -= 0.5.into();
+        assert_eq!(pool.value(), 1.0e8 + 0.5);
+        pool -= 2.0e8.into();
+        assert_eq!(pool.value(), 0.0);
+    }
+
+    #[test]
+    fn compensated_sum_keeps_small_values() {
+        let values = [1.0e16, 1.0, -1.0e16, 1.0];
+        assert_eq!(compensated_sum(values.into_iter()), 2.0);
+    }
+
+    #[test]
+    fn f32_positive_mixes_with_plain_numbers() {
+        let num: F32Positive = 3.0.into();
+        assert_eq!(6.0, num * 2.0);
+        assert_eq!(1.5, num * F32ZeroToOne::from(0.5));
+        assert_eq!(1.5, num / 2);
+        assert!(2.0 < num && 3.0 >= num);
+        assert_eq!(f32::MAX, F32Positive::from(f32::MAX).saturating_add(num));
+        assert_eq!(0.0, num.saturating_sub(4.0.into()));
+    }
+
+    #[test]
+    fn f32_positive_scaling_clips_at_zero() {
+        let num: F32Positive = 3.0.into();
+        assert_eq!(0.0, num * -1.0);
+        assert_eq!(0.0, num * f32::NAN);
+    }
+
+    #[test]
+    fn f32_positive_hashes_equal_values_alike() {
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |num: F32Positive| {
+            let mut hasher = DefaultHasher::new();
+            num.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(F32Positive::clipped(-0.0)), hash(F32Positive::unchecked(-0.0)));
+        assert_eq!(hash(F32Positive::unchecked(-0.0)), hash(0.0.into()));
+        assert_ne!(hash(1.0.into()), hash(2.0.into()));
+    }
+
+    #[test]
+    fn f32_zero_to_one_stays_in_range() {
+        let half: F32ZeroToOne = 0.5.into();
+        assert_eq!(1.0, half + 0.75.into());
+        assert_eq!(0.0, half - 0.75.into());
+        assert_eq!(0.25, half * half);
+        assert_eq!(0.25, half / 2);
+        assert_eq!(1.5, [half, half, half].iter().sum::<F32Positive>());
+        assert_eq!(0.0, F32ZeroToOne::default());
+    }
+
+    #[test]
+    fn f32_zero_to_one_scaling_clips_to_range() {
+        let half: F32ZeroToOne = 0.5.into();
+        assert_eq!(1.0, half * 3.0);
+        assert_eq!(0.0, half * -1.0);
+        assert_eq!(0.25, half * 0.5);
+    }
+
     #[test]
     fn f32_zero_to_one_clips() {
         assert_eq!(F32ZeroToOne::clipped(-0.5), 0.0.into());
         assert_eq!(F32ZeroToOne::clipped(0.5), 0.5.into());
         assert_eq!(F32ZeroToOne::clipped(1.5), 1.0.into());
     }
+
+    fn is_positive(num: F32Positive) -> bool {
+        num.value() >= 0.0
+    }
+
+    fn is_zero_to_one(num: F32ZeroToOne) -> bool {
+        (0.0..=1.0).contains(&num.value())
+    }
+
+    proptest! {
+        #[test]
+        fn clipping_any_float_gives_an_in_range_number(value in prop::num::f32::ANY) {
+            prop_assert!(is_positive(F32Positive::clipped(value)));
+            prop_assert!(is_zero_to_one(F32ZeroToOne::clipped(value)));
+        }
+
+        #[test]
+        fn f32_positive_arithmetic_stays_positive(a in 0.0_f32..1e6, b in 0.0_f32..1e6, divisor in 1e-3_f32..1e6, count in 1_usize..1000) {
+            let (a, b, divisor) = (F32Positive::unchecked(a), F32Positive::unchecked(b), F32Positive::unchecked(divisor));
+            for result in [a + b, a - b, a * b, a / divisor, a / count, a.saturating_add(b), a.saturating_sub(b), a.min(b), a.max(b)] {
+                prop_assert!(is_positive(result), "{}", result);
+            }
+        }
+
+        #[test]
+        fn f32_zero_to_one_arithmetic_stays_in_range(a in 0.0_f32..=1.0, b in 0.0_f32..=1.0, count in 1_usize..1000) {
+            let (a, b) = (F32ZeroToOne::unchecked(a), F32ZeroToOne::unchecked(b));
+            for result in [a + b, a - b, a * b, a / count, a.saturating_add(b), a.saturating_sub(b)] {
+                prop_assert!(is_zero_to_one(result), "{}", result);
+            }
+            prop_assert!(is_positive(a * F32Positive::from(b)));
+        }
+    }
 }
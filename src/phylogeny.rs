@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use crate::cell::Cell;
+use crate::world::{World, WorldObserver};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Lineage {
+    parent_id: Option<u64>,
+    alive: bool,
+    num_children: u32,
+}
+
+/// The ancestry of a world's cells, built by observing its births and deaths. Extinct lineages
+/// are pruned as soon as their last cell dies, so only the ancestors of living cells are kept,
+/// plus a sample of extinct cells (and their ancestors) if `with_extinct_sample_interval` is set.
+/// That keeps the memory used proportional to the population, not to the number of births.
+#[derive(Clone, Debug, Default)]
+pub struct Phylogeny {
+    lineages: HashMap<u64, Lineage>,
+    extinct_sample_interval: u64,
+}
+
+impl Phylogeny {
+    /// Starts with the world's current cells as founders.
+    pub fn new(world: &World) -> Self {
+        let mut phylogeny = Self::default();
+        for state in world.cells().states() {
+            phylogeny.add(state.id, state.parent_id);
+        }
+        phylogeny
+    }
+
+    /// Keeps every extinct cell whose id is a multiple of `interval`, or none if it is 0.
+    pub fn with_extinct_sample_interval(mut self, interval: u64) -> Self {
+        self.extinct_sample_interval = interval;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.lineages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lineages.is_empty()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.lineages.contains_key(&id)
+    }
+
+    pub fn is_alive(&self, id: u64) -> bool {
+        self.lineages.get(&id).is_some_and(|lineage| lineage.alive)
+    }
+
+    pub fn parent(&self, id: u64) -> Option<u64> {
+        self.lineages.get(&id).and_then(|lineage| lineage.parent_id)
+    }
+
+    /// The ids of the cell's ancestors that are still tracked, parent first.
+    pub fn ancestors(&self, id: u64) -> Vec<u64> {
+        let mut ancestors = vec![];
+        let mut current = self.parent(id);
+        while let Some(ancestor_id) = current {
+            if !self.contains(ancestor_id) {
+                break;
+            }
+            ancestors.push(ancestor_id);
+            current = self.parent(ancestor_id);
+        }
+        ancestors
+    }
+
+    fn add(&mut self, id: u64, parent_id: Option<u64>) {
+        if let Some(parent) = parent_id.and_then(|parent_id| self.lineages.get_mut(&parent_id)) {
+            parent.num_children += 1;
+        }
+        self.lineages.insert(id, Lineage { parent_id, alive: true, num_children: 0 });
+    }
+
+    fn prune(&mut self, id: u64) {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            match self.lineages.get(&id) {
+                Some(lineage) if !lineage.alive && lineage.num_children == 0 && !self.is_sampled(id) => {
+                    current = lineage.parent_id;
+                    self.lineages.remove(&id);
+                    if let Some(parent) = current.and_then(|parent_id| self.lineages.get_mut(&parent_id)) {
+                        parent.num_children -= 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn is_sampled(&self, id: u64) -> bool {
+        self.extinct_sample_interval != 0 && id.is_multiple_of(self.extinct_sample_interval)
+    }
+}
+
+impl WorldObserver for Phylogeny {
+    fn on_birth(&mut self, cell: &Cell) {
+        self.add(cell.id(), cell.parent_id());
+    }
+
+    fn on_death(&mut self, cell: &Cell) {
+        if let Some(lineage) = self.lineages.get_mut(&cell.id()) {
+            lineage.alive = false;
+        }
+        self.prune(cell.id());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{CellConstants, NullMutationNumberSource};
+    use crate::genome::Genome;
+
+    fn cell(id: u64) -> Cell {
+        let mut cell = Cell::new(&Rc::new(CellConstants::DEFAULT), Genome::DEFAULT);
+        cell.set_id(id);
+        cell
+    }
+
+    #[test]
+    fn extinct_lineages_are_pruned_up_to_living_ancestry() {
+        let mut phylogeny = Phylogeny::default();
+        phylogeny.add(0, None);
+        phylogeny.add(1, Some(0));
+        phylogeny.add(2, Some(1));
+        phylogeny.add(3, Some(0));
+        phylogeny.on_death(&cell(0));
+        phylogeny.on_death(&cell(1));
+
+        phylogeny.on_death(&cell(2));
+
+        assert!(!phylogeny.contains(1));
+        assert!(!phylogeny.contains(2));
+        assert_eq!(phylogeny.ancestors(3), vec![0]);
+
+        phylogeny.on_death(&cell(3));
+
+        assert!(phylogeny.is_empty());
+    }
+
+    #[test]
+    fn sampled_extinct_cells_keep_their_ancestry() {
+        let mut phylogeny = Phylogeny::default().with_extinct_sample_interval(2);
+        phylogeny.add(1, None);
+        phylogeny.add(2, Some(1));
+        phylogeny.add(3, Some(2));
+        for id in [1, 2, 3] {
+            phylogeny.on_death(&cell(id));
+        }
+
+        assert_eq!(phylogeny.len(), 2);
+        assert!(!phylogeny.is_alive(2));
+        assert_eq!(phylogeny.ancestors(2), vec![1]);
+    }
+
+    #[test]
+    fn tracks_world_births_and_deaths() {
+        let constants = Rc::new(CellConstants {
+            create_child_energy: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants, Genome { child_threshold_energy: 1.0.into(), ..Genome::DEFAULT })
+                .with_energy(3.0.into()),
+        ]);
+        let mut phylogeny = Phylogeny::new(&world);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        for _ in 0..3 {
+            world.step_observed(&mut mutation_number_source, &mut phylogeny);
+        }
+
+        for state in world.cells().states() {
+            assert!(phylogeny.is_alive(state.id));
+            assert_eq!(phylogeny.ancestors(state.id).last().copied().unwrap_or(state.id), 0);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::cell::CellState;
+use crate::genome::Genome;
+use crate::number_types::{F32Positive, F32ZeroToOne};
+use crate::run_id::RunId;
+use crate::world::World;
+
+/// File formats for population dumps, chosen by the dump file's extension: `.json` for JSON and
+/// anything else for CSV.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DumpFormat {
+    Csv,
+    Json,
+}
+
+impl DumpFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => DumpFormat::Json,
+            _ => DumpFormat::Csv,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PopulationRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_id: Option<RunId>,
+    step: u32,
+    cells: Vec<CellRef<'a>>,
+}
+
+#[derive(Serialize)]
+struct CellRef<'a> {
+    index: usize,
+    genome: &'a Genome,
+    state: StateRef<'a>,
+}
+
+/// A cell's state together with the energy and health that `CellColumns` keeps apart from the
+/// rest, so that dumps read as they did when they were one.
+#[derive(Serialize)]
+struct StateRef<'a> {
+    energy: F32Positive,
+    health: F32ZeroToOne,
+    #[serde(flatten)]
+    rest: &'a CellState,
+}
+
+/// Writes every cell's genome and state to a file, for analyzing evolved trait distributions
+/// offline. JSON dumps also record the run ID, if any.
+pub fn save(path: &Path, step: u32, run_id: Option<RunId>, world: &World) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match DumpFormat::for_path(path) {
+        DumpFormat::Csv => write_csv(&mut writer, step, world)?,
+        DumpFormat::Json => write_json(&mut writer, step, run_id, world)?,
+    }
+    writer.flush()
+}
+
+/// The file for a dump taken partway through a run, e.g. `population.500.csv` at step 500 for
+/// `population.csv`, so that earlier dumps are not overwritten.
+pub fn path_for_step(path: &Path, step: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, step, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, step),
+    };
+    path.with_file_name(file_name)
+}
+
+pub fn write_csv<W: Write>(writer: &mut W, step: u32, world: &World) -> io::Result<()> {
+    let gene_names: Vec<&str> = Genome::gene_names().collect();
+    writeln!(writer, "step,index,{},energy,health,age,attack_energy,trade_energy", gene_names.join(","))?;
+    for cell in world.cells().iter() {
+        let (index, state) = (cell.index(), cell.state());
+        let gene_values: Vec<String> = cell.genome().gene_values().map(|value| value.to_string()).collect();
+        writeln!(writer, "{},{},{},{},{},{},{},{}",
+                 step,
+                 index,
+                 gene_values.join(","),
+                 cell.energy(),
+                 cell.health(),
+                 state.age,
+                 state.attack_energy,
+                 state.trade_energy)?;
+    }
+    Ok(())
+}
+
+pub fn write_json<W: Write>(writer: &mut W, step: u32, run_id: Option<RunId>, world: &World) -> io::Result<()> {
+    let cells = world.cells().iter()
+        .map(|cell| CellRef {
+            index: cell.index(),
+            genome: cell.genome(),
+            state: StateRef { energy: cell.energy(), health: cell.health(), rest: cell.state() },
+        })
+        .collect();
+    serde_json::to_writer(writer, &PopulationRef { run_id, step, cells })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+
+    fn world() -> World {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        World::new().with_cells(vec![
+            Cell::new(&constants, Genome {
+                attempted_eating_energy: 2.0.into(),
+                ..Genome::ZERO
+            })
+                .with_energy(5.0.into())
+                .with_health(0.5.into()),
+        ])
+    }
+
+    #[test]
+    fn csv_dump_has_a_row_per_cell() {
+        let mut output = vec![];
+
+        write_csv(&mut output, 7, &world()).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("step,index,attempted_eating_energy,"));
+        assert!(lines[0].ends_with(",energy,health,age,attack_energy,trade_energy"));
+        assert!(lines[1].starts_with("7,0,2,"));
+        assert!(lines[1].ends_with(",5,0.5,0,0,0"));
+    }
+
+    #[test]
+    fn json_dump_has_genome_and_state_per_cell() {
+        let mut output = vec![];
+
+        write_json(&mut output, 7, Some("2a".parse().unwrap()), &world()).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["run_id"], "0000002a");
+        assert_eq!(json["step"], 7);
+        assert_eq!(json["cells"][0]["index"], 0);
+        assert_eq!(json["cells"][0]["genome"]["attempted_eating_energy"], 2.0);
+        assert_eq!(json["cells"][0]["state"]["health"], 0.5);
+    }
+
+    #[test]
+    fn interval_dumps_get_step_in_file_name() {
+        assert_eq!(path_for_step(Path::new("out/population.csv"), 500), PathBuf::from("out/population.500.csv"));
+        assert_eq!(path_for_step(Path::new("population"), 500), PathBuf::from("population.500"));
+        assert_eq!(DumpFormat::for_path(Path::new("population.JSON")), DumpFormat::Json);
+        assert_eq!(DumpFormat::for_path(Path::new("population.csv")), DumpFormat::Csv);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::cell::{BehaviorContext, CellMut};
+
+/// Something a cell does each step it is active, after budgeting its energy. The built-in
+/// `CellProcess`es run in the `ProcessOrder` of the cell's constants, followed by their
+/// `behaviors`, so that new behaviors can be tried without
+/// changing `CellMut::step`.
+pub trait CellBehavior {
+    fn name(&self) -> &str;
+
+    fn run(&self, cell: &mut CellMut, context: &mut BehaviorContext);
+}
+
+/// The behaviors added to the built-in processes, in the order they run. They are usually
+/// unit structs listed in a constant, e.g. `CellBehaviors::new(&[&Sunbathing])`. Like observers,
+/// they are code rather than data, so they are not saved with checkpoints and must be given
+/// again to resumed cells' constants.
+#[derive(Clone, Copy, Default)]
+pub struct CellBehaviors(&'static [&'static dyn CellBehavior]);
+
+impl CellBehaviors {
+    pub const NONE: CellBehaviors = CellBehaviors(&[]);
+
+    pub const fn new(behaviors: &'static [&'static dyn CellBehavior]) -> Self {
+        CellBehaviors(behaviors)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&'static dyn CellBehavior> {
+        self.0.iter().copied()
+    }
+}
+
+impl PartialEq for CellBehaviors {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.iter().zip(other.iter()).all(|(a, b)| a.name() == b.name())
+    }
+}
+
+impl fmt::Debug for CellBehaviors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter().map(|behavior| behavior.name())).finish()
+    }
+}
+
+/// One of the things a cell does with its budgeted energy during a step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellProcess {
+    /// Eat food and digest it into energy.
+    Eat,
+    /// Absorb light and convert it into energy.
+    Photosynthesize,
+    /// Lose `health_reduction_from_entropy`.
+    Entropy,
+    /// Convert healing energy into health.
+    Heal,
+}
+
+impl CellProcess {
+    const ALL: [CellProcess; 4] = [CellProcess::Eat, CellProcess::Photosynthesize, CellProcess::Entropy, CellProcess::Heal];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            CellProcess::Eat => "eat",
+            CellProcess::Photosynthesize => "photosynthesize",
+            CellProcess::Entropy => "entropy",
+            CellProcess::Heal => "heal",
+        }
+    }
+}
+
+/// The order in which a cell runs its processes after budgeting its energy each step. Every
+/// process runs exactly once. With large per-step amounts the order matters, e.g. a cell near
+/// death survives entropy only if it heals first, so runs can be repeated with other orders to
+/// test how sensitive their outcomes are to it. Orders are written as comma-separated process
+/// names, e.g. `heal,entropy,eat,photosynthesize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProcessOrder([CellProcess; 4]);
+
+impl ProcessOrder {
+    /// Gain energy, suffer entropy, then heal.
+    pub const DEFAULT: ProcessOrder = ProcessOrder(CellProcess::ALL);
+
+    pub fn processes(self) -> [CellProcess; 4] {
+        self.0
+    }
+}
+
+impl Default for ProcessOrder {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FromStr for ProcessOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names: Vec<&str> = s.split(',').map(str::trim).collect();
+        let mut processes = CellProcess::ALL;
+        for (process, name) in processes.iter_mut().zip(&names) {
+            *process = CellProcess::ALL.into_iter()
+                .find(|process| process.name() == *name)
+                .ok_or_else(|| format!("unknown cell process '{}' (expected eat, photosynthesize, entropy, or heal)", name))?;
+        }
+        if names.len() != processes.len() || CellProcess::ALL.iter().any(|process| !processes.contains(process)) {
+            return Err(format!("process order '{}' must list each of eat, photosynthesize, entropy, and heal once", s));
+        }
+        Ok(ProcessOrder(processes))
+    }
+}
+
+impl fmt::Display for ProcessOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(|process| process.name()).collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_lists_each_process_once() {
+        let order: ProcessOrder = "heal, entropy,eat,photosynthesize".parse().unwrap();
+        assert_eq!(order.processes(), [CellProcess::Heal, CellProcess::Entropy, CellProcess::Eat, CellProcess::Photosynthesize]);
+        assert_eq!(order.to_string(), "heal,entropy,eat,photosynthesize");
+        assert_eq!(ProcessOrder::DEFAULT.to_string().parse(), Ok(ProcessOrder::DEFAULT));
+
+        assert!("heal,entropy,eat".parse::<ProcessOrder>().is_err());
+        assert!("heal,heal,eat,photosynthesize".parse::<ProcessOrder>().is_err());
+        assert!("heal,entropy,eat,photosynthesize,eat".parse::<ProcessOrder>().is_err());
+        assert!("heal,entropy,eat,sleep".parse::<ProcessOrder>().is_err());
+    }
+}
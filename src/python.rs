@@ -0,0 +1,115 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+use crate::genome::Genome;
+use crate::simulation::Simulation;
+use crate::simulation_config::SimulationConfig;
+
+/// A `Simulation` for Python, e.g. in a notebook after `maturin develop --release` has built the
+/// `evo2` module. It is configured with the fields of a `SimulationConfig` as keyword arguments
+/// and reports in dictionaries, so that results drop straight into tools such as pandas.
+#[pyclass(name = "Simulation", unsendable)]
+pub struct PySimulation {
+    simulation: Simulation,
+}
+
+// pyo3 generates the methods' wrappers beside the impl, where they convert errors that are
+// already `PyErr`s, so the allow goes on an item enclosing both.
+#[allow(clippy::useless_conversion)]
+const _: () = {
+    #[pymethods]
+    impl PySimulation {
+        /// Creates a simulation, e.g. `Simulation(seed=3, cells=10, added_food=5, genome={...})`,
+        /// raising `ValueError` if the configuration is invalid.
+        #[new]
+        #[pyo3(signature = (**config))]
+        fn new(py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+            let config_json = match config {
+                Some(config) => py.import_bound("json")?.call_method1("dumps", (config,))?.extract()?,
+                None => "{}".to_string(),
+            };
+            let simulation = SimulationConfig::from_json(&config_json)
+                .and_then(|config| config.simulation())
+                .map_err(PyValueError::new_err)?;
+            Ok(PySimulation { simulation })
+        }
+
+        /// The number of steps run so far.
+        #[getter]
+        fn step(&self) -> u32 {
+            self.simulation.step()
+        }
+
+        #[getter]
+        fn num_cells(&self) -> usize {
+            self.simulation.world().num_cells()
+        }
+
+        /// Advances up to `steps` steps, stopping early if every cell dies, and returns the
+        /// `EpochStats` over them.
+        #[pyo3(signature = (steps = 1))]
+        fn run_for<'py>(&mut self, py: Python<'py>, steps: u32) -> PyResult<Bound<'py, PyAny>> {
+            to_python(py, &self.simulation.run_for(steps))
+        }
+
+        /// The `EpochSnapshot` of the current step, starting a new epoch.
+        fn epoch_snapshot<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+            to_python(py, &self.simulation.epoch_snapshot())
+        }
+
+        /// A dictionary of gene values by gene name for each living cell.
+        fn genomes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+            let genomes = PyList::empty_bound(py);
+            for genome in self.simulation.world().cells().genomes() {
+                let genes = PyDict::new_bound(py);
+                for (name, value) in Genome::gene_names().zip(genome.gene_values()) {
+                    genes.set_item(name, value)?;
+                }
+                genomes.append(genes)?;
+            }
+            Ok(genomes)
+        }
+    }
+};
+
+/// Converts `value` to Python by way of JSON.
+fn to_python<'py, T: Serialize>(py: Python<'py>, value: &T) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_string(value).expect("stats serialize to JSON");
+    py.import_bound("json")?.call_method1("loads", (json,))
+}
+
+#[pymodule]
+fn evo2(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySimulation>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+
+    #[test]
+    fn simulation_runs_from_keyword_config_to_dictionaries() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let config = PyDict::new_bound(py);
+            config.set_item("seed", 3).unwrap();
+            config.set_item("cells", 4).unwrap();
+            config.set_item("genome", [("attempted_eating_energy", 1.0)].into_py_dict_bound(py)).unwrap();
+            let mut simulation = PySimulation::new(py, Some(&config)).unwrap();
+
+            let stats = simulation.run_for(py, 2).unwrap();
+
+            assert_eq!(stats.get_item("num_steps").unwrap().extract::<u32>().unwrap(), 2);
+            assert_eq!(simulation.step(), 2);
+            let genomes = simulation.genomes(py).unwrap();
+            assert_eq!(genomes.len(), 4);
+            let eating = genomes.get_item(0).unwrap().get_item("attempted_eating_energy").unwrap();
+            assert_eq!(eating.extract::<f32>().unwrap(), 1.0);
+
+            config.set_item("sede", 3).unwrap();
+            assert!(PySimulation::new(py, Some(&config)).err().unwrap().is_instance_of::<PyValueError>(py));
+        });
+    }
+}
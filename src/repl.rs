@@ -0,0 +1,193 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use crate::cell::RandomMutationNumberSource;
+use crate::genome::Genome;
+use crate::interventions::Action;
+use crate::main_support::{InterventionPlanner, WorldChange};
+use crate::number_types::F32Positive;
+use crate::population_dump;
+use crate::run_id::RunId;
+use crate::world::World;
+
+const HELP: &str = "\
+commands:
+  step [<steps>]                     step the world (once by default)
+  stats                              show population and food stats
+  cell <id>                          show a cell's genome and state
+  food <amount>                      set the world's food
+  kill <fraction>                    kill this fraction of the cells, chosen at random
+  add <count> [<gene>=<value>,...]   add cells with the gene means, overridden by any genes given
+  set <constant> <value>             set a cell constant for every cell from now on
+  dump <file>                        write a population dump
+  help                               show this help
+  quit                               stop";
+
+/// A command typed at the interactive prompt.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Step(u32),
+    Stats,
+    Cell(u64),
+    Food(F32Positive),
+    /// One of the actions intervention scripts take, in the same form.
+    Intervene(Action),
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words[..] {
+            ["step"] => Ok(Command::Step(1)),
+            ["step", steps] => steps.parse().map(Command::Step)
+                .map_err(|_| format!("invalid number of steps '{}'", steps)),
+            ["stats"] => Ok(Command::Stats),
+            ["cell", id] => id.parse().map(Command::Cell)
+                .map_err(|_| format!("invalid cell id '{}'", id)),
+            ["food", amount] => amount.parse::<f32>().ok()
+                .filter(|amount| *amount >= 0.0)
+                .map(|amount| Command::Food(amount.into()))
+                .ok_or_else(|| format!("food must be a number of at least 0 (got {})", amount)),
+            ["help"] => Ok(Command::Help),
+            ["quit"] | ["exit"] => Ok(Command::Quit),
+            [verb, ..] if ["kill", "add", "set", "dump"].contains(&verb) => s.parse().map(Command::Intervene),
+            _ => Err(format!("unknown command '{}' (type help for the commands)", s.trim())),
+        }
+    }
+}
+
+/// Reads commands from `input`, one per line, and carries them out on the world, writing
+/// prompts and results to `output`, until the user quits or the input ends. Steps count on from
+/// `first_step`.
+pub fn run<R: BufRead, W: Write>(input: R, output: &mut W, world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+                                 first_step: u32, planner: &mut InterventionPlanner, run_id: Option<RunId>) -> io::Result<()> {
+    let mut step = first_step;
+    writeln!(output, "# type help for the commands")?;
+    write_stats(output, world, step)?;
+    let mut lines = input.lines();
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(Command::Step(steps)) => {
+                let (mut num_born, mut num_died) = (0, 0);
+                for _ in 0..steps {
+                    if world.num_cells() == 0 {
+                        break;
+                    }
+                    let (born, died) = world.step(mutation_number_source);
+                    num_born += born;
+                    num_died += died;
+                    step += 1;
+                }
+                writeln!(output, "+{} -{}", num_born, num_died)?;
+                write_stats(output, world, step)?;
+            }
+            Ok(Command::Stats) => write_stats(output, world, step)?,
+            Ok(Command::Cell(id)) => write_cell(output, world, id)?,
+            Ok(Command::Food(amount)) => {
+                world.set_food(amount);
+                writeln!(output, "food: {}", world.food())?;
+            }
+            Ok(Command::Intervene(action)) => match planner.change(&action) {
+                Ok(change) => apply(output, world, change, step, run_id)?,
+                Err(message) => writeln!(output, "error: {}", message)?,
+            },
+            Ok(Command::Help) => writeln!(output, "{}", HELP)?,
+            Ok(Command::Quit) => break,
+            Err(message) => writeln!(output, "error: {}", message)?,
+        }
+    }
+    Ok(())
+}
+
+fn apply<W: Write>(output: &mut W, world: &mut World, change: WorldChange, step: u32, run_id: Option<RunId>) -> io::Result<()> {
+    match change {
+        WorldChange::Kill(fraction) => writeln!(output, "killed {} cells", world.cull(fraction)),
+        WorldChange::AddCells(cells) => {
+            let num_added = cells.len();
+            world.add_cells(cells);
+            writeln!(output, "added {} cells", num_added)
+        }
+        WorldChange::SetCellConstants(constants) => {
+            world.set_cell_constants(&constants);
+            writeln!(output, "constants set")
+        }
+        WorldChange::Dump(path) => match population_dump::save(&path, step, run_id, world) {
+            Ok(()) => writeln!(output, "dumped {} cells to {}", world.num_cells(), path.display()),
+            Err(err) => writeln!(output, "error: cannot dump population to {}: {}", path.display(), err),
+        },
+    }
+}
+
+fn write_stats<W: Write>(output: &mut W, world: &World, step: u32) -> io::Result<()> {
+    let totals = world.totals();
+    writeln!(output, "step {}: {} cells (h: {}, e: {}, a: {}/{}), food {}, protein {}; {} born and {} died in all",
+             step, world.num_cells(), world.mean_health(), world.mean_energy(), world.mean_age(), world.max_age(),
+             world.food(), world.protein(), totals.num_born, totals.num_died)
+}
+
+fn write_cell<W: Write>(output: &mut W, world: &World, id: u64) -> io::Result<()> {
+    let cell = match world.find_cell(id) {
+        Some(cell) => cell,
+        None => return writeln!(output, "error: no living cell has id {}", id),
+    };
+    let state = cell.state();
+    let parent = state.parent_id.map_or("none".to_string(), |parent_id| parent_id.to_string());
+    writeln!(output, "cell {}: parent {}, {} children, age {}, energy {}, health {}{}",
+             state.id, parent, state.num_children, state.age, cell.energy(), cell.health(),
+             if state.dormant { ", dormant" } else { "" })?;
+    for (name, value) in Genome::gene_names().zip(cell.genome().gene_values()) {
+        writeln!(output, "  {}: {}", name, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::main_support::{Args, WorldArgs};
+
+    #[test]
+    fn commands_parse_from_their_lines() {
+        assert_eq!("step".parse(), Ok(Command::Step(1)));
+        assert_eq!("step 10".parse(), Ok(Command::Step(10)));
+        assert_eq!("cell 3".parse(), Ok(Command::Cell(3)));
+        assert_eq!("food 2.5".parse(), Ok(Command::Food(2.5.into())));
+        assert_eq!("kill 0.5".parse(), Ok(Command::Intervene(Action::Kill(0.5.into()))));
+        assert!("food -1".parse::<Command>().is_err());
+        assert!("fly".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn session_steps_inspects_and_changes_the_world() {
+        let args = Args { world: WorldArgs { cells: 2, seed: Some(0), ..WorldArgs::DEFAULT }, ..Args::DEFAULT };
+        let (mut world, mut mutation_number_source) = crate::main_support::create_world_and_mutation_number_source(&args);
+        let mut planner = InterventionPlanner::new(Some(&args.world), RunId::generate());
+        let input = "food 7\nadd 3\nstep 2\ncell 0\ncell 99\nfly\nquit\nstep\n";
+        let mut output = vec![];
+
+        run(input.as_bytes(), &mut output, &mut world, &mut mutation_number_source, 0, &mut planner, None).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("food: 7"));
+        assert!(output.contains("added 3 cells"));
+        assert!(output.contains("step 2: "));
+        assert!(output.contains("cell 0: parent none"));
+        assert!(output.contains("  attempted_eating_energy: "));
+        assert!(output.contains("error: no living cell has id 99"));
+        assert!(output.contains("error: unknown command 'fly'"));
+        assert!(!output.contains("step 3: "));
+    }
+}
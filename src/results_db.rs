@@ -0,0 +1,154 @@
+use rusqlite::{params, params_from_iter, Connection};
+use std::path::Path;
+use crate::build_info::BuildInfo;
+use crate::genome::Genome;
+use crate::run_id::RunId;
+use crate::world::World;
+
+/// Steps recorded between commits, so that a long run neither pays for a commit every step nor
+/// loses much if it is killed.
+const STEPS_PER_COMMIT: u32 = 1000;
+
+/// A SQLite file of run results that many runs can share, so that experiments can be compared
+/// with queries across runs. Each run adds a row to `runs`, a row per step to `steps`, and a row
+/// per cell per snapshot to `cells`, with one column per gene. Rows from one run are tied
+/// together by the `run` column, the `runs` table's row ID.
+pub struct ResultsDb {
+    connection: Connection,
+    run: i64,
+    steps_since_commit: u32,
+}
+
+impl ResultsDb {
+    pub fn open(path: &Path, run_id: Option<RunId>, first_step: u32) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?, run_id, first_step)
+    }
+
+    fn with_connection(connection: Connection, run_id: Option<RunId>, first_step: u32) -> rusqlite::Result<Self> {
+        let gene_columns: Vec<String> = Genome::gene_names().map(|name| format!("{} REAL NOT NULL", name)).collect();
+        connection.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS runs (
+                 id INTEGER PRIMARY KEY,
+                 run_id TEXT,
+                 build TEXT NOT NULL,
+                 first_step INTEGER NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS steps (
+                 run INTEGER NOT NULL REFERENCES runs(id),
+                 step INTEGER NOT NULL,
+                 num_born INTEGER NOT NULL,
+                 num_died INTEGER NOT NULL,
+                 num_cells INTEGER NOT NULL,
+                 mean_health REAL NOT NULL,
+                 mean_energy REAL NOT NULL,
+                 mean_age REAL NOT NULL,
+                 max_age INTEGER NOT NULL,
+                 food REAL NOT NULL,
+                 PRIMARY KEY (run, step)
+             );
+             CREATE TABLE IF NOT EXISTS cells (
+                 run INTEGER NOT NULL REFERENCES runs(id),
+                 step INTEGER NOT NULL,
+                 id INTEGER NOT NULL,
+                 parent_id INTEGER,
+                 {},
+                 energy REAL NOT NULL,
+                 health REAL NOT NULL,
+                 age INTEGER NOT NULL,
+                 attack_energy REAL NOT NULL,
+                 trade_energy REAL NOT NULL,
+                 PRIMARY KEY (run, step, id)
+             );",
+            gene_columns.join(",\n                 ")))?;
+        connection.execute(
+            "INSERT INTO runs (run_id, build, first_step) VALUES (?1, ?2, ?3)",
+            params![run_id.map(|run_id| run_id.to_string()), BuildInfo::current().to_string(), first_step])?;
+        let run = connection.last_insert_rowid();
+        connection.execute_batch("BEGIN")?;
+        Ok(ResultsDb { connection, run, steps_since_commit: 0 })
+    }
+
+    pub fn record_step(&mut self, step: u32, num_born: usize, num_died: usize, world: &World) -> rusqlite::Result<()> {
+        self.connection.prepare_cached(
+            "INSERT INTO steps (run, step, num_born, num_died, num_cells, mean_health, mean_energy, mean_age, max_age, food)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?
+            .execute(params![
+                self.run,
+                step,
+                num_born,
+                num_died,
+                world.num_cells(),
+                world.mean_health(),
+                world.mean_energy(),
+                world.mean_age(),
+                world.max_age(),
+                world.food().value(),
+            ])?;
+
+        self.steps_since_commit += 1;
+        if self.steps_since_commit >= STEPS_PER_COMMIT {
+            self.connection.execute_batch("COMMIT; BEGIN")?;
+            self.steps_since_commit = 0;
+        }
+        Ok(())
+    }
+
+    /// Records every cell's genome and state.
+    pub fn record_cells(&mut self, step: u32, world: &World) -> rusqlite::Result<()> {
+        let gene_names: Vec<&str> = Genome::gene_names().collect();
+        let placeholders = vec!["?"; gene_names.len() + 9].join(", ");
+        let mut statement = self.connection.prepare_cached(&format!(
+            "INSERT INTO cells (run, step, id, parent_id, {}, energy, health, age, attack_energy, trade_energy) VALUES ({})",
+            gene_names.join(", "), placeholders))?;
+        for cell in world.cells().iter() {
+            let (genome, state) = (cell.genome(), cell.state());
+            let mut values: Vec<rusqlite::types::Value> = vec![
+                self.run.into(),
+                step.into(),
+                (state.id as i64).into(),
+                state.parent_id.map(|parent_id| parent_id as i64).into(),
+            ];
+            values.extend(genome.gene_values().map(|value| (value as f64).into()));
+            values.extend([
+                (cell.energy().value() as f64).into(),
+                (cell.health().value() as f64).into(),
+                state.age.into(),
+                (state.attack_energy.value() as f64).into(),
+                (state.trade_energy.value() as f64).into(),
+            ]);
+            statement.execute(params_from_iter(values))?;
+        }
+        Ok(())
+    }
+
+    /// Commits everything recorded so far.
+    pub fn finish(self) -> rusqlite::Result<()> {
+        self.connection.execute_batch("COMMIT")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants, NullMutationNumberSource};
+
+    #[test]
+    fn records_steps_and_cells_of_each_run() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants, Genome::DEFAULT),
+            Cell::new(&constants, Genome::DEFAULT),
+        ]);
+        let mut db = ResultsDb::with_connection(Connection::open_in_memory().unwrap(), None, 0).unwrap();
+
+        let (num_born, num_died) = world.step(&mut NullMutationNumberSource::new());
+        db.record_step(1, num_born, num_died, &world).unwrap();
+        db.record_cells(1, &world).unwrap();
+
+        let count = |sql: &str| db.connection.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM runs"), 1);
+        assert_eq!(count("SELECT num_cells FROM steps WHERE step = 1"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM cells WHERE run = 1 AND step = 1"), 2);
+    }
+}
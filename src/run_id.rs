@@ -0,0 +1,76 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A short random identifier for one run, written as eight hex digits. It is recorded in every
+/// output the run writes, and output paths can include it as `{run}`, so that concurrent runs
+/// writing to the same directory never overwrite each other's files. Unlike the seed, it is
+/// random even for seeded runs, since sweeps often repeat seeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RunId(u32);
+
+impl RunId {
+    pub const PLACEHOLDER: &'static str = "{run}";
+
+    pub fn generate() -> Self {
+        RunId(rand::random())
+    }
+
+    /// Replaces each `{run}` in the path with this ID.
+    pub fn expand(&self, path: &Path) -> PathBuf {
+        PathBuf::from(path.to_string_lossy().replace(Self::PLACEHOLDER, &self.to_string()))
+    }
+}
+
+impl FromStr for RunId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > 8 {
+            return Err(format!("run ID '{}' must be 1 to 8 hex digits", s));
+        }
+        u32::from_str_radix(s, 16)
+            .map(RunId)
+            .map_err(|_| format!("run ID '{}' must be 1 to 8 hex digits", s))
+    }
+}
+
+impl fmt::Display for RunId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08x}", self.0)
+    }
+}
+
+impl Serialize for RunId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RunId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_id_round_trips_through_hex() {
+        let run_id: RunId = "2a".parse().unwrap();
+        assert_eq!(run_id.to_string(), "0000002a");
+        assert_eq!(run_id.to_string().parse(), Ok(run_id));
+        assert!("not-hex".parse::<RunId>().is_err());
+        assert!("123456789".parse::<RunId>().is_err());
+    }
+
+    #[test]
+    fn run_id_fills_in_path_placeholders() {
+        let run_id = RunId(0xabc);
+        assert_eq!(run_id.expand(Path::new("out/{run}/pop.{run}.csv")), PathBuf::from("out/00000abc/pop.00000abc.csv"));
+        assert_eq!(run_id.expand(Path::new("pop.csv")), PathBuf::from("pop.csv"));
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+use crate::epoch_snapshot::{gene_stats, GeneStats};
+use crate::fitness::{OffspringCounts, OffspringStats};
+use crate::run_id::RunId;
+use crate::world::World;
+
+/// A summary of a whole run, built up step by step and completed at its end, so that its outcome
+/// can be read without scraping the step log: how long it ran, how its population rose and fell,
+/// how skewed reproduction was, and where each gene ended up among the survivors.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<RunId>,
+    pub first_step: u32,
+    pub last_step: u32,
+    pub num_cells: usize,
+    pub min_cells: usize,
+    pub max_cells: usize,
+    /// The step at which the last cell died, if the population died out.
+    pub extinction_step: Option<u32>,
+    /// The times the population died out, including any after which it was reseeded.
+    pub num_extinctions: u64,
+    #[serde(skip)]
+    start_extinctions: u64,
+    /// The mean and standard deviation of each gene over the survivors, in `Genome::gene_names`
+    /// order. Empty until the report is finished, and if there are no survivors.
+    pub genes: Vec<GeneStats>,
+    /// The lifetime offspring counts of the cells that died during the run, if any did and they
+    /// were recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offspring: Option<OffspringStats>,
+}
+
+impl RunReport {
+    pub fn start(run_id: Option<RunId>, first_step: u32, world: &World) -> Self {
+        let num_cells = world.num_cells();
+        RunReport {
+            run_id,
+            first_step,
+            last_step: first_step,
+            num_cells,
+            min_cells: num_cells,
+            max_cells: num_cells,
+            extinction_step: if num_cells == 0 { Some(first_step) } else { None },
+            num_extinctions: 0,
+            start_extinctions: world.totals().num_extinctions,
+            genes: vec![],
+            offspring: None,
+        }
+    }
+
+    pub fn num_steps(&self) -> u32 {
+        self.last_step - self.first_step
+    }
+
+    pub fn record(&mut self, step: u32, world: &World) {
+        let num_cells = world.num_cells();
+        self.last_step = step;
+        self.num_cells = num_cells;
+        self.min_cells = self.min_cells.min(num_cells);
+        self.max_cells = self.max_cells.max(num_cells);
+        if num_cells == 0 && self.extinction_step.is_none() {
+            self.extinction_step = Some(step);
+        }
+        self.num_extinctions = world.totals().num_extinctions - self.start_extinctions;
+    }
+
+    /// Records the last step and summarizes the survivors' genes.
+    pub fn finish(&mut self, step: u32, world: &World) {
+        self.record(step, world);
+        let genomes = world.cells().genomes();
+        self.genes = if genomes.is_empty() { vec![] } else { gene_stats(genomes.iter().map(Rc::as_ref)) };
+    }
+
+    pub fn record_offspring(&mut self, offspring_counts: &OffspringCounts) {
+        self.offspring = offspring_counts.stats();
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "# summary: {} steps ({}-{}), {} cells (min {}, max {})",
+               self.num_steps(), self.first_step, self.last_step, self.num_cells, self.min_cells, self.max_cells)?;
+        if self.num_extinctions > 0 {
+            write!(writer, ", {} extinctions", self.num_extinctions)?;
+        }
+        match self.extinction_step {
+            Some(step) => writeln!(writer, ", extinct at step {}", step)?,
+            None => writeln!(writer)?,
+        }
+        if let Some(offspring) = &self.offspring {
+            offspring.write(writer)?;
+        }
+        for gene in &self.genes {
+            writeln!(writer, "#   {}: mean {}, stdev {}", gene.name, gene.mean, gene.stdev)?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+    use crate::genome::Genome;
+
+    #[test]
+    fn report_tracks_population_and_summarizes_survivor_genes() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cell = |eating: f32| Cell::new(&constants, Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT });
+        let mut report = RunReport::start(None, 10, &World::new().with_cells(vec![cell(1.0)]));
+
+        report.record(11, &World::new().with_cells(vec![cell(1.0), cell(2.0), cell(3.0)]));
+        report.finish(12, &World::new().with_cells(vec![cell(1.0), cell(3.0)]));
+
+        assert_eq!((report.num_steps(), report.num_cells, report.min_cells, report.max_cells), (2, 2, 1, 3));
+        assert_eq!(report.extinction_step, None);
+        assert_eq!(report.genes[0], GeneStats { name: "attempted_eating_energy", mean: 2.0, stdev: 1.0 });
+        assert_eq!(report.genes.len(), Genome::gene_names().count());
+
+        report.finish(13, &World::new());
+        assert_eq!((report.min_cells, report.extinction_step), (0, Some(13)));
+        assert!(report.genes.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+/// A named set of command-line options for a run with interesting dynamics, so that it can be
+/// run with `--scenario <name>` instead of its full list of options. Options given alongside the
+/// scenario override its settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scenario {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub args: &'static [&'static str],
+}
+
+pub const SCENARIOS: [Scenario; 4] = [
+    Scenario {
+        name: "eat_then_starve",
+        description: "one cell eats a fixed supply of food, then starves",
+        args: &[
+            "--cells=1",
+            "--steps=1000",
+            "--initial-energy-mean=10",
+            "--initial-food=50",
+            "--energy-yield-from-digestion=0.5",
+            "--food-yield-from-eating=10",
+            "--health-increase-per-healing-energy=0.5",
+            "--health-reduction-from-entropy=0.5",
+            "--health-reduction-per-energy-expended=0.1",
+            "--attempted-eating-energy-mean=1",
+            "--attempted-healing-energy-mean=2",
+        ],
+    },
+    Scenario {
+        name: "eat_and_reproduce_then_starve",
+        description: "one cell's descendants eat a fixed supply of food, then starve",
+        args: &[
+            "--cells=1",
+            "--steps=1000",
+            "--initial-energy-mean=10",
+            "--initial-food=100",
+            "--create-child-energy=1",
+            "--energy-yield-from-digestion=1",
+            "--food-yield-from-eating=10",
+            "--health-increase-per-healing-energy=0.5",
+            "--health-reduction-from-entropy=0.5",
+            "--health-reduction-per-energy-expended=0.1",
+            "--attempted-eating-energy-mean=1",
+            "--attempted-healing-energy-mean=2",
+            "--child-threshold-energy-mean=2",
+            "--child-threshold-food-mean=1",
+        ],
+    },
+    Scenario {
+        name: "malthus",
+        description: "descendants of one cell multiply until they outgrow a steady food supply",
+        args: &[
+            "--cells=1",
+            "--steps=1000",
+            "--initial-energy-mean=10",
+            "--initial-food=100",
+            "--added-food=10",
+            "--create-child-energy=1",
+            "--energy-yield-from-digestion=1",
+            "--food-yield-from-eating=10",
+            "--health-increase-per-healing-energy=0.5",
+            "--health-reduction-from-entropy=0.5",
+            "--health-reduction-per-energy-expended=0.1",
+            "--maintenance-energy=0.5",
+            "--health-reduction-per-maintenance-shortfall=0.2",
+            "--attempted-eating-energy-mean=1",
+            "--attempted-healing-energy-mean=2",
+            "--child-threshold-energy-mean=2",
+            "--child-threshold-food-mean=1",
+        ],
+    },
+    Scenario {
+        name: "boom_bust",
+        description: "a population that booms while food is plentiful, then dies out in the first famine",
+        args: &[
+            "--cells=1",
+            "--steps=1000",
+            "--initial-energy-mean=10",
+            "--initial-food=100",
+            "--added-food=10",
+            "--environment=boom-bust",
+            "--create-child-energy=1",
+            "--energy-yield-from-digestion=1",
+            "--food-yield-from-eating=10",
+            "--health-increase-per-healing-energy=0.5",
+            "--health-reduction-from-entropy=0.5",
+            "--health-reduction-per-energy-expended=0.1",
+            "--maintenance-energy=0.5",
+            "--health-reduction-per-maintenance-shortfall=0.2",
+            "--attempted-eating-energy-mean=1",
+            "--attempted-healing-energy-mean=2",
+            "--child-threshold-energy-mean=2",
+            "--child-threshold-food-mean=1",
+        ],
+    },
+];
+
+impl Scenario {
+    pub fn find(name: &str) -> Result<&'static Scenario, String> {
+        SCENARIOS.iter()
+            .find(|scenario| scenario.name == name)
+            .ok_or_else(|| {
+                let names: Vec<String> = SCENARIOS.iter()
+                    .map(|scenario| format!("  {}: {}", scenario.name, scenario.description))
+                    .collect();
+                format!("unknown scenario '{}'; scenarios are:\n{}", name, names.join("\n"))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenarios_are_found_by_name() {
+        let scenario = Scenario::find("malthus").unwrap();
+        assert!(scenario.args.contains(&"--maintenance-energy=0.5"));
+        assert!(Scenario::find("utopia").unwrap_err().contains("boom_bust"));
+    }
+}
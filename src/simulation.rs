@@ -0,0 +1,513 @@
+use serde::Serialize;
+use crate::cell::{Cell, MutationNumberSource, MutationStats, RandomMutationNumberSource};
+use crate::checkpoint::Checkpoint;
+use crate::epoch_snapshot::{DeathCauses, EpochSnapshot};
+use crate::world::{World, WorldObserver, WorldTotals};
+
+/// A world together with its mutation randomness and step count, for callers such as GUIs that
+/// advance a run in blocks of steps and poll for aggregated stats rather than printing each step.
+/// Callbacks registered with `on_step`, `on_birth`, and `on_death` let embedding code collect
+/// its own metrics as the run advances, and `epoch_snapshot` gives the standard analysis of the
+/// run at its current step.
+pub struct Simulation {
+    world: World,
+    mutation_number_source: RandomMutationNumberSource,
+    step: u32,
+    step_callbacks: Vec<Box<StepCallback>>,
+    observers: Observers,
+    epoch_start: EpochStart,
+    species_threshold: f32,
+}
+
+type StepCallback = dyn FnMut(u32, &World);
+
+type CellCallback = dyn FnMut(&Cell);
+
+#[derive(Default)]
+struct CellCallbacks {
+    on_birth: Vec<Box<CellCallback>>,
+    on_death: Vec<Box<CellCallback>>,
+}
+
+impl WorldObserver for CellCallbacks {
+    fn on_birth(&mut self, cell: &Cell) {
+        for callback in &mut self.on_birth {
+            callback(cell);
+        }
+    }
+
+    fn on_death(&mut self, cell: &Cell) {
+        for callback in &mut self.on_death {
+            callback(cell);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Observers {
+    cell_callbacks: CellCallbacks,
+    death_causes: DeathCauses,
+}
+
+impl WorldObserver for Observers {
+    fn on_birth(&mut self, cell: &Cell) {
+        self.cell_callbacks.on_birth(cell);
+    }
+
+    fn on_death(&mut self, cell: &Cell) {
+        self.cell_callbacks.on_death(cell);
+        self.death_causes.on_death(cell);
+    }
+}
+
+/// Where the current epoch, i.e. the steps since the last snapshot, started.
+struct EpochStart {
+    step: u32,
+    totals: WorldTotals,
+}
+
+impl EpochStart {
+    fn new(step: u32, world: &World) -> Self {
+        EpochStart { step, totals: world.totals() }
+    }
+}
+
+impl Simulation {
+    pub const DEFAULT_SPECIES_THRESHOLD: f32 = 1.0;
+
+    pub fn new(world: World, mutation_number_source: RandomMutationNumberSource) -> Self {
+        Self::at_step(world, mutation_number_source, 0)
+    }
+
+    pub fn from_checkpoint(checkpoint: Checkpoint) -> Self {
+        Self::at_step(checkpoint.world, checkpoint.mutation_number_source, checkpoint.step)
+    }
+
+    fn at_step(world: World, mutation_number_source: RandomMutationNumberSource, step: u32) -> Self {
+        let epoch_start = EpochStart::new(step, &world);
+        Simulation {
+            world,
+            mutation_number_source,
+            step,
+            step_callbacks: vec![],
+            observers: Observers::default(),
+            epoch_start,
+            species_threshold: Self::DEFAULT_SPECIES_THRESHOLD,
+        }
+    }
+
+    /// Sets the genome distance within which snapshots count cells as the same species.
+    pub fn with_species_threshold(mut self, species_threshold: f32) -> Self {
+        self.species_threshold = species_threshold;
+        self
+    }
+
+    /// Calls `callback` with the step number and world after each step.
+    pub fn on_step<F: FnMut(u32, &World) + 'static>(mut self, callback: F) -> Self {
+        self.step_callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with each cell born, including the children that replace a generation.
+    pub fn on_birth<F: FnMut(&Cell) + 'static>(mut self, callback: F) -> Self {
+        self.observers.cell_callbacks.on_birth.push(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with each cell that dies, including the parents of a replaced generation.
+    pub fn on_death<F: FnMut(&Cell) + 'static>(mut self, callback: F) -> Self {
+        self.observers.cell_callbacks.on_death.push(Box::new(callback));
+        self
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn mutation_number_source(&self) -> &RandomMutationNumberSource {
+        &self.mutation_number_source
+    }
+
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Takes a snapshot of the current step, covering the epoch since the previous snapshot (or
+    /// since the simulation was created), and starts a new epoch.
+    pub fn epoch_snapshot(&mut self) -> EpochSnapshot {
+        let snapshot = EpochSnapshot::take(&self.world, self.epoch_start.step, self.step, &self.epoch_start.totals,
+                                           self.observers.death_causes, self.species_threshold);
+        self.epoch_start = EpochStart::new(self.step, &self.world);
+        self.observers.death_causes = DeathCauses::default();
+        snapshot
+    }
+
+    /// Advances up to `num_steps` steps, stopping early if every cell dies.
+    pub fn run_for(&mut self, num_steps: u32) -> EpochStats {
+        let mut stats = EpochStats::new(self.step, self.world.num_cells());
+        let start_mutation_stats = self.mutation_number_source.stats().clone();
+        for _ in 0..num_steps {
+            if self.world.num_cells() == 0 {
+                break;
+            }
+
+            let (num_born, num_died) = self.world.step_observed(&mut self.mutation_number_source, &mut self.observers);
+            self.step += 1;
+            stats.record_step(num_born, num_died, self.world.num_cells());
+            for callback in &mut self.step_callbacks {
+                callback(self.step, &self.world);
+            }
+        }
+        stats.finish(&self.world);
+        stats.mutations = self.mutation_number_source.stats().since(&start_mutation_stats);
+        stats
+    }
+
+    /// Like `run_for`, but aggregates nothing beyond birth, death, and food totals, for sweeps and
+    /// optimizers that only need the outcome of many long runs of large populations.
+    pub fn run_fast(&mut self, num_steps: u32) -> RunSummary {
+        let mut summary = RunSummary::new(self.step, &self.world);
+        for _ in 0..num_steps {
+            if self.world.num_cells() == 0 {
+                break;
+            }
+
+            self.world.step_observed(&mut self.mutation_number_source, &mut self.observers);
+            self.step += 1;
+            summary.num_steps += 1;
+            for callback in &mut self.step_callbacks {
+                callback(self.step, &self.world);
+            }
+        }
+        summary.finish(&self.world);
+        summary
+    }
+}
+
+/// Steps a world up to `num_steps` times, stopping early if every cell dies, keeping only birth
+/// and death counts and food totals.
+pub fn run_summarized(world: &mut World, mutation_number_source: &mut dyn MutationNumberSource, first_step: u32, num_steps: u32) -> RunSummary {
+    run_summarized_with(world, mutation_number_source, first_step, num_steps, |_, _| {})
+}
+
+/// `run_summarized`, calling `after_step` with each step's number and the world after stepping to it,
+/// e.g. to make scheduled changes to the world.
+pub fn run_summarized_with<F: FnMut(u32, &mut World)>(world: &mut World, mutation_number_source: &mut dyn MutationNumberSource,
+                                                       first_step: u32, num_steps: u32, mut after_step: F) -> RunSummary {
+    let mut summary = RunSummary::new(first_step, world);
+    for _ in 0..num_steps {
+        if world.num_cells() == 0 {
+            break;
+        }
+
+        world.step(mutation_number_source);
+        summary.num_steps += 1;
+        after_step(summary.last_step(), world);
+    }
+    summary.finish(world);
+    summary
+}
+
+/// The outcome of a run made without per-step stats. The counts and food amounts cover only
+/// this run's steps; `World::totals` has them since the world was created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub first_step: u32,
+    pub num_steps: u32,
+    pub num_born: u64,
+    pub num_died: u64,
+    pub food_added: f64,
+    pub food_eaten: f64,
+    pub num_cells: usize,
+    pub num_extinctions: u64,
+    start_totals: WorldTotals,
+}
+
+impl RunSummary {
+    fn new(first_step: u32, world: &World) -> Self {
+        RunSummary {
+            first_step,
+            num_steps: 0,
+            num_born: 0,
+            num_died: 0,
+            food_added: 0.0,
+            food_eaten: 0.0,
+            num_cells: world.num_cells(),
+            num_extinctions: 0,
+            start_totals: world.totals(),
+        }
+    }
+
+    fn finish(&mut self, world: &World) {
+        let totals = world.totals();
+        self.num_born = totals.num_born - self.start_totals.num_born;
+        self.num_died = totals.num_died - self.start_totals.num_died;
+        self.food_added = totals.food_added - self.start_totals.food_added;
+        self.food_eaten = totals.food_eaten - self.start_totals.food_eaten;
+        self.num_cells = world.num_cells();
+        self.num_extinctions = totals.num_extinctions - self.start_totals.num_extinctions;
+    }
+
+    pub fn last_step(&self) -> u32 {
+        self.first_step + self.num_steps
+    }
+}
+
+/// Stats aggregated over the steps of one `Simulation::run_for` call.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EpochStats {
+    pub first_step: u32,
+    pub num_steps: u32,
+    pub num_born: u64,
+    pub num_died: u64,
+    pub min_cells: usize,
+    pub max_cells: usize,
+    pub mean_cells: f64,
+    pub num_cells: usize,
+    pub mean_health: f32,
+    pub mean_energy: f32,
+    pub mean_age: f32,
+    pub food: f32,
+    pub mutations: MutationStats,
+}
+
+impl EpochStats {
+    fn new(first_step: u32, num_cells: usize) -> Self {
+        EpochStats {
+            first_step,
+            num_steps: 0,
+            num_born: 0,
+            num_died: 0,
+            min_cells: num_cells,
+            max_cells: num_cells,
+            mean_cells: 0.0,
+            num_cells,
+            mean_health: 0.0,
+            mean_energy: 0.0,
+            mean_age: 0.0,
+            food: 0.0,
+            mutations: MutationStats::default(),
+        }
+    }
+
+    fn record_step(&mut self, num_born: usize, num_died: usize, num_cells: usize) {
+        self.num_steps += 1;
+        self.num_born += num_born as u64;
+        self.num_died += num_died as u64;
+        self.min_cells = self.min_cells.min(num_cells);
+        self.max_cells = self.max_cells.max(num_cells);
+        self.mean_cells += (num_cells as f64 - self.mean_cells) / self.num_steps as f64;
+    }
+
+    fn finish(&mut self, world: &World) {
+        self.num_cells = world.num_cells();
+        self.mean_health = world.mean_health();
+        self.mean_energy = world.mean_energy();
+        self.mean_age = world.mean_age();
+        self.food = world.food().value();
+        if self.num_steps == 0 {
+            self.mean_cells = self.num_cells as f64;
+        }
+    }
+
+    pub fn is_extinct(&self) -> bool {
+        self.num_cells == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{Cell, CellConstants};
+    use crate::genome::Genome;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn run_for_aggregates_stats_over_steps() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_from_entropy: 0.25.into(),
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(&constants, Genome::DEFAULT),
+                Cell::new(&constants, Genome::DEFAULT).with_health(0.5.into()),
+            ]);
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0));
+
+        let stats = simulation.run_for(3);
+
+        assert_eq!(stats.first_step, 0);
+        assert_eq!(stats.num_steps, 3);
+        assert_eq!(stats.num_died, 1);
+        assert_eq!(stats.min_cells, 1);
+        assert_eq!(stats.max_cells, 2);
+        assert_eq!(stats.num_cells, 1);
+        assert_eq!(simulation.step(), 3);
+    }
+
+    #[test]
+    fn run_summarized_with_changes_the_world_after_steps() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells(vec![Cell::new(&constants, Genome::DEFAULT)]);
+        let mut steps = vec![];
+
+        let summary = run_summarized_with(&mut world, &mut RandomMutationNumberSource::with_seed(0), 5, 3, |step, world| {
+            steps.push(step);
+            if step == 6 {
+                world.add_cells(vec![Cell::new(&constants, Genome::DEFAULT)]);
+            }
+        });
+
+        assert_eq!(steps, vec![6, 7, 8]);
+        assert_eq!(summary.last_step(), 8);
+        assert_eq!(summary.num_cells, 2);
+    }
+
+    #[test]
+    fn epoch_snapshot_covers_the_steps_since_the_last_one() {
+        let old_constants = Rc::new(CellConstants {
+            max_age: 2,
+            ..CellConstants::DEFAULT
+        });
+        let sick_constants = Rc::new(CellConstants {
+            health_reduction_from_entropy: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(&old_constants, Genome::DEFAULT),
+                Cell::new(&sick_constants, Genome::DEFAULT).with_health(0.5.into()),
+                Cell::new(&Rc::new(CellConstants::DEFAULT), Genome::DEFAULT),
+            ]);
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0));
+
+        simulation.run_for(3);
+        let snapshot = simulation.epoch_snapshot();
+
+        assert_eq!((snapshot.first_step, snapshot.step), (0, 3));
+        assert_eq!((snapshot.population.num_cells, snapshot.population.num_died), (1, 2));
+        assert_eq!(snapshot.deaths, DeathCauses { old_age: 1, health_lost: 1, replaced: 0 });
+        assert_eq!((snapshot.diversity.num_species, snapshot.diversity.largest_species_size), (1, 1));
+        assert_eq!(snapshot.genes.len(), Genome::gene_names().count());
+
+        simulation.run_for(1);
+        let snapshot = simulation.epoch_snapshot();
+
+        assert_eq!((snapshot.first_step, snapshot.step), (3, 4));
+        assert_eq!((snapshot.population.num_died, snapshot.deaths.total()), (0, 0));
+    }
+
+    #[test]
+    fn run_fast_summarizes_births_and_deaths() {
+        let constants = Rc::new(CellConstants {
+            max_age: 2,
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![Cell::new(&constants, Genome::DEFAULT), Cell::new(&constants, Genome::DEFAULT)]);
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0));
+
+        let summary = simulation.run_fast(10);
+
+        assert_eq!((summary.first_step, summary.num_steps), (0, 2));
+        assert_eq!((summary.num_born, summary.num_died, summary.num_cells), (0, 2, 0));
+        assert_eq!(summary.last_step(), simulation.step());
+    }
+
+    #[test]
+    fn run_for_stops_at_extinction() {
+        let constants = Rc::new(CellConstants {
+            max_age: 2,
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![Cell::new(&constants, Genome::DEFAULT)]);
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0));
+
+        let stats = simulation.run_for(10);
+
+        assert_eq!(stats.num_steps, 2);
+        assert!(stats.is_extinct());
+        assert_eq!(simulation.run_for(10).num_steps, 0);
+    }
+
+    #[test]
+    fn callbacks_see_steps_births_and_deaths() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_from_entropy: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(&constants, Genome {
+                    child_threshold_energy: 1.into(),
+                    child_threshold_food: 0.into(),
+                    ..Genome::DEFAULT
+                }).with_energy(1.into()),
+            ]);
+        let steps = Rc::new(RefCell::new(vec![]));
+        let births = Rc::new(RefCell::new(0));
+        let deaths = Rc::new(RefCell::new(0));
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0))
+            .on_step({
+                let steps = Rc::clone(&steps);
+                move |step, world| steps.borrow_mut().push((step, world.num_cells()))
+            })
+            .on_birth({
+                let births = Rc::clone(&births);
+                move |_| *births.borrow_mut() += 1
+            })
+            .on_death({
+                let deaths = Rc::clone(&deaths);
+                move |cell| {
+                    assert!(!cell.is_alive());
+                    *deaths.borrow_mut() += 1
+                }
+            });
+
+        let stats = simulation.run_for(2);
+
+        assert_eq!(*steps.borrow(), vec![(1, 2), (2, 2)]);
+        assert_eq!((*births.borrow(), stats.num_born), (2, 2));
+        assert_eq!((*deaths.borrow(), stats.num_died), (1, 1));
+    }
+
+    #[test]
+    fn run_for_counts_mutations() {
+        let constants = Rc::new(CellConstants {
+            attempted_eating_energy_mutation_stdev: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(&constants, Genome {
+                    attempted_eating_energy: 0.1.into(),
+                    child_threshold_energy: 1.into(),
+                    child_threshold_food: 0.into(),
+                    ..Genome::DEFAULT
+                }).with_energy(100.into()),
+            ]);
+        let mut simulation = Simulation::new(world, RandomMutationNumberSource::with_seed(0));
+
+        simulation.run_for(2);
+        let stats = simulation.run_for(3);
+
+        let mutations = &stats.mutations;
+        assert_eq!(mutations.num_genomes_mutated, stats.num_born);
+        assert!(mutations.num_draws >= mutations.num_genomes_mutated);
+        assert!(mutations.num_rejected_draws > 0);
+        let mean_changes = mutations.mean_gene_changes();
+        assert!(mean_changes[0] > 0.0);
+        assert!(mean_changes[1..].iter().all(|&change| change == 0.0));
+    }
+}
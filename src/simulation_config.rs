@@ -0,0 +1,157 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::rc::Rc;
+use crate::cell::{Cell, CellConstants, RandomMutationNumberSource};
+use crate::environments::Environment;
+use crate::food_allocation::FoodAllocationPolicy;
+use crate::food_sources::ConstantFoodSource;
+use crate::genome::Genome;
+use crate::mutation_models::MutationModel;
+use crate::simulation::Simulation;
+use crate::world::World;
+
+/// How to set up a `Simulation`, for the JavaScript and Python bindings, which take it as JSON.
+/// Every field is optional. `genome` and `constants` hold just the fields that differ from
+/// `Genome::DEFAULT` and `CellConstants::DEFAULT`, in which every cell process is off, so a
+/// useful world sets at least the yields of the processes it exercises.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    pub cells: usize,
+    pub initial_energy: f32,
+    pub food: f32,
+    pub added_food: f32,
+    pub protein: f32,
+    pub added_protein: f32,
+    /// A preset pattern of added food, as the `--environment` option takes it.
+    pub environment: Option<String>,
+    /// The length of the run, for environments such as `gradual-decline` that depend on it.
+    pub steps: u32,
+    /// How genes mutate, as the `--mutation-model` option takes it.
+    pub mutation_model: Option<String>,
+    /// How cells share the food, as the `--food-allocation` option takes it.
+    pub food_allocation: Option<String>,
+    pub genome: Map<String, Value>,
+    pub constants: Map<String, Value>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            seed: 0,
+            cells: 100,
+            initial_energy: 10.0,
+            food: 0.0,
+            added_food: 0.0,
+            protein: 0.0,
+            added_protein: 0.0,
+            environment: None,
+            steps: u32::MAX,
+            mutation_model: None,
+            food_allocation: None,
+            genome: Map::new(),
+            constants: Map::new(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid simulation config: {}", err))
+    }
+
+    pub fn simulation(&self) -> Result<Simulation, String> {
+        let genome: Genome = with_overrides(&Genome::DEFAULT, &self.genome)
+            .map_err(|err| format!("invalid genome: {}", err))?;
+        let constants: CellConstants = with_overrides(&CellConstants::DEFAULT, &self.constants)
+            .map_err(|err| format!("invalid constants: {}", err))?;
+        let environment = match &self.environment {
+            Some(name) => name.parse()?,
+            None => Environment::Stable,
+        };
+        let mutation_model = match &self.mutation_model {
+            Some(name) => name.parse()?,
+            None => MutationModel::Gaussian,
+        };
+        let food_allocation = match &self.food_allocation {
+            Some(name) => name.parse()?,
+            None => FoodAllocationPolicy::Equal,
+        };
+        let initial_energy = checked_positive("initial_energy", self.initial_energy)?;
+        let food = checked_positive("food", self.food)?;
+        let added_food = checked_positive("added_food", self.added_food)?;
+        let protein = checked_positive("protein", self.protein)?;
+        let added_protein = checked_positive("added_protein", self.added_protein)?;
+
+        let constants = Rc::new(constants);
+        let mut rng = Pcg64::seed_from_u64(self.seed);
+        let world = World::new()
+            .with_seed(rng.gen())
+            .with_cells((0..self.cells).map(|_| Cell::new(&constants, genome).with_energy(initial_energy.into())).collect())
+            .with_food(food.into())
+            .with_food_sources(vec![environment.food_source(added_food.into(), self.steps, &mut rng)])
+            .with_food_allocation(food_allocation.allocation())
+            .with_protein(protein.into())
+            .with_protein_sources(vec![Box::new(ConstantFoodSource::new(added_protein.into()))]);
+        Ok(Simulation::new(world, RandomMutationNumberSource::with_seed(rng.gen()).with_model(mutation_model)))
+    }
+}
+
+/// Deserializes `base` with the fields in `overrides` replacing its own.
+fn with_overrides<T: Serialize + DeserializeOwned>(base: &T, overrides: &Map<String, Value>) -> Result<T, serde_json::Error> {
+    let mut value = serde_json::to_value(base)?;
+    if let Value::Object(fields) = &mut value {
+        for (name, override_value) in overrides {
+            if !fields.contains_key(name) {
+                return Err(serde::de::Error::custom(format!("unknown field '{}'", name)));
+            }
+            fields.insert(name.clone(), override_value.clone());
+        }
+    }
+    serde_json::from_value(value)
+}
+
+fn checked_positive(name: &str, value: f32) -> Result<f32, String> {
+    if value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{} must be at least 0, not {}", name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_builds_a_simulation() {
+        let config = SimulationConfig::from_json(r#"{
+            "seed": 3,
+            "cells": 4,
+            "added_food": 10,
+            "genome": {"attempted_eating_energy": 1},
+            "constants": {"food_yield_from_eating": 2, "energy_yield_from_digestion": 0.5}
+        }"#).unwrap();
+
+        let simulation = config.simulation().unwrap();
+
+        assert_eq!(simulation.world().num_cells(), 4);
+        assert_eq!(simulation.world().cells().genomes()[0].attempted_eating_energy, 1.0.into());
+    }
+
+    #[test]
+    fn invalid_configs_are_reported() {
+        assert!(SimulationConfig::from_json(r#"{"sede": 3}"#).unwrap_err().contains("sede"));
+        let config = |json: &str| SimulationConfig::from_json(json).unwrap();
+        assert!(config(r#"{"genome": {"eating": 1}}"#).simulation().err().unwrap().contains("eating"));
+        assert!(config(r#"{"constants": {"max_age": -1}}"#).simulation().is_err());
+        assert!(config(r#"{"environment": "tropical"}"#).simulation().err().unwrap().contains("tropical"));
+        assert!(config(r#"{"mutation_model": "cauchy"}"#).simulation().err().unwrap().contains("cauchy"));
+        assert!(config(r#"{"food_allocation": "fair"}"#).simulation().err().unwrap().contains("fair"));
+        assert!(config(r#"{"food": -1}"#).simulation().is_err());
+    }
+}
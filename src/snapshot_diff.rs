@@ -0,0 +1,347 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use crate::cell::CellState;
+use crate::checkpoint::Checkpoint;
+use crate::genome::Genome;
+use crate::population_dump::DumpFormat;
+use crate::species;
+
+/// The cells of a checkpoint or population dump, as far as the file records them. CSV dumps
+/// record no cell ids, so snapshots loaded from them have no `parentage`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub step: u32,
+    pub genomes: Vec<Genome>,
+    /// Each cell's id and parent's id.
+    pub parentage: Option<Vec<(u64, Option<u64>)>>,
+}
+
+/// Just enough of a JSON file to tell a checkpoint from a population dump, without parsing
+/// the rest into `serde_json::Value`, which would round a checkpoint's RNG state.
+#[derive(Deserialize)]
+struct JsonKind {
+    world: Option<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+struct PopulationDump {
+    step: u32,
+    cells: Vec<DumpedCell>,
+}
+
+#[derive(Deserialize)]
+struct DumpedCell {
+    genome: Genome,
+    state: CellState,
+}
+
+impl Snapshot {
+    /// Loads a checkpoint, a JSON population dump, or a CSV population dump, telling the JSON
+    /// files apart by whether they hold a world.
+    pub fn load(path: &Path) -> io::Result<Snapshot> {
+        let contents = fs::read_to_string(path)?;
+        match DumpFormat::for_path(path) {
+            DumpFormat::Json => Self::from_json(&contents),
+            DumpFormat::Csv => Self::from_csv(&contents),
+        }
+    }
+
+    fn from_json(json: &str) -> io::Result<Snapshot> {
+        let kind: JsonKind = serde_json::from_str(json)?;
+        if kind.world.is_some() {
+            let checkpoint: Checkpoint = serde_json::from_str(json)?;
+            let cells = checkpoint.world.cells();
+            Ok(Snapshot {
+                step: checkpoint.step,
+                genomes: cells.genomes().iter().map(|genome| **genome).collect(),
+                parentage: Some(cells.states().iter().map(|state| (state.id, state.parent_id)).collect()),
+            })
+        } else {
+            let dump: PopulationDump = serde_json::from_str(json)?;
+            Ok(Snapshot {
+                step: dump.step,
+                genomes: dump.cells.iter().map(|cell| cell.genome).collect(),
+                parentage: Some(dump.cells.iter().map(|cell| (cell.state.id, cell.state.parent_id)).collect()),
+            })
+        }
+    }
+
+    fn from_csv(csv: &str) -> io::Result<Snapshot> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = csv.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+        let gene_columns: Vec<(usize, &str)> = header.iter().copied().enumerate()
+            .filter(|(_, name)| Genome::gene_names().any(|gene_name| gene_name == *name))
+            .collect();
+        let mut step = 0;
+        let mut genomes = vec![];
+        for line in lines.filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split(',').collect();
+            step = fields[0].parse().map_err(|_| invalid(format!("invalid step '{}'", fields[0])))?;
+            let spec: Vec<String> = gene_columns.iter()
+                .map(|(column, name)| format!("{}={}", name, fields.get(*column).copied().unwrap_or_default()))
+                .collect();
+            genomes.push(Genome::DEFAULT.with_gene_values(&spec.join(",")).map_err(invalid)?);
+        }
+        Ok(Snapshot { step, genomes, parentage: None })
+    }
+
+    /// Writes the snapshot's step, population size, the mean, stdev, and median of each gene, and
+    /// its species, clustered as `species::cluster` does.
+    pub fn write_summary<W: Write>(&self, writer: &mut W, species_threshold: f32) -> io::Result<()> {
+        writeln!(writer, "step: {}", self.step)?;
+        writeln!(writer, "cells: {}", self.genomes.len())?;
+        writeln!(writer, "genes (mean, stdev, median):")?;
+        for (index, name) in Genome::gene_names().enumerate() {
+            let values = sorted_gene_values(&self.genomes, index);
+            writeln!(writer, "  {}: {:.4}, {:.4}, {:.4}", name, mean(&values), stdev(&values), median(&values))?;
+        }
+        species::write(writer, &species::cluster(self.genomes.iter(), species_threshold))
+    }
+}
+
+/// How two snapshots' populations differ.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotDiff {
+    pub steps: (u32, u32),
+    pub num_cells: (usize, usize),
+    /// In `Genome::gene_names` order.
+    pub genes: Vec<GeneDiff>,
+    /// The sizes in each snapshot of the species found by clustering both populations together,
+    /// largest overall first.
+    pub species_sizes: Vec<(usize, usize)>,
+    pub lineage: Option<LineageComposition>,
+}
+
+/// Summary statistics of one gene's values in each snapshot, and the Kolmogorov-Smirnov
+/// statistic: the largest difference between the two populations' cumulative distributions,
+/// from 0 for identical distributions to 1 for ones that do not overlap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneDiff {
+    pub name: &'static str,
+    pub means: (f32, f32),
+    pub stdevs: (f32, f32),
+    pub medians: (f32, f32),
+    pub ks_statistic: f32,
+}
+
+/// Where the second snapshot's cells came from, relative to the first's.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineageComposition {
+    /// Cells present in both snapshots.
+    pub num_surviving: usize,
+    /// Cells whose parents are in the first snapshot.
+    pub num_children: usize,
+    /// Cells descended from the first snapshot's cells more distantly, or unrelated to them.
+    pub num_other: usize,
+    /// Cells of the first snapshot with children or themselves in the second.
+    pub num_ancestors: usize,
+}
+
+/// Compares `before` with `after`, clustering species as `species::cluster` does.
+pub fn diff(before: &Snapshot, after: &Snapshot, species_threshold: f32) -> SnapshotDiff {
+    let genes = Genome::gene_names().enumerate()
+        .map(|(index, name)| {
+            let values1 = sorted_gene_values(&before.genomes, index);
+            let values2 = sorted_gene_values(&after.genomes, index);
+            GeneDiff {
+                name,
+                means: (mean(&values1), mean(&values2)),
+                stdevs: (stdev(&values1), stdev(&values2)),
+                medians: (median(&values1), median(&values2)),
+                ks_statistic: ks_statistic(&values1, &values2),
+            }
+        })
+        .collect();
+    SnapshotDiff {
+        steps: (before.step, after.step),
+        num_cells: (before.genomes.len(), after.genomes.len()),
+        genes,
+        species_sizes: species_sizes(before, after, species_threshold),
+        lineage: match (&before.parentage, &after.parentage) {
+            (Some(parentage1), Some(parentage2)) => Some(lineage_composition(parentage1, parentage2)),
+            _ => None,
+        },
+    }
+}
+
+fn sorted_gene_values(genomes: &[Genome], gene_index: usize) -> Vec<f32> {
+    let mut values: Vec<f32> = genomes.iter()
+        .map(|genome| genome.gene_values().nth(gene_index).unwrap())
+        .collect();
+    values.sort_by(f32::total_cmp);
+    values
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() { 0.0 } else { values.iter().sum::<f32>() / values.len() as f32 }
+}
+
+fn stdev(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = mean(values);
+    (values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt()
+}
+
+fn median(sorted_values: &[f32]) -> f32 {
+    match sorted_values.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (sorted_values[len / 2 - 1] + sorted_values[len / 2]) / 2.0,
+        len => sorted_values[len / 2],
+    }
+}
+
+/// The largest gap between the two empirical cumulative distributions, checked just after
+/// each value in either.
+fn ks_statistic(sorted_values1: &[f32], sorted_values2: &[f32]) -> f32 {
+    if sorted_values1.is_empty() || sorted_values2.is_empty() {
+        return if sorted_values1.len() == sorted_values2.len() { 0.0 } else { 1.0 };
+    }
+    let (mut index1, mut index2) = (0, 0);
+    let mut max_gap: f32 = 0.0;
+    while index1 < sorted_values1.len() && index2 < sorted_values2.len() {
+        let value = sorted_values1[index1].min(sorted_values2[index2]);
+        while index1 < sorted_values1.len() && sorted_values1[index1] <= value {
+            index1 += 1;
+        }
+        while index2 < sorted_values2.len() && sorted_values2[index2] <= value {
+            index2 += 1;
+        }
+        let gap = index1 as f32 / sorted_values1.len() as f32 - index2 as f32 / sorted_values2.len() as f32;
+        max_gap = max_gap.max(gap.abs());
+    }
+    max_gap
+}
+
+/// Clusters both populations together, so that a species means the same thing in each, and
+/// counts each one's members from each population.
+fn species_sizes(before: &Snapshot, after: &Snapshot, threshold: f32) -> Vec<(usize, usize)> {
+    let combined = species::cluster(before.genomes.iter().chain(&after.genomes), threshold);
+    let assign = |genomes: &[Genome]| {
+        let mut sizes = vec![0; combined.len()];
+        for genome in genomes {
+            let values: Vec<f32> = genome.gene_values().collect();
+            let nearest = (0..combined.len())
+                .min_by(|&index1, &index2| {
+                    distance(&combined[index1].mean_gene_values, &values)
+                        .total_cmp(&distance(&combined[index2].mean_gene_values, &values))
+                });
+            if let Some(index) = nearest {
+                sizes[index] += 1;
+            }
+        }
+        sizes
+    };
+    let (sizes1, sizes2) = (assign(&before.genomes), assign(&after.genomes));
+    let mut sizes: Vec<(usize, usize)> = sizes1.into_iter().zip(sizes2)
+        .filter(|(size1, size2)| size1 + size2 > 0)
+        .collect();
+    sizes.sort_by_key(|(size1, size2)| std::cmp::Reverse(size1 + size2));
+    sizes
+}
+
+fn distance(values1: &[f32], values2: &[f32]) -> f32 {
+    values1.iter().zip(values2)
+        .map(|(value1, value2)| (value1 - value2).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+fn lineage_composition(before: &[(u64, Option<u64>)], after: &[(u64, Option<u64>)]) -> LineageComposition {
+    let before_ids: HashSet<u64> = before.iter().map(|(id, _)| *id).collect();
+    let mut ancestors: HashSet<u64> = HashSet::new();
+    let mut composition = LineageComposition { num_surviving: 0, num_children: 0, num_other: 0, num_ancestors: 0 };
+    for (id, parent_id) in after {
+        if before_ids.contains(id) {
+            composition.num_surviving += 1;
+            ancestors.insert(*id);
+        } else if let Some(parent_id) = parent_id.filter(|parent_id| before_ids.contains(parent_id)) {
+            composition.num_children += 1;
+            ancestors.insert(parent_id);
+        } else {
+            composition.num_other += 1;
+        }
+    }
+    composition.num_ancestors = ancestors.len();
+    composition
+}
+
+impl SnapshotDiff {
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "steps: {} -> {}", self.steps.0, self.steps.1)?;
+        writeln!(writer, "cells: {} -> {} ({:+})", self.num_cells.0, self.num_cells.1,
+                 self.num_cells.1 as i64 - self.num_cells.0 as i64)?;
+        writeln!(writer, "genes (mean, stdev, median; KS statistic):")?;
+        for gene in &self.genes {
+            writeln!(writer, "  {}: {:.4} -> {:.4}, {:.4} -> {:.4}, {:.4} -> {:.4}; D {:.3}",
+                     gene.name, gene.means.0, gene.means.1, gene.stdevs.0, gene.stdevs.1,
+                     gene.medians.0, gene.medians.1, gene.ks_statistic)?;
+        }
+        writeln!(writer, "species: {}", self.species_sizes.len())?;
+        for (index, (size1, size2)) in self.species_sizes.iter().enumerate() {
+            writeln!(writer, "  {}: {} -> {}", index, size1, size2)?;
+        }
+        match &self.lineage {
+            Some(lineage) => writeln!(writer, "lineage: {} surviving, {} children, {} other, from {} ancestors",
+                                      lineage.num_surviving, lineage.num_children, lineage.num_other, lineage.num_ancestors),
+            None => writeln!(writer, "lineage: unknown (CSV dumps record no cell ids)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genome(eating: f32) -> Genome {
+        Genome { attempted_eating_energy: eating.into(), ..Genome::ZERO }
+    }
+
+    fn snapshot(step: u32, eatings: &[f32], parentage: Vec<(u64, Option<u64>)>) -> Snapshot {
+        Snapshot { step, genomes: eatings.iter().map(|eating| genome(*eating)).collect(), parentage: Some(parentage) }
+    }
+
+    #[test]
+    fn ks_statistic_measures_distribution_overlap() {
+        assert_eq!(ks_statistic(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 0.0);
+        assert_eq!(ks_statistic(&[1.0, 2.0], &[3.0, 4.0]), 1.0);
+        assert_eq!(ks_statistic(&[1.0, 2.0, 3.0, 4.0], &[3.0, 4.0]), 0.5);
+    }
+
+    #[test]
+    fn diff_compares_sizes_genes_species_and_lineage() {
+        let before = snapshot(10, &[1.0, 1.0], vec![(1, None), (2, None)]);
+        let after = snapshot(20, &[1.0, 5.0, 5.0], vec![(1, None), (3, Some(2)), (4, Some(3))]);
+
+        let diff = diff(&before, &after, 1.0);
+
+        assert_eq!((diff.steps, diff.num_cells), ((10, 20), (2, 3)));
+        let eating = &diff.genes[0];
+        assert_eq!(eating.name, "attempted_eating_energy");
+        assert_eq!((eating.means, eating.medians), ((1.0, 11.0 / 3.0), (1.0, 5.0)));
+        assert!((eating.ks_statistic - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(diff.species_sizes, vec![(2, 1), (0, 2)]);
+        assert_eq!(diff.lineage, Some(LineageComposition { num_surviving: 1, num_children: 1, num_other: 1, num_ancestors: 2 }));
+    }
+
+    #[test]
+    fn csv_dumps_load_without_parentage() {
+        let gene_names: Vec<&str> = Genome::gene_names().collect();
+        let mut gene_values = vec!["0"; gene_names.len()];
+        gene_values[0] = "2.5";
+        let csv = format!("step,index,{},energy\n7,0,{},5\n", gene_names.join(","), gene_values.join(","));
+
+        let snapshot = Snapshot::from_csv(&csv).unwrap();
+
+        assert_eq!(snapshot.step, 7);
+        assert_eq!(snapshot.genomes, vec![Genome { attempted_eating_energy: 2.5.into(), ..Genome::ZERO }]);
+        assert_eq!(snapshot.parentage, None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::io;
+use std::io::Write;
+use crate::genome::Genome;
+use crate::history::{History, RetentionPolicy};
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Sparkline {
+    values: History<f32>,
+}
+
+impl Sparkline {
+    pub fn new(width: usize) -> Self {
+        Self::with_retention(RetentionPolicy::keep_last(width))
+    }
+
+    /// A sparkline that also renders downsampled older values to the left of the recent ones.
+    pub fn with_retention(policy: RetentionPolicy) -> Self {
+        Sparkline {
+            values: History::new(policy),
+        }
+    }
+
+    pub fn push(&mut self, value: f32) {
+        self.values.push(value);
+    }
+
+    pub fn latest(&self) -> Option<f32> {
+        self.values.latest().copied()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.values.memory_bytes()
+    }
+
+    pub fn render(&self) -> String {
+        let min = self.values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = self.values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let range = max - min;
+        self.values.iter()
+            .map(|value| {
+                if range > 0.0 {
+                    let bar = ((value - min) / range * (BARS.len() - 1) as f32).round() as usize;
+                    BARS[bar]
+                } else {
+                    BARS[0]
+                }
+            })
+            .collect()
+    }
+}
+
+struct GeneTrace {
+    #[cfg_attr(not(feature = "cli"), allow(dead_code))]
+    name: &'static str,
+    sparkline: Sparkline,
+}
+
+/// Recent trajectories of the population mean of each gene.
+pub struct GeneTraces {
+    traces: Vec<GeneTrace>,
+}
+
+impl GeneTraces {
+    pub fn new(retention: RetentionPolicy) -> Self {
+        GeneTraces {
+            traces: Genome::gene_names()
+                .map(|name| GeneTrace { name, sparkline: Sparkline::with_retention(retention) })
+                .collect(),
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.traces.iter().map(|trace| trace.sparkline.memory_bytes()).sum()
+    }
+
+    pub fn record<'a, I>(&mut self, genomes: I)
+        where I: Iterator<Item=&'a Genome>,
+    {
+        let mut sums = vec![0.0_f64; self.traces.len()];
+        let mut count = 0;
+        for genome in genomes {
+            for (sum, value) in sums.iter_mut().zip(genome.gene_values()) {
+                *sum += value as f64;
+            }
+            count += 1;
+        }
+
+        if count > 0 {
+            for (trace, sum) in self.traces.iter_mut().zip(sums) {
+                trace.sparkline.push((sum / count as f64) as f32);
+            }
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "  gene traces ({} bytes):", self.memory_bytes())?;
+        let name_width = self.traces.iter().map(|trace| trace.name.len()).max().unwrap_or(0);
+        for trace in &self.traces {
+            writeln!(writer, "  {:name_width$} {} {}",
+                     trace.name,
+                     trace.sparkline.render(),
+                     trace.sparkline.latest().unwrap_or(0.0),
+                     name_width = name_width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_scales_between_min_and_max() {
+        let mut sparkline = Sparkline::new(8);
+        for value in [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0] {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.render(), "▁▂▃▄▅▆▇█");
+    }
+
+    #[test]
+    fn sparkline_keeps_only_most_recent_values() {
+        let mut sparkline = Sparkline::new(2);
+        for value in [5.0, 0.0, 1.0] {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.render(), "▁█");
+    }
+
+    #[test]
+    fn sparkline_renders_downsampled_older_values() {
+        let mut sparkline = Sparkline::with_retention(RetentionPolicy {
+            recent: 2,
+            older: 1,
+            downsample_factor: 2,
+        });
+        for value in [0.0, 7.0, 7.0, 7.0] {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.render(), "▁██");
+    }
+
+    #[test]
+    fn flat_sparkline_renders_lowest_bars() {
+        let mut sparkline = Sparkline::new(3);
+        for value in [2.0, 2.0, 2.0] {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.render(), "▁▁▁");
+    }
+
+    #[test]
+    fn gene_traces_record_population_means() {
+        let genomes = [
+            Genome { attempted_eating_energy: 1.0.into(), ..Genome::DEFAULT },
+            Genome { attempted_eating_energy: 3.0.into(), ..Genome::DEFAULT },
+        ];
+        let mut traces = GeneTraces::new(RetentionPolicy::keep_last(4));
+        traces.record(genomes.iter());
+        assert_eq!(traces.traces[0].sparkline.latest(), Some(2.0));
+    }
+}
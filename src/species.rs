@@ -0,0 +1,100 @@
+use std::cmp::Reverse;
+use std::io;
+use std::io::Write;
+use crate::genome::Genome;
+
+/// A group of cells whose genomes are all within the clustering threshold of the genome of its
+/// first member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    pub size: usize,
+    pub mean_gene_values: Vec<f32>,
+}
+
+/// Clusters genomes into species by simple threshold clustering: each genome joins the first
+/// species whose founding genome is within `threshold` of it (Euclidean distance over gene
+/// values), or else founds a new species. Returns the species largest first.
+pub fn cluster<'a, I>(genomes: I, threshold: f32) -> Vec<Species>
+    where I: Iterator<Item=&'a Genome>,
+{
+    let mut founders: Vec<Vec<f64>> = vec![];
+    let mut sums: Vec<Vec<f64>> = vec![];
+    let mut sizes: Vec<usize> = vec![];
+    for genome in genomes {
+        let values: Vec<f64> = genome.gene_values().map(|value| value as f64).collect();
+        let species_index = match founders.iter().position(|founder| distance(founder, &values) <= threshold as f64) {
+            Some(index) => index,
+            None => {
+                founders.push(values.clone());
+                sums.push(vec![0.0; values.len()]);
+                sizes.push(0);
+                founders.len() - 1
+            }
+        };
+        for (sum, value) in sums[species_index].iter_mut().zip(&values) {
+            *sum += value;
+        }
+        sizes[species_index] += 1;
+    }
+
+    let mut species: Vec<Species> = sums.into_iter().zip(sizes)
+        .map(|(sums, size)| Species {
+            size,
+            mean_gene_values: sums.iter().map(|sum| (sum / size as f64) as f32).collect(),
+        })
+        .collect();
+    species.sort_by_key(|species| Reverse(species.size));
+    species
+}
+
+fn distance(values1: &[f64], values2: &[f64]) -> f64 {
+    values1.iter().zip(values2)
+        .map(|(value1, value2)| (value1 - value2).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+pub fn write<W: Write>(writer: &mut W, species: &[Species]) -> io::Result<()> {
+    writeln!(writer, "  species: {}", species.len())?;
+    let gene_names: Vec<&str> = Genome::gene_names().collect();
+    for (index, species) in species.iter().enumerate() {
+        let genes: Vec<String> = gene_names.iter().zip(&species.mean_gene_values)
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        writeln!(writer, "  {}: {} cells ({})", index + 1, species.size, genes.join(", "))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearby_genomes_form_one_species() {
+        let genomes = [
+            Genome { attempted_eating_energy: 1.0.into(), ..Genome::ZERO },
+            Genome { attempted_eating_energy: 2.0.into(), ..Genome::ZERO },
+        ];
+
+        let species = cluster(genomes.iter(), 1.5);
+
+        assert_eq!(species.len(), 1);
+        assert_eq!(species[0].size, 2);
+        assert_eq!(species[0].mean_gene_values[0], 1.5);
+    }
+
+    #[test]
+    fn distant_genomes_form_species_largest_first() {
+        let genomes = [
+            Genome { attempted_eating_energy: 1.0.into(), ..Genome::ZERO },
+            Genome { attempted_attack_energy: 10.0.into(), ..Genome::ZERO },
+            Genome { attempted_attack_energy: 11.0.into(), ..Genome::ZERO },
+        ];
+
+        let species = cluster(genomes.iter(), 2.0);
+
+        assert_eq!(species.iter().map(|species| species.size).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(species[0].mean_gene_values[4], 10.5);
+    }
+}
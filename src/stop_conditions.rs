@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use crate::genome::Genome;
+use crate::world::World;
+
+/// A rule for ending a run, built from comparisons and stability tests of world quantities
+/// combined with `and`, `or`, and `not`. Parsed from expressions such as
+/// `cells > 10000 or (stable(attempted_eating_energy, 500, 0.01) and step > 5000)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopCondition {
+    Extinct,
+    Compare { quantity: Quantity, comparison: Comparison, value: f64 },
+    Stable(Stability),
+    And(Box<StopCondition>, Box<StopCondition>),
+    Or(Box<StopCondition>, Box<StopCondition>),
+    Not(Box<StopCondition>),
+}
+
+impl StopCondition {
+    /// Updates any stability windows with this step and reports whether the run should stop.
+    /// Every subcondition is updated, even when the result is already known.
+    pub fn should_stop(&mut self, step: u32, world: &World) -> bool {
+        match self {
+            StopCondition::Extinct => world.num_cells() == 0,
+            StopCondition::Compare { quantity, comparison, value } =>
+                comparison.holds(quantity.value(step, world), *value),
+            StopCondition::Stable(stability) => stability.update(step, world),
+            StopCondition::And(left, right) => {
+                let left = left.should_stop(step, world);
+                let right = right.should_stop(step, world);
+                left && right
+            }
+            StopCondition::Or(left, right) => {
+                let left = left.should_stop(step, world);
+                let right = right.should_stop(step, world);
+                left || right
+            }
+            StopCondition::Not(condition) => !condition.should_stop(step, world),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Quantity {
+    Step,
+    Cells,
+    Food,
+    MeanHealth,
+    MeanEnergy,
+    MeanAge,
+    MeanGene(usize),
+}
+
+impl Quantity {
+    fn named(name: &str) -> Option<Quantity> {
+        match name {
+            "step" => Some(Quantity::Step),
+            "cells" => Some(Quantity::Cells),
+            "food" => Some(Quantity::Food),
+            "mean_health" => Some(Quantity::MeanHealth),
+            "mean_energy" => Some(Quantity::MeanEnergy),
+            "mean_age" => Some(Quantity::MeanAge),
+            _ => Genome::gene_names().position(|gene_name| gene_name == name).map(Quantity::MeanGene),
+        }
+    }
+
+    pub fn value(&self, step: u32, world: &World) -> f64 {
+        match self {
+            Quantity::Step => step as f64,
+            Quantity::Cells => world.num_cells() as f64,
+            Quantity::Food => world.food().value() as f64,
+            Quantity::MeanHealth => world.mean_health() as f64,
+            Quantity::MeanEnergy => world.mean_energy() as f64,
+            Quantity::MeanAge => world.mean_age() as f64,
+            Quantity::MeanGene(index) => {
+                if world.num_cells() == 0 {
+                    return 0.0;
+                }
+                let sum: f64 = world.cells().genomes().iter()
+                    .filter_map(|genome| genome.gene_values().nth(*index))
+                    .map(|value| value as f64)
+                    .sum();
+                sum / world.num_cells() as f64
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// Holds once a quantity has stayed within `tolerance` (max minus min) for `window` steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stability {
+    quantity: Quantity,
+    window: usize,
+    tolerance: f64,
+    recent_values: VecDeque<f64>,
+}
+
+impl Stability {
+    pub fn new(quantity: Quantity, window: usize, tolerance: f64) -> Self {
+        Stability {
+            quantity,
+            window,
+            tolerance,
+            recent_values: VecDeque::with_capacity(window),
+        }
+    }
+
+    fn update(&mut self, step: u32, world: &World) -> bool {
+        if self.recent_values.len() == self.window {
+            self.recent_values.pop_front();
+        }
+        self.recent_values.push_back(self.quantity.value(step, world));
+        if self.recent_values.len() < self.window {
+            return false;
+        }
+
+        let min = self.recent_values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self.recent_values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        max - min <= self.tolerance
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for StopCondition {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?, position: 0 };
+        let condition = parser.or()?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => Err(ParseError(format!("unexpected '{}'", token))),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<String>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if "(),".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if "<>".contains(c) {
+            chars.next();
+            if chars.peek() == Some(&'=') {
+                chars.next();
+                tokens.push(format!("{}=", c));
+            } else {
+                tokens.push(c.to_string());
+            }
+        } else if c.is_alphanumeric() || "_.-".contains(c) {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_alphanumeric() || "_.-".contains(c)) {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        } else {
+            return Err(ParseError(format!("unexpected character '{}'", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Result<String, ParseError> {
+        let token = self.tokens.get(self.position).cloned()
+            .ok_or_else(|| ParseError("unexpected end of condition".to_string()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), ParseError> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(ParseError(format!("expected '{}' but found '{}'", expected, token)));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<StopCondition, ParseError> {
+        let mut condition = self.and()?;
+        while self.peek() == Some("or") {
+            self.next()?;
+            condition = StopCondition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<StopCondition, ParseError> {
+        let mut condition = self.not()?;
+        while self.peek() == Some("and") {
+            self.next()?;
+            condition = StopCondition::And(Box::new(condition), Box::new(self.not()?));
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<StopCondition, ParseError> {
+        if self.peek() == Some("not") {
+            self.next()?;
+            return Ok(StopCondition::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<StopCondition, ParseError> {
+        let token = self.next()?;
+        match token.as_str() {
+            "(" => {
+                let condition = self.or()?;
+                self.expect(")")?;
+                Ok(condition)
+            }
+            "extinct" => Ok(StopCondition::Extinct),
+            "stable" => {
+                self.expect("(")?;
+                let quantity = self.quantity()?;
+                self.expect(",")?;
+                let window = self.number()? as usize;
+                self.expect(",")?;
+                let tolerance = self.number()?;
+                self.expect(")")?;
+                Ok(StopCondition::Stable(Stability::new(quantity, window, tolerance)))
+            }
+            _ => {
+                self.position -= 1;
+                let quantity = self.quantity()?;
+                let comparison = match self.next()?.as_str() {
+                    "<" => Comparison::Less,
+                    "<=" => Comparison::LessOrEqual,
+                    ">" => Comparison::Greater,
+                    ">=" => Comparison::GreaterOrEqual,
+                    other => return Err(ParseError(format!("expected a comparison but found '{}'", other))),
+                };
+                let value = self.number()?;
+                Ok(StopCondition::Compare { quantity, comparison, value })
+            }
+        }
+    }
+
+    fn quantity(&mut self) -> Result<Quantity, ParseError> {
+        let name = self.next()?;
+        Quantity::named(&name).ok_or_else(|| ParseError(format!("unknown quantity '{}'", name)))
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let token = self.next()?;
+        token.parse().map_err(|_| ParseError(format!("expected a number but found '{}'", token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{Cell, CellConstants};
+    use std::rc::Rc;
+
+    fn world_with_cells(num_cells: usize) -> World {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        World::new().with_cells(vec![Cell::new(&constants, Genome::DEFAULT); num_cells])
+    }
+
+    #[test]
+    fn parses_combined_conditions_with_precedence() {
+        let condition: StopCondition = "cells > 10 or not extinct and step >= 5".parse().unwrap();
+        assert_eq!(condition, StopCondition::Or(
+            Box::new(StopCondition::Compare { quantity: Quantity::Cells, comparison: Comparison::Greater, value: 10.0 }),
+            Box::new(StopCondition::And(
+                Box::new(StopCondition::Not(Box::new(StopCondition::Extinct))),
+                Box::new(StopCondition::Compare { quantity: Quantity::Step, comparison: Comparison::GreaterOrEqual, value: 5.0 }),
+            )),
+        ));
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        assert!("cells >".parse::<StopCondition>().is_err());
+        assert!("nonsense > 1".parse::<StopCondition>().is_err());
+        assert!("(extinct".parse::<StopCondition>().is_err());
+        assert!("extinct extinct".parse::<StopCondition>().is_err());
+    }
+
+    #[test]
+    fn evaluates_combined_conditions() {
+        let mut condition: StopCondition = "(cells > 2 or extinct) and step > 10".parse().unwrap();
+        assert!(!condition.should_stop(11, &world_with_cells(2)));
+        assert!(!condition.should_stop(10, &world_with_cells(3)));
+        assert!(condition.should_stop(11, &world_with_cells(3)));
+        assert!(condition.should_stop(11, &world_with_cells(0)));
+    }
+
+    #[test]
+    fn stable_holds_once_quantity_stays_within_tolerance_for_window() {
+        let mut condition: StopCondition = "stable(cells, 3, 1)".parse().unwrap();
+        assert!(!condition.should_stop(1, &world_with_cells(10)));
+        assert!(!condition.should_stop(2, &world_with_cells(5)));
+        assert!(!condition.should_stop(3, &world_with_cells(5)));
+        assert!(condition.should_stop(4, &world_with_cells(6)));
+    }
+
+    #[test]
+    fn gene_means_can_be_compared() {
+        let mut condition: StopCondition = "attempted_eating_energy < 1".parse().unwrap();
+        assert!(condition.should_stop(0, &world_with_cells(2)));
+    }
+}
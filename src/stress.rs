@@ -0,0 +1,215 @@
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+use crate::number_types::F32Positive;
+
+/// An environmental stress, such as heat or drought, that multiplies the entropy damage cells
+/// suffer each step. At a stress of 1, entropy is as configured; cells resist stress with their
+/// `stress_tolerance`.
+#[typetag::serde]
+pub trait StressSource {
+    /// The stress during the world's `step`th step, counting from 0. Called once per step, in
+    /// order.
+    fn stress_at(&mut self, step: u32) -> F32Positive;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConstantStress {
+    stress: F32Positive,
+}
+
+impl ConstantStress {
+    pub fn new(stress: F32Positive) -> Self {
+        ConstantStress { stress }
+    }
+}
+
+#[typetag::serde]
+impl StressSource for ConstantStress {
+    fn stress_at(&mut self, _step: u32) -> F32Positive {
+        self.stress
+    }
+}
+
+/// Stress changing linearly from `start` to `end` over `steps` steps, then staying at `end`.
+#[derive(Serialize, Deserialize)]
+pub struct RampStress {
+    start: F32Positive,
+    end: F32Positive,
+    steps: u32,
+}
+
+impl RampStress {
+    pub fn new(start: F32Positive, end: F32Positive, steps: u32) -> Self {
+        RampStress { start, end, steps }
+    }
+}
+
+#[typetag::serde]
+impl StressSource for RampStress {
+    fn stress_at(&mut self, step: u32) -> F32Positive {
+        let progress = (step as f32 / self.steps.max(1) as f32).min(1.0);
+        F32Positive::clipped(self.start.value() + (self.end.value() - self.start.value()) * progress)
+    }
+}
+
+/// Stress cycling around `mean` by up to `amplitude` with a period of `period` steps, like the
+/// seasons. Troughs deeper than the mean are clipped at 0.
+#[derive(Serialize, Deserialize)]
+pub struct SineStress {
+    mean: F32Positive,
+    amplitude: F32Positive,
+    period: u32,
+}
+
+impl SineStress {
+    pub fn new(mean: F32Positive, amplitude: F32Positive, period: u32) -> Self {
+        SineStress { mean, amplitude, period }
+    }
+}
+
+#[typetag::serde]
+impl StressSource for SineStress {
+    fn stress_at(&mut self, step: u32) -> F32Positive {
+        let phase = (step % self.period.max(1)) as f32 / self.period.max(1) as f32;
+        F32Positive::clipped(self.mean.value() + self.amplitude.value() * (TAU * phase).sin())
+    }
+}
+
+/// Stress starting at `start` and wandering by a normally distributed change with a standard
+/// deviation of `step_stdev` each step, never going below 0.
+#[derive(Serialize, Deserialize)]
+pub struct RandomWalkStress {
+    stress: F32Positive,
+    step_stdev: F32Positive,
+    rng: Pcg64,
+}
+
+impl RandomWalkStress {
+    pub fn new(start: F32Positive, step_stdev: F32Positive, seed: u64) -> Self {
+        RandomWalkStress {
+            stress: start,
+            step_stdev,
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl StressSource for RandomWalkStress {
+    fn stress_at(&mut self, step: u32) -> F32Positive {
+        if step > 0 {
+            let change = Normal::new(0.0, self.step_stdev.value()).unwrap().sample(&mut self.rng);
+            self.stress = F32Positive::clipped(self.stress.value() + change);
+        }
+        self.stress
+    }
+}
+
+/// The stress sources that can be chosen on the command line, by the names `constant:STRESS`,
+/// `ramp:START:END` (over the whole run), `sine:MEAN:AMPLITUDE:PERIOD`, and
+/// `random-walk:START:STDEV`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StressPattern {
+    Constant(f32),
+    Ramp(f32, f32),
+    Sine(f32, f32, u32),
+    RandomWalk(f32, f32),
+}
+
+impl StressPattern {
+    /// The stress source for a run of `steps` steps. Only `RandomWalk` uses `seed`.
+    pub fn source(self, steps: u32, seed: u64) -> Box<dyn StressSource> {
+        match self {
+            StressPattern::Constant(stress) => Box::new(ConstantStress::new(stress.into())),
+            StressPattern::Ramp(start, end) => Box::new(RampStress::new(start.into(), end.into(), steps)),
+            StressPattern::Sine(mean, amplitude, period) => Box::new(SineStress::new(mean.into(), amplitude.into(), period)),
+            StressPattern::RandomWalk(start, step_stdev) => Box::new(RandomWalkStress::new(start.into(), step_stdev.into(), seed)),
+        }
+    }
+}
+
+impl FromStr for StressPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, params) = s.split_once(':').unwrap_or((s, ""));
+        let numbers: Vec<f32> = params.split(':')
+            .map(|param| param.parse::<f32>().ok().filter(|number| *number >= 0.0 && number.is_finite()))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("parameters in stress '{}' are not numbers of at least 0", s))?;
+        let expect = |count: usize, usage: &str| if numbers.len() == count {
+            Ok(())
+        } else {
+            Err(format!("stress '{}' should be {}", s, usage))
+        };
+        match name {
+            "constant" => expect(1, "constant:STRESS").map(|()| StressPattern::Constant(numbers[0])),
+            "ramp" => expect(2, "ramp:START:END").map(|()| StressPattern::Ramp(numbers[0], numbers[1])),
+            "sine" => expect(3, "sine:MEAN:AMPLITUDE:PERIOD")
+                .and_then(|()| if numbers[2] >= 1.0 && numbers[2].fract() == 0.0 {
+                    Ok(StressPattern::Sine(numbers[0], numbers[1], numbers[2] as u32))
+                } else {
+                    Err(format!("period in stress '{}' is not a whole number of steps", s))
+                }),
+            "random-walk" => expect(2, "random-walk:START:STDEV").map(|()| StressPattern::RandomWalk(numbers[0], numbers[1])),
+            _ => Err(format!("unknown stress '{}' (expected constant:STRESS, ramp:START:END, sine:MEAN:AMPLITUDE:PERIOD, or random-walk:START:STDEV)", s)),
+        }
+    }
+}
+
+impl fmt::Display for StressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StressPattern::Constant(stress) => write!(f, "constant:{}", stress),
+            StressPattern::Ramp(start, end) => write!(f, "ramp:{}:{}", start, end),
+            StressPattern::Sine(mean, amplitude, period) => write!(f, "sine:{}:{}:{}", mean, amplitude, period),
+            StressPattern::RandomWalk(start, step_stdev) => write!(f, "random-walk:{}:{}", start, step_stdev),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stresses(pattern: StressPattern, steps: u32) -> Vec<f32> {
+        let mut source = pattern.source(steps, 0);
+        (0..=steps).map(|step| source.stress_at(step).value()).collect()
+    }
+
+    #[test]
+    fn patterns_parse_from_their_names() {
+        for name in ["constant:2", "ramp:1:3", "sine:1:0.5:100", "random-walk:1:0.1"] {
+            assert_eq!(name.parse::<StressPattern>().unwrap().to_string(), name);
+        }
+        for bad in ["hot", "constant", "constant:-1", "ramp:1", "sine:1:0.5:0", "sine:1:0.5:2.5", "random-walk:x:1"] {
+            assert!(bad.parse::<StressPattern>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn ramp_runs_from_start_to_end() {
+        assert_eq!(stresses(StressPattern::Ramp(1.0, 3.0), 4), vec![1.0, 1.5, 2.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    fn sine_cycles_around_its_mean() {
+        let stresses = stresses(StressPattern::Sine(1.0, 2.0, 4), 4);
+        assert!((stresses[1] - 3.0).abs() < 1e-5);
+        assert_eq!(stresses[3], 0.0);
+        assert_eq!(stresses[4], stresses[0]);
+    }
+
+    #[test]
+    fn random_walk_wanders_but_stays_non_negative() {
+        let stresses = stresses(StressPattern::RandomWalk(0.5, 1.0), 100);
+        assert_eq!(stresses[0], 0.5);
+        assert!(stresses.windows(2).any(|pair| pair[0] != pair[1]));
+        assert!(stresses.iter().all(|&stress| stress >= 0.0));
+    }
+}
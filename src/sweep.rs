@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+/// One option varied by a sweep, with the values it takes. Parsed from `name=values`, where the
+/// values are separated by commas and each is a single value or a `start:stop:step` range that
+/// includes `stop` if the steps land on it, e.g. `added_food=0:10:2.5` or `cells=10,100,1000`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepAxis {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+impl SweepAxis {
+    /// The command-line flag for the option, e.g. `--added-food` for `added_food`.
+    pub fn flag(&self) -> String {
+        format!("--{}", self.name.replace('_', "-"))
+    }
+}
+
+impl FromStr for SweepAxis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, values) = s.split_once('=')
+            .ok_or_else(|| format!("expected name=values, got '{}'", s))?;
+        let name = name.trim().trim_start_matches("--").to_string();
+        if name.is_empty() {
+            return Err(format!("missing option name in '{}'", s));
+        }
+
+        let mut axis = SweepAxis { name, values: vec![] };
+        for item in values.split(',').map(str::trim) {
+            if item.contains(':') {
+                axis.values.extend(parse_range(item)?);
+            } else if !item.is_empty() {
+                axis.values.push(item.to_string());
+            }
+        }
+        if axis.values.is_empty() {
+            return Err(format!("no values for '{}'", axis.name));
+        }
+        Ok(axis)
+    }
+}
+
+fn parse_range(s: &str) -> Result<Vec<String>, String> {
+    let bounds: Vec<f64> = s.split(':')
+        .map(|part| part.trim().parse::<f64>().map_err(|_| format!("invalid number '{}' in range '{}'", part, s)))
+        .collect::<Result<_, _>>()?;
+    let [start, stop, step] = bounds[..] else {
+        return Err(format!("expected start:stop:step, got '{}'", s));
+    };
+    if !(step > 0.0 && step.is_finite() && start.is_finite() && stop.is_finite()) {
+        return Err(format!("range '{}' needs finite bounds and a positive step", s));
+    }
+
+    // Computing each value from its index, rather than adding up steps, keeps values like 0.3
+    // from picking up rounding error along the way.
+    let num_steps = ((stop - start) / step + 1e-9).floor();
+    if num_steps < 0.0 {
+        return Err(format!("range '{}' stops before it starts", s));
+    }
+    Ok((0..=num_steps as u64).map(|index| (start + index as f64 * step).to_string()).collect())
+}
+
+/// Every combination of one value from each axis, each listing its values in axis order. The last
+/// axis varies fastest.
+pub fn configurations(axes: &[SweepAxis]) -> Vec<Vec<&str>> {
+    let mut configurations: Vec<Vec<&str>> = vec![vec![]];
+    for axis in axes {
+        configurations = configurations.into_iter()
+            .flat_map(|configuration| axis.values.iter().map(move |value| {
+                let mut configuration = configuration.clone();
+                configuration.push(value.as_str());
+                configuration
+            }))
+            .collect();
+    }
+    configurations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_parse_lists_and_ranges() {
+        let axis: SweepAxis = "added_food=0:1:0.25,5".parse().unwrap();
+        assert_eq!(axis.name, "added_food");
+        assert_eq!(axis.flag(), "--added-food");
+        assert_eq!(axis.values, vec!["0", "0.25", "0.5", "0.75", "1", "5"]);
+
+        assert!("added_food".parse::<SweepAxis>().is_err());
+        assert!("added_food=".parse::<SweepAxis>().is_err());
+        assert!("added_food=0:1:0".parse::<SweepAxis>().is_err());
+        assert!("added_food=2:1:1".parse::<SweepAxis>().is_err());
+    }
+
+    #[test]
+    fn configurations_are_the_cartesian_product() {
+        let axes = vec![
+            "added_food=0,5".parse().unwrap(),
+            "cells=10,100,1000".parse().unwrap(),
+        ];
+
+        let configurations = configurations(&axes);
+
+        assert_eq!(configurations.len(), 6);
+        assert_eq!(configurations[0], vec!["0", "10"]);
+        assert_eq!(configurations[1], vec!["0", "100"]);
+        assert_eq!(configurations[5], vec!["5", "1000"]);
+    }
+}
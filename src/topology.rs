@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What lies beyond the edges of a spatial world's grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Topology {
+    /// The grid ends at its edges: there is nothing beyond them to move to or exchange with.
+    #[default]
+    Bounded,
+    /// Each edge wraps around to the opposite one, so that no tile is special.
+    Toroidal,
+}
+
+impl Topology {
+    /// The tile `dx` and `dy` away from `(x, y)` on a `width` by `height` grid, or `None` if that
+    /// is beyond the edge of a bounded grid.
+    pub fn offset(self, x: usize, y: usize, dx: isize, dy: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        Some((self.offset_coord(x, dx, width)?, self.offset_coord(y, dy, height)?))
+    }
+
+    /// The four tiles left, right, above, and below `(x, y)`, with `None` for those beyond the
+    /// edge of a bounded grid.
+    pub fn neighbors(self, x: usize, y: usize, width: usize, height: usize) -> [Option<(usize, usize)>; 4] {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dy)| self.offset(x, y, dx, dy, width, height))
+    }
+
+    fn offset_coord(self, coord: usize, delta: isize, size: usize) -> Option<usize> {
+        match self {
+            Topology::Bounded => coord.checked_add_signed(delta).filter(|&coord| coord < size),
+            Topology::Toroidal => Some((coord as isize + delta).rem_euclid(size as isize) as usize),
+        }
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bounded" => Ok(Topology::Bounded),
+            "toroidal" => Ok(Topology::Toroidal),
+            _ => Err(format!("unknown topology '{}' (expected bounded or toroidal)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::Bounded => write!(f, "bounded"),
+            Topology::Toroidal => write!(f, "toroidal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_edges_have_no_neighbors_beyond_them() {
+        assert_eq!(Topology::Bounded.neighbors(0, 2, 3, 3), [None, Some((1, 2)), Some((0, 1)), None]);
+        assert_eq!(Topology::Bounded.offset(1, 1, 2, 0, 3, 3), None);
+    }
+
+    #[test]
+    fn toroidal_edges_wrap_around() {
+        assert_eq!(Topology::Toroidal.neighbors(0, 2, 3, 3), [Some((2, 2)), Some((1, 2)), Some((0, 1)), Some((0, 0))]);
+        assert_eq!(Topology::Toroidal.offset(1, 1, 5, -4, 3, 3), Some((0, 0)));
+        assert_eq!("toroidal".parse(), Ok(Topology::Toroidal));
+    }
+}
@@ -0,0 +1,88 @@
+use std::io;
+use std::io::Write;
+use crate::world::{World, WorldObserver};
+
+/// Writes a histogram of one gene's values across the population every `every` steps, as a CSV
+/// matrix with a row per step and a column per bin, for heatmaps of the gene's distribution over
+/// time, in which evolutionary branching shows as one band splitting in two. The header names
+/// each bin by its lower edge. The bins split `min` to `max` evenly, and values outside that
+/// range count in the first or last bin.
+pub struct TraitHeatmap<W: Write> {
+    writer: W,
+    gene_index: usize,
+    min: f32,
+    max: f32,
+    num_bins: usize,
+    every: u32,
+    step: u32,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraitHeatmap<W> {
+    /// Writes the header, and starts counting steps from `first_step`.
+    pub fn new(mut writer: W, gene_index: usize, min: f32, max: f32, num_bins: usize, every: u32, first_step: u32) -> io::Result<Self> {
+        let num_bins = num_bins.max(1);
+        let edges: Vec<String> = (0..num_bins)
+            .map(|bin| (min + (max - min) * bin as f32 / num_bins as f32).to_string())
+            .collect();
+        writeln!(writer, "step,{}", edges.join(","))?;
+        Ok(TraitHeatmap { writer, gene_index, min, max, num_bins, every, step: first_step, error: None })
+    }
+
+    /// Flushes the rows written so far, returning the first error in writing any of them.
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+
+    fn bin(&self, value: f32) -> usize {
+        let fraction = (value - self.min) / (self.max - self.min);
+        ((fraction * self.num_bins as f32).floor().max(0.0) as usize).min(self.num_bins - 1)
+    }
+
+    fn write_row(&mut self, world: &World) -> io::Result<()> {
+        let mut counts = vec![0; self.num_bins];
+        for genome in world.cells().genomes() {
+            counts[self.bin(genome.gene_values().nth(self.gene_index).unwrap())] += 1;
+        }
+        let counts: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+        writeln!(self.writer, "{},{}", self.step, counts.join(","))
+    }
+}
+
+impl<W: Write> WorldObserver for TraitHeatmap<W> {
+    fn after_step(&mut self, world: &World, _num_born: usize, _num_died: usize) {
+        self.step += 1;
+        if self.error.is_none() && self.every > 0 && self.step.is_multiple_of(self.every) {
+            if let Err(err) = self.write_row(world) {
+                self.error = Some(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+    use crate::genome::Genome;
+
+    #[test]
+    fn heatmap_bins_the_gene_each_step() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cell = |eating: f32| Cell::new(&constants, Genome { attempted_eating_energy: eating.into(), ..Genome::ZERO })
+            .with_energy(10.0.into());
+        let world = World::new().with_cells(vec![cell(0.1), cell(1.5), cell(1.9), cell(7.0)]);
+        let mut heatmap = TraitHeatmap::new(vec![], 0, 0.0, 4.0, 4, 2, 0).unwrap();
+
+        for _ in 0..4 {
+            heatmap.after_step(&world, 0, 0);
+        }
+        heatmap.finish().unwrap();
+
+        assert_eq!(String::from_utf8(heatmap.writer).unwrap(), "step,0,1,2,3\n2,1,2,0,1\n4,1,2,0,1\n");
+    }
+}
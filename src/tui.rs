@@ -0,0 +1,309 @@
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::cell::RefCell;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::async_writer::AsyncWriter;
+use crate::cell::RandomMutationNumberSource;
+use crate::coloring::{ColorRule, Rgb};
+use crate::history::{History, RetentionPolicy};
+use crate::main_support::{RunOptions, StepEffects};
+use crate::world::World;
+
+/// Height in rows of the panel of cell colors, which shows one character per cell for as many
+/// cells as fit.
+const CELLS_PANEL_HEIGHT: u16 = 6;
+
+const PLOT_RETENTION: RetentionPolicy = RetentionPolicy {
+    recent: 500,
+    older: 500,
+    downsample_factor: 10,
+};
+
+/// Runs the world while plotting its population dynamics in a terminal dashboard, until the
+/// run ends or the user presses `q`. Reports the run's options ask for are printed once the
+/// terminal is restored, since printing them during the run would garble the dashboard.
+pub fn run(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+           first_step: u32, steps: u32, options: &RunOptions) -> io::Result<()> {
+    let held_output = HeldOutput::default();
+    let mut terminal = ratatui::init();
+    let result = run_dashboard(&mut terminal, world, mutation_number_source, first_step, steps, options, held_output.clone());
+    ratatui::restore();
+    io::stdout().write_all(&held_output.0.lock().unwrap())?;
+    result
+}
+
+fn run_dashboard(terminal: &mut DefaultTerminal, world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+                 first_step: u32, steps: u32, options: &RunOptions, held_output: HeldOutput) -> io::Result<()> {
+    let mut dashboard = Dashboard::new(steps, options.color_rule);
+    dashboard.record(first_step, world);
+
+    let output = Rc::new(RefCell::new(AsyncWriter::new(held_output)));
+    let mut effects = StepEffects::start(world, first_step, steps, options, output.clone(), None);
+    let mut sampler = FrameSampler::new(options.frame_rate);
+    let mut frame_start = Instant::now();
+    while effects.step(world, mutation_number_source) {
+        if sampler.is_frame_step() {
+            let draw_start = Instant::now();
+            dashboard.record(effects.current_step(), world);
+            dashboard.steps_per_frame = sampler.steps_per_frame;
+            terminal.draw(|frame| dashboard.draw(frame))?;
+            frame_start = sampler.frame_drawn(draw_start - frame_start, draw_start.elapsed());
+            if quit_requested(Duration::ZERO)? {
+                break;
+            }
+        }
+    }
+
+    if dashboard.step != effects.current_step() {
+        dashboard.record(effects.current_step(), world);
+    }
+    effects.finish(world, mutation_number_source);
+    output.borrow_mut().flush()?;
+
+    dashboard.finished = true;
+    loop {
+        terminal.draw(|frame| dashboard.draw(frame))?;
+        if quit_requested(Duration::from_millis(250))? {
+            return Ok(());
+        }
+    }
+}
+
+/// Output held in memory while the dashboard has the terminal.
+#[derive(Clone, Default)]
+struct HeldOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for HeldOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn quit_requested(timeout: Duration) -> io::Result<bool> {
+    while event::poll(timeout)? {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                return Ok(true);
+            }
+        }
+        if timeout > Duration::ZERO {
+            break;
+        }
+    }
+    Ok(false)
+}
+
+/// Chooses the steps on which the dashboard samples the world and redraws. Drawing every step
+/// would hold the simulation to the terminal's speed, so the number of steps per frame adapts to
+/// the measured stepping and drawing times: enough steps to fill out the target frame interval,
+/// and never so few that drawing takes longer than stepping.
+struct FrameSampler {
+    frame_interval: Duration,
+    steps_per_frame: u32,
+    steps_since_frame: u32,
+}
+
+impl FrameSampler {
+    fn new(frame_rate: u32) -> Self {
+        FrameSampler {
+            frame_interval: Duration::from_secs(1) / frame_rate.max(1),
+            steps_per_frame: 1,
+            steps_since_frame: 0,
+        }
+    }
+
+    fn is_frame_step(&mut self) -> bool {
+        self.steps_since_frame += 1;
+        self.steps_since_frame >= self.steps_per_frame
+    }
+
+    /// Adapts the steps per frame to the time spent stepping since the previous frame and the
+    /// time spent drawing this one, and returns when the next frame's stepping started.
+    fn frame_drawn(&mut self, stepping: Duration, drawing: Duration) -> Instant {
+        let step_time = stepping.as_secs_f64() / self.steps_since_frame as f64;
+        if step_time > 0.0 {
+            let to_fill_interval = self.frame_interval.saturating_sub(drawing).as_secs_f64() / step_time;
+            let to_outlast_drawing = drawing.as_secs_f64() / step_time;
+            self.steps_per_frame = to_fill_interval.max(to_outlast_drawing).ceil().clamp(1.0, u32::MAX as f64) as u32;
+        }
+        self.steps_since_frame = 0;
+        Instant::now()
+    }
+}
+
+struct Plot {
+    title: &'static str,
+    value: fn(&World) -> f64,
+    points: History<(f64, f64)>,
+}
+
+impl Plot {
+    fn new(title: &'static str, value: fn(&World) -> f64) -> Self {
+        Plot {
+            title,
+            value,
+            points: History::new(PLOT_RETENTION),
+        }
+    }
+
+    fn record(&mut self, step: u32, world: &World) {
+        self.points.push((step as f64, (self.value)(world)));
+    }
+
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let points: Vec<(f64, f64)> = self.points.iter().copied().collect();
+        let (min_x, max_x) = bounds(points.iter().map(|(x, _)| *x));
+        let (min_y, max_y) = bounds(points.iter().map(|(_, y)| *y));
+        let latest = points.last().map_or(0.0, |(_, y)| *y);
+
+        let dataset = Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .data(&points);
+        let chart = Chart::new(vec![dataset])
+            .block(Block::bordered().title(format!("{} ({})", self.title, latest)))
+            .x_axis(Axis::default()
+                .bounds([min_x, max_x])
+                .labels([format!("{}", min_x), format!("{}", max_x)]))
+            .y_axis(Axis::default()
+                .bounds([min_y, max_y])
+                .labels([format!("{:.1}", min_y), format!("{:.1}", max_y)]));
+        frame.render_widget(chart, area);
+    }
+}
+
+fn bounds<I: Iterator<Item=f64>>(values: I) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY),
+                                 |(min, max), value| (min.min(value), max.max(value)));
+    if min > max {
+        (0.0, 1.0)
+    } else if min == max {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    }
+}
+
+struct Dashboard {
+    plots: [Plot; 4],
+    color_rule: ColorRule,
+    cell_colors: Vec<Rgb>,
+    step: u32,
+    steps: u32,
+    steps_per_frame: u32,
+    finished: bool,
+}
+
+impl Dashboard {
+    fn new(steps: u32, color_rule: ColorRule) -> Self {
+        Dashboard {
+            plots: [
+                Plot::new("population", |world| world.num_cells() as f64),
+                Plot::new("mean energy", |world| world.mean_energy() as f64),
+                Plot::new("mean health", |world| world.mean_health() as f64),
+                Plot::new("food", |world| world.food().value() as f64),
+            ],
+            color_rule,
+            cell_colors: vec![],
+            step: 0,
+            steps,
+            steps_per_frame: 1,
+            finished: false,
+        }
+    }
+
+    fn record(&mut self, step: u32, world: &World) {
+        self.step = step;
+        for plot in &mut self.plots {
+            plot.record(step, world);
+        }
+        self.cell_colors = world.cells().iter()
+            .map(|cell| self.color_rule.color(&cell))
+            .collect();
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [status_area, top_area, bottom_area, cells_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(CELLS_PANEL_HEIGHT),
+        ]).areas(frame.area());
+        let [top_left, top_right] = Layout::horizontal([Constraint::Percentage(50); 2]).areas(top_area);
+        let [bottom_left, bottom_right] = Layout::horizontal([Constraint::Percentage(50); 2]).areas(bottom_area);
+
+        frame.render_widget(Paragraph::new(self.status()), status_area);
+        for (plot, area) in self.plots.iter().zip([top_left, top_right, bottom_left, bottom_right]) {
+            plot.draw(frame, area);
+        }
+        self.draw_cells(frame, cells_area);
+    }
+
+    fn draw_cells(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(format!("cells ({})", self.color_rule));
+        let width = block.inner(area).width.max(1) as usize;
+        let lines: Vec<Line> = self.cell_colors.chunks(width)
+            .take(block.inner(area).height as usize)
+            .map(|colors| Line::from(colors.iter()
+                .map(|&Rgb(r, g, b)| Span::styled("█", Style::default().fg(Color::Rgb(r, g, b))))
+                .collect::<Vec<_>>()))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn status(&self) -> String {
+        let steps = if self.steps == u32::MAX { "∞".to_string() } else { self.steps.to_string() };
+        let state = if self.finished { "finished" } else { "running" };
+        format!("step {}/{} ({}, drawing every {} steps) - press q to quit", self.step, steps, state, self.steps_per_frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_draws_every_step_until_it_has_timings() {
+        let mut sampler = FrameSampler::new(20);
+
+        assert!(sampler.is_frame_step());
+        assert!(sampler.is_frame_step());
+    }
+
+    #[test]
+    fn sampler_skips_enough_steps_to_fill_frame_interval() {
+        let mut sampler = FrameSampler::new(20);
+        sampler.is_frame_step();
+        sampler.is_frame_step();
+
+        sampler.frame_drawn(Duration::from_millis(2), Duration::from_millis(10));
+
+        assert_eq!(sampler.steps_per_frame, 40);
+        assert!(!sampler.is_frame_step());
+    }
+
+    #[test]
+    fn sampler_steps_at_least_as_long_as_it_draws() {
+        let mut sampler = FrameSampler::new(20);
+        sampler.is_frame_step();
+
+        sampler.frame_drawn(Duration::from_millis(1), Duration::from_millis(200));
+
+        assert_eq!(sampler.steps_per_frame, 200);
+    }
+}
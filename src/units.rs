@@ -0,0 +1,210 @@
+use crate::cell::CellConstants;
+use crate::genome::{Gene, GeneValue, Genome};
+use crate::number_types::{F32Positive, F32ZeroToOnePerF32Positive};
+
+/// The unit a configured quantity is measured in. Health, ages, and fractions such as
+/// `health_reduction_from_entropy` have no unit; rates are all per step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    None,
+    Energy,
+    Food,
+    Light,
+}
+
+/// Sizes of the energy, food, and light units relative to the ones a configuration was written
+/// in. Rescaling every quantity in a configuration by the same `Units` leaves the dynamics
+/// unchanged, with energies, food, and light reported in the new units, so comparing a run with
+/// its rescaled twin checks that results do not depend on the magnitudes chosen. Scaling by
+/// powers of two is exact; other factors can differ by floating-point rounding.
+///
+/// Values at `f32::MAX`, such as a `child_threshold_energy` meaning "never reproduce", stay at
+/// `f32::MAX`. Health changes per unit energy cannot exceed 1, so shrinking the energy unit can
+/// clip them and change the dynamics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub energy: f32,
+    pub food: f32,
+    pub light: f32,
+}
+
+impl Units {
+    pub const BASE: Units = Units {
+        energy: 1.0,
+        food: 1.0,
+        light: 1.0,
+    };
+
+    pub fn factor(&self, unit: Unit) -> f32 {
+        match unit {
+            Unit::None => 1.0,
+            Unit::Energy => self.energy,
+            Unit::Food => self.food,
+            Unit::Light => self.light,
+        }
+    }
+
+    pub fn scale(&self, unit: Unit, value: f32) -> f32 {
+        (value * self.factor(unit)).min(f32::MAX)
+    }
+
+    pub fn energy(&self, value: f32) -> f32 {
+        self.scale(Unit::Energy, value)
+    }
+
+    pub fn food(&self, value: f32) -> f32 {
+        self.scale(Unit::Food, value)
+    }
+
+    pub fn light(&self, value: f32) -> f32 {
+        self.scale(Unit::Light, value)
+    }
+
+    pub fn genome(&self, genome: &Genome) -> Genome {
+        let mut scaled = *genome;
+        for gene in &Genome::F32_POSITIVE_GENES {
+            self.scale_gene(gene, &mut scaled);
+        }
+        for gene in &Genome::F32_ZERO_TO_ONE_GENES {
+            self.scale_gene(gene, &mut scaled);
+        }
+        scaled
+    }
+
+    fn scale_gene<T: GeneValue>(&self, gene: &Gene<T>, genome: &mut Genome) {
+        let value = gene.value_mut(genome);
+        *value = T::clipped(self.scale(gene.unit, (*value).into()));
+    }
+
+    pub fn constants(&self, constants: &CellConstants) -> CellConstants {
+        CellConstants {
+            create_child_energy: self.positive(Unit::Energy, constants.create_child_energy),
+            energy_yield_from_digestion: self.positive_ratio(Unit::Energy, Unit::Food, constants.energy_yield_from_digestion),
+            food_yield_from_eating: self.positive_ratio(Unit::Food, Unit::Energy, constants.food_yield_from_eating),
+            health_increase_per_healing_energy: self.per_energy(constants.health_increase_per_healing_energy),
+            health_reduction_per_energy_expended: self.per_energy(constants.health_reduction_per_energy_expended),
+            attempted_eating_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_eating_energy_mutation_stdev),
+            attempted_healing_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_healing_energy_mutation_stdev),
+            child_threshold_energy_mutation_stdev: self.positive(Unit::Energy, constants.child_threshold_energy_mutation_stdev),
+            child_threshold_food_mutation_stdev: self.positive(Unit::Food, constants.child_threshold_food_mutation_stdev),
+            attempted_attack_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_attack_energy_mutation_stdev),
+            attack_defense: self.positive(Unit::Energy, constants.attack_defense),
+            health_reduction_per_attack_energy: self.per_energy(constants.health_reduction_per_attack_energy),
+            attempted_photosynthesis_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_photosynthesis_energy_mutation_stdev),
+            light_yield_from_photosynthesis: self.positive_ratio(Unit::Light, Unit::Energy, constants.light_yield_from_photosynthesis),
+            energy_yield_from_light: self.positive_ratio(Unit::Energy, Unit::Light, constants.energy_yield_from_light),
+            attempted_trade_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_trade_energy_mutation_stdev),
+            trade_reciprocity_threshold_mutation_stdev: self.positive(Unit::Energy, constants.trade_reciprocity_threshold_mutation_stdev),
+            food_access_per_trade_energy: self.positive_ratio(Unit::Food, Unit::Energy, constants.food_access_per_trade_energy),
+            scarce_food_threshold_mutation_stdev: self.positive(Unit::Food, constants.scarce_food_threshold_mutation_stdev),
+            maintenance_energy: self.positive(Unit::Energy, constants.maintenance_energy),
+            health_reduction_per_maintenance_shortfall: self.per_energy(constants.health_reduction_per_maintenance_shortfall),
+            max_energy_storage_mutation_stdev: self.positive(Unit::Energy, constants.max_energy_storage_mutation_stdev),
+            health_reduction_per_excess_energy: self.per_energy(constants.health_reduction_per_excess_energy),
+            attempted_detox_energy_mutation_stdev: self.positive(Unit::Energy, constants.attempted_detox_energy_mutation_stdev),
+            toxin_tolerance: self.positive(Unit::Energy, constants.toxin_tolerance),
+            health_reduction_per_toxin: self.per_energy(constants.health_reduction_per_toxin),
+            max_food_intake: self.positive(Unit::Food, constants.max_food_intake),
+            donation_threshold_mutation_stdev: self.positive(Unit::Energy, constants.donation_threshold_mutation_stdev),
+            dormancy_threshold_energy_mutation_stdev: self.positive(Unit::Energy, constants.dormancy_threshold_energy_mutation_stdev),
+            wake_threshold_food_mutation_stdev: self.positive(Unit::Food, constants.wake_threshold_food_mutation_stdev),
+            state_energy_scale_mutation_stdev: self.positive(Unit::Energy, constants.state_energy_scale_mutation_stdev),
+            state_food_scale_mutation_stdev: self.positive(Unit::Food, constants.state_food_scale_mutation_stdev),
+            protein_yield_from_eating: self.positive_ratio(Unit::Food, Unit::Energy, constants.protein_yield_from_eating),
+            energy_yield_from_protein_digestion: self.positive_ratio(Unit::Energy, Unit::Food, constants.energy_yield_from_protein_digestion),
+            maintenance_energy_per_efficiency: self.positive(Unit::Energy, constants.maintenance_energy_per_efficiency),
+            maintenance_energy_per_stress_tolerance: self.positive(Unit::Energy, constants.maintenance_energy_per_stress_tolerance),
+            ..*constants
+        }
+    }
+
+    fn positive(&self, unit: Unit, value: F32Positive) -> F32Positive {
+        F32Positive::clipped(self.scale(unit, value.value()))
+    }
+
+    fn positive_ratio(&self, numerator: Unit, denominator: Unit, value: F32Positive) -> F32Positive {
+        F32Positive::clipped((value.value() * self.factor(numerator) / self.factor(denominator)).min(f32::MAX))
+    }
+
+    fn per_energy(&self, value: F32ZeroToOnePerF32Positive) -> F32ZeroToOnePerF32Positive {
+        F32ZeroToOnePerF32Positive::clipped(value.value() / self.energy)
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self::BASE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::cell::{Cell, NullMutationNumberSource};
+    use crate::food_sources::ConstantFoodSource;
+    use crate::light_sources::ConstantLightSource;
+    use crate::world::World;
+
+    const CONSTANTS: CellConstants = CellConstants {
+        create_child_energy: F32Positive::unchecked(1.0),
+        energy_yield_from_digestion: F32Positive::unchecked(1.5),
+        food_yield_from_eating: F32Positive::unchecked(2.0),
+        health_increase_per_healing_energy: F32ZeroToOnePerF32Positive::unchecked(0.25),
+        health_reduction_per_energy_expended: F32ZeroToOnePerF32Positive::unchecked(0.0625),
+        light_yield_from_photosynthesis: F32Positive::unchecked(3.0),
+        energy_yield_from_light: F32Positive::unchecked(0.5),
+        maintenance_energy: F32Positive::unchecked(0.5),
+        health_reduction_per_maintenance_shortfall: F32ZeroToOnePerF32Positive::unchecked(0.125),
+        maintenance_energy_per_storage: F32Positive::unchecked(0.03125),
+        health_reduction_per_excess_energy: F32ZeroToOnePerF32Positive::unchecked(0.25),
+        ..CellConstants::DEFAULT
+    };
+
+    const GENOME: Genome = Genome {
+        attempted_eating_energy: F32Positive::unchecked(1.0),
+        attempted_healing_energy: F32Positive::unchecked(0.5),
+        attempted_photosynthesis_energy: F32Positive::unchecked(0.25),
+        child_threshold_energy: F32Positive::unchecked(6.0),
+        child_threshold_food: F32Positive::unchecked(1.0),
+        max_energy_storage: F32Positive::unchecked(12.0),
+        size: F32Positive::unchecked(1.0),
+        eating_efficiency: F32Positive::unchecked(1.0),
+        digestion_efficiency: F32Positive::unchecked(1.0),
+        ..Genome::ZERO
+    };
+
+    fn world(units: &Units) -> World {
+        let constants = Rc::new(units.constants(&CONSTANTS));
+        World::new()
+            .with_cells(vec![
+                Cell::new(&constants, units.genome(&GENOME)).with_energy(units.energy(5.0).into()),
+                Cell::new(&constants, units.genome(&GENOME)).with_energy(units.energy(3.0).into()),
+            ])
+            .with_food(units.food(20.0).into())
+            .with_food_sources(vec![Box::new(ConstantFoodSource::new(units.food(3.0).into()))])
+            .with_light_sources(vec![Box::new(ConstantLightSource::new(units.light(2.0).into()))])
+    }
+
+    #[test]
+    fn rescaled_world_has_same_dynamics() {
+        let units = Units { energy: 2.0, food: 4.0, light: 0.5 };
+        let mut base_world = world(&Units::BASE);
+        let mut scaled_world = world(&units);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        for _ in 0..20 {
+            assert_eq!(scaled_world.step(&mut mutation_number_source), base_world.step(&mut mutation_number_source));
+            assert_eq!(scaled_world.mean_health(), base_world.mean_health());
+            assert_eq!(scaled_world.mean_energy(), units.energy(base_world.mean_energy()));
+            assert_eq!(scaled_world.food().value(), units.food(base_world.food().value()));
+        }
+        assert!(base_world.num_cells() > 2);
+    }
+
+    #[test]
+    fn never_reached_thresholds_stay_at_max() {
+        let units = Units { energy: 2.0, ..Units::BASE };
+        assert_eq!(units.genome(&Genome::DEFAULT).child_threshold_energy.value(), f32::MAX);
+    }
+}
@@ -0,0 +1,49 @@
+use wasm_bindgen::prelude::*;
+use crate::simulation::Simulation;
+use crate::simulation_config::SimulationConfig;
+
+/// A `Simulation` for JavaScript, e.g. an in-browser demo built with `wasm-pack build --features
+/// wasm`. It is configured and reports in JSON, so that pages need no bindings beyond these.
+#[wasm_bindgen(js_name = Simulation)]
+pub struct WasmSimulation {
+    simulation: Simulation,
+}
+
+#[wasm_bindgen(js_class = Simulation)]
+impl WasmSimulation {
+    /// Creates a simulation from a `SimulationConfig` in JSON, throwing an error if it is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<WasmSimulation, JsError> {
+        let simulation = SimulationConfig::from_json(config_json)
+            .and_then(|config| config.simulation())
+            .map_err(|message| JsError::new(&message))?;
+        Ok(WasmSimulation { simulation })
+    }
+
+    /// Advances one step, unless every cell has died, and returns the step's `EpochStats` as
+    /// JSON.
+    pub fn step(&mut self) -> String {
+        step_json(&mut self.simulation)
+    }
+}
+
+fn step_json(simulation: &mut Simulation) -> String {
+    serde_json::to_string(&simulation.run_for(1)).expect("stats serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn simulation_steps_to_json_stats() {
+        let config = SimulationConfig::from_json(r#"{"seed": 3, "cells": 4, "added_food": 10}"#).unwrap();
+        let mut simulation = config.simulation().unwrap();
+
+        let stats: Value = serde_json::from_str(&step_json(&mut simulation)).unwrap();
+
+        assert_eq!((stats["first_step"].as_u64(), stats["num_steps"].as_u64()), (Some(0), Some(1)));
+        assert_eq!(stats["num_cells"].as_u64(), Some(4));
+    }
+}
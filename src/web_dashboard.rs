@@ -0,0 +1,272 @@
+use base64::Engine;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::async_writer::AsyncWriter;
+use crate::cell::RandomMutationNumberSource;
+use crate::epoch_snapshot::gene_stats;
+use crate::main_support::{RunOptions, StepEffects};
+use crate::world::World;
+
+const DASHBOARD_HTML: &str = include_str!("web_dashboard.html");
+
+/// Appended to a WebSocket client's key to derive the handshake's accept key (RFC 6455).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How long sending an event to a browser may block before the browser is dropped, so that one
+/// that stops reading holds up neither the run nor the other browsers for long.
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// One sample of the world's stats, sent to browsers as JSON, in a WebSocket message or a
+/// server-sent event.
+#[derive(Serialize)]
+struct StatsEvent<'a> {
+    step: u32,
+    cells: usize,
+    food: f32,
+    mean_health: f32,
+    mean_energy: f32,
+    mean_age: f32,
+    genes: Vec<(&'a str, f32)>,
+    finished: bool,
+}
+
+/// Browsers receiving the stats stream, and the latest event, which newly connected browsers get
+/// right away.
+#[derive(Default)]
+struct Clients {
+    streams: Vec<(TcpStream, Protocol)>,
+    latest_event: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Protocol {
+    EventStream,
+    WebSocket,
+}
+
+impl Protocol {
+    /// Wraps a JSON event for sending over this protocol.
+    fn message(&self, event: &str) -> Vec<u8> {
+        match self {
+            Protocol::EventStream => format!("data: {}\n\n", event).into_bytes(),
+            Protocol::WebSocket => websocket_text_frame(event),
+        }
+    }
+}
+
+/// Runs the world while serving a dashboard at `address` that charts its stats live in any
+/// browser, for watching headless runs remotely. The page at `/` needs nothing beyond the
+/// browser, and follows the stats stream, sampled at the frame rate, over the WebSocket at `/ws`.
+/// The same stream is served as server-sent events at `/stats`, for clients such as `curl`. The
+/// server stops when the run ends, leaving the final charts in open pages.
+pub fn serve(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource,
+             first_step: u32, steps: u32, options: &RunOptions, address: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("# dashboard at http://{}/", listener.local_addr()?);
+    let clients = Arc::new(Mutex::new(Clients::default()));
+    let listener_clients = Arc::clone(&clients);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let clients = Arc::clone(&listener_clients);
+            thread::spawn(move || handle_connection(stream, &clients));
+        }
+    });
+
+    let frame_interval = Duration::from_secs_f64(1.0 / options.frame_rate.max(1) as f64);
+    broadcast(&clients, &stats_event(first_step, world, false));
+    let mut last_frame = Instant::now();
+    let output = Rc::new(RefCell::new(AsyncWriter::stdout()));
+    let mut effects = StepEffects::start(world, first_step, steps, options, output.clone(), None);
+    while effects.step(world, mutation_number_source) {
+        if last_frame.elapsed() >= frame_interval {
+            broadcast(&clients, &stats_event(effects.current_step(), world, false));
+            last_frame = Instant::now();
+        }
+    }
+    broadcast(&clients, &stats_event(effects.current_step(), world, true));
+    effects.finish(world, mutation_number_source);
+    output.borrow_mut().flush()?;
+    Ok(())
+}
+
+fn stats_event(step: u32, world: &World, finished: bool) -> String {
+    let event = StatsEvent {
+        step,
+        cells: world.num_cells(),
+        food: world.food().value(),
+        mean_health: world.mean_health(),
+        mean_energy: world.mean_energy(),
+        mean_age: world.mean_age(),
+        genes: gene_stats(world.cells().genomes().iter().map(Rc::as_ref)).into_iter().map(|gene| (gene.name, gene.mean as f32)).collect(),
+        finished,
+    };
+    serde_json::to_string(&event).expect("stats serialize to JSON")
+}
+
+/// Sends the event to every browser following the stats stream, dropping those that have gone or
+/// that take longer than `CLIENT_WRITE_TIMEOUT` to accept it.
+fn broadcast(clients: &Mutex<Clients>, event: &str) {
+    let mut clients = clients.lock().unwrap();
+    clients.streams.retain_mut(|(stream, protocol)| {
+        stream.write_all(&protocol.message(event)).and_then(|()| stream.flush()).is_ok()
+    });
+    clients.latest_event = Some(event.to_string());
+}
+
+fn handle_connection(stream: TcpStream, clients: &Mutex<Clients>) {
+    // A browser that disconnects mid-request needs no response, so errors are dropped.
+    let _ = respond(stream, clients);
+}
+
+fn respond(mut stream: TcpStream, clients: &Mutex<Clients>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut websocket_key = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            }
+        }
+        header.clear();
+    }
+
+    match (request_line.split_whitespace().nth(1).unwrap_or("/"), websocket_key) {
+        ("/" | "/index.html", _) => write!(stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            DASHBOARD_HTML.len(), DASHBOARD_HTML),
+        ("/stats", _) => {
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n")?;
+            follow(stream, Protocol::EventStream, clients)
+        }
+        ("/ws", Some(key)) => {
+            write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                   websocket_accept_key(&key))?;
+            follow(stream, Protocol::WebSocket, clients)
+        }
+        ("/ws", None) => write!(stream, "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+        _ => write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    }
+}
+
+/// Adds the stream to those receiving the stats stream, starting it with the latest event.
+fn follow(mut stream: TcpStream, protocol: Protocol, clients: &Mutex<Clients>) -> io::Result<()> {
+    stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT))?;
+    let mut clients = clients.lock().unwrap();
+    if let Some(event) = &clients.latest_event {
+        stream.write_all(&protocol.message(event))?;
+    }
+    clients.streams.push((stream, protocol));
+    Ok(())
+}
+
+fn websocket_accept_key(key: &str) -> String {
+    let digest = sha1_smol::Sha1::from(format!("{}{}", key, WEBSOCKET_GUID)).digest().bytes();
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+/// A single unmasked frame holding the whole text message, as servers send them.
+fn websocket_text_frame(text: &str) -> Vec<u8> {
+    let payload = text.as_bytes();
+    let mut frame = vec![0x81];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::rc::Rc;
+    use crate::cell::{Cell, CellConstants};
+    use crate::genome::Genome;
+
+    fn request(path: &str, headers: &str, clients: &Mutex<Clients>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(client, "GET {} HTTP/1.1\r\nHost: localhost\r\n{}\r\n", path, headers).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        respond(server, clients).unwrap();
+        drop(clients.lock().unwrap().streams.drain(..));
+        let mut response = vec![];
+        client.read_to_end(&mut response).unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[test]
+    fn serves_the_page_and_the_latest_stats() {
+        let clients = Mutex::new(Clients::default());
+        let world = World::new().with_cells(vec![Cell::new(&Rc::new(CellConstants::DEFAULT), Genome::DEFAULT)]);
+        broadcast(&clients, &stats_event(7, &world, false));
+
+        let page = request("/", "", &clients);
+        let stats = request("/stats", "", &clients);
+
+        assert!(page.starts_with("HTTP/1.1 200 OK") && page.contains("<html"));
+        assert!(stats.contains("text/event-stream"));
+        assert!(stats.contains(r#"data: {"step":7,"cells":1,"#));
+        assert!(request("/nothing", "", &clients).starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn drops_browsers_that_stop_reading() {
+        let clients = Mutex::new(Clients::default());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(client, "GET /stats HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let (server, _) = listener.accept().unwrap();
+        respond(server, &clients).unwrap();
+
+        let event = "x".repeat(1 << 20);
+        for _ in 0..100 {
+            if clients.lock().unwrap().streams.is_empty() {
+                break;
+            }
+            broadcast(&clients, &event);
+        }
+
+        assert!(clients.lock().unwrap().streams.is_empty());
+    }
+
+    #[test]
+    fn streams_the_latest_stats_over_a_websocket() {
+        // The key and accept key are the example handshake's in RFC 6455.
+        let clients = Mutex::new(Clients::default());
+        broadcast(&clients, r#"{"step":7}"#);
+
+        let response = request("/ws", "Upgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n", &clients);
+
+        assert!(response.starts_with("HTTP/1.1 101 Switching Protocols"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        // The frame's first byte isn't UTF-8, but its length byte, 10, is a newline.
+        assert!(response.ends_with("\n{\"step\":7}"));
+        assert!(request("/ws", "", &clients).starts_with("HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn websocket_frames_give_long_messages_extended_lengths() {
+        assert_eq!(websocket_text_frame("hi"), vec![0x81, 2, b'h', b'i']);
+        assert_eq!(websocket_text_frame(&"x".repeat(300))[..4], [0x81, 126, 1, 44]);
+        assert_eq!(websocket_text_frame(&"x".repeat(70_000))[..10], [0x81, 127, 0, 0, 0, 0, 0, 1, 17, 112]);
+    }
+}
@@ -1,38 +1,328 @@
-use rand::distributions::Distribution;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::index;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rand_distr::Normal;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Sub;
 use std::rc::Rc;
-use crate::cell::{Cell, CellEnvironment, CellConstants, CellParams, MutationNumberSource};
-use crate::food_sources::FoodSource;
-use crate::number_types::F32Positive;
+use std::str::FromStr;
+use crate::audit::Audit;
+use crate::cell::{Cell, CellEnvironment, CellConstants, CellMut, CellState, CellStepOutcome, DeathCause, MateSource, MutationNumberSource, NoMate};
+use crate::cell_columns::{CellColumns, CellView, OtherGenomes};
+use crate::genome::Genome;
+use crate::day_cycle::DayCycle;
+use crate::cell_sources::{CellSource, ExtinctionReseeding};
+use crate::food_allocation::{EqualShares, FoodAllocation};
+use crate::food_sources::{FoodSource, WorldSummary};
+use crate::light_sources::LightSource;
+use crate::number_types::{compensated_sum, F32Positive, F32ZeroToOne, F64Positive, PoolAmount};
+use crate::stress::StressSource;
 
+#[derive(Serialize, Deserialize)]
 pub struct World {
-    cells: Vec<Cell>,
-    food: F32Positive,
+    cells: CellColumns,
+    next_cell_id: u64,
+    food: PoolAmount,
+    food_decay_rate: F32ZeroToOne,
     food_sources: Vec<Box<dyn FoodSource>>,
+    light_sources: Vec<Box<dyn LightSource>>,
+    generations: Generations,
+    reproduction_mode: ReproductionMode,
+    steps_into_generation: u32,
+    day_cycle: DayCycle,
+    rng: Pcg64,
+    #[serde(default)]
+    totals: WorldTotals,
+    #[serde(default)]
+    toxin: PoolAmount,
+    #[serde(default)]
+    toxin_per_energy_expended: F32Positive,
+    #[serde(default)]
+    max_cells: usize,
+    #[serde(default)]
+    crowding: Crowding,
+    #[serde(default)]
+    num_steps: u32,
+    #[serde(default)]
+    protein: PoolAmount,
+    #[serde(default)]
+    protein_sources: Vec<Box<dyn FoodSource>>,
+    #[serde(default)]
+    cell_sources: Vec<Box<dyn CellSource>>,
+    #[serde(default)]
+    extinction_reseeding: Option<ExtinctionReseeding>,
+    #[serde(default)]
+    audit: Audit,
+    #[serde(default)]
+    corpse_recycling: CorpseRecycling,
+    #[serde(default = "World::default_food_allocation")]
+    food_allocation: Box<dyn FoodAllocation>,
+    #[serde(default)]
+    stress_source: Option<Box<dyn StressSource>>,
+    #[serde(skip)]
+    observers: Vec<Rc<RefCell<dyn WorldObserver>>>,
+}
+
+/// Running totals over a world's whole history, resumed from checkpoints along with the world.
+/// They are kept in `u64` and `f64`, since per-step amounts summed in `f32` over millions of steps
+/// stop changing once the total dwarfs them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldTotals {
+    pub num_born: u64,
+    pub num_died: u64,
+    /// Cells added by cell sources, which are not counted in `num_born`.
+    #[serde(default)]
+    pub num_immigrated: u64,
+    /// Steps that ended with every cell dead, counting any after which an
+    /// `ExtinctionReseeding` founded a new population.
+    #[serde(default)]
+    pub num_extinctions: u64,
+    pub food_added: f64,
+    pub food_eaten: f64,
+    /// Food cells would have eaten but for their `max_food_intake`.
+    #[serde(default)]
+    pub food_beyond_intake: f64,
+    pub food_decayed: f64,
+    /// Food left behind by dead cells, which is not counted in `food_added`.
+    #[serde(default)]
+    pub food_from_corpses: f64,
+    /// Energy given away by cells to their kin, before any lost to `donation_efficiency`.
+    #[serde(default)]
+    pub energy_donated: f64,
+    /// The deaths in `num_died` by cause, counted since this field was added to checkpoints.
+    #[serde(default)]
+    pub deaths: DeathCounts,
+    #[serde(default)]
+    pub protein_added: f64,
+    #[serde(default)]
+    pub protein_eaten: f64,
+    #[serde(default)]
+    pub protein_decayed: f64,
+    /// Toxin left behind by the energy cells spent.
+    #[serde(default)]
+    pub toxin_added: f64,
+    #[serde(default)]
+    pub toxin_neutralized: f64,
+    /// The ages of the cells counted in `deaths`, except the parents replaced by their
+    /// generation's offspring.
+    #[serde(default)]
+    pub ages_at_death: AgesAtDeath,
+}
+
+/// Turns dead cells back into food, closing the loop from food to cells and back, so that
+/// scavengers can live off the dead. Each dead cell leaves `food_per_energy` food per unit of the
+/// energy it died with, plus `food_per_size` per unit of its size, for the body that remains
+/// even of a cell that starved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorpseRecycling {
+    pub food_per_energy: F32Positive,
+    pub food_per_size: F32Positive,
+}
+
+impl CorpseRecycling {
+    pub const NONE: CorpseRecycling = CorpseRecycling {
+        food_per_energy: F32Positive::unchecked(0.0),
+        food_per_size: F32Positive::unchecked(0.0),
+    };
+
+    pub fn food_from(&self, cell: &Cell) -> F32Positive {
+        cell.energy() * self.food_per_energy + cell.genome().size * self.food_per_size
+    }
+}
+
+/// Sums of the ages at which cells died, for their mean and standard deviation, and the oldest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgesAtDeath {
+    pub count: u64,
+    pub sum: f64,
+    pub sum_of_squares: f64,
+    pub max: u32,
+}
+
+impl AgesAtDeath {
+    pub fn record(&mut self, age: u32) {
+        self.count += 1;
+        self.sum += age as f64;
+        self.sum_of_squares += (age as f64).powi(2);
+        self.max = self.max.max(age);
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum / self.count as f64 }
+    }
+
+    pub fn stdev(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        (self.sum_of_squares / self.count as f64 - self.mean().powi(2)).max(0.0).sqrt()
+    }
+}
+
+/// Numbers of cells that died of each `DeathCause`, plus the living parents replaced by their
+/// generation's offspring, which have none.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeathCounts {
+    pub old_age: u64,
+    pub entropy: u64,
+    pub starvation: u64,
+    pub energy_expenditure: u64,
+    pub toxin: u64,
+    pub excess_energy: u64,
+    pub attack: u64,
+    pub crowding: u64,
+    pub hazard: u64,
+    pub culled: u64,
+    pub replaced: u64,
+}
+
+impl DeathCounts {
+    pub fn record(&mut self, cell: &Cell) {
+        let count = match cell.death_cause() {
+            Some(DeathCause::OldAge) => &mut self.old_age,
+            Some(DeathCause::Entropy) => &mut self.entropy,
+            Some(DeathCause::Starvation) => &mut self.starvation,
+            Some(DeathCause::EnergyExpenditure) => &mut self.energy_expenditure,
+            Some(DeathCause::Toxin) => &mut self.toxin,
+            Some(DeathCause::ExcessEnergy) => &mut self.excess_energy,
+            Some(DeathCause::Attack) => &mut self.attack,
+            Some(DeathCause::Crowding) => &mut self.crowding,
+            Some(DeathCause::Hazard) => &mut self.hazard,
+            Some(DeathCause::Culled) => &mut self.culled,
+            None => &mut self.replaced,
+        };
+        *count += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.by_cause().iter().map(|(_, count)| count).sum()
+    }
+
+    /// Each count with its cause's name, as `DeathCause` displays it, ending with `replaced`.
+    pub fn by_cause(&self) -> [(&'static str, u64); 11] {
+        [
+            ("old_age", self.old_age),
+            ("entropy", self.entropy),
+            ("starvation", self.starvation),
+            ("energy_expenditure", self.energy_expenditure),
+            ("toxin", self.toxin),
+            ("excess_energy", self.excess_energy),
+            ("attack", self.attack),
+            ("crowding", self.crowding),
+            ("hazard", self.hazard),
+            ("culled", self.culled),
+            ("replaced", self.replaced),
+        ]
+    }
+}
+
+impl Sub for DeathCounts {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        DeathCounts {
+            old_age: self.old_age - rhs.old_age,
+            entropy: self.entropy - rhs.entropy,
+            starvation: self.starvation - rhs.starvation,
+            energy_expenditure: self.energy_expenditure - rhs.energy_expenditure,
+            toxin: self.toxin - rhs.toxin,
+            excess_energy: self.excess_energy - rhs.excess_energy,
+            attack: self.attack - rhs.attack,
+            crowding: self.crowding - rhs.crowding,
+            hazard: self.hazard - rhs.hazard,
+            culled: self.culled - rhs.culled,
+            replaced: self.replaced - rhs.replaced,
+        }
+    }
 }
 
 impl World {
+    /// An empty world, seeded from the operating system's randomness with the `entropy` feature,
+    /// and with 0 otherwise, until `with_seed`.
     pub fn new() -> Self {
         World {
-            cells: vec![],
-            food: 0.0.into(),
+            cells: CellColumns::new(),
+            next_cell_id: 0,
+            food: PoolAmount::default(),
+            food_decay_rate: 0.0.into(),
             food_sources: vec![],
+            light_sources: vec![],
+            generations: Generations::Overlapping,
+            reproduction_mode: ReproductionMode::Asexual,
+            steps_into_generation: 0,
+            day_cycle: DayCycle::new(0),
+            rng: Self::initial_rng(),
+            totals: WorldTotals::default(),
+            toxin: PoolAmount::default(),
+            toxin_per_energy_expended: 0.0.into(),
+            max_cells: 0,
+            crowding: Crowding::CullWeakest,
+            num_steps: 0,
+            protein: PoolAmount::default(),
+            protein_sources: vec![],
+            cell_sources: vec![],
+            extinction_reseeding: None,
+            audit: Audit::Off,
+            corpse_recycling: CorpseRecycling::NONE,
+            food_allocation: Self::default_food_allocation(),
+            stress_source: None,
+            observers: vec![],
         }
     }
 
-    pub fn with_cells(mut self, cells: Vec<Cell>) -> Self {
-        self.cells = cells;
+    fn default_food_allocation() -> Box<dyn FoodAllocation> {
+        Box::new(EqualShares)
+    }
+
+    #[cfg(feature = "entropy")]
+    fn initial_rng() -> Pcg64 {
+        Pcg64::from_entropy()
+    }
+
+    #[cfg(not(feature = "entropy"))]
+    fn initial_rng() -> Pcg64 {
+        Pcg64::seed_from_u64(0)
+    }
+
+    pub fn with_day_length(mut self, day_length: u32) -> Self {
+        self.day_cycle = DayCycle::new(day_length);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_cells(mut self, mut cells: Vec<Cell>) -> Self {
+        self.assign_ids(&mut cells);
+        self.cells = CellColumns::from(cells);
         self
     }
 
     #[allow(dead_code)]
-    pub fn with_cell(mut self, cell: Cell) -> Self {
+    pub fn with_cell(mut self, mut cell: Cell) -> Self {
+        self.assign_ids(std::slice::from_mut(&mut cell));
         self.cells.push(cell);
         self
     }
 
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
     pub fn with_food(mut self, food: F32Positive) -> Self {
-        self.food = food;
+        self.food = food.into();
+        self
+    }
+
+    /// Uneaten food loses this fraction of itself at the end of each step.
+    pub fn with_food_decay_rate(mut self, food_decay_rate: F32ZeroToOne) -> Self {
+        self.food_decay_rate = food_decay_rate;
         self
     }
 
@@ -41,9 +331,112 @@ impl World {
         self
     }
 
+    /// Protein is a second nutrient, which cells eat with the share of their eating energy
+    /// given by their `protein_preference`. It decays at the same rate as food.
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
+    pub fn with_protein(mut self, protein: F32Positive) -> Self {
+        self.protein = protein.into();
+        self
+    }
+
+    /// Sources of protein, which see the world's protein where other food sources see its food.
+    pub fn with_protein_sources(mut self, protein_sources: Vec<Box<dyn FoodSource>>) -> Self {
+        self.protein_sources = protein_sources;
+        self
+    }
+
+    /// Cell sources add immigrants to the world at the start of each step.
+    pub fn with_cell_sources(mut self, cell_sources: Vec<Box<dyn CellSource>>) -> Self {
+        self.cell_sources = cell_sources;
+        self
+    }
+
+    pub fn with_extinction_reseeding(mut self, extinction_reseeding: ExtinctionReseeding) -> Self {
+        self.extinction_reseeding = Some(extinction_reseeding);
+        self
+    }
+
+    pub fn with_food_allocation(mut self, food_allocation: Box<dyn FoodAllocation>) -> Self {
+        self.food_allocation = food_allocation;
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Audit) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    pub fn with_light_sources(mut self, light_sources: Vec<Box<dyn LightSource>>) -> Self {
+        self.light_sources = light_sources;
+        self
+    }
+
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
+    pub fn with_toxin(mut self, toxin: F32Positive) -> Self {
+        self.toxin = toxin.into();
+        self
+    }
+
+    /// Cells leave behind this much toxin for each unit of energy they expend. Toxin stays in
+    /// the world, harming every cell once there is more per cell than its `toxin_tolerance`,
+    /// until cells spend detox energy neutralizing it. Detox benefits all cells while costing
+    /// only the ones that do it.
+    pub fn with_toxin_per_energy_expended(mut self, toxin_per_energy_expended: F32Positive) -> Self {
+        self.toxin_per_energy_expended = toxin_per_energy_expended;
+        self
+    }
+
+    pub fn with_corpse_recycling(mut self, corpse_recycling: CorpseRecycling) -> Self {
+        self.corpse_recycling = corpse_recycling;
+        self
+    }
+
+    /// Multiplies cells' entropy damage by the stress from `stress_source` each step. Without
+    /// one, the stress is always 1.
+    pub fn with_stress_source(mut self, stress_source: Box<dyn StressSource>) -> Self {
+        self.stress_source = Some(stress_source);
+        self
+    }
+
+    pub fn with_generations(mut self, generations: Generations) -> Self {
+        self.generations = generations;
+        self
+    }
+
+    /// Keeps the population near `max_cells` (0 for no limit) in the way `crowding` says.
+    pub fn with_max_cells(mut self, max_cells: usize, crowding: Crowding) -> Self {
+        self.max_cells = max_cells;
+        self.crowding = crowding;
+        self
+    }
+
+    pub fn with_reproduction_mode(mut self, reproduction_mode: ReproductionMode) -> Self {
+        self.reproduction_mode = reproduction_mode;
+        self
+    }
+
     #[allow(dead_code)]
-    pub fn cell(&self, index: usize) -> &Cell {
-        &self.cells[index]
+    pub fn cell(&self, index: usize) -> CellView<'_> {
+        self.cells.get(index)
+    }
+
+    pub fn cells(&self) -> &CellColumns {
+        &self.cells
+    }
+
+    /// The living cell with this id, if any.
+    pub fn find_cell(&self, id: u64) -> Option<CellView<'_>> {
+        self.cells.states().iter()
+            .position(|state| state.id == id)
+            .map(|index| self.cells.get(index))
+    }
+
+    /// `n` distinct cells chosen at random, or every cell in random order if there are no more
+    /// than `n`, for examining a large population's genomes without copying them all.
+    pub fn sample_cells<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<CellView<'_>> {
+        index::sample(rng, self.cells.len(), n.min(self.cells.len())).into_iter()
+            .map(|index| self.cells.get(index))
+            .collect()
     }
 
     pub fn num_cells(&self) -> usize {
@@ -51,67 +444,617 @@ impl World {
     }
 
     pub fn mean_health(&self) -> f32 {
-        if self.cells.is_empty() {
-            return 0.0;
-        }
-
-        self.cells.iter().map(|cell| cell.health().value()).sum::<f32>() / self.cells.len() as f32
+        self.mean_over_cells(self.cells.healths().iter().map(|health| health.value() as f64))
     }
 
     pub fn mean_energy(&self) -> f32 {
+        self.mean_over_cells(self.cells.energies().iter().map(|energy| energy.value() as f64))
+    }
+
+    pub fn mean_age(&self) -> f32 {
+        self.mean_over_cells(self.cells.states().iter().map(|state| state.age as f64))
+    }
+
+    /// Means over large populations are summed with compensation, so they do not drift with
+    /// the number of cells.
+    fn mean_over_cells<I: Iterator<Item=f64>>(&self, values: I) -> f32 {
         if self.cells.is_empty() {
             return 0.0;
         }
 
-        self.cells.iter().map(|cell| cell.energy().value()).sum::<f32>() / self.cells.len() as f32
+        (compensated_sum(values) / self.cells.len() as f64) as f32
+    }
+
+    pub fn max_age(&self) -> u32 {
+        self.cells.states().iter().map(|state| state.age).max().unwrap_or(0)
     }
 
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
     pub fn food(&self) -> F32Positive {
-        self.food
+        self.food.into()
+    }
+
+    /// Replaces the world's food, such as by an intervention.
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
+    pub fn set_food(&mut self, food: F32Positive) {
+        self.food = food.into();
+    }
+
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
+    pub fn protein(&self) -> F32Positive {
+        self.protein.into()
+    }
+
+    #[cfg_attr(not(feature = "f64"), allow(clippy::useless_conversion))]
+    pub fn toxin(&self) -> F32Positive {
+        self.toxin.into()
+    }
+
+    pub fn totals(&self) -> WorldTotals {
+        self.totals
+    }
+
+    /// The number of steps the world has taken, including any before it was checkpointed.
+    pub fn num_steps(&self) -> u32 {
+        self.num_steps
+    }
+
+    /// Adds cells from outside the world, such as by an intervention. They are not counted as
+    /// births in the totals.
+    pub fn add_cells(&mut self, mut cells: Vec<Cell>) {
+        self.assign_ids(&mut cells);
+        self.cells.extend(cells);
+    }
+
+    /// Kills `fraction` of the cells, rounded to the nearest cell and chosen at random, and
+    /// returns how many it killed. They are counted as deaths in the totals.
+    pub fn cull(&mut self, fraction: F32ZeroToOne) -> usize {
+        let num_killed = (self.cells.len() as f32 * fraction.value()).round() as usize;
+        let mut indexes = index::sample(&mut self.rng, self.cells.len(), num_killed.min(self.cells.len())).into_vec();
+        indexes.sort_unstable();
+        for &index in &indexes {
+            self.cells.with_cell_mut(index, |cell| cell.die_of(DeathCause::Culled));
+        }
+        let registered = std::mem::take(&mut self.observers);
+        self.remove_cells(&mut indexes, &mut ObserverSet { registered: &registered, passed: &mut NullObserver {} });
+        self.observers = registered;
+        self.totals.num_died += indexes.len() as u64;
+        indexes.len()
+    }
+
+    /// Registers an observer to be told about every step from now on, until it is removed.
+    /// Observers are not saved with the world.
+    pub fn add_observer(&mut self, observer: Rc<RefCell<dyn WorldObserver>>) {
+        self.observers.push(observer);
+    }
+
+    pub fn remove_observer<O: WorldObserver>(&mut self, observer: &Rc<RefCell<O>>) {
+        let address = Rc::as_ptr(observer) as *const u8;
+        self.observers.retain(|registered| Rc::as_ptr(registered) as *const u8 != address);
+    }
+
+    /// Audits the steps from now on as `audit` says, e.g. in a world resumed from a checkpoint.
+    pub fn set_audit(&mut self, audit: Audit) {
+        self.audit = audit;
+    }
+
+    /// Gives every cell these constants, which their descendants then inherit.
+    pub fn set_cell_constants(&mut self, constants: &Rc<CellConstants>) {
+        self.cells.set_constants(constants);
     }
 
     pub fn step(&mut self, mutation_number_source: &mut dyn MutationNumberSource) -> (usize, usize) {
+        self.step_observed(mutation_number_source, &mut NullObserver {})
+    }
+
+    /// Like `step`, but also tells `observer` about this step, along with the registered
+    /// observers.
+    pub fn step_observed(&mut self, mutation_number_source: &mut dyn MutationNumberSource, observer: &mut dyn WorldObserver) -> (usize, usize) {
+        let registered = std::mem::take(&mut self.observers);
+        let mut observers = ObserverSet { registered: &registered, passed: observer };
+        observers.before_step(self);
+        let (num_born, num_died) = self.step_cells_and_pools(mutation_number_source, &mut observers);
+        observers.after_step(self, num_born, num_died);
+        self.observers = registered;
+        (num_born, num_died)
+    }
+
+    fn step_cells_and_pools(&mut self, mutation_number_source: &mut dyn MutationNumberSource, observer: &mut dyn WorldObserver) -> (usize, usize) {
+        let pools_before = self.audit.is_on().then(|| self.pool_balances());
         self.step_food_sources();
+        self.step_cell_sources();
+        let light = self.step_light_sources();
 
         let environment = CellEnvironment {
-            food_per_cell: (self.food.value() / (self.cells.len() as f32)).into(),
+            food_per_cell: self.per_cell(self.food()),
+            protein_per_cell: self.per_cell(self.protein()),
+            light_per_cell: self.per_cell(light),
+            toxin_per_cell: self.per_cell(self.toxin()),
+            time_of_day: self.day_cycle.time_of_day(),
+            reproduction_allowed: self.generations == Generations::Overlapping,
+            stress: self.step_stress_source(),
         };
         let mut new_cells = vec![];
-        let mut dead_cell_indexes = Vec::with_capacity(self.cells.len());
 
-        self.step_cells(mutation_number_source, &environment, &mut new_cells, &mut dead_cell_indexes);
+        self.step_cells(mutation_number_source, &environment, &mut new_cells);
+        self.predation();
+        self.trade();
+        self.donate_to_kin();
+        self.decay_food();
+        if let Some(pools_before) = pools_before {
+            self.audit_pools(&pools_before);
+        }
+        self.death_hazards();
+        if self.crowding == Crowding::Damage {
+            self.crowding_damage();
+        }
+        let mut dead_cell_indexes = self.dead_cell_indexes();
 
         let num_added = new_cells.len();
-        self.cells.append(&mut new_cells);
-        self.remove_cells(&mut dead_cell_indexes);
+        self.assign_ids(&mut new_cells);
+        for cell in &new_cells {
+            observer.on_birth(cell);
+        }
+        self.cells.extend(new_cells);
+        self.remove_cells(&mut dead_cell_indexes, observer);
+        let mut num_died = dead_cell_indexes.len();
+        if self.crowding == Crowding::CullWeakest {
+            num_died += self.cull_weakest(observer);
+        }
+        self.day_cycle.advance();
+
+        let (num_born, num_died) = if self.end_of_generation() {
+            let (num_born, num_replaced) = self.replace_generation(mutation_number_source, observer);
+            (num_added + num_born, num_died + num_replaced)
+        } else {
+            (num_added, num_died)
+        };
+        self.totals.num_born += num_born as u64;
+        self.totals.num_died += num_died as u64;
+        if num_died > 0 && self.cells.is_empty() {
+            self.totals.num_extinctions += 1;
+            self.reseed();
+        }
+        self.num_steps += 1;
+        (num_born, num_died)
+    }
+
+    /// Founders are not counted as births.
+    fn reseed(&mut self) {
+        if let Some(reseeding) = &mut self.extinction_reseeding {
+            let founders = reseeding.founders();
+            self.add_cells(founders);
+        }
+    }
+
+    fn assign_ids(&mut self, cells: &mut [Cell]) {
+        for cell in cells {
+            cell.set_id(self.next_cell_id);
+            self.next_cell_id += 1;
+        }
+    }
+
+    fn end_of_generation(&mut self) -> bool {
+        match self.generations {
+            Generations::Overlapping => false,
+            Generations::NonOverlapping { steps_per_generation } | Generations::Selected { steps_per_generation, .. } => {
+                self.steps_into_generation += 1;
+                if self.steps_into_generation < steps_per_generation {
+                    return false;
+                }
+                self.steps_into_generation = 0;
+                true
+            }
+        }
+    }
+
+    fn replace_generation(&mut self, mutation_number_source: &mut dyn MutationNumberSource, observer: &mut dyn WorldObserver) -> (usize, usize) {
+        let mut offspring = match self.generations {
+            Generations::Selected { population_size, child_energy, .. } =>
+                self.select_offspring(population_size, child_energy, mutation_number_source),
+            _ => self.offspring_from_accumulated_energy(mutation_number_source),
+        };
+
+        let num_parents = self.cells.len();
+        self.assign_ids(&mut offspring);
+        for child in &offspring {
+            observer.on_birth(child);
+        }
+        let parents: Vec<Cell> = self.cells.iter().map(|parent| parent.to_cell()).collect();
+        // Parents give way to their offspring rather than dying, so they leave no corpses and
+        // no ages at death.
+        for parent in &parents {
+            self.totals.deaths.record(parent);
+            observer.on_death(parent);
+        }
+        self.cells = CellColumns::from(offspring);
+        (self.cells.len(), num_parents)
+    }
+
+    fn offspring_from_accumulated_energy(&mut self, mutation_number_source: &mut dyn MutationNumberSource) -> Vec<Cell> {
+        let mut offspring = vec![];
+        for parent_index in 0..self.cells.len() {
+            offspring.append(&mut self.with_cell_and_mate_mut(parent_index, |parent, mate| {
+                parent.reproduce_from_accumulated_energy(mate, mutation_number_source)
+            }));
+        }
+        offspring
+    }
+
+    /// Draws `population_size` parents, with replacement, in proportion to their energy, or
+    /// uniformly if none has any, and gives each one child.
+    fn select_offspring(&mut self, population_size: usize, child_energy: F32Positive, mutation_number_source: &mut dyn MutationNumberSource) -> Vec<Cell> {
+        if self.cells.is_empty() {
+            return vec![];
+        }
 
-        (num_added, dead_cell_indexes.len())
+        let by_energy = WeightedIndex::new(self.cells.energies().iter().map(|energy| energy.value())).ok();
+        let mut offspring = Vec::with_capacity(population_size);
+        for _ in 0..population_size {
+            let parent_index = match &by_energy {
+                Some(by_energy) => by_energy.sample(&mut self.rng),
+                None => self.rng.gen_range(0..self.cells.len()),
+            };
+            offspring.extend(self.with_cell_and_mate_mut(parent_index, |parent, mate| {
+                parent.create_selected_child(child_energy, mate, mutation_number_source)
+            }));
+        }
+        offspring
     }
 
     fn step_food_sources(&mut self) {
+        let summary = WorldSummary {
+            step: self.num_steps,
+            num_cells: self.cells.len(),
+            food: self.food(),
+        };
         for food_source in &mut self.food_sources {
-            self.food += food_source.food_this_step();
+            let food = food_source.food_this_step(&summary);
+            self.food += food;
+            self.totals.food_added += food.value() as f64;
+        }
+
+        let summary = WorldSummary { food: self.protein(), ..summary };
+        for protein_source in &mut self.protein_sources {
+            let protein = protein_source.food_this_step(&summary);
+            self.protein += protein;
+            self.totals.protein_added += protein.value() as f64;
+        }
+    }
+
+    fn step_cell_sources(&mut self) {
+        let summary = WorldSummary {
+            step: self.num_steps,
+            num_cells: self.cells.len(),
+            food: self.food(),
+        };
+        let mut immigrants = vec![];
+        for cell_source in &mut self.cell_sources {
+            immigrants.extend(cell_source.cells_this_step(&summary));
+        }
+        self.totals.num_immigrated += immigrants.len() as u64;
+        self.add_cells(immigrants);
+    }
+
+    fn decay_food(&mut self) {
+        let decayed = self.food() * self.food_decay_rate;
+        self.totals.food_decayed += decayed.value() as f64;
+        self.food -= decayed;
+
+        let decayed = self.protein() * self.food_decay_rate;
+        self.totals.protein_decayed += decayed.value() as f64;
+        self.protein -= decayed;
+    }
+
+    fn per_cell(&self, amount: F32Positive) -> F32Positive {
+        if self.cells.is_empty() {
+            return amount;
+        }
+
+        amount / self.cells.len()
+    }
+
+    fn step_stress_source(&mut self) -> F32Positive {
+        match &mut self.stress_source {
+            Some(stress_source) => stress_source.stress_at(self.num_steps),
+            None => 1.0.into(),
         }
     }
 
-    fn step_cells(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment, new_cells: &mut Vec<Cell>, dead_cell_indexes: &mut Vec<usize>) {
-        for (index, cell) in self.cells.iter_mut().enumerate() {
-            let (child, food_eaten) = cell.step(mutation_number_source, environment);
-            if let Some(child) = child {
+    fn step_light_sources(&mut self) -> F32Positive {
+        self.light_sources.iter_mut()
+            .map(|light_source| light_source.light_this_step())
+            .sum()
+    }
+
+    /// Each cell senses its own share of the food as its food per cell.
+    fn step_cells(&mut self, mutation_number_source: &mut dyn MutationNumberSource, environment: &CellEnvironment, new_cells: &mut Vec<Cell>) {
+        let mut food_shares = self.food_allocation.shares(self.food(), &self.cells, &mut self.rng);
+        self.deliver_food_access(&mut food_shares);
+        for (index, food_share) in food_shares.into_iter().enumerate() {
+            let environment = &CellEnvironment { food_per_cell: food_share, ..*environment };
+            let energy_before = self.audit.is_on().then(|| self.cells.energies()[index].value() as f64);
+            let outcome = self.with_cell_and_mate_mut(index, |cell, mate| {
+                cell.step_with_mate(mutation_number_source, environment, mate)
+            });
+            if let Some(energy_before) = energy_before {
+                self.audit_cell_step(index, energy_before, &outcome, environment);
+            }
+            if let Some(child) = outcome.child {
                 new_cells.push(child);
             }
-            self.food -= food_eaten;
-            if !cell.is_alive() {
-                dead_cell_indexes.push(index);
+            self.food -= outcome.food_eaten;
+            self.totals.food_eaten += outcome.food_eaten.value() as f64;
+            self.totals.food_beyond_intake += outcome.food_beyond_intake.value() as f64;
+            self.protein -= outcome.protein_eaten;
+            self.totals.protein_eaten += outcome.protein_eaten.value() as f64;
+            self.toxin -= outcome.toxin_neutralized;
+            self.totals.toxin_neutralized += outcome.toxin_neutralized.value() as f64;
+            let toxin = outcome.energy_expended * self.toxin_per_energy_expended;
+            self.toxin += toxin;
+            self.totals.toxin_added += toxin.value() as f64;
+        }
+    }
+
+    /// Each cell that traded for energy last step gives its partner up to the food access it owes
+    /// out of its food share. Owed access is lost if either cell has since died.
+    fn deliver_food_access(&mut self, food_shares: &mut [F32Positive]) {
+        if self.cells.states().iter().all(|state| state.food_access_owed.is_none()) {
+            return;
+        }
+
+        let index_by_id: HashMap<u64, usize> = self.cells.states().iter().enumerate()
+            .map(|(index, state)| (state.id, index))
+            .collect();
+        for index in 0..self.cells.len() {
+            let Some((partner_id, owed)) = self.cells.with_cell_mut(index, |cell| cell.take_food_access_owed()) else {
+                continue;
+            };
+            let Some(&partner_index) = index_by_id.get(&partner_id) else {
+                continue;
+            };
+            let delivered = owed.min(food_shares[index]);
+            food_shares[index] -= delivered;
+            food_shares[partner_index] += delivered;
+            self.cells.with_cell_mut(partner_index, |partner| partner.receive_food_access(delivered));
+        }
+    }
+
+    fn pool_balances(&self) -> PoolBalances {
+        PoolBalances {
+            food: pool_amount(self.food),
+            protein: pool_amount(self.protein),
+            toxin: pool_amount(self.toxin),
+            totals: self.totals,
+        }
+    }
+
+    /// Each pool must have changed by exactly what the step's flows into and out of it add up
+    /// to. A pool asked for more than it held is emptied rather than going negative, so
+    /// overeating shows up here.
+    fn audit_pools(&self, before: &PoolBalances) {
+        let (step, totals) = (self.num_steps + 1, &self.totals);
+        let after = self.pool_balances();
+        self.audit.check_balance(step, "food", after.food,
+                                 before.food + (totals.food_added - before.totals.food_added)
+                                     + (totals.food_from_corpses - before.totals.food_from_corpses)
+                                     - (totals.food_eaten - before.totals.food_eaten)
+                                     - (totals.food_decayed - before.totals.food_decayed));
+        self.audit.check_balance(step, "protein", after.protein,
+                                 before.protein + (totals.protein_added - before.totals.protein_added)
+                                     - (totals.protein_eaten - before.totals.protein_eaten)
+                                     - (totals.protein_decayed - before.totals.protein_decayed));
+        self.audit.check_balance(step, "toxin", after.toxin,
+                                 before.toxin + (totals.toxin_added - before.totals.toxin_added)
+                                     - (totals.toxin_neutralized - before.totals.toxin_neutralized));
+    }
+
+    /// A cell can end its step with no more energy than it had less what it spent, which includes
+    /// any it gave its child or offered for trade, plus what digesting the food and protein it
+    /// ate and absorbing all its share of the light could give it.
+    fn audit_cell_step(&self, index: usize, energy_before: f64, outcome: &CellStepOutcome, environment: &CellEnvironment) {
+        let cell = self.cells.get(index);
+        let constants = cell.constants();
+        let gain_limit = outcome.food_eaten.value() as f64 * constants.energy_yield_from_digestion.value() as f64 * cell.genome().digestion_efficiency.value() as f64
+            + outcome.protein_eaten.value() as f64 * constants.energy_yield_from_protein_digestion.value() as f64
+            + environment.light_per_cell.value() as f64 * constants.energy_yield_from_light.value() as f64;
+        self.audit.check_limit(self.num_steps + 1, &format!("energy of cell {} after its step", cell.id()),
+                               cell.energy().value() as f64, energy_before - outcome.energy_expended.value() as f64 + gain_limit);
+    }
+
+    /// The energy held by all the cells, for audits, or `None` when not auditing.
+    fn audited_energy(&self) -> Option<f64> {
+        self.audit.is_on().then(|| compensated_sum(self.cells.energies().iter().zip(self.cells.states()).map(held_energy)))
+    }
+
+    fn audit_energy(&self, what: &str, energy_before: Option<f64>, gain_limit: f64) {
+        if let (Some(energy_before), Some(energy_after)) = (energy_before, self.audited_energy()) {
+            self.audit.check_limit(self.num_steps + 1, what, energy_after, energy_before + gain_limit);
+        }
+    }
+
+    /// Attackers take energy only from their victims.
+    fn predation(&mut self) {
+        if self.cells.len() < 2 {
+            return;
+        }
+
+        let energy_before = self.audited_energy();
+        for attacker_index in 0..self.cells.len() {
+            if self.cells.states()[attacker_index].attack_energy.value() <= 0.0 || !self.cells.is_alive(attacker_index) {
+                continue;
+            }
+
+            let victim_index = self.choose_other_cell(attacker_index);
+            if !self.cells.is_alive(victim_index) {
+                continue;
+            }
+
+            self.cells.with_two_cells_mut(attacker_index, victim_index, |attacker, victim| attacker.attack(victim));
+        }
+        self.audit_energy("cells' energy after predation", energy_before, 0.0);
+    }
+
+    /// The world has no geometry, so the cells that are adjacent for trading are random pairs
+    /// drawn fresh each step.
+    fn trade(&mut self) {
+        if !self.cells.states().iter().any(|state| state.trade_energy.value() > 0.0) {
+            return;
+        }
+
+        let energy_before = self.audited_energy();
+        let gain_limit = compensated_sum(self.cells.iter()
+            .map(|cell| cell.trade_energy().value() as f64 * (cell.constants().trade_efficiency.value() as f64 - 1.0).max(0.0)));
+        let mut trader_indexes: Vec<usize> = (0..self.cells.len())
+            .filter(|&index| self.cells.is_alive(index))
+            .collect();
+        trader_indexes.shuffle(&mut self.rng);
+        for pair in trader_indexes.chunks(2) {
+            match *pair {
+                [index1, index2] => self.cells.with_two_cells_mut(index1, index2, |cell1, cell2| cell1.trade(cell2)),
+                [index] => self.cells.with_cell_mut(index, |cell| cell.withdraw_trade_offer()),
+                _ => unreachable!(),
+            }
+        }
+        self.audit_energy("cells' energy after trading", energy_before, gain_limit);
+    }
+
+    /// Cells can donate only to their kin, i.e. their parent, children, and siblings, and
+    /// donate to whichever living one of them has the least energy.
+    fn donate_to_kin(&mut self) {
+        if !self.cells.genomes().iter().any(|genome| genome.donation_fraction.value() > 0.0) {
+            return;
+        }
+
+        let energy_before = self.audited_energy();
+        let index_by_id: HashMap<u64, usize> = self.cells.states().iter().enumerate()
+            .map(|(index, state)| (state.id, index))
+            .collect();
+        let mut child_indexes_by_parent_id: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, state) in self.cells.states().iter().enumerate() {
+            if let Some(parent_id) = state.parent_id {
+                child_indexes_by_parent_id.entry(parent_id).or_default().push(index);
+            }
+        }
+
+        for donor_index in 0..self.cells.len() {
+            let donor = &self.cells.states()[donor_index];
+            if self.cells.genomes()[donor_index].donation_fraction.value() <= 0.0 || !self.cells.is_alive(donor_index) {
+                continue;
+            }
+
+            let parent_index = donor.parent_id.and_then(|parent_id| index_by_id.get(&parent_id).copied());
+            let child_indexes = child_indexes_by_parent_id.get(&donor.id).into_iter().flatten();
+            let sibling_indexes = donor.parent_id.and_then(|parent_id| child_indexes_by_parent_id.get(&parent_id)).into_iter().flatten();
+            let recipient_index = parent_index.iter().chain(child_indexes).chain(sibling_indexes)
+                .copied()
+                .filter(|&index| index != donor_index && self.cells.is_alive(index))
+                .min_by(|&index1, &index2| self.cells.energies()[index1].value().total_cmp(&self.cells.energies()[index2].value()));
+            if let Some(recipient_index) = recipient_index {
+                let donated = self.cells.with_two_cells_mut(donor_index, recipient_index, |donor, recipient| donor.donate(recipient));
+                self.totals.energy_donated += donated.value() as f64;
             }
         }
+        self.audit_energy("cells' energy after donating", energy_before, 0.0);
+    }
+
+    fn death_hazards(&mut self) {
+        for index in 0..self.cells.len() {
+            self.cells.with_cell_mut(index, |cell| cell.face_death_hazard(&mut self.rng));
+        }
+    }
+
+    /// Runs `f` on a cell with the source of its mate. Sexually reproducing cells mate with a
+    /// random other cell, chosen only if they reproduce.
+    fn with_cell_and_mate_mut<R, F: FnOnce(&mut CellMut, &mut dyn MateSource) -> R>(&mut self, index: usize, f: F) -> R {
+        let (reproduction_mode, rng) = (self.reproduction_mode, &mut self.rng);
+        self.cells.with_cell_mut_and_other_genomes(index, |cell, others| match reproduction_mode {
+            ReproductionMode::Asexual => f(cell, &mut NoMate),
+            ReproductionMode::Sexual => f(cell, &mut RandomMate { others, rng }),
+        })
+    }
+
+    fn choose_other_cell(&mut self, index: usize) -> usize {
+        let other_index = self.rng.gen_range(0..self.cells.len() - 1);
+        if other_index >= index { other_index + 1 } else { other_index }
+    }
+
+    fn excess_cells(&self) -> usize {
+        if self.max_cells == 0 { 0 } else { self.cells.len().saturating_sub(self.max_cells) }
+    }
+
+    /// Every cell loses the fraction of the population that is over the limit from its health.
+    fn crowding_damage(&mut self) {
+        let excess = self.excess_cells();
+        if excess == 0 {
+            return;
+        }
+        let health_reduction = F32ZeroToOne::clipped(excess as f32 / self.cells.len() as f32);
+        for index in 0..self.cells.len() {
+            self.cells.with_cell_mut(index, |cell| cell.suffer_crowding(health_reduction));
+        }
+    }
+
+    /// Removes the cells over the limit with the least health, and of those with equal health,
+    /// the least energy, and returns how many it removed.
+    fn cull_weakest(&mut self, observer: &mut dyn WorldObserver) -> usize {
+        let excess = self.excess_cells();
+        if excess == 0 {
+            return 0;
+        }
+        let (healths, energies) = (self.cells.healths(), self.cells.energies());
+        let mut indexes: Vec<usize> = (0..self.cells.len()).collect();
+        indexes.sort_by(|&a, &b| healths[a].value().total_cmp(&healths[b].value())
+            .then(energies[a].value().total_cmp(&energies[b].value())));
+        indexes.truncate(excess);
+        indexes.sort_unstable();
+        for &index in &indexes {
+            self.cells.with_cell_mut(index, |cell| cell.die_of(DeathCause::Crowding));
+        }
+        self.remove_cells(&mut indexes, observer);
+        excess
+    }
+
+    fn dead_cell_indexes(&self) -> Vec<usize> {
+        self.cells.healths().iter().enumerate()
+            .filter(|(_, health)| health.value() <= 0.0)
+            .map(|(index, _)| index)
+            .collect()
     }
 
-    fn remove_cells(&mut self, sorted_indexes: &mut [usize]) {
+    fn remove_cells(&mut self, sorted_indexes: &mut [usize], observer: &mut dyn WorldObserver) {
         for index in sorted_indexes.iter().rev() {
-            self.cells.swap_remove(*index);
+            let cell = self.cells.swap_remove(*index);
+            self.record_death(&cell);
+            observer.on_death(&cell);
         }
     }
+
+    fn record_death(&mut self, cell: &Cell) {
+        self.totals.deaths.record(cell);
+        self.totals.ages_at_death.record(cell.age());
+        let food = self.corpse_recycling.food_from(cell);
+        self.food += food;
+        self.totals.food_from_corpses += food.value() as f64;
+    }
+}
+
+/// The pools and totals at the start of a step, for audits.
+struct PoolBalances {
+    food: f64,
+    protein: f64,
+    toxin: f64,
+    totals: WorldTotals,
+}
+
+#[cfg_attr(feature = "f64", allow(clippy::useless_conversion))]
+fn pool_amount(amount: PoolAmount) -> f64 {
+    F64Positive::from(amount).value()
+}
+
+/// A cell's energy, including any trade energy it has offered but not yet traded.
+fn held_energy((energy, state): (&F32Positive, &CellState)) -> f64 {
+    energy.value() as f64 + state.trade_energy.value() as f64
 }
 
 impl Default for World {
@@ -120,35 +1063,183 @@ impl Default for World {
     }
 }
 
-pub fn generate_cells(
+/// Instrumentation for a world's steps, registered with `World::add_observer` or passed to
+/// `World::step_observed`. Cells that die are passed after removal from the world, with their
+/// final state.
+pub trait WorldObserver {
+    fn before_step(&mut self, _world: &World) {}
+
+    /// Called with the numbers of cells born and died during the step, as `World::step` returns.
+    fn after_step(&mut self, _world: &World, _num_born: usize, _num_died: usize) {}
+
+    fn on_birth(&mut self, _cell: &Cell) {}
+
+    fn on_death(&mut self, _cell: &Cell) {}
+}
+
+struct NullObserver {}
+
+impl WorldObserver for NullObserver {}
+
+/// The registered observers and the one passed to a step, told about it in that order.
+struct ObserverSet<'a> {
+    registered: &'a [Rc<RefCell<dyn WorldObserver>>],
+    passed: &'a mut dyn WorldObserver,
+}
+
+impl WorldObserver for ObserverSet<'_> {
+    fn before_step(&mut self, world: &World) {
+        for observer in self.registered {
+            observer.borrow_mut().before_step(world);
+        }
+        self.passed.before_step(world);
+    }
+
+    fn after_step(&mut self, world: &World, num_born: usize, num_died: usize) {
+        for observer in self.registered {
+            observer.borrow_mut().after_step(world, num_born, num_died);
+        }
+        self.passed.after_step(world, num_born, num_died);
+    }
+
+    fn on_birth(&mut self, cell: &Cell) {
+        for observer in self.registered {
+            observer.borrow_mut().on_birth(cell);
+        }
+        self.passed.on_birth(cell);
+    }
+
+    fn on_death(&mut self, cell: &Cell) {
+        for observer in self.registered {
+            observer.borrow_mut().on_death(cell);
+        }
+        self.passed.on_death(cell);
+    }
+}
+
+/// Overlapping generations (the default) let cells reproduce whenever they can, so parents
+/// and children share the world. Non-overlapping generations hold all reproduction until the
+/// end of each generation, when every adult spends its accumulated energy on offspring and is
+/// then replaced by them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Generations {
+    Overlapping,
+    NonOverlapping { steps_per_generation: u32 },
+    /// Non-overlapping generations replaced like those of a genetic algorithm: the next
+    /// generation is `population_size` children of parents drawn in proportion to their energy
+    /// at the end of the generation, each starting with `child_energy`.
+    Selected { steps_per_generation: u32, population_size: usize, child_energy: F32Positive },
+}
+
+/// The mate of a sexually reproducing cell: a random other cell, whose genome the child takes
+/// each gene from with the given probability. Both choices draw from the world's random numbers.
+struct RandomMate<'a> {
+    others: OtherGenomes<'a>,
+    rng: &'a mut Pcg64,
+}
+
+impl MateSource for RandomMate<'_> {
+    fn cross_over(&mut self, child_genome: &mut Rc<Genome>, probability: F32ZeroToOne) {
+        if self.others.is_empty() {
+            return;
+        }
+
+        let mate = self.others.get(self.rng.gen_range(0..self.others.len()));
+        Genome::cross_over(child_genome, mate, probability, self.rng);
+    }
+}
+
+/// Asexual children are mutated clones of their parents. Sexual children also take each gene
+/// from a randomly chosen mate with the parent's `crossover_probability`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReproductionMode {
+    Asexual,
+    Sexual,
+}
+
+impl FromStr for ReproductionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asexual" => Ok(ReproductionMode::Asexual),
+            "sexual" => Ok(ReproductionMode::Sexual),
+            _ => Err(format!("unknown reproduction mode '{}' (expected asexual or sexual)", s)),
+        }
+    }
+}
+
+impl fmt::Display for ReproductionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReproductionMode::Asexual => write!(f, "asexual"),
+            ReproductionMode::Sexual => write!(f, "sexual"),
+        }
+    }
+}
+
+/// How a world with a `max_cells` limit treats cells beyond it. Culling removes the weakest
+/// cells over the limit at the end of each step: those with the least health, then the least
+/// energy. Damage instead takes health from every cell, the excess as a fraction of the
+/// population, so crowding kills the weakest over a few steps and the limit is not strict.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Crowding {
+    #[default]
+    CullWeakest,
+    Damage,
+}
+
+impl FromStr for Crowding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cull" => Ok(Crowding::CullWeakest),
+            "damage" => Ok(Crowding::Damage),
+            _ => Err(format!("unknown crowding response '{}' (expected cull or damage)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Crowding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Crowding::CullWeakest => write!(f, "cull"),
+            Crowding::Damage => write!(f, "damage"),
+        }
+    }
+}
+
+/// Initial healths are clipped to the range 0 to 1, so a wide distribution can found cells that
+/// are already dead.
+pub fn generate_cells<R: Rng>(
     num_cells: usize,
     initial_energies: Normal<f32>,
-    eating_energies: Normal<f32>,
-    healing_energies: Normal<f32>,
-    child_threshold_energies: Normal<f32>,
-    child_threshold_foods: Normal<f32>,
+    initial_healths: Normal<f32>,
+    gene_means: &Genome,
+    gene_stdevs: &Genome,
     constants: &Rc<CellConstants>,
+    rng: &mut R,
 ) -> Vec<Cell> {
-    let mut rng = rand::thread_rng();
     let mut cells = Vec::with_capacity(num_cells);
     for _ in 0..num_cells {
         cells.push(Cell::new(
             constants,
-            CellParams {
-                child_threshold_energy: child_threshold_energies.sample(&mut rng).into(),
-                child_threshold_food: child_threshold_foods.sample(&mut rng).into(),
-                attempted_eating_energy: eating_energies.sample(&mut rng).into(),
-                attempted_healing_energy: healing_energies.sample(&mut rng).into(),
-            },
-        ).with_energy(initial_energies.sample(&mut rng).into()));
+            Genome::sample(gene_means, gene_stdevs, rng),
+        )
+            .with_energy(F32Positive::clipped(initial_energies.sample(rng)))
+            .with_health(F32ZeroToOne::clipped(initial_healths.sample(rng))));
     }
     cells
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::cell::NullMutationNumberSource;
+    use crate::cell::{BehaviorContext, CellMut, NullMutationNumberSource};
+    use crate::cell_sources::{ConstantCellSource, ExtinctionReseeding, Immigrants};
     use crate::food_sources::ConstantFoodSource;
+    use crate::light_sources::ConstantLightSource;
+    use crate::process_order::{CellBehavior, CellBehaviors};
     use crate::world::generate_cells;
     use super::*;
 
@@ -156,9 +1247,9 @@ mod tests {
     fn world_counts_both_living_and_dead_cells() {
         let constants = Rc::new(CellConstants::DEFAULT);
         let world = World::new().with_cells(vec![
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(1.0.into()),
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(0.0.into()),
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(1.0.into()),
+            Cell::new(&constants, Genome::DEFAULT).with_energy(1.0.into()),
+            Cell::new(&constants, Genome::DEFAULT).with_energy(0.0.into()),
+            Cell::new(&constants, Genome::DEFAULT).with_energy(1.0.into()),
         ]);
         assert_eq!(world.num_cells(), 3);
     }
@@ -172,157 +1263,667 @@ mod tests {
     fn world_calculates_mean_energy() {
         let constants = Rc::new(CellConstants::DEFAULT);
         let world = World::new().with_cells(vec![
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(1.0.into()),
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(2.0.into()),
+            Cell::new(&constants, Genome::DEFAULT).with_energy(1.0.into()),
+            Cell::new(&constants, Genome::DEFAULT).with_energy(2.0.into()),
         ]);
         assert_eq!(world.mean_energy(), 1.5);
     }
 
+    #[test]
+    fn world_calculates_mean_and_max_age() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants, Genome::DEFAULT),
+        ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        world.cells.push(Cell::new(&constants, Genome::DEFAULT));
+        assert_eq!(world.mean_age(), 0.5);
+        assert_eq!(world.max_age(), 1);
+    }
+
+    #[test]
+    fn world_totals_ages_at_death() {
+        let constants = |max_age| Rc::new(CellConstants { max_age, ..CellConstants::DEFAULT });
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants(1), Genome::DEFAULT),
+            Cell::new(&constants(3), Genome::DEFAULT),
+        ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        for _ in 0..3 {
+            world.step(&mut mutation_number_source);
+        }
+        let ages = world.totals().ages_at_death;
+        assert_eq!((ages.count, ages.mean(), ages.stdev(), ages.max), (2, 2.0, 1.0, 3));
+    }
+
+    #[test]
+    fn replaced_parents_have_no_age_at_death() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_generations(Generations::NonOverlapping { steps_per_generation: 2 })
+            .with_cells(vec![Cell::new(&constants, Genome::DEFAULT)]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.totals().deaths.replaced, 1);
+        assert_eq!(world.totals().ages_at_death.count, 0);
+    }
+
+    #[test]
+    fn dead_cells_leave_food() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_from_entropy: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_cells(vec![Cell::new(&constants, Genome { size: 2.into(), ..Genome::DEFAULT }).with_energy(4.0.into())])
+            .with_corpse_recycling(CorpseRecycling { food_per_energy: 0.5.into(), food_per_size: 0.25.into() });
+        world.step(&mut NullMutationNumberSource::new());
+        assert_eq!(world.num_cells(), 0);
+        assert_eq!(world.food(), 2.5.into());
+        assert_eq!(world.totals().food_from_corpses, 2.5);
+    }
+
+    #[test]
+    fn replaced_parents_leave_no_food() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let genome = Genome {
+            child_threshold_energy: 4.0.into(),
+            child_threshold_food: 0.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new()
+            .with_generations(Generations::NonOverlapping { steps_per_generation: 2 })
+            .with_cells(vec![
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+                Cell::new(&constants, genome).with_energy(3.0.into()),
+            ])
+            .with_corpse_recycling(CorpseRecycling { food_per_energy: 1.0.into(), food_per_size: 1.0.into() });
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.num_cells(), 2);
+        assert_eq!(world.totals().deaths.replaced, 2);
+        assert_eq!(world.food(), 0.0.into());
+        assert_eq!(world.totals().food_from_corpses, 0.0);
+    }
+
+    #[test]
+    fn corpses_sustain_scavengers() {
+        let scavengers_left = |corpse_recycling| {
+            let prey_constants = Rc::new(CellConstants {
+                health_reduction_from_entropy: 1.0.into(),
+                ..CellConstants::DEFAULT
+            });
+            let scavenger_constants = Rc::new(CellConstants {
+                food_yield_from_eating: 1.0.into(),
+                energy_yield_from_digestion: 3.0.into(),
+                maintenance_energy: 1.0.into(),
+                health_reduction_per_maintenance_shortfall: 1.0.into(),
+                ..CellConstants::DEFAULT
+            });
+            let scavenger = Cell::new(&scavenger_constants, Genome { attempted_eating_energy: 1.into(), ..Genome::DEFAULT })
+                .with_energy(5.0.into());
+            let prey = Immigrants::sampled(Genome::DEFAULT, Genome::ZERO, 2.0.into(), &prey_constants, 0);
+            let mut world = World::new()
+                .with_cells(vec![scavenger])
+                .with_cell_sources(vec![Box::new(ConstantCellSource::new(prey, 1.0.into()))])
+                .with_corpse_recycling(corpse_recycling);
+            let mut mutation_number_source = NullMutationNumberSource::new();
+            for _ in 0..20 {
+                world.step(&mut mutation_number_source);
+            }
+            world.cells().genomes().iter().filter(|genome| genome.attempted_eating_energy.value() > 0.0).count()
+        };
+
+        assert_eq!(scavengers_left(CorpseRecycling::NONE), 0);
+        assert_eq!(scavengers_left(CorpseRecycling { food_per_energy: 1.0.into(), food_per_size: 0.0.into() }), 1);
+    }
+
     #[test]
     fn generate_cells_with_normal_energy_distribution() {
         let constants = Rc::new(CellConstants::DEFAULT);
         let cells = generate_cells(
             100,
             Normal::new(100.0, 5.0).unwrap(),
-            Normal::new(0.0, 0.0).unwrap(),
-            Normal::new(0.0, 0.0).unwrap(),
-            Normal::new(0.0, 0.0).unwrap(),
-            Normal::new(f32::MAX, 0.0).unwrap(),
+            Normal::new(1.0, 0.0).unwrap(),
+            &Genome::DEFAULT,
+            &Genome::ZERO,
             &constants,
+            &mut rand::thread_rng(),
         );
         assert!(cells.iter().map(|cell| cell.energy()).any(|e| e < 100.0.into()));
         assert!(cells.iter().map(|cell| cell.energy()).any(|e| e > 100.0.into()));
     }
 
     #[test]
-    fn world_adds_new_cells() {
+    fn generate_cells_with_normal_health_distribution() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cells = generate_cells(
+            100,
+            Normal::new(100.0, 0.0).unwrap(),
+            Normal::new(0.5, 0.1).unwrap(),
+            &Genome::DEFAULT,
+            &Genome::ZERO,
+            &constants,
+            &mut rand::thread_rng(),
+        );
+        assert!(cells.iter().map(|cell| cell.health()).any(|h| h < 0.5.into()));
+        assert!(cells.iter().map(|cell| cell.health()).any(|h| h > 0.5.into()));
+    }
+
+    #[test]
+    fn generate_cells_with_normal_gene_distributions() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cells = generate_cells(
+            100,
+            Normal::new(100.0, 0.0).unwrap(),
+            Normal::new(1.0, 0.0).unwrap(),
+            &Genome {
+                attempted_eating_energy: 10.0.into(),
+                ..Genome::DEFAULT
+            },
+            &Genome {
+                attempted_eating_energy: 1.0.into(),
+                ..Genome::ZERO
+            },
+            &constants,
+            &mut rand::thread_rng(),
+        );
+        let eating_energies = || cells.iter().map(|cell| cell.genome().attempted_eating_energy);
+        assert!(eating_energies().any(|e| e < 10.0.into()));
+        assert!(eating_energies().any(|e| e > 10.0.into()));
+        assert!(cells.iter().all(|cell| cell.genome().attempted_healing_energy == 0.0.into()));
+    }
+
+    #[test]
+    fn world_adds_new_cells() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_food(0.0.into())
+            .with_cells(vec![
+                Cell::new(
+                    &constants,
+                    Genome {
+                        child_threshold_energy: 4.0.into(),
+                        child_threshold_food: 0.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.num_cells(), 2);
+    }
+
+    #[test]
+    fn world_reports_num_added() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let genome = Genome {
+            child_threshold_energy: 4.0.into(),
+            child_threshold_food: 0.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new()
+            .with_food(0.0.into())
+            .with_cells(vec![
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        let (num_added, _) = world.step(&mut mutation_number_source);
+        assert_eq!(num_added, 2);
+    }
+
+    #[test]
+    fn world_removes_dead_cells() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_food(0.0.into())
+            .with_cells(vec![
+                Cell::new(&constants, Genome::DEFAULT).with_health(1.0.into()),
+                Cell::new(&constants, Genome::DEFAULT).with_health(0.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.num_cells(), 1);
+    }
+
+    #[test]
+    fn world_reports_num_died() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_per_energy_expended: 0.2.into(),
+            ..CellConstants::DEFAULT
+        });
+        let hungry_genome = Genome {
+            attempted_eating_energy: 5.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants, Genome::DEFAULT).with_energy(10.0.into()),
+            Cell::new(&constants, hungry_genome).with_energy(5.0.into()),
+            Cell::new(&constants, hungry_genome).with_energy(5.0.into()),
+        ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        let (_, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!(num_died, 2);
+        assert_eq!(world.totals().num_died, 2);
+        assert_eq!(world.totals().deaths, DeathCounts { energy_expenditure: 2, ..DeathCounts::default() });
+    }
+
+    #[test]
+    fn registered_observers_see_each_step_until_removed() {
+        #[derive(Default)]
+        struct Counter {
+            num_cells_before: Vec<usize>,
+            counts_after: Vec<(usize, usize)>,
+            num_deaths: usize,
+        }
+
+        impl WorldObserver for Counter {
+            fn before_step(&mut self, world: &World) {
+                self.num_cells_before.push(world.num_cells());
+            }
+
+            fn after_step(&mut self, _world: &World, num_born: usize, num_died: usize) {
+                self.counts_after.push((num_born, num_died));
+            }
+
+            fn on_death(&mut self, _cell: &Cell) {
+                self.num_deaths += 1;
+            }
+        }
+
+        let constants = Rc::new(CellConstants {
+            health_reduction_per_energy_expended: 0.2.into(),
+            ..CellConstants::DEFAULT
+        });
+        let hungry_genome = Genome {
+            attempted_eating_energy: 5.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new().with_cells(vec![
+            Cell::new(&constants, Genome::DEFAULT).with_energy(10.0.into()),
+            Cell::new(&constants, hungry_genome).with_energy(5.0.into()),
+        ]);
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        world.add_observer(counter.clone());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+        world.remove_observer(&counter);
+        world.step(&mut mutation_number_source);
+
+        let counter = counter.borrow();
+        assert_eq!(counter.num_cells_before, vec![2, 1]);
+        assert_eq!(counter.counts_after, vec![(0, 1), (0, 0)]);
+        assert_eq!(counter.num_deaths, 1);
+    }
+
+    #[test]
+    fn cells_consume_world_food() {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: F32Positive::unchecked(1.0),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_food(10.0.into())
+            .with_cells(vec![
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_eating_energy: 2.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(10.0.into()),
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_eating_energy: 3.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.food().value(), 5.0);
+    }
+
+    #[test]
+    fn cells_cannot_consume_more_than_their_share_of_world_food() {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: F32Positive::unchecked(1.0),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_food(4.0.into())
+            .with_cells(vec![
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_eating_energy: 3.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(10.0.into()),
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_eating_energy: 1.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.food().value(), 1.0);
+    }
+
+    #[test]
+    fn non_overlapping_generations_defer_reproduction() {
         let constants = Rc::new(CellConstants::DEFAULT);
         let mut world = World::new()
-            .with_food(0.0.into())
+            .with_generations(Generations::NonOverlapping { steps_per_generation: 2 })
             .with_cells(vec![
                 Cell::new(
                     &constants,
-                    CellParams {
+                    Genome {
                         child_threshold_energy: 4.0.into(),
                         child_threshold_food: 0.0.into(),
-                        ..CellParams::DEFAULT
+                        ..Genome::DEFAULT
                     })
                     .with_energy(10.0.into()),
             ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
-        world.step(&mut mutation_number_source);
-        assert_eq!(world.num_cells(), 2);
+        let (num_added, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!((num_added, num_died), (0, 0));
+        assert_eq!(world.num_cells(), 1);
     }
 
     #[test]
-    fn world_reports_num_added() {
+    fn non_overlapping_generations_replace_adults_with_offspring() {
         let constants = Rc::new(CellConstants::DEFAULT);
-        let params = CellParams {
+        let genome = Genome {
             child_threshold_energy: 4.0.into(),
             child_threshold_food: 0.0.into(),
-            ..CellParams::DEFAULT
+            ..Genome::DEFAULT
         };
         let mut world = World::new()
-            .with_food(0.0.into())
+            .with_generations(Generations::NonOverlapping { steps_per_generation: 2 })
             .with_cells(vec![
-                Cell::new(&constants, params).with_energy(10.0.into()),
-                Cell::new(&constants, params).with_energy(10.0.into()),
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+                Cell::new(&constants, genome).with_energy(3.0.into()),
             ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
-        let (num_added, _) = world.step(&mut mutation_number_source);
-        assert_eq!(num_added, 2);
+        world.step(&mut mutation_number_source);
+        let (num_added, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!((num_added, num_died), (2, 2));
+        assert_eq!(world.num_cells(), 2);
+        assert_eq!(world.mean_energy(), 4.0);
     }
 
     #[test]
-    fn world_removes_dead_cells() {
+    fn selected_generations_descend_from_parents_drawn_by_energy() {
         let constants = Rc::new(CellConstants::DEFAULT);
+        let genome = |eating: f32| Genome { attempted_eating_energy: eating.into(), ..Genome::DEFAULT };
         let mut world = World::new()
-            .with_food(0.0.into())
+            .with_seed(0)
+            .with_generations(Generations::Selected { steps_per_generation: 2, population_size: 4, child_energy: 2.0.into() })
             .with_cells(vec![
-                Cell::new(&constants, CellParams::DEFAULT).with_health(1.0.into()),
-                Cell::new(&constants, CellParams::DEFAULT).with_health(0.0.into()),
+                Cell::new(&constants, genome(0.0)).with_energy(10.0.into()),
+                Cell::new(&constants, genome(0.5)).with_energy(0.0.into()),
             ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
         world.step(&mut mutation_number_source);
-        assert_eq!(world.num_cells(), 1);
+        let (num_added, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!((num_added, num_died), (4, 2));
+        assert!(world.cells().genomes().iter().all(|child| **child == genome(0.0)));
+        assert_eq!(world.mean_energy(), 2.0);
     }
 
     #[test]
-    fn world_reports_num_died() {
+    fn sexual_reproduction_crosses_parent_with_mate() {
         let constants = Rc::new(CellConstants {
-            health_reduction_per_energy_expended: 0.2.into(),
+            crossover_probability: 1.0.into(),
             ..CellConstants::DEFAULT
         });
-        let hungry_params = CellParams {
-            attempted_eating_energy: 5.0.into(),
-            ..CellParams::DEFAULT
-        };
-        let mut world = World::new().with_cells(vec![
-            Cell::new(&constants, CellParams::DEFAULT).with_energy(10.0.into()),
-            Cell::new(&constants, hungry_params).with_energy(5.0.into()),
-            Cell::new(&constants, hungry_params).with_energy(5.0.into()),
-        ]);
+        let mut world = World::new()
+            .with_seed(0)
+            .with_reproduction_mode(ReproductionMode::Sexual)
+            .with_cells(vec![
+                Cell::new(
+                    &constants,
+                    Genome {
+                        child_threshold_energy: 1.0.into(),
+                        child_threshold_food: 0.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(1.0.into()),
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_healing_energy: 2.0.into(),
+                        ..Genome::DEFAULT
+                    }),
+            ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
-        let (_, num_died) = world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.num_cells(), 3);
+        assert_eq!(*world.cell(2).genome(), *world.cell(1).genome());
+    }
+
+    #[test]
+    fn sexual_cells_choose_no_mate_unless_they_reproduce() {
+        let world = |reproduction_mode| World::new()
+            .with_seed(0)
+            .with_reproduction_mode(reproduction_mode)
+            .with_cells((0..3).map(|_| Cell::new(&Rc::new(CellConstants::DEFAULT), Genome::DEFAULT)).collect());
+        let mut asexual_world = world(ReproductionMode::Asexual);
+        let mut sexual_world = world(ReproductionMode::Sexual);
+
+        asexual_world.step(&mut NullMutationNumberSource::new());
+        sexual_world.step(&mut NullMutationNumberSource::new());
+
+        assert!(sexual_world.rng == asexual_world.rng);
+    }
+
+    #[test]
+    fn reproduction_mode_parses_from_its_name() {
+        assert_eq!("sexual".parse::<ReproductionMode>(), Ok(ReproductionMode::Sexual));
+        assert_eq!(ReproductionMode::Asexual.to_string(), "asexual");
+        assert!("binary".parse::<ReproductionMode>().is_err());
+    }
+
+    #[test]
+    fn crowded_world_culls_weakest_cells() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let cell = |health: f32, energy: f32| Cell::new(&constants, Genome::DEFAULT)
+            .with_health(health.into())
+            .with_energy(energy.into());
+        let mut world = World::new()
+            .with_cells(vec![cell(1.0, 1.0), cell(0.5, 2.0), cell(1.0, 3.0), cell(0.5, 1.0)])
+            .with_max_cells(2, Crowding::CullWeakest);
+
+        let (_, num_died) = world.step(&mut NullMutationNumberSource::new());
+
         assert_eq!(num_died, 2);
+        let mut energies: Vec<f32> = world.cells().energies().iter().map(|energy| energy.value()).collect();
+        energies.sort_by(f32::total_cmp);
+        assert_eq!(energies, vec![1.0, 3.0]);
     }
 
     #[test]
-    fn cells_consume_world_food() {
+    fn crowding_damage_takes_excess_fraction_of_health() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_cells((0..4).map(|_| Cell::new(&constants, Genome::DEFAULT).with_energy(1.0.into())).collect())
+            .with_max_cells(3, Crowding::Damage);
+
+        world.step(&mut NullMutationNumberSource::new());
+
+        assert_eq!(world.num_cells(), 4);
+        assert!(world.cells().healths().iter().all(|&health| health == 0.75.into()));
+    }
+
+    #[test]
+    fn crowding_parses_from_its_name() {
+        assert_eq!("damage".parse::<Crowding>(), Ok(Crowding::Damage));
+        assert_eq!(Crowding::CullWeakest.to_string(), "cull");
+        assert!("starve".parse::<Crowding>().is_err());
+    }
+
+    #[test]
+    fn predator_takes_energy_from_prey() {
         let constants = Rc::new(CellConstants {
-            food_yield_from_eating: F32Positive::unchecked(1.0),
+            attack_efficiency: 2.0.into(),
+            health_reduction_per_attack_energy: 0.25.into(),
             ..CellConstants::DEFAULT
         });
         let mut world = World::new()
-            .with_food(10.0.into())
+            .with_seed(0)
             .with_cells(vec![
                 Cell::new(
                     &constants,
-                    CellParams {
-                        attempted_eating_energy: 2.0.into(),
-                        ..CellParams::DEFAULT
+                    Genome {
+                        attempted_attack_energy: 1.0.into(),
+                        ..Genome::DEFAULT
                     })
-                    .with_energy(10.0.into()),
+                    .with_energy(5.0.into()),
+                Cell::new(&constants, Genome::DEFAULT).with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.cell(0).energy(), 6.0.into());
+        assert_eq!(world.cell(1).energy(), 8.0.into());
+        assert_eq!(world.cell(1).health(), 0.75.into());
+    }
+
+    #[test]
+    fn world_removes_cells_killed_by_predators() {
+        let constants = Rc::new(CellConstants {
+            health_reduction_per_attack_energy: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
                 Cell::new(
                     &constants,
-                    CellParams {
-                        attempted_eating_energy: 3.0.into(),
-                        ..CellParams::DEFAULT
+                    Genome {
+                        attempted_attack_energy: 1.0.into(),
+                        ..Genome::DEFAULT
                     })
-                    .with_energy(10.0.into()),
+                    .with_energy(5.0.into()),
+                Cell::new(&constants, Genome::DEFAULT),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        let (_, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!(num_died, 1);
+        assert_eq!(world.num_cells(), 1);
+    }
+
+    #[test]
+    fn trading_partners_exchange_energy() {
+        let constants = Rc::new(CellConstants {
+            trade_efficiency: 2.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(
+                    &constants,
+                    Genome {
+                        attempted_trade_energy: 1.0.into(),
+                        ..Genome::DEFAULT
+                    })
+                    .with_energy(5.0.into()),
+                Cell::new(&constants, Genome::DEFAULT).with_energy(5.0.into()),
             ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
         world.step(&mut mutation_number_source);
-        assert_eq!(world.food().value(), 5.0);
+        assert_eq!(world.cell(0).energy(), 4.0.into());
+        assert_eq!(world.cell(1).energy(), 7.0.into());
     }
 
     #[test]
-    fn cells_cannot_consume_more_than_their_share_of_world_food() {
+    fn trading_partner_gives_food_access_from_its_share() {
         let constants = Rc::new(CellConstants {
-            food_yield_from_eating: F32Positive::unchecked(1.0),
+            food_yield_from_eating: 1.0.into(),
+            trade_efficiency: 1.0.into(),
+            food_access_per_trade_energy: 1.0.into(),
             ..CellConstants::DEFAULT
         });
         let mut world = World::new()
+            .with_seed(0)
             .with_food(4.0.into())
             .with_cells(vec![
                 Cell::new(
                     &constants,
-                    CellParams {
-                        attempted_eating_energy: 3.0.into(),
-                        ..CellParams::DEFAULT
+                    Genome {
+                        attempted_eating_energy: 2.0.into(),
+                        attempted_trade_energy: 1.0.into(),
+                        ..Genome::DEFAULT
                     })
                     .with_energy(10.0.into()),
+                Cell::new(&constants, Genome::DEFAULT).with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.food(), 2.0.into());
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.food(), 0.0.into());
+    }
+
+    #[test]
+    fn light_from_light_sources_is_shared_among_cells() {
+        let constants = Rc::new(CellConstants {
+            light_yield_from_photosynthesis: 10.0.into(),
+            energy_yield_from_light: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let genome = Genome {
+            attempted_photosynthesis_energy: 1.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new()
+            .with_light_sources(vec![
+                Box::new(ConstantLightSource::new(4.0.into())),
+                Box::new(ConstantLightSource::new(2.0.into())),
+            ])
+            .with_cells(vec![
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+                Cell::new(&constants, genome).with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.mean_energy(), 12.0);
+    }
+
+    #[test]
+    fn cells_see_world_time_of_day() {
+        let constants = Rc::new(CellConstants {
+            activity_fraction: 0.5.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_day_length(2)
+            .with_cells(vec![
                 Cell::new(
                     &constants,
-                    CellParams {
+                    Genome {
                         attempted_eating_energy: 1.0.into(),
-                        ..CellParams::DEFAULT
+                        active_phase: 0.5.into(),
+                        ..Genome::DEFAULT
                     })
                     .with_energy(10.0.into()),
             ]);
         let mut mutation_number_source = NullMutationNumberSource::new();
         world.step(&mut mutation_number_source);
-        assert_eq!(world.food().value(), 1.0);
+        assert_eq!(world.cell(0).energy(), 10.0.into());
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.cell(0).energy(), 9.0.into());
     }
 
     #[test]
@@ -337,4 +1938,293 @@ mod tests {
         world.step(&mut mutation_number_source);
         assert_eq!(world.food().value(), 5.0);
     }
+
+    #[test]
+    fn extinctions_are_counted_and_can_be_reseeded() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let dying_cell = || Cell::new(&constants, Genome::DEFAULT).with_health(0.0.into());
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let mut world = World::new().with_cells(vec![dying_cell()]);
+        world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+        assert_eq!((world.num_cells(), world.totals().num_extinctions), (0, 1));
+
+        let founders = Immigrants::sampled(Genome::DEFAULT, Genome::ZERO, 2.0.into(), &constants, 0);
+        let mut world = World::new()
+            .with_cells(vec![dying_cell()])
+            .with_extinction_reseeding(ExtinctionReseeding::new(founders, 3));
+        let (num_born, num_died) = world.step(&mut mutation_number_source);
+        assert_eq!((num_born, num_died, world.num_cells()), (0, 1, 3));
+        assert_eq!(world.totals().num_extinctions, 1);
+        assert_eq!(world.cell(0).energy(), 2.0.into());
+    }
+
+    #[test]
+    fn cells_can_be_found_by_id_and_sampled() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let world = World::new()
+            .with_cells((0..5).map(|energy| Cell::new(&constants, Genome::DEFAULT).with_energy(energy.into())).collect());
+        let mut rng = Pcg64::seed_from_u64(0);
+
+        assert_eq!(world.find_cell(3).map(|cell| cell.energy()), Some(3.0.into()));
+        assert!(world.find_cell(5).is_none());
+        let mut ids: Vec<u64> = world.sample_cells(3, &mut rng).iter().map(|cell| cell.id()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(world.sample_cells(10, &mut rng).len(), 5);
+    }
+
+    #[test]
+    fn cell_sources_add_immigrants_before_cells_step() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let immigrants = Immigrants::sampled(Genome::DEFAULT, Genome::ZERO, 3.0.into(), &constants, 0);
+        let mut world = World::new()
+            .with_cell_sources(vec![Box::new(ConstantCellSource::new(immigrants, 2.0.into()))]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        let (num_born, _) = world.step(&mut mutation_number_source);
+
+        assert_eq!((num_born, world.num_cells()), (0, 2));
+        assert_eq!(world.totals().num_immigrated, 2);
+        assert_eq!(world.cell(1).age(), 1);
+    }
+
+    #[test]
+    fn uneaten_food_decays() {
+        let mut world = World::new()
+            .with_food(8.0.into())
+            .with_food_decay_rate(0.25.into())
+            .with_food_sources(vec![Box::new(ConstantFoodSource::new(4.0.into()))]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        assert_eq!(world.food().value(), 9.0);
+    }
+
+    #[test]
+    fn specialists_eat_from_separate_nutrient_pools() {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: F32Positive::unchecked(1.0),
+            protein_yield_from_eating: F32Positive::unchecked(1.0),
+            ..CellConstants::DEFAULT
+        });
+        let specialist = |protein_preference: f32| Cell::new(&constants, Genome {
+            attempted_eating_energy: 1.0.into(),
+            protein_preference: protein_preference.into(),
+            ..Genome::DEFAULT
+        }).with_energy(10.0.into());
+        let mut world = World::new()
+            .with_food_decay_rate(0.5.into())
+            .with_food_sources(vec![Box::new(ConstantFoodSource::new(4.0.into()))])
+            .with_protein(2.0.into())
+            .with_protein_sources(vec![Box::new(ConstantFoodSource::new(4.0.into()))])
+            .with_cells(vec![specialist(0.0), specialist(1.0)]);
+        world.step(&mut NullMutationNumberSource::new());
+
+        assert_eq!((world.food().value(), world.protein().value()), (1.5, 2.5));
+        let totals = world.totals();
+        assert_eq!((totals.food_eaten, totals.protein_added, totals.protein_eaten, totals.protein_decayed), (1.0, 4.0, 1.0, 2.5));
+    }
+
+    #[test]
+    fn totals_accumulate_across_steps() {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: F32Positive::unchecked(1.0),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_food_decay_rate(0.5.into())
+            .with_food_sources(vec![Box::new(ConstantFoodSource::new(4.0.into()))])
+            .with_cells(vec![
+                Cell::new(&constants, Genome { attempted_eating_energy: 1.0.into(), ..Genome::DEFAULT })
+                    .with_energy(10.0.into()),
+            ]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+        world.step(&mut mutation_number_source);
+        world.step(&mut mutation_number_source);
+
+        let totals = world.totals();
+        assert_eq!((totals.food_added, totals.food_eaten, totals.food_decayed), (8.0, 2.0, 3.75));
+        assert_eq!(totals.food_added - totals.food_eaten - totals.food_decayed, world.food().value() as f64);
+    }
+
+    #[test]
+    fn food_sources_see_the_step_number_and_world_food() {
+        let mut world = World::new()
+            .with_food(50.into())
+            .with_food_sources(vec![Box::new(StepAndFoodSource)]);
+        let mut mutation_number_source = NullMutationNumberSource::new();
+
+        world.step(&mut mutation_number_source);
+        assert_eq!((world.num_steps(), world.food().value()), (1, 55.0));
+        world.step(&mut mutation_number_source);
+        assert_eq!((world.num_steps(), world.food().value()), (2, 61.5));
+    }
+
+    /// Adds the step number plus a tenth of the world's food.
+    #[derive(Serialize, Deserialize)]
+    struct StepAndFoodSource;
+
+    #[typetag::serde]
+    impl FoodSource for StepAndFoodSource {
+        fn food_this_step(&mut self, world: &WorldSummary) -> F32Positive {
+            F32Positive::unchecked(world.step as f32 + world.food.value() / 10.0)
+        }
+    }
+
+    #[test]
+    fn cells_donate_only_to_kin() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let donor = Cell::new(&constants, Genome {
+            donation_threshold: 4.into(),
+            donation_fraction: 0.5.into(),
+            ..Genome::DEFAULT
+        }).with_energy(10.into());
+        let child = Cell::from_parts(Rc::clone(&constants), Rc::new(Genome::DEFAULT), 0.0.into(), 1.0.into(), CellState {
+            parent_id: Some(0),
+            ..CellState::DEFAULT
+        }).with_energy(1.into());
+        let stranger = Cell::new(&constants, Genome::DEFAULT).with_energy(0.into());
+        let mut world = World::new().with_cells(vec![donor, child, stranger]);
+
+        world.step(&mut NullMutationNumberSource::new());
+
+        let energies: Vec<f32> = world.cells().energies().iter().map(|energy| energy.value()).collect();
+        assert_eq!(energies, vec![7.0, 4.0, 0.0]);
+        assert_eq!(world.totals().energy_donated, 3.0);
+    }
+
+    #[test]
+    fn cull_kills_a_fraction_of_cells() {
+        let constants = Rc::new(CellConstants::DEFAULT);
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells((0..10).map(|_| Cell::new(&constants, Genome::DEFAULT)).collect());
+
+        assert_eq!(world.cull(0.25.into()), 3);
+        assert_eq!(world.num_cells(), 7);
+        assert_eq!(world.totals().num_died, 3);
+        assert_eq!((world.totals().deaths.culled, world.totals().deaths.total()), (3, 3));
+    }
+
+    #[test]
+    fn expended_energy_leaves_toxin_that_detox_neutralizes() {
+        let constants = Rc::new(CellConstants {
+            toxin_yield_from_detox: 2.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_toxin(3.into())
+            .with_toxin_per_energy_expended(0.5.into())
+            .with_cells(vec![
+                Cell::new(&constants, Genome { attempted_healing_energy: 4.into(), ..Genome::DEFAULT }).with_energy(10.into()),
+                Cell::new(&constants, Genome { attempted_detox_energy: 1.into(), ..Genome::DEFAULT }).with_energy(10.into()),
+            ]);
+
+        world.step(&mut NullMutationNumberSource::new());
+
+        assert_eq!(world.toxin(), 4.into());
+    }
+
+    #[test]
+    fn death_hazard_kills_cells_at_random() {
+        let constants = Rc::new(CellConstants {
+            death_probability: 0.25.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells((0..1000).map(|_| Cell::new(&constants, Genome::DEFAULT)).collect());
+
+        let (_, num_died) = world.step(&mut NullMutationNumberSource::new());
+
+        assert!((200..300).contains(&num_died), "{} died", num_died);
+    }
+
+    #[test]
+    fn death_hazard_can_rise_as_health_falls() {
+        let constants = Rc::new(CellConstants {
+            death_probability_per_health_lost: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_seed(0)
+            .with_cells(vec![
+                Cell::new(&constants, Genome::DEFAULT).with_health(1.0.into()),
+                Cell::new(&constants, Genome::DEFAULT).with_health(0.001.into()),
+            ]);
+
+        world.step(&mut NullMutationNumberSource::new());
+
+        assert_eq!(world.num_cells(), 1);
+        assert_eq!(world.cells().get(0).health(), 1.0.into());
+    }
+
+    #[test]
+    fn audited_world_balances_its_books() {
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: 1.0.into(),
+            energy_yield_from_digestion: 1.0.into(),
+            create_child_energy: 1.0.into(),
+            attack_efficiency: 1.0.into(),
+            trade_efficiency: 0.5.into(),
+            toxin_yield_from_detox: 1.0.into(),
+            ..CellConstants::DEFAULT
+        });
+        let genome = Genome {
+            attempted_eating_energy: 2.0.into(),
+            attempted_attack_energy: 0.5.into(),
+            attempted_trade_energy: 0.5.into(),
+            attempted_detox_energy: 0.5.into(),
+            child_threshold_energy: 5.0.into(),
+            child_threshold_food: 0.0.into(),
+            donation_fraction: 0.5.into(),
+            donation_threshold: 8.0.into(),
+            ..Genome::DEFAULT
+        };
+        let mut world = World::new()
+            .with_seed(0)
+            .with_food(20.0.into())
+            .with_food_sources(vec![Box::new(ConstantFoodSource::new(10.0.into()))])
+            .with_food_decay_rate(0.1.into())
+            .with_toxin_per_energy_expended(0.1.into())
+            .with_audit(Audit::Panic)
+            .with_cells((0..20).map(|_| Cell::new(&constants, genome).with_energy(10.0.into())).collect());
+
+        for _ in 0..20 {
+            world.step(&mut NullMutationNumberSource::new());
+        }
+        assert!(world.totals().num_born > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "energy of cell 0 after its step")]
+    fn audit_catches_food_digested_twice() {
+        struct SecondHelping;
+
+        impl CellBehavior for SecondHelping {
+            fn name(&self) -> &str {
+                "second_helping"
+            }
+
+            fn run(&self, cell: &mut CellMut, context: &mut BehaviorContext) {
+                *cell.energy_mut() += context.food_eaten;
+            }
+        }
+
+        let constants = Rc::new(CellConstants {
+            food_yield_from_eating: 1.0.into(),
+            energy_yield_from_digestion: 1.0.into(),
+            behaviors: CellBehaviors::new(&[&SecondHelping]),
+            ..CellConstants::DEFAULT
+        });
+        let mut world = World::new()
+            .with_food(10.0.into())
+            .with_audit(Audit::Panic)
+            .with_cell(Cell::new(&constants, Genome { attempted_eating_energy: 2.0.into(), ..Genome::DEFAULT })
+                .with_energy(10.0.into()));
+
+        world.step(&mut NullMutationNumberSource::new());
+    }
 }
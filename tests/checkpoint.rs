@@ -0,0 +1,72 @@
+use evo2::cell::{Cell, CellConstants, RandomMutationNumberSource};
+use evo2::genome::Genome;
+use evo2::checkpoint;
+use evo2::food_sources::LinearlyGrowingFoodSource;
+use evo2::world::World;
+use std::rc::Rc;
+
+fn create_world() -> World {
+    let constants = Rc::new(CellConstants {
+        create_child_energy: 1.into(),
+        energy_yield_from_digestion: 1.into(),
+        food_yield_from_eating: 10.into(),
+        health_increase_per_healing_energy: 0.5.into(),
+        health_reduction_from_entropy: 0.5.into(),
+        health_reduction_per_energy_expended: 0.1.into(),
+        attempted_eating_energy_mutation_stdev: 0.1.into(),
+        attempted_healing_energy_mutation_stdev: 0.1.into(),
+        child_threshold_energy_mutation_stdev: 0.1.into(),
+        child_threshold_food_mutation_stdev: 0.1.into(),
+        ..CellConstants::DEFAULT
+    });
+    World::new()
+        .with_seed(7)
+        .with_cells(vec![
+            Cell::new(
+                &constants,
+                Genome {
+                    attempted_eating_energy: 1.into(),
+                    attempted_healing_energy: 2.into(),
+                    child_threshold_energy: 2.into(),
+                    child_threshold_food: 1.into(),
+                    ..Genome::DEFAULT
+                })
+                .with_energy(10.into()),
+        ])
+        .with_food(100.into())
+        .with_food_sources(vec![
+            Box::new(LinearlyGrowingFoodSource::new(10.into(), 5.into()))
+        ])
+}
+
+fn run(world: &mut World, mutation_number_source: &mut RandomMutationNumberSource, steps: u32) {
+    for _ in 0..steps {
+        world.step(mutation_number_source);
+    }
+}
+
+#[test]
+fn resumed_run_continues_exactly_like_uninterrupted_run() {
+    let mut uninterrupted_world = create_world();
+    let mut uninterrupted_source = RandomMutationNumberSource::with_seed(42);
+    run(&mut uninterrupted_world, &mut uninterrupted_source, 30);
+
+    let mut world = create_world();
+    let mut mutation_number_source = RandomMutationNumberSource::with_seed(42);
+    run(&mut world, &mut mutation_number_source, 15);
+    let path = std::env::temp_dir().join(format!("evo2_checkpoint_test_{}.json", std::process::id()));
+    checkpoint::save(&path, 15, None, &world, &mutation_number_source).unwrap();
+
+    let checkpoint = checkpoint::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(checkpoint.step, 15);
+    let mut resumed_world = checkpoint.world;
+    let mut resumed_source = checkpoint.mutation_number_source;
+    run(&mut resumed_world, &mut resumed_source, 15);
+
+    assert!(uninterrupted_world.num_cells() > 1);
+    assert_eq!(serde_json::to_string(&resumed_world).unwrap(),
+               serde_json::to_string(&uninterrupted_world).unwrap());
+    assert_eq!(serde_json::to_string(&resumed_source).unwrap(),
+               serde_json::to_string(&uninterrupted_source).unwrap());
+}
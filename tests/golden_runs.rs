@@ -0,0 +1,172 @@
+#![cfg(not(feature = "f64"))]
+
+use evo2::cell::{Cell, CellConstants, NullMutationNumberSource, RandomMutationNumberSource, MutationNumberSource};
+use evo2::checksum;
+use evo2::food_sources::{ConstantFoodSource, LinearlyGrowingFoodSource};
+use evo2::genome::Genome;
+use evo2::world::{ReproductionMode, World};
+use std::rc::Rc;
+
+// Exact trajectories of small seeded runs. A change that alters any of them changes how worlds
+// behave; if the change is meant to, rerun the test and update the trajectory from its output.
+// The trajectories are for the world's default f32 food and toxin pools.
+
+fn trajectory(world: &mut World, mutation_number_source: &mut dyn MutationNumberSource, steps: u32, every: u32) -> Vec<String> {
+    let mut lines = vec![line(0, world)];
+    for step in 1..=steps {
+        world.step(mutation_number_source);
+        if step % every == 0 || world.num_cells() == 0 {
+            lines.push(line(step, world));
+        }
+        if world.num_cells() == 0 {
+            break;
+        }
+    }
+    lines.push(format!("genomes {:016x}", checksum::population_checksum(world.cells().genomes().iter().map(Rc::as_ref))));
+    lines
+}
+
+fn line(step: u32, world: &World) -> String {
+    format!("{}: {} cells, e {}, h {}, f {}", step, world.num_cells(), world.mean_energy(), world.mean_health(), world.food())
+}
+
+#[test]
+fn single_cell_eats_then_starves() {
+    let constants = Rc::new(CellConstants {
+        energy_yield_from_digestion: 0.5.into(),
+        food_yield_from_eating: 10.into(),
+        health_increase_per_healing_energy: 0.5.into(),
+        health_reduction_from_entropy: 0.5.into(),
+        health_reduction_per_energy_expended: 0.1.into(),
+        ..CellConstants::DEFAULT
+    });
+    let mut world = World::new()
+        .with_seed(0)
+        .with_cells(vec![
+            Cell::new(&constants, Genome {
+                attempted_eating_energy: 1.into(),
+                attempted_healing_energy: 2.into(),
+                ..Genome::DEFAULT
+            }).with_energy(10.into()),
+        ])
+        .with_food(50.into());
+
+    let lines = trajectory(&mut world, &mut NullMutationNumberSource::new(), 100, 1);
+
+    assert_eq!(lines, GOLDEN_EAT_THEN_STARVE);
+}
+
+const GOLDEN_EAT_THEN_STARVE: &[&str] = &[
+    "0: 1 cells, e 10, h 1, f 50",
+    "1: 1 cells, e 12, h 1, f 40",
+    "2: 1 cells, e 14, h 1, f 30",
+    "3: 1 cells, e 16, h 1, f 20",
+    "4: 1 cells, e 18, h 1, f 10",
+    "5: 1 cells, e 20, h 1, f 0",
+    "6: 1 cells, e 17, h 1, f 0",
+    "7: 1 cells, e 14, h 1, f 0",
+    "8: 1 cells, e 11, h 1, f 0",
+    "9: 1 cells, e 8, h 1, f 0",
+    "10: 1 cells, e 5, h 1, f 0",
+    "11: 1 cells, e 2, h 1, f 0",
+    "12: 1 cells, e 0, h 0.9666667, f 0",
+    "13: 1 cells, e 0, h 0.4666667, f 0",
+    "14: 0 cells, e 0, h 0, f 0",
+    "genomes cbf29ce484222325",
+];
+
+#[test]
+fn mutating_lineage_grows_on_a_growing_food_supply() {
+    let constants = Rc::new(CellConstants {
+        create_child_energy: 1.into(),
+        energy_yield_from_digestion: 1.into(),
+        food_yield_from_eating: 10.into(),
+        health_increase_per_healing_energy: 0.5.into(),
+        health_reduction_from_entropy: 0.5.into(),
+        health_reduction_per_energy_expended: 0.1.into(),
+        maintenance_energy: 0.25.into(),
+        attempted_eating_energy_mutation_stdev: 0.1.into(),
+        attempted_healing_energy_mutation_stdev: 0.1.into(),
+        child_threshold_energy_mutation_stdev: 0.1.into(),
+        child_threshold_food_mutation_stdev: 0.1.into(),
+        ..CellConstants::DEFAULT
+    });
+    let mut world = World::new()
+        .with_seed(7)
+        .with_cells(vec![
+            Cell::new(&constants, Genome {
+                attempted_eating_energy: 1.into(),
+                attempted_healing_energy: 2.into(),
+                child_threshold_energy: 2.into(),
+                child_threshold_food: 1.into(),
+                ..Genome::DEFAULT
+            }).with_energy(10.into()),
+        ])
+        .with_food(100.into())
+        .with_food_sources(vec![Box::new(LinearlyGrowingFoodSource::new(10.into(), 5.into()))]);
+
+    let lines = trajectory(&mut world, &mut RandomMutationNumberSource::with_seed(42), 40, 5);
+
+    assert_eq!(lines, GOLDEN_MUTATING_LINEAGE);
+}
+
+const GOLDEN_MUTATING_LINEAGE: &[&str] = &[
+    "0: 1 cells, e 10, h 1, f 100",
+    "5: 9 cells, e 8.541798, h 0.8985, f 86.77064",
+    "10: 41 cells, e 2.8647683, h 0.79283947, f 0",
+    "15: 51 cells, e 1.8205822, h 0.46704164, f 0.000046491623",
+    "20: 54 cells, e 1.9444449, h 0.5411101, f 0.00002026558",
+    "25: 54 cells, e 2.4074075, h 0.6894198, f 0",
+    "30: 54 cells, e 2.8703706, h 0.9429645, f 0.00000047683716",
+    "35: 54 cells, e 3.4695597, h 1, f 0.00008535385",
+    "40: 67 cells, e 3.9684405, h 0.97260904, f 3.7784314",
+    "genomes 22b117637ce0f7f2",
+];
+
+#[test]
+fn sexual_predators_and_traders_under_a_death_hazard() {
+    let constants = Rc::new(CellConstants {
+        create_child_energy: 0.5.into(),
+        energy_yield_from_digestion: 1.into(),
+        food_yield_from_eating: 4.into(),
+        health_increase_per_healing_energy: 0.5.into(),
+        health_reduction_from_entropy: 0.2.into(),
+        attack_efficiency: 0.5.into(),
+        health_reduction_per_attack_energy: 0.1.into(),
+        trade_efficiency: 1.5.into(),
+        death_probability: 0.02.into(),
+        attempted_attack_energy_mutation_stdev: 0.2.into(),
+        attempted_trade_energy_mutation_stdev: 0.2.into(),
+        ..CellConstants::DEFAULT
+    });
+    let genome = |attack: f32, trade: f32| Genome {
+        attempted_eating_energy: 1.into(),
+        attempted_healing_energy: 0.5.into(),
+        attempted_attack_energy: attack.into(),
+        attempted_trade_energy: trade.into(),
+        child_threshold_energy: 6.into(),
+        child_threshold_food: 2.into(),
+        ..Genome::DEFAULT
+    };
+    let mut world = World::new()
+        .with_seed(3)
+        .with_reproduction_mode(ReproductionMode::Sexual)
+        .with_cells((0..12).map(|index| Cell::new(&constants, genome((index % 3) as f32 * 0.5, (index % 2) as f32)).with_energy(5.into())).collect())
+        .with_food(60.into())
+        .with_food_sources(vec![Box::new(ConstantFoodSource::new(20.into()))]);
+
+    let lines = trajectory(&mut world, &mut RandomMutationNumberSource::with_seed(5), 60, 10);
+
+    assert_eq!(lines, GOLDEN_PREDATORS_AND_TRADERS);
+}
+
+const GOLDEN_PREDATORS_AND_TRADERS: &[&str] = &[
+    "0: 12 cells, e 5, h 1, f 60",
+    "10: 13 cells, e 6.3684664, h 0.79076207, f 0",
+    "20: 11 cells, e 7.569934, h 0.634539, f 0",
+    "30: 15 cells, e 6.844374, h 0.75539285, f 0",
+    "40: 11 cells, e 5.1949596, h 0.57477343, f 0",
+    "50: 10 cells, e 5.7118006, h 0.51886415, f 0",
+    "60: 10 cells, e 6.5708885, h 0.7265949, f 0",
+    "genomes 571ef964813b06ee",
+];